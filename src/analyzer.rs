@@ -0,0 +1,821 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the reusable [`SpectrumAnalyzer`] and its builder
+//! [`SpectrumAnalyzerBuilder`]. The analyzer also offers an allocation-free
+//! path for real-time threads, see [`SpectrumAnalyzer::analyze_into`].
+
+use crate::detrend::Detrend;
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::{calc_checked, Fft, FftBackend, FftImpl};
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::limit::FrequencyLimit;
+use crate::padding::Padding;
+use crate::pre_emphasis::pre_emphasis;
+use crate::scaling::{std_dev, SpectrumDataStats, SpectrumScalingFunction};
+use crate::spectrum::FrequencySpectrum;
+use crate::windows::{Window, WindowFunction};
+use crate::{
+    complex_to_magnitude, fft_calc_frequency_resolution, fft_result_to_spectrum,
+    fft_result_with_frequencies, validate_fft_len, validate_frequency_limit, validate_samples,
+};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::Range;
+
+/// Builder for a [`SpectrumAnalyzer`].
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-4096")] {
+/// use spectrum_analyzer::{FrequencyLimit, SpectrumAnalyzerBuilder};
+/// use spectrum_analyzer::scaling::divide_by_N_sqrt;
+/// use spectrum_analyzer::windows::hann_window;
+/// let mut analyzer = SpectrumAnalyzerBuilder::new(2048, 44100)
+///     .window_fn(&hann_window)
+///     .frequency_limit(FrequencyLimit::Max(4000.0))
+///     .scaling_fn(divide_by_N_sqrt)
+///     .build()
+///     .unwrap();
+/// # let frames = vec![vec![0.0; 2048]; 2];
+/// for frame in frames {
+///     let spectrum = analyzer.analyze(&frame).unwrap();
+/// }
+/// # }
+/// ```
+pub struct SpectrumAnalyzerBuilder {
+    samples_len: usize,
+    sampling_rate: u32,
+    detrend: Detrend,
+    pre_emphasis: Option<f32>,
+    window: Option<BuilderWindow>,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<Box<SpectrumScalingFunction>>,
+    padding: Padding,
+    fft_backend: Option<Box<dyn Fft>>,
+    compensate_window_gain: bool,
+}
+
+/// Window of a [`SpectrumAnalyzerBuilder`]. A [`Window`] is validated and
+/// calculated in [`SpectrumAnalyzerBuilder::build`].
+enum BuilderWindow {
+    /// Coefficients calculated by a [`WindowFunction`].
+    Coefficients(Vec<f32>),
+    /// See [`Window`].
+    Window(Window),
+}
+
+impl Debug for SpectrumAnalyzerBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpectrumAnalyzerBuilder")
+            .field("samples_len", &self.samples_len)
+            .field("sampling_rate", &self.sampling_rate)
+            .field("detrend", &self.detrend)
+            .field("pre_emphasis", &self.pre_emphasis)
+            .field("window", &self.window.is_some())
+            .field("frequency_limit", &self.frequency_limit)
+            .field("scaling_fn", &self.scaling_fn.is_some())
+            .field("padding", &self.padding)
+            .field("fft_backend", &self.fft_backend)
+            .field("compensate_window_gain", &self.compensate_window_gain)
+            .finish()
+    }
+}
+
+impl SpectrumAnalyzerBuilder {
+    /// Creates a new builder without detrending, pre-emphasis, window
+    /// function, frequency limit, scaling, and padding, that uses the default
+    /// [`FftBackend`].
+    ///
+    /// ## Parameters
+    /// * `samples_len` Number of samples passed to each
+    ///                 [`SpectrumAnalyzer::analyze`] call. Must be a power
+    ///                 of two, unless a [`Padding`] is configured or the
+    ///                 [`Fft`] of [`Self::fft_backend`] supports other
+    ///                 lengths.
+    /// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+    #[must_use]
+    pub const fn new(samples_len: usize, sampling_rate: u32) -> Self {
+        Self {
+            samples_len,
+            sampling_rate,
+            detrend: Detrend::None,
+            pre_emphasis: None,
+            window: None,
+            frequency_limit: FrequencyLimit::All,
+            scaling_fn: None,
+            padding: Padding::None,
+            fft_backend: None,
+            compensate_window_gain: false,
+        }
+    }
+
+    /// Sets how a trend, such as a DC offset, is removed from the samples
+    /// before the window function is applied. See [`Detrend`].
+    #[must_use]
+    pub const fn detrend(mut self, detrend: Detrend) -> Self {
+        self.detrend = detrend;
+        self
+    }
+
+    /// Enables the pre-emphasis filter with the given coefficient, e.g.
+    /// [`crate::pre_emphasis::DEFAULT_PRE_EMPHASIS`]. It is applied to the
+    /// samples of each [`SpectrumAnalyzer::analyze`] call after the
+    /// detrending and before the window function. See
+    /// [`crate::pre_emphasis::pre_emphasis`]. `alpha` must be in range
+    /// `0.0..=1.0`, which is checked by [`Self::build`].
+    #[must_use]
+    pub const fn pre_emphasis(mut self, alpha: f32) -> Self {
+        self.pre_emphasis = Some(alpha);
+        self
+    }
+
+    /// Sets the window function. The window is calculated once and applied
+    /// to the samples of each [`SpectrumAnalyzer::analyze`] call. Therefore,
+    /// the window function must multiply each sample with a coefficient that
+    /// only depends on the position of the sample, as the window functions in
+    /// [`crate::windows`] do.
+    #[must_use]
+    pub fn window_fn(mut self, window_fn: &WindowFunction) -> Self {
+        self.window = Some(BuilderWindow::Coefficients(window_fn(&vec![
+            1.0;
+            self.samples_len
+        ])));
+        self
+    }
+
+    /// Like [`Self::window_fn`] but takes the window as a configuration
+    /// value. See [`Window`]. The parameter of the window is checked by
+    /// [`Self::build`].
+    #[must_use]
+    pub fn window(mut self, window: Window) -> Self {
+        self.window = Some(BuilderWindow::Window(window));
+        self
+    }
+
+    /// Sets the frequency limit. See [`FrequencyLimit`].
+    #[must_use]
+    pub const fn frequency_limit(mut self, frequency_limit: FrequencyLimit) -> Self {
+        self.frequency_limit = frequency_limit;
+        self
+    }
+
+    /// Sets the scaling function. See
+    /// [`crate::scaling::SpectrumScalingFunction`].
+    #[must_use]
+    pub fn scaling_fn(
+        mut self,
+        scaling_fn: impl Fn(f32, &SpectrumDataStats) -> f32 + 'static,
+    ) -> Self {
+        self.scaling_fn = Some(Box::new(scaling_fn));
+        self
+    }
+
+    /// Sets the padding. See [`Padding`].
+    #[must_use]
+    pub const fn padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Enables or disables the automatic compensation of the window gain.
+    /// If enabled, the magnitudes are divided by the coherent gain of the
+    /// window (see [`crate::windows::WindowCoefficients::coherent_gain`]),
+    /// so that a sine has the same magnitude regardless of the window. The
+    /// compensation happens before the scaling function. It has no effect
+    /// without a window. Disabled by default.
+    #[must_use]
+    pub const fn compensate_window_gain(mut self, compensate: bool) -> Self {
+        self.compensate_window_gain = compensate;
+        self
+    }
+
+    /// Sets the FFT backend used by [`SpectrumAnalyzer::analyze`]. This is
+    /// either one of [`FftBackend`] or a custom implementation of [`Fft`].
+    #[must_use]
+    pub fn fft_backend(mut self, fft_backend: impl Fft + 'static) -> Self {
+        self.fft_backend = Some(Box::new(fft_backend));
+        self
+    }
+
+    /// Validates the configuration and creates the analyzer.
+    ///
+    /// ## Errors
+    /// * [`SpectrumAnalyzerError::TooFewSamples`] if `samples_len < 2`,
+    /// * the error of [`Fft::validate_len`] if the FFT backend doesn't
+    ///   support the padded length, e.g.
+    ///   [`SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo`] or
+    ///   [`SpectrumAnalyzerError::TooManySamples`] for [`FftBackend`],
+    /// * [`SpectrumAnalyzerError::InvalidFrequencyLimit`] if the frequency
+    ///   limit is invalid,
+    /// * [`SpectrumAnalyzerError::InvalidFrequencyResolution`] if no
+    ///   frequency of the FFT is inside the frequency limit,
+    /// * [`SpectrumAnalyzerError::InvalidPreEmphasis`] if the coefficient of
+    ///   [`Self::pre_emphasis`] isn't in range `0.0..=1.0`,
+    /// * [`SpectrumAnalyzerError::InvalidWindow`] if the parameter of the
+    ///   [`Window`] is out of range.
+    ///
+    /// ## Panics
+    /// See [`Padding::padded_len`].
+    pub fn build(mut self) -> Result<SpectrumAnalyzer, SpectrumAnalyzerError> {
+        if self.samples_len < 2 {
+            return Err(SpectrumAnalyzerError::TooFewSamples);
+        }
+        let fft_len = self.padding.padded_len(self.samples_len);
+        let fft_backend = self
+            .fft_backend
+            .take()
+            .unwrap_or_else(|| Box::new(FftBackend::MicrofftReal));
+        fft_backend.validate_len(fft_len)?;
+        validate_frequency_limit(self.sampling_rate, &self.frequency_limit)?;
+
+        let frequency_resolution =
+            fft_calc_frequency_resolution(self.sampling_rate, fft_len as u32);
+        let indices = (0..=fft_len / 2).collect::<Vec<_>>();
+        let bins = fft_result_with_frequencies(
+            fft_len,
+            &indices,
+            frequency_resolution,
+            self.frequency_limit,
+        )
+        .map(|(_fr, index)| *index)
+        .collect::<Vec<_>>();
+        // the frequency limit is too narrow for the frequency resolution
+        if bins.is_empty() {
+            return Err(SpectrumAnalyzerError::InvalidFrequencyResolution(
+                frequency_resolution,
+            ));
+        }
+        let bins = bins[0]..bins[bins.len() - 1] + 1;
+
+        if let Some(alpha) = self.pre_emphasis {
+            if !(0.0..=1.0).contains(&alpha) {
+                return Err(SpectrumAnalyzerError::InvalidPreEmphasis(alpha));
+            }
+        }
+        let mut window = match self.window.take() {
+            None => None,
+            Some(BuilderWindow::Coefficients(coefficients)) => Some(coefficients),
+            Some(BuilderWindow::Window(window)) => {
+                window.try_validate()?;
+                Some(window.coefficients(self.samples_len))
+            }
+        };
+
+        // As the FFT is linear, scaling the window scales the magnitudes.
+        if let (true, Some(window)) = (self.compensate_window_gain, &mut window) {
+            let coherent_gain = window.iter().sum::<f32>() / window.len() as f32;
+            window.iter_mut().for_each(|w| *w /= coherent_gain);
+        }
+
+        Ok(SpectrumAnalyzer {
+            samples_len: self.samples_len,
+            sampling_rate: self.sampling_rate,
+            detrend: self.detrend,
+            pre_emphasis: self.pre_emphasis,
+            window,
+            frequency_limit: self.frequency_limit,
+            scaling_fn: self.scaling_fn,
+            fft_backend,
+            buffer: vec![0.0; fft_len],
+            frequency_resolution,
+            scratch: vec![0.0; bins.len()],
+            bins,
+        })
+    }
+}
+
+/// Reusable spectrum analyzer for real-time loops. It holds its
+/// configuration, the precalculated window, and the FFT input buffer, so
+/// they are not recreated on each call. Create it with a
+/// [`SpectrumAnalyzerBuilder`].
+pub struct SpectrumAnalyzer {
+    samples_len: usize,
+    sampling_rate: u32,
+    detrend: Detrend,
+    pre_emphasis: Option<f32>,
+    window: Option<Vec<f32>>,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<Box<SpectrumScalingFunction>>,
+    fft_backend: Box<dyn Fft>,
+    /// Windowed and padded samples. Has the length of the FFT.
+    buffer: Vec<f32>,
+    frequency_resolution: f32,
+    /// Indices of the FFT result that are inside the frequency limit.
+    bins: Range<usize>,
+    /// Buffer for the median calculation of [`Self::analyze_into`].
+    scratch: Vec<f32>,
+}
+
+impl Debug for SpectrumAnalyzer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpectrumAnalyzer")
+            .field("samples_len", &self.samples_len)
+            .field("fft_len", &self.buffer.len())
+            .field("sampling_rate", &self.sampling_rate)
+            .field("detrend", &self.detrend)
+            .field("pre_emphasis", &self.pre_emphasis)
+            .field("window", &self.window.is_some())
+            .field("frequency_limit", &self.frequency_limit)
+            .field("scaling_fn", &self.scaling_fn.is_some())
+            .field("fft_backend", &self.fft_backend)
+            .finish()
+    }
+}
+
+impl SpectrumAnalyzer {
+    /// Returns the number of samples expected by [`Self::analyze`].
+    #[must_use]
+    pub const fn samples_len(&self) -> usize {
+        self.samples_len
+    }
+
+    /// Returns the length of the FFT, i.e. the number of samples after the
+    /// padding.
+    #[must_use]
+    pub fn fft_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the sampling rate.
+    #[must_use]
+    pub const fn sampling_rate(&self) -> u32 {
+        self.sampling_rate
+    }
+
+    /// Returns the number of (frequency, frequency value)-pairs of each
+    /// spectrum, i.e. the required length of the output buffer of
+    /// [`Self::analyze_into`].
+    #[must_use]
+    pub fn bins_len(&self) -> usize {
+        self.bins.len()
+    }
+
+    /// Applies the window function and the padding to the samples, and
+    /// calculates the spectrum. See [`crate::samples_fft_to_spectrum`].
+    ///
+    /// ## Parameters
+    /// * `samples` Raw samples without window function. The length must equal
+    ///             [`Self::samples_len`].
+    ///
+    /// ## Errors
+    /// * [`SpectrumAnalyzerError::SamplesLengthMismatch`] if the number of
+    ///   samples doesn't match,
+    /// * [`SpectrumAnalyzerError::NaNValuesNotSupported`] or
+    ///   [`SpectrumAnalyzerError::InfinityValuesNotSupported`] for invalid
+    ///   samples,
+    /// * [`SpectrumAnalyzerError::ScalingError`] if the scaling function
+    ///   produced invalid values.
+    ///
+    /// ## Panics
+    /// If a custom [`Fft`] returns a result of the wrong length.
+    pub fn analyze(&mut self, samples: &[f32]) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+        self.prepare_buffer(samples)?;
+        let fft_res = calc_checked(self.fft_backend.as_ref(), &self.buffer);
+        let mut spectrum = fft_result_to_spectrum(
+            self.buffer.len(),
+            &fft_res,
+            self.sampling_rate,
+            self.frequency_limit,
+            self.scaling_fn.as_deref(),
+        )?;
+        spectrum.set_unpadded_samples_len(self.samples_len as u32);
+        Ok(spectrum)
+    }
+
+    /// Like [`Self::analyze`] but writes the (frequency, frequency value)-pairs
+    /// into the caller-provided buffer instead of creating a
+    /// [`FrequencySpectrum`]. The FFT is calculated in-place. Hence, this
+    /// performs no heap allocation at all, which makes it suitable for hard
+    /// real-time audio threads. This always uses
+    /// [`FftBackend::MicrofftReal`], as only the real FFT can reuse the
+    /// memory of the samples, even if another FFT backend is configured.
+    ///
+    /// ## Parameters
+    /// * `samples` See [`Self::analyze`].
+    /// * `out` Output buffer with length [`Self::bins_len`]. It contains the
+    ///         pairs sorted from the lowest to the highest frequency
+    ///         afterwards.
+    ///
+    /// ## Errors
+    /// * [`SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo`] or
+    ///   [`SpectrumAnalyzerError::TooManySamples`] if
+    ///   [`FftBackend::MicrofftReal`] doesn't support the FFT length, which
+    ///   is only possible with a custom FFT backend,
+    /// * see [`Self::analyze`] for the other errors.
+    ///
+    /// ## Panics
+    /// If `out` doesn't have the length [`Self::bins_len`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// # #[cfg(feature = "size-1024")] {
+    /// use spectrum_analyzer::SpectrumAnalyzerBuilder;
+    /// let mut analyzer = SpectrumAnalyzerBuilder::new(1024, 44100).build().unwrap();
+    /// // setup: allocate once
+    /// let mut out = vec![(0.0.into(), 0.0.into()); analyzer.bins_len()];
+    /// // audio callback: no allocations
+    /// # let samples = [0.0; 1024];
+    /// analyzer.analyze_into(&samples, &mut out).unwrap();
+    /// # }
+    /// ```
+    pub fn analyze_into(
+        &mut self,
+        samples: &[f32],
+        out: &mut [(Frequency, FrequencyValue)],
+    ) -> Result<(), SpectrumAnalyzerError> {
+        assert_eq!(
+            out.len(),
+            self.bins.len(),
+            "The output buffer must have the length `bins_len()`!"
+        );
+        validate_fft_len(self.buffer.len())?;
+        self.prepare_buffer(samples)?;
+
+        let fft_len = self.buffer.len();
+        let fft_res = FftImpl::calc_in_place(&mut self.buffer);
+        // the real value of the Nyquist frequency is packed inside the DC
+        // component
+        let nyquist_fr_val = fft_res[0].im;
+        fft_res[0].im = 0.0;
+        for (pair, index) in out.iter_mut().zip(self.bins.clone()) {
+            let magnitude = if index == fft_len / 2 {
+                libm::fabsf(nyquist_fr_val)
+            } else {
+                complex_to_magnitude(&fft_res[index])
+            };
+            *pair = (
+                (index as f32 * self.frequency_resolution).into(),
+                magnitude.into(),
+            );
+        }
+
+        if let Some(scaling_fn) = &self.scaling_fn {
+            let stats = calc_stats(out, &mut self.scratch, fft_len);
+            for (_fr, fr_val) in out.iter_mut() {
+                let scaled_val = scaling_fn(fr_val.val(), &stats);
+                if scaled_val.is_nan() || scaled_val.is_infinite() {
+                    return Err(SpectrumAnalyzerError::ScalingError(
+                        fr_val.val(),
+                        scaled_val,
+                    ));
+                }
+                *fr_val = scaled_val.into();
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the samples and writes them with the trend removed, the
+    /// pre-emphasis and the window applied, and the padding into
+    /// [`Self::buffer`].
+    fn prepare_buffer(&mut self, samples: &[f32]) -> Result<(), SpectrumAnalyzerError> {
+        if samples.len() != self.samples_len {
+            return Err(SpectrumAnalyzerError::SamplesLengthMismatch(
+                self.samples_len,
+                samples.len(),
+            ));
+        }
+        validate_samples(samples)?;
+
+        let (windowed, padding) = self.buffer.split_at_mut(samples.len());
+        windowed.copy_from_slice(samples);
+        self.detrend.apply(windowed);
+        if let Some(alpha) = self.pre_emphasis {
+            pre_emphasis(windowed, alpha);
+        }
+        if let Some(window) = &self.window {
+            windowed
+                .iter_mut()
+                .zip(window)
+                .for_each(|(sample, coefficient)| *sample *= coefficient);
+        }
+        padding.fill(0.0);
+        Ok(())
+    }
+}
+
+/// Calculates the statistics for the scaling function without heap
+/// allocations, equivalent to the statistics of a [`FrequencySpectrum`].
+/// The median is found by selection in `scratch` instead of sorting.
+fn calc_stats(
+    data: &[(Frequency, FrequencyValue)],
+    scratch: &mut [f32],
+    samples_len: usize,
+) -> SpectrumDataStats {
+    let mut min = f32::INFINITY;
+    let mut max = f32::NEG_INFINITY;
+    let mut sum = 0.0;
+    let mut sum_of_squares = 0.0;
+    for ((_fr, fr_val), scratch_val) in data.iter().zip(scratch.iter_mut()) {
+        let val = fr_val.val();
+        min = min.min(val);
+        max = max.max(val);
+        sum += val;
+        sum_of_squares += val * val;
+        *scratch_val = val;
+    }
+
+    // same definition as for `FrequencySpectrum`: the mean of the elements
+    // `len / 2 - 1` and `len / 2` of the sorted values
+    let mid = scratch.len() / 2;
+    let (lower, upper, _) = scratch.select_nth_unstable_by(mid, f32::total_cmp);
+    let upper = *upper;
+    let lower = lower.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let lower = if lower.is_finite() { lower } else { upper };
+
+    let average = sum / data.len() as f32;
+    SpectrumDataStats {
+        min,
+        max,
+        average,
+        median: (lower + upper) / 2.0,
+        sum,
+        sum_of_squares,
+        std_dev: std_dev(sum_of_squares, average, data.len()),
+        n: samples_len as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pre_emphasis::DEFAULT_PRE_EMPHASIS;
+    use crate::scaling::divide_by_N;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, MAX_SAMPLES_LEN};
+
+    fn samples(len: usize) -> Vec<f32> {
+        (0..len).map(|i| libm::sinf(i as f32 * 0.3)).collect()
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_same_result_as_function() {
+        let samples = samples(256);
+        let mut analyzer = SpectrumAnalyzerBuilder::new(256, 44100)
+            .window_fn(&hann_window)
+            .frequency_limit(FrequencyLimit::Max(10000.0))
+            .scaling_fn(divide_by_N)
+            .build()
+            .unwrap();
+        let expected = samples_fft_to_spectrum(
+            &hann_window(&samples),
+            44100,
+            FrequencyLimit::Max(10000.0),
+            Some(&divide_by_N),
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            let spectrum = analyzer.analyze(&samples).unwrap();
+            assert_eq!(spectrum.data(), expected.data());
+            assert_eq!(spectrum.max(), expected.max());
+        }
+
+        let mut analyzer = SpectrumAnalyzerBuilder::new(256, 44100)
+            .window(Window::Hann)
+            .frequency_limit(FrequencyLimit::Max(10000.0))
+            .scaling_fn(divide_by_N)
+            .build()
+            .unwrap();
+        assert_eq!(analyzer.analyze(&samples).unwrap().data(), expected.data());
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_fft_backends() {
+        let samples = samples(1024);
+        let mut real = SpectrumAnalyzerBuilder::new(1024, 44100).build().unwrap();
+        let mut complex = SpectrumAnalyzerBuilder::new(1024, 44100)
+            .fft_backend(FftBackend::MicrofftComplex)
+            .build()
+            .unwrap();
+        let real = real.analyze(&samples).unwrap();
+        let complex = complex.analyze(&samples).unwrap();
+
+        assert_eq!(real.data().len(), complex.data().len());
+        for ((fr_a, val_a), (fr_b, val_b)) in real.data().iter().zip(complex.data()) {
+            assert_eq!(fr_a, fr_b);
+            assert!(
+                (val_a.val() - val_b.val()).abs() < 0.01,
+                "{val_a} != {val_b}"
+            );
+        }
+    }
+
+    #[cfg(all(feature = "rustfft", feature = "size-1024"))]
+    #[test]
+    fn test_rustfft_backend() {
+        let samples = samples(1024);
+        let mut real = SpectrumAnalyzerBuilder::new(1024, 44100).build().unwrap();
+        let mut rustfft = SpectrumAnalyzerBuilder::new(1024, 44100)
+            .fft_backend(FftBackend::RustFft)
+            .build()
+            .unwrap();
+        let real = real.analyze(&samples).unwrap();
+        let rustfft = rustfft.analyze(&samples).unwrap();
+        for ((fr_a, val_a), (fr_b, val_b)) in real.data().iter().zip(rustfft.data()) {
+            assert_eq!(fr_a, fr_b);
+            assert!(
+                (val_a.val() - val_b.val()).abs() < 0.01,
+                "{val_a} != {val_b}"
+            );
+        }
+
+        // no power of two and no padding
+        let mut analyzer = SpectrumAnalyzerBuilder::new(1000, 44100)
+            .fft_backend(FftBackend::RustFft)
+            .build()
+            .unwrap();
+        let spectrum = analyzer.analyze(&samples[0..1000]).unwrap();
+        assert_eq!(spectrum.data().len(), 501);
+        assert_eq!(spectrum.frequency_resolution(), 44.1);
+        assert!(matches!(
+            SpectrumAnalyzerBuilder::new(1000, 44100).build(),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_padding() {
+        let mut analyzer = SpectrumAnalyzerBuilder::new(1000, 44100)
+            .padding(Padding::ZeroToNextPowerOfTwo)
+            .build()
+            .unwrap();
+        assert_eq!(analyzer.fft_len(), 1024);
+        let spectrum = analyzer.analyze(&samples(1000)).unwrap();
+        assert_eq!(spectrum.samples_len(), 1024);
+        assert_eq!(spectrum.unpadded_samples_len(), 1000);
+
+        assert!(matches!(
+            analyzer.analyze(&samples(1024)),
+            Err(SpectrumAnalyzerError::SamplesLengthMismatch(1000, 1024))
+        ));
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_detrend() {
+        // sine exactly at 1000 Hz on top of a DC offset and a drift
+        let samples = (0..1024)
+            .map(|i| {
+                libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 8192.0)
+                    + 5.0
+                    + 0.01 * i as f32
+            })
+            .collect::<Vec<_>>();
+        // returns the value at 0 Hz and the frequency with the maximum value
+        let analyze = |detrend| {
+            let mut analyzer = SpectrumAnalyzerBuilder::new(1024, 8192)
+                .detrend(detrend)
+                .window(Window::Hann)
+                .build()
+                .unwrap();
+            let spectrum = analyzer.analyze(&samples).unwrap();
+            (spectrum.freq_val_exact(0.0).val(), spectrum.max().0.val())
+        };
+        let (dc_none, max_none) = analyze(Detrend::None);
+        let (dc_mean, _) = analyze(Detrend::Mean);
+        let (dc_linear, max_linear) = analyze(Detrend::Linear);
+        assert_eq!(max_none, 0.0);
+        assert_eq!(max_linear, 1000.0);
+        assert!(dc_mean < dc_none / 100.0, "{dc_mean} {dc_none}");
+        assert!(dc_linear < 1e-2, "{dc_linear}");
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_pre_emphasis() {
+        let samples = samples(1024);
+        let mut analyzer = SpectrumAnalyzerBuilder::new(1024, 44100)
+            .pre_emphasis(DEFAULT_PRE_EMPHASIS)
+            .build()
+            .unwrap();
+        let mut emphasized = samples.clone();
+        pre_emphasis(&mut emphasized, DEFAULT_PRE_EMPHASIS);
+        let expected =
+            samples_fft_to_spectrum(&emphasized, 44100, FrequencyLimit::All, None).unwrap();
+        assert_eq!(analyzer.analyze(&samples).unwrap().data(), expected.data());
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_invalid_pre_emphasis_and_window() {
+        assert!(matches!(
+            SpectrumAnalyzerBuilder::new(8, 44100)
+                .pre_emphasis(-0.5)
+                .build(),
+            Err(SpectrumAnalyzerError::InvalidPreEmphasis(alpha)) if alpha == -0.5
+        ));
+        for window in [
+            Window::Kaiser(-1.0),
+            Window::Tukey(2.0),
+            Window::Tukey(f32::NAN),
+        ] {
+            assert!(matches!(
+                SpectrumAnalyzerBuilder::new(8, 44100)
+                    .window(window)
+                    .build(),
+                Err(SpectrumAnalyzerError::InvalidWindow(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_compensate_window_gain() {
+        // sine exactly at 1000 Hz with amplitude 1.0
+        let samples = (0..1024)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        for window in [Window::Rectangular, Window::Hann, Window::Blackman] {
+            let mut analyzer = SpectrumAnalyzerBuilder::new(1024, 8192)
+                .window(window)
+                .compensate_window_gain(true)
+                .scaling_fn(divide_by_N)
+                .build()
+                .unwrap();
+            let amplitude = 2.0 * analyzer.analyze(&samples).unwrap().max().1.val();
+            assert!((amplitude - 1.0).abs() < 1e-4, "{window:?}: {amplitude}");
+        }
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_invalid_config() {
+        assert!(matches!(
+            SpectrumAnalyzerBuilder::new(1000, 44100).build(),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+        assert!(matches!(
+            SpectrumAnalyzerBuilder::new(MAX_SAMPLES_LEN * 2, 44100).build(),
+            Err(SpectrumAnalyzerError::TooManySamples)
+        ));
+        assert!(matches!(
+            SpectrumAnalyzerBuilder::new(1024, 44100)
+                .frequency_limit(FrequencyLimit::Min(30000.0))
+                .build(),
+            Err(SpectrumAnalyzerError::InvalidFrequencyLimit(_))
+        ));
+        assert!(matches!(
+            SpectrumAnalyzerBuilder::new(16, 1600)
+                .frequency_limit(FrequencyLimit::Range(110.0, 190.0))
+                .build(),
+            Err(SpectrumAnalyzerError::InvalidFrequencyResolution(_))
+        ));
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_analyze_into() {
+        let samples = samples(1000);
+        let mut analyzer = SpectrumAnalyzerBuilder::new(1000, 44100)
+            .window_fn(&hann_window)
+            .frequency_limit(FrequencyLimit::Range(100.0, 10000.0))
+            .scaling_fn(|val, stats| (val - stats.median) / (stats.max - stats.min) + stats.average)
+            .padding(Padding::ZeroToNextPowerOfTwo)
+            .build()
+            .unwrap();
+        let expected = analyzer.analyze(&samples).unwrap();
+        assert_eq!(analyzer.bins_len(), expected.data().len());
+
+        let mut out = vec![(0.0.into(), 0.0.into()); analyzer.bins_len()];
+        analyzer.analyze_into(&samples, &mut out).unwrap();
+        for ((fr, val), (expected_fr, expected_val)) in out.iter().zip(expected.data()) {
+            assert_eq!(fr, expected_fr);
+            float_cmp::assert_approx_eq!(f32, val.val(), expected_val.val(), epsilon = 0.0001);
+        }
+
+        // with DC and Nyquist frequency
+        let mut analyzer = SpectrumAnalyzerBuilder::new(8, 8).build().unwrap();
+        let samples = [1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, 1.0];
+        let expected = analyzer.analyze(&samples).unwrap();
+        let mut out = vec![(0.0.into(), 0.0.into()); 5];
+        analyzer.analyze_into(&samples, &mut out).unwrap();
+        assert_eq!(out, expected.data());
+    }
+}
@@ -0,0 +1,116 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Rendering of a spectrum as ASCII bar chart for quick debugging on the
+//! command line or in CI logs. See [`render_ascii`].
+
+use crate::bars::{BarReduction, FrequencyScale};
+use crate::spectrum::FrequencySpectrum;
+use alloc::format;
+use alloc::string::String;
+
+/// Renders the spectrum as ASCII bar chart with `width` columns and `height`
+/// rows of bars, followed by a line with the lowest and the highest
+/// frequency. Each column is a bar of equally wide frequency ranges with the
+/// maximum value of the range (see [`FrequencySpectrum::to_bars`]). The bars
+/// are scaled relative to the maximum of the spectrum. Lines are separated
+/// by `\n`.
+///
+/// ## Panics
+/// If `width` or `height` is zero.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-16")] {
+/// use spectrum_analyzer::ascii::render_ascii;
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+/// let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// println!("{}", render_ascii(&spectrum, 40, 8));
+/// # }
+/// ```
+#[must_use]
+pub fn render_ascii(spectrum: &FrequencySpectrum, width: usize, height: usize) -> String {
+    assert!(width > 0, "width must not be zero!");
+    assert!(height > 0, "height must not be zero!");
+
+    let bars = spectrum.to_bars(width, FrequencyScale::Linear, BarReduction::Max);
+    let max = spectrum.max().1.val();
+    let bar_heights = bars
+        .iter()
+        .map(|bar| {
+            if max > 0.0 {
+                libm::roundf(bar.value.val().max(0.0) / max * height as f32) as usize
+            } else {
+                0
+            }
+        })
+        .collect::<alloc::vec::Vec<_>>();
+
+    let mut chart = String::with_capacity((width + 1) * (height + 1));
+    for row in 0..height {
+        let min_height = height - row;
+        chart.extend(bar_heights.iter().map(
+            |&bar_height| {
+                if bar_height >= min_height {
+                    '#'
+                } else {
+                    ' '
+                }
+            },
+        ));
+        chart.push('\n');
+    }
+
+    let min_label = format!("{}Hz", spectrum.min_fr());
+    let max_label = format!("{}Hz", spectrum.max_fr());
+    let padding = width
+        .saturating_sub(min_label.len() + max_label.len())
+        .max(1);
+    chart.push_str(&min_label);
+    chart.extend(core::iter::repeat(' ').take(padding));
+    chart.push_str(&max_label);
+    chart
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frequency::{Frequency, FrequencyValue};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_render_ascii() {
+        // 0, 100, ..., 700 Hz
+        let data = [0.0, 1.0, 4.0, 2.0, 0.0, 0.0, 0.0, 3.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| (Frequency::from(i as f32 * 100.0), FrequencyValue::from(val)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        let spectrum = FrequencySpectrum::new(data, 100.0, 16, &mut working_buffer);
+        let chart = render_ascii(&spectrum, 8, 4);
+        let expected = "  #     \n  #    #\n  ##   #\n ###   #\n0Hz 700Hz";
+        assert_eq!(chart, expected);
+    }
+}
@@ -0,0 +1,349 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Analysis of compressed audio files, such as MP3, FLAC, and OGG Vorbis,
+//! decoded with `symphonia`. The samples of all channels are mixed down to
+//! mono and analyzed frame by frame with the [`StreamingAnalyzer`], which
+//! results in a [`Spectrogram`]. See [`analyze_audio_file`] and
+//! [`analyze_audio`]. Needs the `symphonia` feature.
+//!
+//! Uncompressed WAV files are supported as well, but [`crate::wav`] works
+//! without `std` and additional dependencies.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::multichannel::downmix;
+use crate::spectrogram::Spectrogram;
+use crate::windows::WindowFunction;
+use crate::{FrequencyLimit, SpectrumAnalyzerBuilder, StreamingAnalyzer};
+use alloc::boxed::Box;
+use core::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Possible errors of [`analyze_audio`] and [`analyze_audio_file`].
+#[derive(Debug)]
+pub enum AudioFileError {
+    /// The file couldn't be opened.
+    Io(std::io::Error),
+    /// The file couldn't be decoded, e.g. because its format is unknown or
+    /// unsupported.
+    Decode(SymphoniaError),
+    /// The file contains no audio track with a known sampling rate.
+    NoAudioTrack,
+    /// The frame or hop length is invalid, or the analysis of a frame
+    /// failed. See [`SpectrumAnalyzerError`].
+    Analysis(SpectrumAnalyzerError),
+}
+
+impl Display for AudioFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "can't open audio file: {e}"),
+            Self::Decode(e) => write!(f, "can't decode audio file: {e}"),
+            Self::NoAudioTrack => write!(f, "no audio track found"),
+            Self::Analysis(e) => write!(f, "analysis failed: {e:?}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioFileError {}
+
+impl From<SymphoniaError> for AudioFileError {
+    fn from(e: SymphoniaError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+impl From<SpectrumAnalyzerError> for AudioFileError {
+    fn from(e: SpectrumAnalyzerError) -> Self {
+        Self::Analysis(e)
+    }
+}
+
+/// Result of [`analyze_audio`].
+#[derive(Debug)]
+pub struct AudioFileAnalysis {
+    /// Sampling rate of the audio track.
+    pub sampling_rate: u32,
+    /// Number of channels of the audio track. The channels are mixed down to
+    /// mono before the analysis.
+    pub channels: usize,
+    /// Spectrum of each frame with the time of its beginning in seconds.
+    pub spectrogram: Spectrogram,
+}
+
+/// Opens the audio file at `path` and analyzes it. The file extension is
+/// used as hint for the format. See [`analyze_audio`].
+///
+/// ## Errors
+/// [`AudioFileError::Io`] if the file can't be opened. Otherwise, see
+/// [`analyze_audio`].
+pub fn analyze_audio_file(
+    path: impl AsRef<Path>,
+    frame_len: usize,
+    hop_len: usize,
+    window_fn: Option<&WindowFunction>,
+    frequency_limit: FrequencyLimit,
+) -> Result<AudioFileAnalysis, AudioFileError> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path).map_err(AudioFileError::Io)?;
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    analyze_audio(
+        file,
+        extension,
+        frame_len,
+        hop_len,
+        window_fn,
+        frequency_limit,
+    )
+}
+
+/// Decodes the first audio track of the audio file, mixes all channels down
+/// to mono, and analyzes each frame of `frame_len` samples. Incomplete frames
+/// at the end are ignored. Packets with malformed data are skipped.
+///
+/// ## Parameters
+/// * `source` Content of the audio file, e.g. a [`std::fs::File`] or a
+///            [`std::io::Cursor`] of the bytes.
+/// * `extension` Optional file extension, e.g. `"mp3"`, as hint for the
+///               format. The format is detected from the content otherwise.
+/// * `frame_len` Number of samples of each frame. Must be a power of two.
+/// * `hop_len` Number of samples between the beginnings of two frames, e.g.
+///             `frame_len / 2` for an overlap of 50%. Must not be zero.
+/// * `window_fn` Optional window function that is applied to each frame,
+///               e.g. [`crate::windows::hann_window`].
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+///
+/// ## Example
+/// ```rust,no_run
+/// use spectrum_analyzer::audio_file::analyze_audio_file;
+/// use spectrum_analyzer::windows::hann_window;
+/// use spectrum_analyzer::FrequencyLimit;
+/// let analysis = analyze_audio_file("song.mp3", 2048, 1024, Some(&hann_window), FrequencyLimit::All)
+///     .unwrap();
+/// for (time, spectrum) in analysis.spectrogram.iter() {
+///     println!("{time:.2}s: {}", spectrum.max().0);
+/// }
+/// ```
+///
+/// ## Errors
+/// * [`AudioFileError::Decode`] or [`AudioFileError::NoAudioTrack`] if the
+///   audio file can't be decoded,
+/// * [`AudioFileError::Analysis`] with
+///   [`SpectrumAnalyzerError::InvalidHopLength`] if `hop_len` is zero, and
+///   see [`crate::SpectrumAnalyzerBuilder::build`] for the other errors.
+pub fn analyze_audio(
+    source: impl MediaSource + 'static,
+    extension: Option<&str>,
+    frame_len: usize,
+    hop_len: usize,
+    window_fn: Option<&WindowFunction>,
+    frequency_limit: FrequencyLimit,
+) -> Result<AudioFileAnalysis, AudioFileError> {
+    if hop_len == 0 {
+        return Err(SpectrumAnalyzerError::InvalidHopLength.into());
+    }
+
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    let stream = MediaSourceStream::new(Box::new(source), Default::default());
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| {
+            track.codec_params.codec != CODEC_TYPE_NULL && track.codec_params.sample_rate.is_some()
+        })
+        .ok_or(AudioFileError::NoAudioTrack)?;
+    let track_id = track.id;
+    let sampling_rate = track.codec_params.sample_rate.unwrap();
+    let mut decoder =
+        symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut builder =
+        SpectrumAnalyzerBuilder::new(frame_len, sampling_rate).frequency_limit(frequency_limit);
+    if let Some(window_fn) = window_fn {
+        builder = builder.window_fn(window_fn);
+    }
+    let mut streaming = StreamingAnalyzer::new(builder.build()?, hop_len);
+
+    let mut channels = 0;
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    let mut spectrogram = Spectrogram::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let spec = *decoded.spec();
+        channels = spec.channels.count();
+        // the capacity of a packet rarely changes, so the buffer is reused
+        if buffer.as_ref().map_or(true, |buffer| {
+            buffer.capacity() < decoded.capacity() * channels
+        }) {
+            buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+        }
+        let buffer = buffer.as_mut().unwrap();
+        buffer.copy_interleaved_ref(decoded);
+
+        for spectrum in streaming.push(&downmix(buffer.samples(), channels))? {
+            let time = (spectrogram.len() * hop_len) as f32 / sampling_rate as f32;
+            spectrogram.push(time, spectrum?);
+        }
+    }
+
+    Ok(AudioFileAnalysis {
+        sampling_rate,
+        channels,
+        spectrogram,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use alloc::vec::Vec;
+    use core::f32::consts::PI;
+    use std::io::Cursor;
+
+    /// Creates a 16 bit mono WAV file with a sine wave.
+    fn wav_16bit_mono(frequency: f32, sampling_rate: u32, len: usize) -> Vec<u8> {
+        let data_len = (len * 2) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16_u32.to_le_bytes());
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&sampling_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sampling_rate * 2).to_le_bytes());
+        bytes.extend_from_slice(&2_u16.to_le_bytes());
+        bytes.extend_from_slice(&16_u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for i in 0..len {
+            let t = i as f32 / sampling_rate as f32;
+            let sample = (libm::sinf(2.0 * PI * frequency * t) * 16384.0) as i16;
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_analyze_audio() {
+        let bytes = wav_16bit_mono(1000.0, 8000, 4000);
+        let analysis = analyze_audio(
+            Cursor::new(bytes),
+            Some("wav"),
+            1024,
+            512,
+            Some(&hann_window),
+            FrequencyLimit::All,
+        )
+        .unwrap();
+        assert_eq!(analysis.sampling_rate, 8000);
+        assert_eq!(analysis.channels, 1);
+        // frames start at 0, 512, ..., 2560
+        assert_eq!(analysis.spectrogram.len(), 6);
+        assert_eq!(analysis.spectrogram.times()[2], 0.128);
+        for (_time, spectrum) in analysis.spectrogram.iter() {
+            assert_eq!(spectrum.max().0.val(), 1000.0);
+        }
+    }
+
+    #[cfg(feature = "size-2048")]
+    #[test]
+    fn test_analyze_mp3_file() {
+        let analysis = analyze_audio_file(
+            "test/samples/high-hat-sample.mp3",
+            2048,
+            2048,
+            None,
+            FrequencyLimit::All,
+        )
+        .unwrap();
+        assert_eq!(analysis.sampling_rate, 44100);
+        assert!(!analysis.spectrogram.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_audio() {
+        assert!(matches!(
+            analyze_audio(
+                Cursor::new(vec![0; 64]),
+                None,
+                8,
+                8,
+                None,
+                FrequencyLimit::All
+            ),
+            Err(AudioFileError::Decode(_))
+        ));
+        assert!(matches!(
+            analyze_audio(
+                Cursor::new(wav_16bit_mono(1000.0, 8000, 16)),
+                Some("wav"),
+                8,
+                0,
+                None,
+                FrequencyLimit::All
+            ),
+            Err(AudioFileError::Analysis(
+                SpectrumAnalyzerError::InvalidHopLength
+            ))
+        ));
+        assert!(matches!(
+            analyze_audio_file("does-not-exist.mp3", 8, 8, None, FrequencyLimit::All),
+            Err(AudioFileError::Io(_))
+        ));
+    }
+}
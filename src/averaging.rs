@@ -0,0 +1,463 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Averaging of multiple spectra, e.g. of consecutive frames of a signal.
+//! - [`SpectrumAverager`] reduces the variance of noise measurements.
+//! - [`SpectrumSmoother`] applies the frame-to-frame ballistics (attack,
+//!   release, and peak-hold) that audio visualizers need.
+
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// How a [`SpectrumAverager`] averages the spectra.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AveragingMode {
+    /// Arithmetic mean of the values.
+    Linear,
+    /// Root mean square of the values, i.e. the mean of the power. This is
+    /// the correct average for noise measurements.
+    Power,
+    /// Exponential moving average with the given smoothing factor in range
+    /// `(0.0, 1.0]`. The newest spectrum has the weight of the factor. Useful
+    /// for continuous streams, as older spectra fade out.
+    Exponential(f32),
+}
+
+/// Accumulates spectra of the same size and frequencies and yields their
+/// average as new [`FrequencySpectrum`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::averaging::{AveragingMode, SpectrumAverager};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let mut averager = SpectrumAverager::new(AveragingMode::Power);
+/// # let frames: Vec<Vec<f32>> = vec![vec![0.0, 1.1, 5.5, -5.5]; 4];
+/// for frame in frames {
+///     let spectrum = samples_fft_to_spectrum(&frame, 44100, FrequencyLimit::All, None).unwrap();
+///     averager.add(&spectrum);
+/// }
+/// let average = averager.average().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpectrumAverager {
+    mode: AveragingMode,
+    /// Frequencies of the first spectrum.
+    frequencies: Vec<Frequency>,
+    /// Sum of the values or sum of the squared values or the current
+    /// exponential average, depending on [`Self::mode`].
+    accumulator: Vec<f32>,
+    count: usize,
+    frequency_resolution: f32,
+    samples_len: u32,
+}
+
+impl SpectrumAverager {
+    /// Creates a new averager.
+    ///
+    /// ## Panics
+    /// If the smoothing factor of [`AveragingMode::Exponential`] is not in
+    /// range `(0.0, 1.0]`.
+    #[must_use]
+    pub fn new(mode: AveragingMode) -> Self {
+        if let AveragingMode::Exponential(alpha) = mode {
+            assert!(
+                alpha > 0.0 && alpha <= 1.0,
+                "smoothing factor must be in range (0.0, 1.0]!"
+            );
+        }
+        Self {
+            mode,
+            frequencies: Vec::new(),
+            accumulator: Vec::new(),
+            count: 0,
+            frequency_resolution: 0.0,
+            samples_len: 0,
+        }
+    }
+
+    /// Adds a spectrum to the average.
+    ///
+    /// ## Panics
+    /// If the spectrum has other frequencies than the previous spectra.
+    pub fn add(&mut self, spectrum: &FrequencySpectrum) {
+        let data = spectrum.data();
+        if self.count == 0 {
+            self.frequencies = data.iter().map(|(fr, _val)| *fr).collect();
+            self.accumulator = alloc::vec![0.0; data.len()];
+            self.frequency_resolution = spectrum.frequency_resolution();
+            self.samples_len = spectrum.samples_len();
+        } else {
+            assert!(
+                data.len() == self.frequencies.len()
+                    && data[0].0 == self.frequencies[0]
+                    && spectrum.frequency_resolution() == self.frequency_resolution,
+                "All spectra must have the same frequencies!"
+            );
+        }
+
+        for (acc, (_fr, val)) in self.accumulator.iter_mut().zip(data) {
+            let val = val.val();
+            *acc = match self.mode {
+                AveragingMode::Linear => *acc + val,
+                AveragingMode::Power => *acc + val * val,
+                AveragingMode::Exponential(_) if self.count == 0 => val,
+                AveragingMode::Exponential(alpha) => alpha * val + (1.0 - alpha) * *acc,
+            };
+        }
+        self.count += 1;
+    }
+
+    /// Returns the number of spectra added since the creation or the last
+    /// [`Self::reset`].
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the averaged spectrum or `None` if no spectrum was added yet.
+    #[must_use]
+    pub fn average(&self) -> Option<FrequencySpectrum> {
+        if self.count == 0 {
+            return None;
+        }
+        let count = self.count as f32;
+        let data = self
+            .frequencies
+            .iter()
+            .zip(&self.accumulator)
+            .map(|(fr, acc)| {
+                let val = match self.mode {
+                    AveragingMode::Linear => acc / count,
+                    AveragingMode::Power => libm::sqrtf(acc / count),
+                    AveragingMode::Exponential(_) => *acc,
+                };
+                (*fr, FrequencyValue::from(val))
+            })
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        Some(FrequencySpectrum::new(
+            data,
+            self.frequency_resolution,
+            self.samples_len,
+            &mut working_buffer,
+        ))
+    }
+
+    /// Removes all added spectra.
+    pub fn reset(&mut self) {
+        self.frequencies.clear();
+        self.accumulator.clear();
+        self.count = 0;
+    }
+}
+
+/// Configuration for the optional peak-hold of a [`SpectrumSmoother`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PeakHoldConfig {
+    /// Number of frames a peak is held before it decays.
+    pub hold_frames: usize,
+    /// Factor in range `[0.0, 1.0)` a held peak is multiplied with in each
+    /// frame after the hold time, e.g. `0.95`.
+    pub decay: f32,
+}
+
+/// Configuration for a [`SpectrumSmoother`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SmootherConfig {
+    /// Smoothing factor in range `(0.0, 1.0]` for rising values. `1.0` means
+    /// that the value immediately follows the input.
+    pub attack: f32,
+    /// Smoothing factor in range `(0.0, 1.0]` for falling values. Usually
+    /// smaller than [`Self::attack`], so that values rise fast and fall
+    /// slowly.
+    pub release: f32,
+    /// Optional peak-hold. See [`SpectrumSmoother::peaks`].
+    pub peak_hold: Option<PeakHoldConfig>,
+}
+
+impl SmootherConfig {
+    /// Creates a configuration for a simple exponential moving average with
+    /// the same factor for rising and falling values and without peak-hold.
+    #[must_use]
+    pub const fn ema(alpha: f32) -> Self {
+        Self {
+            attack: alpha,
+            release: alpha,
+            peak_hold: None,
+        }
+    }
+}
+
+/// Smooths consecutive spectra frame-to-frame, e.g. for visualizers. Each
+/// value moves towards the value of the new spectrum with the
+/// [`SmootherConfig::attack`] or [`SmootherConfig::release`] factor,
+/// depending on the direction.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::averaging::{PeakHoldConfig, SmootherConfig, SpectrumSmoother};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let mut smoother = SpectrumSmoother::new(SmootherConfig {
+///     attack: 0.8,
+///     release: 0.2,
+///     peak_hold: Some(PeakHoldConfig {
+///         hold_frames: 10,
+///         decay: 0.9,
+///     }),
+/// });
+/// # let frames: Vec<Vec<f32>> = vec![vec![0.0, 1.1, 5.5, -5.5]; 4];
+/// for frame in frames {
+///     let spectrum = samples_fft_to_spectrum(&frame, 44100, FrequencyLimit::All, None).unwrap();
+///     let smoothed = smoother.process(&spectrum);
+///     let peaks = smoother.peaks().unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpectrumSmoother {
+    config: SmootherConfig,
+    frequencies: Vec<Frequency>,
+    values: Vec<f32>,
+    peaks: Vec<f32>,
+    /// Number of frames since each peak was set.
+    peak_ages: Vec<usize>,
+    frequency_resolution: f32,
+    samples_len: u32,
+}
+
+impl SpectrumSmoother {
+    /// Creates a new smoother.
+    ///
+    /// ## Panics
+    /// If a factor of the configuration is out of range.
+    #[must_use]
+    pub fn new(config: SmootherConfig) -> Self {
+        assert!(
+            config.attack > 0.0 && config.attack <= 1.0,
+            "attack must be in range (0.0, 1.0]!"
+        );
+        assert!(
+            config.release > 0.0 && config.release <= 1.0,
+            "release must be in range (0.0, 1.0]!"
+        );
+        if let Some(peak_hold) = config.peak_hold {
+            assert!(
+                (0.0..1.0).contains(&peak_hold.decay),
+                "decay must be in range [0.0, 1.0)!"
+            );
+        }
+        Self {
+            config,
+            frequencies: Vec::new(),
+            values: Vec::new(),
+            peaks: Vec::new(),
+            peak_ages: Vec::new(),
+            frequency_resolution: 0.0,
+            samples_len: 0,
+        }
+    }
+
+    /// Processes the next frame and returns the smoothed spectrum. The first
+    /// frame is returned unchanged.
+    ///
+    /// ## Panics
+    /// If the spectrum has other frequencies than the previous spectra.
+    pub fn process(&mut self, spectrum: &FrequencySpectrum) -> FrequencySpectrum {
+        let data = spectrum.data();
+        if self.values.is_empty() {
+            self.frequencies = data.iter().map(|(fr, _val)| *fr).collect();
+            self.values = data.iter().map(|(_fr, val)| val.val()).collect();
+            self.peaks = self.values.clone();
+            self.peak_ages = alloc::vec![0; data.len()];
+            self.frequency_resolution = spectrum.frequency_resolution();
+            self.samples_len = spectrum.samples_len();
+        } else {
+            assert!(
+                data.len() == self.frequencies.len()
+                    && data[0].0 == self.frequencies[0]
+                    && spectrum.frequency_resolution() == self.frequency_resolution,
+                "All spectra must have the same frequencies!"
+            );
+            for (value, (_fr, val)) in self.values.iter_mut().zip(data) {
+                let factor = if val.val() > *value {
+                    self.config.attack
+                } else {
+                    self.config.release
+                };
+                *value += factor * (val.val() - *value);
+            }
+            if let Some(peak_hold) = self.config.peak_hold {
+                self.update_peaks(peak_hold);
+            }
+        }
+        self.to_spectrum(&self.values)
+    }
+
+    /// Returns the held peaks of the smoothed values or `None` if peak-hold
+    /// is not configured or no spectrum was processed yet.
+    #[must_use]
+    pub fn peaks(&self) -> Option<FrequencySpectrum> {
+        if self.config.peak_hold.is_none() || self.peaks.is_empty() {
+            None
+        } else {
+            Some(self.to_spectrum(&self.peaks))
+        }
+    }
+
+    /// Resets the smoother to its initial state.
+    pub fn reset(&mut self) {
+        self.frequencies.clear();
+        self.values.clear();
+        self.peaks.clear();
+        self.peak_ages.clear();
+    }
+
+    fn update_peaks(&mut self, config: PeakHoldConfig) {
+        let iter = self
+            .peaks
+            .iter_mut()
+            .zip(self.peak_ages.iter_mut())
+            .zip(&self.values);
+        for ((peak, age), value) in iter {
+            if *value >= *peak {
+                *peak = *value;
+                *age = 0;
+            } else if *age < config.hold_frames {
+                *age += 1;
+            } else {
+                *peak = (*peak * config.decay).max(*value);
+            }
+        }
+    }
+
+    fn to_spectrum(&self, values: &[f32]) -> FrequencySpectrum {
+        let data = self
+            .frequencies
+            .iter()
+            .zip(values)
+            .map(|(fr, val)| (*fr, FrequencyValue::from(*val)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(
+            data,
+            self.frequency_resolution,
+            self.samples_len,
+            &mut working_buffer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spectrum(values: &[f32]) -> FrequencySpectrum {
+        let data = values
+            .iter()
+            .enumerate()
+            .map(|(i, val)| (Frequency::from(i as f32 * 10.0), FrequencyValue::from(*val)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(data, 10.0, values.len() as u32 * 2, &mut working_buffer)
+    }
+
+    fn values(spectrum: &FrequencySpectrum) -> Vec<f32> {
+        spectrum.data().iter().map(|(_fr, val)| val.val()).collect()
+    }
+
+    #[test]
+    fn test_linear_and_power() {
+        let mut linear = SpectrumAverager::new(AveragingMode::Linear);
+        let mut power = SpectrumAverager::new(AveragingMode::Power);
+        assert!(linear.average().is_none());
+        for spectrum in [spectrum(&[1.0, 0.0]), spectrum(&[7.0, 0.0])] {
+            linear.add(&spectrum);
+            power.add(&spectrum);
+        }
+        assert_eq!(linear.count(), 2);
+        assert_eq!(values(&linear.average().unwrap()), [4.0, 0.0]);
+        assert_eq!(values(&power.average().unwrap()), [5.0, 0.0]);
+        assert_eq!(linear.average().unwrap().max().1.val(), 4.0);
+
+        linear.reset();
+        assert!(linear.average().is_none());
+    }
+
+    #[test]
+    fn test_exponential() {
+        let mut averager = SpectrumAverager::new(AveragingMode::Exponential(0.5));
+        averager.add(&spectrum(&[4.0, 0.0]));
+        averager.add(&spectrum(&[0.0, 0.0]));
+        averager.add(&spectrum(&[0.0, 8.0]));
+        assert_eq!(values(&averager.average().unwrap()), [1.0, 4.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_different_frequencies() {
+        let mut averager = SpectrumAverager::new(AveragingMode::Linear);
+        averager.add(&spectrum(&[1.0, 0.0]));
+        averager.add(&spectrum(&[1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_smoother() {
+        let mut smoother = SpectrumSmoother::new(SmootherConfig {
+            attack: 1.0,
+            release: 0.5,
+            peak_hold: Some(PeakHoldConfig {
+                hold_frames: 1,
+                decay: 0.5,
+            }),
+        });
+        assert!(smoother.peaks().is_none());
+        assert_eq!(
+            values(&smoother.process(&spectrum(&[0.0, 8.0]))),
+            [0.0, 8.0]
+        );
+        assert_eq!(
+            values(&smoother.process(&spectrum(&[4.0, 0.0]))),
+            [4.0, 4.0]
+        );
+        assert_eq!(values(&smoother.peaks().unwrap()), [4.0, 8.0]);
+        assert_eq!(
+            values(&smoother.process(&spectrum(&[4.0, 0.0]))),
+            [4.0, 2.0]
+        );
+        // hold time is over
+        assert_eq!(values(&smoother.peaks().unwrap()), [4.0, 4.0]);
+        assert_eq!(
+            values(&smoother.process(&spectrum(&[4.0, 0.0]))),
+            [4.0, 1.0]
+        );
+        assert_eq!(values(&smoother.peaks().unwrap()), [4.0, 2.0]);
+
+        let mut smoother = SpectrumSmoother::new(SmootherConfig::ema(0.5));
+        let _ = smoother.process(&spectrum(&[0.0, 8.0]));
+        assert_eq!(
+            values(&smoother.process(&spectrum(&[4.0, 0.0]))),
+            [2.0, 4.0]
+        );
+        assert!(smoother.peaks().is_none());
+    }
+}
@@ -0,0 +1,122 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [Bark scale] and the critical bands of human hearing
+//! according to Zwicker. Critical band energies are the front-end of many
+//! psychoacoustic models and speech quality metrics. See
+//! [`FrequencySpectrum::to_bark_bands`].
+//!
+//! [Bark scale]: https://en.wikipedia.org/wiki/Bark_scale
+
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// Edges of the 24 critical bands in Hertz according to Zwicker. Band `i`
+/// (starting at zero) covers `CRITICAL_BAND_EDGES[i]..CRITICAL_BAND_EDGES[i + 1]`.
+pub const CRITICAL_BAND_EDGES: [f32; 25] = [
+    0.0, 100.0, 200.0, 300.0, 400.0, 510.0, 630.0, 770.0, 920.0, 1080.0, 1270.0, 1480.0, 1720.0,
+    2000.0, 2320.0, 2700.0, 3150.0, 3700.0, 4400.0, 5300.0, 6400.0, 7700.0, 9500.0, 12000.0,
+    15500.0,
+];
+
+/// Center frequencies of the 24 critical bands in Hertz according to
+/// Zwicker.
+pub const CRITICAL_BAND_CENTERS: [f32; 24] = [
+    50.0, 150.0, 250.0, 350.0, 450.0, 570.0, 700.0, 840.0, 1000.0, 1170.0, 1370.0, 1600.0, 1850.0,
+    2150.0, 2500.0, 2900.0, 3400.0, 4000.0, 4800.0, 5800.0, 7000.0, 8500.0, 10500.0, 13500.0,
+];
+
+/// Converts hertz to [Bark](https://en.wikipedia.org/wiki/Bark_scale) using
+/// the formula of Traunmüller (without the corrections at the ends of the
+/// scale), which can be inverted by [`bark_to_hertz`].
+#[must_use]
+pub fn hertz_to_bark(hz: f32) -> f32 {
+    assert!(hz >= 0.0);
+    26.81 * hz / (1960.0 + hz) - 0.53
+}
+
+/// Converts [Bark](https://en.wikipedia.org/wiki/Bark_scale) to hertz. This is
+/// the inverse of [`hertz_to_bark`].
+#[must_use]
+pub fn bark_to_hertz(bark: f32) -> f32 {
+    assert!(
+        (-0.53..26.28).contains(&bark),
+        "Bark value {} is out of range!",
+        bark
+    );
+    1960.0 * (bark + 0.53) / (26.28 - bark)
+}
+
+/// Implementation of [`FrequencySpectrum::to_bark_bands`].
+pub(crate) fn to_bark_bands(spectrum: &FrequencySpectrum) -> Vec<(Frequency, FrequencyValue)> {
+    let max_fr = spectrum.max_fr().val();
+    CRITICAL_BAND_EDGES
+        .windows(2)
+        .zip(CRITICAL_BAND_CENTERS)
+        .take_while(|(edges, _center)| edges[0] < max_fr)
+        .map(|(edges, center)| {
+            (
+                center.into(),
+                spectrum.band_energy(edges[0], edges[1]).into(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bark_conversion() {
+        assert!((hertz_to_bark(1000.0) - 8.527).abs() < 1e-3);
+        for hz in [0.0, 100.0, 1000.0, 4000.0, 15500.0] {
+            assert!((bark_to_hertz(hertz_to_bark(hz)) - hz).abs() < 0.1);
+        }
+        // the formula roughly matches the table
+        for (i, center) in CRITICAL_BAND_CENTERS.iter().enumerate().skip(2).take(20) {
+            let bark = hertz_to_bark(*center);
+            assert!((bark - (i as f32 + 0.5)).abs() < 0.5, "{center}: {bark}");
+        }
+    }
+
+    #[test]
+    fn test_to_bark_bands() {
+        // 0, 100, ..., 1500 Hz, each value is 1.0
+        let data = (0..16)
+            .map(|i| (Frequency::from(i as f32 * 100.0), FrequencyValue::from(1.0)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        let spectrum = FrequencySpectrum::new(data, 100.0, 32, &mut working_buffer);
+        let bands = spectrum.to_bark_bands();
+        // the last band starting below 1500 Hz is 1480..1720 Hz
+        assert_eq!(bands.len(), 12);
+        assert_eq!(bands[0].0.val(), 50.0);
+        // 0..100 Hz: half of 0 Hz and half of 100 Hz
+        assert_eq!(bands[0].1.val(), 1.0);
+        assert_eq!(bands[1].1.val(), 1.0);
+        // 400..510 Hz
+        assert!((bands[4].1.val() - 1.1).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,319 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for grouping the frequencies of a spectrum into a fixed number of
+//! bars, e.g. for LED strips or GUI visualizers. See
+//! [`crate::FrequencySpectrum::to_bars`] for equally wide bars and
+//! [`BandSplitter`] for bands with arbitrary crossover frequencies.
+
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::mel::{hertz_to_mel, mel_to_hertz};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// Scale on which the bars are equally wide.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrequencyScale {
+    /// All bars cover the same number of Hertz.
+    Linear,
+    /// Logarithmic scale, i.e. all bars cover the same number of octaves.
+    /// This matches the perception of pitch best. The DC component is not
+    /// part of any bar.
+    Log,
+    /// [Mel scale](https://en.wikipedia.org/wiki/Mel_scale).
+    Mel,
+}
+
+impl FrequencyScale {
+    fn hertz_to_scale(self, fr: f32) -> f32 {
+        match self {
+            Self::Linear => fr,
+            Self::Log => libm::log2f(fr),
+            Self::Mel => hertz_to_mel(fr),
+        }
+    }
+
+    fn scale_to_hertz(self, val: f32) -> f32 {
+        match self {
+            Self::Linear => val,
+            Self::Log => libm::exp2f(val),
+            Self::Mel => mel_to_hertz(val),
+        }
+    }
+}
+
+/// How the frequency values inside a bar are reduced to the bar value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BarReduction {
+    /// The maximum value.
+    Max,
+    /// The arithmetic mean of the values.
+    Average,
+    /// The energy, i.e. the sum of the squared values.
+    Energy,
+}
+
+impl BarReduction {
+    fn reduce(self, values: &[f32]) -> f32 {
+        match self {
+            Self::Max => values.iter().copied().fold(0.0, f32::max),
+            Self::Average => values.iter().sum::<f32>() / values.len() as f32,
+            Self::Energy => values.iter().map(|val| val * val).sum(),
+        }
+    }
+}
+
+/// A bar created by [`crate::FrequencySpectrum::to_bars`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bar {
+    /// Lower edge of the bar (inclusive).
+    pub from: Frequency,
+    /// Upper edge of the bar (exclusive, except for the last bar).
+    pub to: Frequency,
+    /// Value of the bar. See [`BarReduction`].
+    pub value: FrequencyValue,
+}
+
+/// Implementation of [`crate::FrequencySpectrum::to_bars`].
+pub(crate) fn to_bars(
+    spectrum: &FrequencySpectrum,
+    n_bars: usize,
+    scale: FrequencyScale,
+    reduction: BarReduction,
+) -> Vec<Bar> {
+    assert!(n_bars > 0, "n_bars must not be zero!");
+    let min_fr = match scale {
+        FrequencyScale::Log => spectrum.min_fr().val().max(spectrum.frequency_resolution()),
+        _ => spectrum.min_fr().val(),
+    };
+    let max_fr = spectrum.max_fr().val();
+    assert!(
+        min_fr < max_fr,
+        "The spectrum doesn't cover a frequency range!"
+    );
+
+    let scale_min = scale.hertz_to_scale(min_fr);
+    let scale_step = (scale.hertz_to_scale(max_fr) - scale_min) / n_bars as f32;
+    let edges = (0..=n_bars)
+        .map(|i| match i {
+            0 => min_fr,
+            _ if i == n_bars => max_fr,
+            _ => scale.scale_to_hertz(scale_min + scale_step * i as f32),
+        })
+        .collect::<Vec<_>>();
+
+    let mut values = Vec::new();
+    edges
+        .windows(2)
+        .enumerate()
+        .map(|(i, edge)| {
+            let (from, to) = (edge[0], edge[1]);
+            let is_last = i == n_bars - 1;
+            values.clear();
+            values.extend(
+                spectrum
+                    .data()
+                    .iter()
+                    .filter(|(fr, _val)| fr.val() >= from && (fr.val() < to || is_last))
+                    .map(|(_fr, val)| val.val()),
+            );
+            // bars that are narrower than the frequency resolution
+            if values.is_empty() {
+                values.push(spectrum.freq_val_exact((from + to) / 2.0).val());
+            }
+            Bar {
+                from: from.into(),
+                to: to.into(),
+                value: reduction.reduce(&values).into(),
+            }
+        })
+        .collect()
+}
+
+/// Splits spectra into bands at fixed crossover frequencies and calculates
+/// the energy of each band, e.g. to drive multi-zone lighting or compressor
+/// sidechains. The splitter is created once and applied to each frame.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-16")] {
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// use spectrum_analyzer::bars::BandSplitter;
+/// // bass, mid, and treble
+/// let splitter = BandSplitter::new(&[250.0, 4000.0]);
+/// let samples = vec![0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+/// let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// let [bass, mid, treble]: [f32; 3] = splitter.split(&spectrum).try_into().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BandSplitter {
+    crossovers: Vec<f32>,
+}
+
+impl BandSplitter {
+    /// Creates a new splitter. `n` crossover frequencies result in `n + 1`
+    /// bands.
+    ///
+    /// ## Parameters
+    /// - `crossovers` Crossover frequencies in Hertz in ascending order.
+    ///
+    /// ## Panics
+    /// If `crossovers` is empty, not strictly ascending, or contains
+    /// frequencies that are not bigger than zero.
+    #[must_use]
+    pub fn new(crossovers: &[f32]) -> Self {
+        assert!(!crossovers.is_empty(), "crossovers must not be empty!");
+        assert!(crossovers[0] > 0.0, "crossovers must be bigger than 0 Hz!");
+        assert!(
+            crossovers.windows(2).all(|pair| pair[0] < pair[1]),
+            "crossovers must be strictly ascending!"
+        );
+        Self {
+            crossovers: crossovers.to_vec(),
+        }
+    }
+
+    /// Returns the crossover frequencies.
+    #[must_use]
+    pub fn crossovers(&self) -> &[f32] {
+        &self.crossovers
+    }
+
+    /// Returns the number of bands.
+    #[must_use]
+    pub fn n_bands(&self) -> usize {
+        self.crossovers.len() + 1
+    }
+
+    /// Calculates the energy of each band, see
+    /// [`crate::FrequencySpectrum::band_energy`]. The first band starts at
+    /// the lowest and the last band ends at the highest frequency of the
+    /// spectrum.
+    ///
+    /// ## Return
+    /// The energy of each band, from the lowest to the highest band.
+    #[must_use]
+    pub fn split(&self, spectrum: &FrequencySpectrum) -> Vec<f32> {
+        let edges = core::iter::once(f32::NEG_INFINITY)
+            .chain(self.crossovers.iter().copied())
+            .chain(core::iter::once(f32::INFINITY))
+            .collect::<Vec<_>>();
+        edges
+            .windows(2)
+            .map(|edge| spectrum.band_energy(edge[0], edge[1]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spectrum() -> FrequencySpectrum {
+        // 0, 100, ..., 1500 Hz
+        let data = (0..16)
+            .map(|i| {
+                (
+                    Frequency::from(i as f32 * 100.0),
+                    FrequencyValue::from(i as f32),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(data, 100.0, 32, &mut working_buffer)
+    }
+
+    #[test]
+    fn test_linear_bars() {
+        let bars = to_bars(&spectrum(), 3, FrequencyScale::Linear, BarReduction::Max);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[0].from.val(), 0.0);
+        assert_eq!(bars[0].to.val(), 500.0);
+        assert_eq!(bars[2].to.val(), 1500.0);
+        let values = bars.iter().map(|bar| bar.value.val()).collect::<Vec<_>>();
+        assert_eq!(values, [4.0, 9.0, 15.0]);
+
+        let bars = to_bars(
+            &spectrum(),
+            3,
+            FrequencyScale::Linear,
+            BarReduction::Average,
+        );
+        let values = bars.iter().map(|bar| bar.value.val()).collect::<Vec<_>>();
+        assert_eq!(values, [2.0, 7.0, 12.5]);
+
+        let bars = to_bars(&spectrum(), 1, FrequencyScale::Linear, BarReduction::Energy);
+        assert_eq!(bars[0].value.val(), 1240.0);
+    }
+
+    #[test]
+    fn test_log_bars() {
+        let bars = to_bars(&spectrum(), 4, FrequencyScale::Log, BarReduction::Max);
+        // 100 Hz to 1500 Hz
+        assert_eq!(bars[0].from.val(), 100.0);
+        for bar in &bars {
+            float_cmp::assert_approx_eq!(
+                f32,
+                bar.to.val() / bar.from.val(),
+                libm::powf(15.0, 0.25),
+                epsilon = 0.001
+            );
+        }
+        // first bar covers 100 Hz to ~197 Hz
+        assert_eq!(bars[0].value.val(), 1.0);
+        assert_eq!(bars[3].value.val(), 15.0);
+    }
+
+    #[test]
+    fn test_narrow_bars() {
+        let bars = to_bars(&spectrum(), 16, FrequencyScale::Log, BarReduction::Max);
+        assert_eq!(bars.len(), 16);
+        assert!(bars.iter().all(|bar| bar.value.val() > 0.0));
+        let bars = to_bars(&spectrum(), 8, FrequencyScale::Mel, BarReduction::Max);
+        assert!(bars.windows(2).all(|bars| bars[0].value <= bars[1].value));
+    }
+
+    #[test]
+    fn test_band_splitter() {
+        let splitter = BandSplitter::new(&[250.0, 1000.0]);
+        assert_eq!(splitter.n_bands(), 3);
+        let bands = splitter.split(&spectrum());
+        assert_eq!(bands.len(), 3);
+        // 0 Hz, 100 Hz, and 200 Hz, which covers 150 Hz to 250 Hz
+        float_cmp::assert_approx_eq!(f32, bands[0], 5.0, epsilon = 0.0001);
+        let total = spectrum()
+            .data()
+            .iter()
+            .map(|(_fr, val)| val.val() * val.val())
+            .sum::<f32>();
+        float_cmp::assert_approx_eq!(f32, bands.iter().sum::<f32>(), total, epsilon = 0.01);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_band_splitter_invalid_crossovers() {
+        let _ = BandSplitter::new(&[1000.0, 250.0]);
+    }
+}
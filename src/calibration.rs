@@ -0,0 +1,426 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Calibration of measurement microphones. A [`CalibrationCurve`] contains
+//! corrections in dB for several frequencies and interpolates between them.
+//! Apply it with [`FrequencySpectrum::apply_calibration`].
+//!
+//! Calibration files of measurement microphones, such as the miniDSP UMIK-1,
+//! can be parsed with [`CalibrationCurve::parse`] and
+//! `CalibrationCurve::from_file` (needs the `std` feature).
+//!
+//! An [`SplCalibration`] maps the level of a spectrum to the absolute sound
+//! pressure level (SPL), based on the measurement of a known reference, such
+//! as a `94 dB SPL` calibrator at `1 kHz`.
+
+use crate::measurement::{closest_index, notch_power};
+use crate::scaling::SpectrumScalingFunction;
+use crate::spectrum::FrequencySpectrum;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// Possible errors of [`CalibrationCurve::parse`] and
+/// `CalibrationCurve::from_file`.
+#[derive(Debug)]
+pub enum CalibrationError {
+    /// The file couldn't be read.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The line with the given number (starting at `1`) starts with a
+    /// frequency but has no valid correction, or the frequency isn't
+    /// positive.
+    InvalidLine(usize),
+    /// The calibration file contains no frequencies.
+    Empty,
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "can't read calibration file: {e}"),
+            Self::InvalidLine(line) => write!(f, "invalid calibration data in line {line}"),
+            Self::Empty => write!(f, "calibration file contains no frequencies"),
+        }
+    }
+}
+
+/// Frequency dependent corrections in dB, e.g. of a measurement microphone.
+/// Between two frequencies, the correction is interpolated linearly over the
+/// logarithm of the frequency. Below the lowest and above the highest
+/// frequency, the correction of the lowest respectively highest frequency
+/// is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationCurve {
+    /// Pairs of frequency and correction in dB, sorted by frequency.
+    points: Vec<(f32, f32)>,
+}
+
+impl CalibrationCurve {
+    /// Creates a new calibration curve.
+    ///
+    /// ## Parameters
+    /// * `points` Pairs of frequency in Hertz and the correction in dB that
+    ///            is added to the value at this frequency. The order doesn't
+    ///            matter.
+    ///
+    /// ## Panics
+    /// If `points` is empty, a frequency isn't positive, or a value isn't
+    /// finite.
+    #[must_use]
+    pub fn new(mut points: Vec<(f32, f32)>) -> Self {
+        assert!(
+            !points.is_empty(),
+            "A calibration needs at least one point!"
+        );
+        assert!(
+            points
+                .iter()
+                .all(|&(fr, db)| fr > 0.0 && fr.is_finite() && db.is_finite()),
+            "Frequencies must be positive and corrections must be finite!"
+        );
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { points }
+    }
+
+    /// Parses a calibration file. Such files list the frequency response of
+    /// the microphone, i.e. the deviation in dB from a flat response. The
+    /// corrections of the calibration curve are the negated deviations.
+    ///
+    /// Each line starts with a frequency in Hertz followed by the deviation
+    /// in dB. Further columns, such as the phase, are ignored. Columns are
+    /// separated by whitespace, commas, or semicolons. Lines that don't start
+    /// with a number, such as the header of UMIK-1 files
+    /// (`"Sens Factor =-1.378dB, SERNO: 7000000"`) or comments, are skipped.
+    ///
+    /// ## Errors
+    /// [`CalibrationError::InvalidLine`] if a line starts with a frequency but
+    /// has no valid deviation. [`CalibrationError::Empty`] if no line
+    /// contains a frequency.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::calibration::CalibrationCurve;
+    /// let file = "\"Sens Factor =-1.378dB, SERNO: 7000000\"\n\
+    ///             20.0\t-1.5\n\
+    ///             1000.0\t0.0\n\
+    ///             10000.0\t2.0\n";
+    /// let calibration = CalibrationCurve::parse(file).unwrap();
+    /// assert_eq!(calibration.correction_db(20.0), 1.5);
+    /// assert_eq!(calibration.correction_db(10000.0), -2.0);
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, CalibrationError> {
+        let mut points = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let mut columns = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .filter(|column| !column.is_empty());
+            let fr = match columns.next().map(str::parse::<f32>) {
+                Some(Ok(fr)) => fr,
+                // header, comment, or empty line
+                _ => continue,
+            };
+            let deviation = columns
+                .next()
+                .and_then(|column| column.parse::<f32>().ok())
+                .filter(|deviation| deviation.is_finite() && fr > 0.0 && fr.is_finite())
+                .ok_or(CalibrationError::InvalidLine(index + 1))?;
+            points.push((fr, -deviation));
+        }
+
+        if points.is_empty() {
+            Err(CalibrationError::Empty)
+        } else {
+            Ok(Self::new(points))
+        }
+    }
+
+    /// Reads and parses the calibration file at `path`. See [`Self::parse`].
+    ///
+    /// ## Errors
+    /// [`CalibrationError::Io`] if the file can't be read. Otherwise, see
+    /// [`Self::parse`].
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, CalibrationError> {
+        let text = std::fs::read_to_string(path).map_err(CalibrationError::Io)?;
+        Self::parse(&text)
+    }
+
+    /// Returns the pairs of frequency and correction in dB, sorted by
+    /// frequency.
+    #[must_use]
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// Returns the interpolated correction in dB for the given frequency.
+    #[must_use]
+    pub fn correction_db(&self, fr: f32) -> f32 {
+        let upper = self.points.partition_point(|&(point_fr, _)| point_fr < fr);
+        if upper == 0 {
+            return self.points[0].1;
+        }
+        if upper == self.points.len() {
+            return self.points[upper - 1].1;
+        }
+        let (fr_low, db_low) = self.points[upper - 1];
+        let (fr_high, db_high) = self.points[upper];
+        let t = libm::log2f(fr / fr_low) / libm::log2f(fr_high / fr_low);
+        db_low + t * (db_high - db_low)
+    }
+
+    /// Returns the interpolated correction as linear gain for the given
+    /// frequency. Magnitudes are multiplied with this gain.
+    #[must_use]
+    pub fn gain(&self, fr: f32) -> f32 {
+        libm::powf(10.0, self.correction_db(fr) / 20.0)
+    }
+}
+
+/// Offset between the level of a spectrum and the absolute sound pressure
+/// level (SPL) in dB. Create it once from the spectrum of a known reference,
+/// e.g. a sound level calibrator, and use it for all following spectra.
+///
+/// The following spectra must be calculated with the same settings as the
+/// reference, i.e., the same number of samples, window function, and
+/// scaling. The values of the spectra are expected to be magnitudes (and
+/// not in decibel).
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-4096")] {
+/// use spectrum_analyzer::calibration::SplCalibration;
+/// use spectrum_analyzer::windows::hann_window;
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// # let calibrator_samples = (0..4096)
+/// #    .map(|i| 0.5 * libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 48000.0))
+/// #    .collect::<Vec<_>>();
+/// # let samples = calibrator_samples.iter().map(|s| s * 0.1).collect::<Vec<_>>();
+/// let reference =
+///     samples_fft_to_spectrum(&hann_window(&calibrator_samples), 48000, FrequencyLimit::All, None)
+///         .unwrap();
+/// // 94 dB SPL calibrator at 1 kHz
+/// let calibration = SplCalibration::from_reference(&reference, 1000.0, 94.0, 3).unwrap();
+///
+/// let spectrum =
+///     samples_fft_to_spectrum(&hann_window(&samples), 48000, FrequencyLimit::All, None).unwrap();
+/// let spl = calibration.band_spl_db(&spectrum, 20.0, 20000.0);
+/// assert!((spl - 74.0).abs() < 0.1);
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplCalibration {
+    /// Offset in dB that is added to the power level of a spectrum.
+    offset_db: f32,
+}
+
+impl SplCalibration {
+    /// Creates a calibration from a previously stored offset. See
+    /// [`Self::offset_db`].
+    ///
+    /// ## Panics
+    /// If `offset_db` isn't finite.
+    #[must_use]
+    pub fn from_offset_db(offset_db: f32) -> Self {
+        assert!(offset_db.is_finite(), "The offset must be finite!");
+        Self { offset_db }
+    }
+
+    /// Creates a calibration from the spectrum of a reference tone with a
+    /// known sound pressure level. The power of the tone is measured over a
+    /// notch around its frequency, as a window function spreads the power
+    /// over several neighbouring frequencies.
+    ///
+    /// ## Parameters
+    /// * `reference` Spectrum of the reference tone with magnitudes.
+    /// * `reference_fr` Frequency of the reference tone in Hertz, e.g.
+    ///                  `1000.0`.
+    /// * `reference_spl_db` Sound pressure level of the reference tone in dB,
+    ///                      e.g. `94.0`.
+    /// * `notch_width` Number of neighbouring frequencies on each side of the
+    ///                 tone that belong to the tone. `3` is a good value for
+    ///                 the Hann window.
+    ///
+    /// ## Return value
+    /// `None` if the power of the reference tone is zero.
+    ///
+    /// ## Panics
+    /// If `reference_fr` is not inside the spectrum.
+    #[must_use]
+    pub fn from_reference(
+        reference: &FrequencySpectrum,
+        reference_fr: f32,
+        reference_spl_db: f32,
+        notch_width: usize,
+    ) -> Option<Self> {
+        let index = closest_index(reference, reference_fr);
+        let power = notch_power(reference, index, notch_width);
+        if power <= 0.0 {
+            return None;
+        }
+        Some(Self::from_offset_db(
+            reference_spl_db - 10.0 * libm::log10f(power),
+        ))
+    }
+
+    /// Returns the offset in dB that is added to the power level of a
+    /// spectrum. Store it to restore the calibration later with
+    /// [`Self::from_offset_db`].
+    #[must_use]
+    pub const fn offset_db(&self) -> f32 {
+        self.offset_db
+    }
+
+    /// Converts a power, i.e. a sum of squared magnitudes, to the sound
+    /// pressure level in dB. A power of zero results in negative infinity.
+    #[must_use]
+    pub fn power_to_spl_db(&self, power: f32) -> f32 {
+        10.0 * libm::log10f(power) + self.offset_db
+    }
+
+    /// Returns the sound pressure level in dB of the band between two
+    /// frequencies. See [`FrequencySpectrum::band_energy`].
+    ///
+    /// ## Panics
+    /// If `from_hz` is bigger than `to_hz`.
+    #[must_use]
+    pub fn band_spl_db(&self, spectrum: &FrequencySpectrum, from_hz: f32, to_hz: f32) -> f32 {
+        self.power_to_spl_db(spectrum.band_energy(from_hz, to_hz))
+    }
+
+    /// Creates a scaling function that converts each magnitude into its
+    /// sound pressure level in dB. Values below `floor_db`, including zero
+    /// magnitudes, are clamped to `floor_db`. Note that a window function
+    /// spreads the power of a tone over several frequencies. Use
+    /// [`Self::band_spl_db`] for the level of a tone or band.
+    #[must_use]
+    pub fn scaling_fn(&self, floor_db: f32) -> Box<SpectrumScalingFunction> {
+        let offset_db = self.offset_db;
+        Box::new(move |fr_val, _stats| {
+            if fr_val > 0.0 {
+                (20.0 * libm::log10f(fr_val) + offset_db).max(floor_db)
+            } else {
+                floor_db
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correction_db() {
+        let calibration = CalibrationCurve::new(vec![(1000.0, 0.0), (100.0, -2.0), (4000.0, 4.0)]);
+        assert_eq!(calibration.points()[0], (100.0, -2.0));
+        assert_eq!(calibration.correction_db(10.0), -2.0);
+        assert_eq!(calibration.correction_db(100.0), -2.0);
+        assert_eq!(calibration.correction_db(1000.0), 0.0);
+        // one octave above 1 kHz, i.e. half way to 4 kHz on a log scale
+        assert!((calibration.correction_db(2000.0) - 2.0).abs() < 1e-5);
+        assert_eq!(calibration.correction_db(20000.0), 4.0);
+        assert!((calibration.gain(4000.0) - 1.584_893).abs() < 1e-5);
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_apply_calibration() {
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        let samples = [0.0, 1.1, 5.5, -5.5, 3.3, 0.0, -1.0, 2.0];
+        let unscaled = samples_fft_to_spectrum(&samples, 8, FrequencyLimit::All, None).unwrap();
+        let mut spectrum = samples_fft_to_spectrum(&samples, 8, FrequencyLimit::All, None).unwrap();
+        let calibration = CalibrationCurve::new(vec![(1.0, 6.0), (4.0, -6.0)]);
+        spectrum.apply_calibration(&calibration);
+        for ((fr, val), (_fr, unscaled)) in spectrum.data().iter().zip(unscaled.data()) {
+            let expected = unscaled.val() * calibration.gain(fr.val());
+            assert!((val.val() - expected).abs() < 1e-5);
+        }
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_spl_calibration() {
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        use alloc::vec::Vec;
+        let sine = |amplitude: f32, fr: f32| {
+            let samples = (0..1024)
+                .map(|i| {
+                    amplitude * libm::sinf(2.0 * core::f32::consts::PI * fr * i as f32 / 8192.0)
+                })
+                .collect::<Vec<_>>();
+            samples_fft_to_spectrum(&samples, 8192, FrequencyLimit::All, None).unwrap()
+        };
+        let calibration =
+            SplCalibration::from_reference(&sine(1.0, 1000.0), 1000.0, 94.0, 0).unwrap();
+        let restored = SplCalibration::from_offset_db(calibration.offset_db());
+        assert_eq!(calibration, restored);
+
+        // -20 dB
+        let spectrum = sine(0.1, 2000.0);
+        let spl = calibration.band_spl_db(&spectrum, 1900.0, 2100.0);
+        assert!((spl - 74.0).abs() < 0.01, "{spl}");
+        assert!(calibration.band_spl_db(&spectrum, 100.0, 200.0) < 0.0);
+
+        let scaled = samples_fft_to_spectrum(
+            &[0.0; 8],
+            8192,
+            FrequencyLimit::All,
+            Some(&calibration.scaling_fn(-10.0)),
+        )
+        .unwrap();
+        assert_eq!(scaled.max().1.val(), -10.0);
+        let silence = samples_fft_to_spectrum(&[0.0; 8], 8192, FrequencyLimit::All, None).unwrap();
+        assert!(SplCalibration::from_reference(&silence, 1024.0, 94.0, 0).is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let file = "\"Sens Factor =-1.378dB, SERNO: 7000000\"\n\
+                    * comment\n\
+                    \n\
+                    10.054\t-3.3470\t0.0\n\
+                    1000,0.5\n\
+                    20000.0 ; 1.0\n";
+        let calibration = CalibrationCurve::parse(file).unwrap();
+        assert_eq!(
+            calibration.points(),
+            &[(10.054, 3.347), (1000.0, -0.5), (20000.0, -1.0)]
+        );
+
+        assert!(matches!(
+            CalibrationCurve::parse("header\n100.0\n"),
+            Err(CalibrationError::InvalidLine(2))
+        ));
+        assert!(matches!(
+            CalibrationCurve::parse("-5.0 1.0\n"),
+            Err(CalibrationError::InvalidLine(1))
+        ));
+        assert!(matches!(
+            CalibrationCurve::parse("header only\n"),
+            Err(CalibrationError::Empty)
+        ));
+    }
+}
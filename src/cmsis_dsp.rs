@@ -0,0 +1,238 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Bindings to the real FFT `arm_rfft_fast_f32` of the [CMSIS-DSP] library
+//! for [`crate::FftBackend::CmsisDsp`]. The library isn't part of this crate.
+//! The final binary must link against a CMSIS-DSP build for the target, e.g.
+//! `libarm_cortexM4lf_math.a`.
+//!
+//! The layout of the instance structs corresponds to CMSIS-DSP builds without
+//! Helium (MVE) support, as for Cortex-M4 and Cortex-M7.
+//!
+//! [CMSIS-DSP]: https://github.com/ARM-software/CMSIS-DSP
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::Complex32;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+
+/// Smallest FFT length that `arm_rfft_fast_f32` supports.
+const MIN_LEN: usize = 32;
+
+/// Biggest FFT length that `arm_rfft_fast_f32` supports.
+const MAX_LEN: usize = 4096;
+
+/// `ARM_MATH_SUCCESS` of `arm_status`.
+const ARM_MATH_SUCCESS: i8 = 0;
+
+/// `arm_cfft_instance_f32` of CMSIS-DSP.
+#[repr(C)]
+struct ArmCfftInstanceF32 {
+    fft_len: u16,
+    p_twiddle: *const f32,
+    p_bit_rev_table: *const u16,
+    bit_rev_length: u16,
+}
+
+/// `arm_rfft_fast_instance_f32` of CMSIS-DSP.
+#[repr(C)]
+struct ArmRfftFastInstanceF32 {
+    sint: ArmCfftInstanceF32,
+    fft_len_rfft: u16,
+    p_twiddle_rfft: *const f32,
+}
+
+extern "C" {
+    fn arm_rfft_fast_init_f32(s: *mut ArmRfftFastInstanceF32, fft_len: u16) -> i8;
+
+    fn arm_rfft_fast_f32(
+        s: *const ArmRfftFastInstanceF32,
+        p: *mut f32,
+        p_out: *mut f32,
+        ifft_flag: u8,
+    );
+}
+
+/// Checks that `arm_rfft_fast_f32` supports the given length, i.e. a power of
+/// two from `32` up to `4096`.
+pub(crate) const fn validate_len(len: usize) -> Result<(), SpectrumAnalyzerError> {
+    if !len.is_power_of_two() {
+        return Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo);
+    }
+    if len < MIN_LEN {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    if len > MAX_LEN {
+        return Err(SpectrumAnalyzerError::TooManySamples);
+    }
+    Ok(())
+}
+
+/// Calculates the FFT with `arm_rfft_fast_f32`. The result has the length
+/// `samples.len() / 2 + 1`.
+///
+/// # Parameters
+/// - `samples`: The length was accepted by [`validate_len`].
+pub(crate) fn calc(samples: &[f32]) -> Vec<Complex32> {
+    debug_assert!(validate_len(samples.len()).is_ok());
+
+    let mut instance = MaybeUninit::<ArmRfftFastInstanceF32>::uninit();
+    // SAFETY: the instance is only written and the length is supported.
+    let status = unsafe { arm_rfft_fast_init_f32(instance.as_mut_ptr(), samples.len() as u16) };
+    assert_eq!(
+        status, ARM_MATH_SUCCESS,
+        "arm_rfft_fast_init_f32 must support the length"
+    );
+    // SAFETY: the instance was initialized successfully.
+    let instance = unsafe { instance.assume_init() };
+
+    // the FFT uses its input as scratch memory
+    let mut buffer = samples.to_vec();
+    let mut output = vec![0.0; samples.len()];
+    // SAFETY: both buffers have the length of the instance.
+    unsafe { arm_rfft_fast_f32(&instance, buffer.as_mut_ptr(), output.as_mut_ptr(), 0) };
+
+    // Like with `microfft::real`, the output are `samples.len() / 2` complex
+    // values, where the real value of the Nyquist frequency is packed inside
+    // the imaginary part of the DC component.
+    let mut res = Vec::with_capacity(samples.len() / 2 + 1);
+    res.push(Complex32::new(output[0], 0.0));
+    res.extend(
+        output[2..]
+            .chunks_exact(2)
+            .map(|pair| Complex32::new(pair[0], pair[1])),
+    );
+    res.push(Complex32::new(output[1], 0.0));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum_with_fft, FftBackend, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    /// Mock of `arm_rfft_fast_init_f32` that only stores the length.
+    #[no_mangle]
+    extern "C" fn arm_rfft_fast_init_f32(s: *mut ArmRfftFastInstanceF32, fft_len: u16) -> i8 {
+        // SAFETY: `s` points to an instance
+        unsafe { (*s).fft_len_rfft = fft_len };
+        ARM_MATH_SUCCESS
+    }
+
+    /// Mock of `arm_rfft_fast_f32` with a naive DFT and the output format of
+    /// CMSIS-DSP.
+    #[no_mangle]
+    extern "C" fn arm_rfft_fast_f32(
+        s: *const ArmRfftFastInstanceF32,
+        p: *mut f32,
+        p_out: *mut f32,
+        _ifft_flag: u8,
+    ) {
+        // SAFETY: both buffers have the length of the instance
+        let (input, output) = unsafe {
+            let len = (*s).fft_len_rfft as usize;
+            (
+                core::slice::from_raw_parts(p, len),
+                core::slice::from_raw_parts_mut(p_out, len),
+            )
+        };
+        let len = input.len();
+        let dft = |k: usize| {
+            input
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (i, x)| {
+                    let angle = -2.0 * PI * ((k * i) % len) as f32 / len as f32;
+                    (re + x * libm::cosf(angle), im + x * libm::sinf(angle))
+                })
+        };
+        output[0] = dft(0).0;
+        output[1] = dft(len / 2).0;
+        for k in 1..len / 2 {
+            (output[2 * k], output[2 * k + 1]) = dft(k);
+        }
+    }
+
+    #[test]
+    fn test_validate_len() {
+        assert!(validate_len(32).is_ok());
+        assert!(validate_len(4096).is_ok());
+        assert!(matches!(
+            validate_len(16),
+            Err(SpectrumAnalyzerError::TooFewSamples)
+        ));
+        assert!(matches!(
+            validate_len(8192),
+            Err(SpectrumAnalyzerError::TooManySamples)
+        ));
+        assert!(matches!(
+            validate_len(100),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+    }
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    fn test_same_spectrum_as_microfft() {
+        let samples = (0..64)
+            .map(|i| libm::sinf(2.0 * PI * 5.0 * i as f32 / 64.0) + 0.5)
+            .collect::<Vec<_>>();
+        let microfft = samples_fft_to_spectrum_with_fft(
+            &samples,
+            64,
+            FrequencyLimit::All,
+            None,
+            &FftBackend::MicrofftReal,
+        )
+        .unwrap();
+        let cmsis_dsp = samples_fft_to_spectrum_with_fft(
+            &samples,
+            64,
+            FrequencyLimit::All,
+            None,
+            &FftBackend::CmsisDsp,
+        )
+        .unwrap();
+        for ((fr_a, val_a), (fr_b, val_b)) in microfft.data().iter().zip(cmsis_dsp.data()) {
+            assert_eq!(fr_a, fr_b);
+            assert!(
+                (val_a.val() - val_b.val()).abs() < 0.01,
+                "{val_a} != {val_b}"
+            );
+        }
+        assert_eq!(cmsis_dsp.max().0.val(), 5.0);
+
+        assert!(matches!(
+            samples_fft_to_spectrum_with_fft(
+                &samples[..16],
+                64,
+                FrequencyLimit::All,
+                None,
+                &FftBackend::CmsisDsp,
+            ),
+            Err(SpectrumAnalyzerError::TooFewSamples)
+        ));
+    }
+}
@@ -0,0 +1,425 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the struct [`ComplexFrequencySpectrum`].
+
+use crate::fft::{Complex32, FftImpl};
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+use core::f32::consts::TAU;
+
+/// Like [`FrequencySpectrum`] but keeps the complex FFT result of each
+/// frequency instead of only its magnitude. Hence, the phase information is
+/// preserved, which is required for example for phase-vocoder style
+/// processing.
+///
+/// Objects of this struct are created by
+/// [`crate::samples_fft_to_complex_spectrum`]. It only contains the
+/// frequencies that were desired, e.g., specified via
+/// [`crate::limit::FrequencyLimit`].
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComplexFrequencySpectrum {
+    /// All (Frequency, Complex32) data pairs sorted from the lowest frequency
+    /// to the highest frequency.
+    data: Vec<(Frequency, Complex32)>,
+    /// Frequency resolution of the examined samples in Hertz,
+    /// i.e the frequency steps between elements in the vector
+    /// inside field [`Self::data`].
+    frequency_resolution: f32,
+    /// Number of samples that were analyzed. Might be bigger than the length
+    /// of `data`, if the spectrum was created with a [`crate::limit::FrequencyLimit`] .
+    samples_len: u32,
+}
+
+impl ComplexFrequencySpectrum {
+    /// Creates a new object.
+    ///
+    /// ## Parameters
+    /// * `data` Vector with all ([`Frequency`], [`Complex32`])-tuples
+    /// * `frequency_resolution` Resolution in Hertz. This equals to
+    ///                          `data[1].0 - data[0].0`.
+    /// * `samples_len` Number of samples. Might be bigger than `data.len()`
+    ///                 if the spectrum is obtained with a frequency limit.
+    #[inline]
+    #[must_use]
+    pub fn new(
+        data: Vec<(Frequency, Complex32)>,
+        frequency_resolution: f32,
+        samples_len: u32,
+    ) -> Self {
+        debug_assert!(
+            data.len() >= 2,
+            "Input data of length={} for spectrum makes no sense!",
+            data.len()
+        );
+        Self {
+            data,
+            frequency_resolution,
+            samples_len,
+        }
+    }
+
+    /// Returns the underlying data.
+    #[inline]
+    #[must_use]
+    pub fn data(&self) -> &[(Frequency, Complex32)] {
+        &self.data
+    }
+
+    /// Returns the underlying data as mutable slice. This enables filtering
+    /// in the frequency domain, e.g. by setting bins to zero, before the
+    /// samples are reconstructed with [`Self::to_samples`].
+    #[inline]
+    #[must_use]
+    pub fn data_mut(&mut self) -> &mut [(Frequency, Complex32)] {
+        &mut self.data
+    }
+
+    /// Returns the frequency resolution of this spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn frequency_resolution(&self) -> f32 {
+        self.frequency_resolution
+    }
+
+    /// Returns the number of samples used to obtain this spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn samples_len(&self) -> u32 {
+        self.samples_len
+    }
+
+    /// Getter for the highest frequency that is captured inside this spectrum.
+    #[inline]
+    #[must_use]
+    pub fn max_fr(&self) -> Frequency {
+        self.data[self.data.len() - 1].0
+    }
+
+    /// Getter for the lowest frequency that is captured inside this spectrum.
+    #[inline]
+    #[must_use]
+    pub fn min_fr(&self) -> Frequency {
+        self.data[0].0
+    }
+
+    /// Returns the complex FFT result of the frequency closest to `search_fr`.
+    ///
+    /// ## Panics
+    /// If parameter `search_fr` (frequency) is below the lowest or above the
+    /// maximum frequency, this function panics! See
+    /// [`FrequencySpectrum::freq_val_closest`].
+    #[inline]
+    #[must_use]
+    pub fn complex_at(&self, search_fr: f32) -> (Frequency, Complex32) {
+        self.data[self.closest_index(search_fr)]
+    }
+
+    /// Returns the magnitude, i.e. `sqrt(re*re + im*im)`, of the frequency
+    /// closest to `search_fr`.
+    ///
+    /// ## Panics
+    /// See [`Self::complex_at`].
+    #[inline]
+    #[must_use]
+    pub fn magnitude_at(&self, search_fr: f32) -> FrequencyValue {
+        let (_fr, complex) = self.complex_at(search_fr);
+        magnitude(&complex).into()
+    }
+
+    /// Returns the phase in radians, in the interval `[-PI; PI]`, of the
+    /// frequency closest to `search_fr`.
+    ///
+    /// ## Panics
+    /// See [`Self::complex_at`].
+    #[inline]
+    #[must_use]
+    pub fn phase_at(&self, search_fr: f32) -> f32 {
+        let (_fr, complex) = self.complex_at(search_fr);
+        phase(&complex)
+    }
+
+    /// Returns the phase in radians of each frequency, unwrapped so that
+    /// there are no jumps of `2*PI` between neighbouring frequencies. The
+    /// phase of the lowest frequency is in the interval `[-PI; PI]`. The
+    /// unwrapping relies on a frequency resolution fine enough that the true
+    /// phase changes by less than `PI` between neighbouring frequencies.
+    ///
+    /// ## Return value
+    /// (frequency, phase)-pairs sorted from the lowest to the highest
+    /// frequency.
+    #[must_use]
+    pub fn unwrapped_phase(&self) -> Vec<(Frequency, f32)> {
+        let mut offset = 0.0;
+        let mut previous: Option<f32> = None;
+        self.data
+            .iter()
+            .map(|(fr, complex)| {
+                let wrapped = phase(complex);
+                if let Some(previous) = previous {
+                    let diff = wrapped + offset - previous;
+                    // round to the nearest multiple of 2*PI
+                    offset -= libm::roundf(diff / TAU) * TAU;
+                }
+                let unwrapped = wrapped + offset;
+                previous = Some(unwrapped);
+                (*fr, unwrapped)
+            })
+            .collect()
+    }
+
+    /// Returns the group delay in seconds of each frequency, i.e. the
+    /// negative derivative of the unwrapped phase with respect to the
+    /// angular frequency. For a system that delays the signal by `t`
+    /// seconds, this is `t` for all frequencies. The derivative is
+    /// approximated with central differences, and with one-sided differences
+    /// at the lowest and the highest frequency.
+    ///
+    /// ## Return value
+    /// (frequency, group delay)-pairs sorted from the lowest to the highest
+    /// frequency.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # #[cfg(feature = "size-16")] {
+    /// use spectrum_analyzer::{samples_fft_to_complex_spectrum, FrequencyLimit};
+    /// // impulse delayed by two samples, i.e. 0.25ms at 8000Hz
+    /// let samples = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+    /// let spectrum = samples_fft_to_complex_spectrum(&samples, 8000, FrequencyLimit::All).unwrap();
+    /// for (_fr, delay) in spectrum.group_delay() {
+    ///     assert!((delay - 0.000_25).abs() < 1e-6);
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn group_delay(&self) -> Vec<(Frequency, f32)> {
+        let phase = self.unwrapped_phase();
+        // step of the angular frequency between neighbouring elements
+        let angular_step = TAU * self.frequency_resolution;
+        let last = phase.len() - 1;
+        (0..phase.len())
+            .map(|i| {
+                let lower = i.saturating_sub(1);
+                let upper = (i + 1).min(last);
+                let delay =
+                    -(phase[upper].1 - phase[lower].1) / ((upper - lower) as f32 * angular_step);
+                (phase[i].0, delay)
+            })
+            .collect()
+    }
+
+    /// Drops the phase information and creates a regular [`FrequencySpectrum`]
+    /// from the magnitudes.
+    #[must_use]
+    pub fn to_magnitude_spectrum(&self) -> FrequencySpectrum {
+        let data = self
+            .data
+            .iter()
+            .map(|(fr, complex)| (*fr, magnitude(complex).into()))
+            .collect::<Vec<(Frequency, FrequencyValue)>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(
+            data,
+            self.frequency_resolution,
+            self.samples_len,
+            &mut working_buffer,
+        )
+    }
+
+    /// Reconstructs the time-domain samples with an inverse FFT. This is the
+    /// inverse of [`crate::samples_fft_to_complex_spectrum`], if the spectrum
+    /// was obtained without a frequency limit and no window function was
+    /// applied. Frequencies that are not part of the spectrum, e.g. because
+    /// of a [`crate::limit::FrequencyLimit`], are treated as zero.
+    ///
+    /// ## Return value
+    /// Vector with [`Self::samples_len`] samples.
+    ///
+    /// ## Panics
+    /// If [`Self::samples_len`] is not a power of two that is supported by
+    /// the FFT implementation, see [`crate::MAX_SAMPLES_LEN`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::{samples_fft_to_complex_spectrum, FrequencyLimit};
+    /// let samples = vec![0.0, 1.1, 5.5, -5.5];
+    /// let mut spectrum = samples_fft_to_complex_spectrum(&samples, 44100, FrequencyLimit::All).unwrap();
+    /// // remove the DC component
+    /// spectrum.data_mut()[0].1 = Default::default();
+    /// let filtered = spectrum.to_samples();
+    /// ```
+    #[must_use]
+    pub fn to_samples(&self) -> Vec<f32> {
+        let samples_len = self.samples_len as usize;
+        assert!(
+            samples_len.is_power_of_two() && (2..=crate::MAX_SAMPLES_LEN).contains(&samples_len),
+            "samples_len={} is not supported by the inverse FFT!",
+            samples_len
+        );
+        let mut fft_res = vec![Complex32::default(); samples_len / 2 + 1];
+        for (fr, complex) in &self.data {
+            let index = libm::roundf(fr.val() / self.frequency_resolution) as usize;
+            fft_res[index] = *complex;
+        }
+        FftImpl::calc_inverse(&fft_res)
+    }
+
+    /// Returns the index of the element in [`Self::data`] whose frequency is
+    /// the closest to `search_fr`.
+    fn closest_index(&self, search_fr: f32) -> usize {
+        let min_fr = self.min_fr().val();
+        let max_fr = self.max_fr().val();
+
+        // https://docs.rs/float-cmp/0.8.0/float_cmp/
+        if float_cmp::approx_eq!(f32, min_fr, search_fr, ulps = 3) {
+            return 0;
+        }
+        if float_cmp::approx_eq!(f32, max_fr, search_fr, ulps = 3) {
+            return self.data.len() - 1;
+        }
+
+        // bounds check
+        if search_fr < min_fr || search_fr > max_fr {
+            panic!(
+                "Frequency {}Hz is out of bounds [{}; {}]!",
+                search_fr, min_fr, max_fr
+            );
+        }
+
+        // all frequencies are equidistant, hence we can calculate the index
+        let index = libm::roundf((search_fr - min_fr) / self.frequency_resolution) as usize;
+        index.min(self.data.len() - 1)
+    }
+}
+
+/// Calculates the magnitude of a complex number, i.e. `sqrt(re*re + im*im)`.
+#[inline]
+fn magnitude(val: &Complex32) -> f32 {
+    libm::sqrtf(val.re * val.re + val.im * val.im)
+}
+
+/// Calculates the phase/argument of a complex number in radians.
+#[inline]
+fn phase(val: &Complex32) -> f32 {
+    libm::atan2f(val.im, val.re)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    /// Test if a complex frequency spectrum can be sent to and shared with
+    /// other threads.
+    #[test]
+    const fn test_impl_send_sync() {
+        #[allow(unused)]
+        // test if this compiles
+        fn consume(s: ComplexFrequencySpectrum) {
+            let _: &dyn Send = &s;
+            let _: &dyn Sync = &s;
+        }
+    }
+
+    fn spectrum() -> ComplexFrequencySpectrum {
+        let data = vec![
+            (0.0.into(), Complex32::new(1.0, 0.0)),
+            (50.0.into(), Complex32::new(0.0, 2.0)),
+            (100.0.into(), Complex32::new(-3.0, 0.0)),
+            (150.0.into(), Complex32::new(3.0, -4.0)),
+        ];
+        ComplexFrequencySpectrum::new(data, 50.0, 6)
+    }
+
+    #[test]
+    fn test_getters() {
+        let spectrum = spectrum();
+        assert_eq!(spectrum.min_fr().val(), 0.0);
+        assert_eq!(spectrum.max_fr().val(), 150.0);
+        assert_eq!(spectrum.magnitude_at(0.0).val(), 1.0);
+        assert_eq!(spectrum.magnitude_at(48.0).val(), 2.0);
+        assert_eq!(spectrum.magnitude_at(140.0).val(), 5.0);
+        float_cmp::assert_approx_eq!(f32, spectrum.phase_at(50.0), PI / 2.0, ulps = 3);
+        float_cmp::assert_approx_eq!(f32, spectrum.phase_at(100.0), PI, ulps = 3);
+        assert_eq!(spectrum.complex_at(74.0).0.val(), 50.0);
+        assert_eq!(spectrum.complex_at(76.0).0.val(), 100.0);
+    }
+
+    #[test]
+    fn test_to_magnitude_spectrum() {
+        let spectrum = spectrum().to_magnitude_spectrum();
+        assert_eq!(spectrum.max(), (150.0.into(), 5.0.into()));
+        assert_eq!(spectrum.min(), (0.0.into(), 1.0.into()));
+        assert_eq!(spectrum.samples_len(), 6);
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_to_samples() {
+        let samples = [0.0, 1.1, 5.5, -5.5, 3.0, 2.0, -1.0, 0.5];
+        let spectrum =
+            crate::samples_fft_to_complex_spectrum(&samples, 8, crate::FrequencyLimit::All)
+                .unwrap();
+        let reconstructed = spectrum.to_samples();
+        assert_eq!(reconstructed.len(), samples.len());
+        for (expected, actual) in samples.iter().zip(&reconstructed) {
+            float_cmp::assert_approx_eq!(f32, *expected, *actual, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_unwrapped_phase_and_group_delay() {
+        // pure delay of 1ms, i.e. the phase decreases linearly by
+        // 2*PI*f*0.001 and wraps around every 1000 Hz
+        let data = (0..=100)
+            .map(|i| {
+                let fr = i as f32 * 50.0;
+                let phase = -TAU * fr * 0.001;
+                (
+                    fr.into(),
+                    Complex32::new(libm::cosf(phase), libm::sinf(phase)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let spectrum = ComplexFrequencySpectrum::new(data, 50.0, 200);
+
+        for (fr, phase) in spectrum.unwrapped_phase() {
+            let expected = -TAU * fr.val() * 0.001;
+            assert!(
+                (phase - expected).abs() < 1e-3,
+                "{fr}: {phase} != {expected}"
+            );
+        }
+        for (fr, delay) in spectrum.group_delay() {
+            assert!((delay - 0.001).abs() < 1e-6, "{fr}: {delay}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        let _ = spectrum().phase_at(151.0);
+    }
+}
@@ -0,0 +1,193 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [constant-Q transform] (CQT). In contrast to the FFT, the
+//! frequencies of the CQT are spaced logarithmically and each frequency has
+//! the same ratio of center frequency to bandwidth (the "Q" factor). Hence,
+//! low frequencies get a fine absolute resolution and high frequencies a
+//! coarse one. This matches musical scales, where each octave has the same
+//! number of notes.
+//!
+//! This is a direct implementation of the CQT as described by
+//! Judith C. Brown in *Calculation of a constant Q spectral transform* (1991).
+//!
+//! [constant-Q transform]: https://en.wikipedia.org/wiki/Constant-Q_transform
+
+use crate::error::SpectrumAnalyzerError;
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::limit::{FrequencyLimit, FrequencyLimitError};
+use crate::validate_samples;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// Calculates the constant-Q transform of the given samples. Each frequency
+/// bin `k` has the center frequency `f_min * 2^(k / bins_per_octave)`. The
+/// transform of each bin uses a Hann-windowed kernel whose length is inversely
+/// proportional to the center frequency. The magnitude of each bin is
+/// normalized by the length of its kernel, so that the values of different
+/// bins are comparable.
+///
+/// ## Parameters
+/// * `samples` raw audio, e.g. 16bit audio data but as f32. Any length is
+///             supported, but it must be at least as long as the kernel of
+///             the lowest frequency, which is roughly
+///             `bins_per_octave * 1.44 * sampling_rate / f_min`.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `f_min` Center frequency of the lowest bin in Hertz. Must be bigger than 0.
+/// * `f_max` Upper bound (inclusive) for the center frequency of the highest
+///           bin in Hertz. Must not be above the Nyquist frequency.
+/// * `bins_per_octave` Number of bins per octave, e.g. `12` for semitones.
+///
+/// ## Return value
+/// `(center frequency, magnitude)`-pairs, sorted from the lowest to the
+/// highest frequency.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::cqt::constant_q_transform;
+/// let samples = vec![0.0; 4096];
+/// // semitones from A2 to A5
+/// let cqt = constant_q_transform(&samples, 16000, 110.0, 880.0, 12).unwrap();
+/// assert_eq!(cqt.len(), 37);
+/// ```
+///
+/// ## Errors
+/// See [`crate::samples_fft_to_spectrum`]. If the frequency range is
+/// invalid, [`SpectrumAnalyzerError::InvalidFrequencyLimit`] is returned. If
+/// there are not enough samples for the lowest frequency,
+/// [`SpectrumAnalyzerError::TooFewSamples`] is returned.
+///
+/// ## Panics
+/// If `bins_per_octave` is zero.
+pub fn constant_q_transform(
+    samples: &[f32],
+    sampling_rate: u32,
+    f_min: f32,
+    f_max: f32,
+    bins_per_octave: u32,
+) -> Result<Vec<(Frequency, FrequencyValue)>, SpectrumAnalyzerError> {
+    assert!(
+        bins_per_octave > 0,
+        "At least one bin per octave is required!"
+    );
+    validate_samples(samples)?;
+    if f_min <= 0.0 {
+        return Err(SpectrumAnalyzerError::InvalidFrequencyLimit(
+            FrequencyLimitError::ValueBelowMinimum(f_min),
+        ));
+    }
+    FrequencyLimit::Range(f_min, f_max)
+        .verify(sampling_rate as f32 / 2.0)
+        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+
+    let q = quality_factor(bins_per_octave);
+    if kernel_len(q, sampling_rate, f_min) > samples.len() {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+
+    let bins_count = libm::floorf(bins_per_octave as f32 * libm::log2f(f_max / f_min)) as u32 + 1;
+    let result = (0..bins_count)
+        .map(|k| f_min * libm::exp2f(k as f32 / bins_per_octave as f32))
+        .map(|fr| {
+            let len = kernel_len(q, sampling_rate, fr);
+            (
+                Frequency::from(fr),
+                FrequencyValue::from(cqt_bin(&samples[0..len], q)),
+            )
+        })
+        .collect();
+    Ok(result)
+}
+
+/// Returns the Q factor, i.e. the ratio of center frequency to bandwidth,
+/// for the given number of bins per octave.
+#[inline]
+fn quality_factor(bins_per_octave: u32) -> f32 {
+    1.0 / (libm::exp2f(1.0 / bins_per_octave as f32) - 1.0)
+}
+
+/// Returns the length of the kernel, i.e. the number of samples, that is
+/// required to resolve `frequency` with the given Q factor.
+#[inline]
+fn kernel_len(q: f32, sampling_rate: u32, frequency: f32) -> usize {
+    libm::ceilf(q * sampling_rate as f32 / frequency) as usize
+}
+
+/// Calculates the magnitude of a single CQT bin. `samples.len()` equals the
+/// kernel length. Within the kernel, the frequency of the bin completes
+/// exactly `q` cycles.
+fn cqt_bin(samples: &[f32], q: f32) -> f32 {
+    let len = samples.len() as f32;
+    let (re, im) = samples
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (n, sample)| {
+            let n = n as f32;
+            // Hann window
+            let window = 0.5 * (1.0 - libm::cosf(2.0 * PI * n / len));
+            let angle = 2.0 * PI * q * n / len;
+            (
+                re + window * sample * libm::cosf(angle),
+                im - window * sample * libm::sinf(angle),
+            )
+        });
+    libm::sqrtf(re * re + im * im) / len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_at_tone() {
+        let samples = (0..4096)
+            .map(|i| libm::sinf(2.0 * PI * 440.0 * i as f32 / 8000.0))
+            .collect::<Vec<_>>();
+        let cqt = constant_q_transform(&samples, 8000, 110.0, 1760.0, 12).unwrap();
+        assert_eq!(cqt.len(), 49);
+        let (max_fr, max_val) = cqt.iter().max_by_key(|(_fr, val)| *val).unwrap();
+        float_cmp::assert_approx_eq!(f32, max_fr.val(), 440.0, epsilon = 0.01);
+        // amplitude 1 * average of the Hann window (0.5) / 2
+        float_cmp::assert_approx_eq!(f32, max_val.val(), 0.25, epsilon = 0.01);
+        // frequencies are spaced logarithmically
+        float_cmp::assert_approx_eq!(f32, cqt[12].0.val(), 220.0, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let samples = vec![0.0; 1024];
+        assert!(matches!(
+            constant_q_transform(&samples, 8000, 0.0, 1000.0, 12).unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyLimit(_)
+        ));
+        assert!(matches!(
+            constant_q_transform(&samples, 8000, 1000.0, 5000.0, 12).unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyLimit(_)
+        ));
+        // the kernel for 50 Hz needs ~2700 samples
+        assert!(matches!(
+            constant_q_transform(&samples, 8000, 50.0, 1000.0, 12).unwrap_err(),
+            SpectrumAnalyzerError::TooFewSamples
+        ));
+    }
+}
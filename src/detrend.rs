@@ -0,0 +1,132 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Removal of a DC offset or a slow drift from samples before the FFT. See
+//! [`Detrend`] and [`crate::SpectrumAnalyzerBuilder::detrend`].
+//!
+//! A DC offset ends up in the frequency bin 0 and, as soon as a window is
+//! applied, leaks into the neighbouring low-frequency bins. A slow drift,
+//! e.g. of a sensor, has the same effect. Hence, these bins often dominate
+//! the spectrum although they carry no information.
+
+/// How a trend is removed from the samples. This happens before the window
+/// function is applied.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::detrend::Detrend;
+/// let mut samples = [1.0, 2.0, 3.0, 4.0];
+/// Detrend::Linear.apply(&mut samples);
+/// assert!(samples.iter().all(|x| x.abs() < 1e-6));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detrend {
+    /// The samples stay unchanged.
+    #[default]
+    None,
+    /// Subtracts the mean of the samples, i.e. removes the DC offset.
+    Mean,
+    /// Subtracts the least-squares line fitted through the samples, i.e.
+    /// removes the DC offset and a linear drift.
+    Linear,
+}
+
+impl Detrend {
+    /// Removes the trend from the samples in-place.
+    ///
+    /// ## Parameters
+    /// * `samples` Samples without NaN or infinite values.
+    pub fn apply(self, samples: &mut [f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let len = samples.len() as f32;
+        let mean = samples.iter().sum::<f32>() / len;
+        match self {
+            Self::None => {}
+            Self::Mean => samples.iter_mut().for_each(|x| *x -= mean),
+            Self::Linear => {
+                // With the positions centered around their mean, the slope
+                // of the regression line is sum((i - i_mean) * x) divided by
+                // sum((i - i_mean)^2) = n * (n^2 - 1) / 12.
+                let center = (len - 1.0) / 2.0;
+                let denominator = len * (len * len - 1.0) / 12.0;
+                let slope = if denominator > 0.0 {
+                    samples
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| (i as f32 - center) * x)
+                        .sum::<f32>()
+                        / denominator
+                } else {
+                    0.0
+                };
+                samples
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(i, x)| *x -= mean + slope * (i as f32 - center));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_detrend() {
+        let samples = (0..64)
+            .map(|i| 3.0 + 0.5 * i as f32 + libm::sinf(i as f32))
+            .collect::<Vec<_>>();
+
+        let mut unchanged = samples.clone();
+        Detrend::None.apply(&mut unchanged);
+        assert_eq!(unchanged, samples);
+
+        let mut mean = samples.clone();
+        Detrend::Mean.apply(&mut mean);
+        assert!(mean.iter().sum::<f32>().abs() < 1e-3);
+        // the drift remains
+        assert!(mean[63] - mean[0] > 30.0);
+
+        let mut linear = samples;
+        Detrend::Linear.apply(&mut linear);
+        assert!(linear.iter().sum::<f32>().abs() < 1e-3);
+        // only the sine remains
+        for (i, x) in linear.iter().enumerate() {
+            assert!((x - libm::sinf(i as f32)).abs() < 0.1, "{i}: {x}");
+        }
+    }
+
+    #[test]
+    fn test_detrend_short_input() {
+        let mut empty: [f32; 0] = [];
+        Detrend::Linear.apply(&mut empty);
+        let mut single = [5.0];
+        Detrend::Linear.apply(&mut single);
+        assert_eq!(single, [0.0]);
+    }
+}
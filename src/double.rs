@@ -0,0 +1,250 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Spectrum analysis with `f64` precision for high-dynamic-range data, e.g.
+//! in scientific applications. See [`samples_fft_to_spectrum_f64`].
+//!
+//! As [`microfft`] only supports `f32`, this module uses its own (slower)
+//! radix-2 FFT. It doesn't have the length limit of
+//! [`crate::MAX_SAMPLES_LEN`].
+
+use crate::error::SpectrumAnalyzerError;
+use crate::frequency::{FrequencyF64, FrequencyValueF64};
+use crate::limit::FrequencyLimit;
+use crate::validate_frequency_limit;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+/// Like [`crate::FrequencySpectrum`] but with `f64` precision. Contains all
+/// frequencies inside the frequency limit.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrequencySpectrumF64 {
+    data: Vec<(FrequencyF64, FrequencyValueF64)>,
+    frequency_resolution: f64,
+    samples_len: u32,
+}
+
+impl FrequencySpectrumF64 {
+    /// Returns the underlying data, sorted from the lowest to the highest
+    /// frequency.
+    #[inline]
+    #[must_use]
+    pub fn data(&self) -> &[(FrequencyF64, FrequencyValueF64)] {
+        &self.data
+    }
+
+    /// Returns the frequency resolution of this spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn frequency_resolution(&self) -> f64 {
+        self.frequency_resolution
+    }
+
+    /// Returns the number of samples used to obtain this spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn samples_len(&self) -> u32 {
+        self.samples_len
+    }
+
+    /// Returns the (frequency, frequency value)-pair with the maximum
+    /// frequency value.
+    #[must_use]
+    pub fn max(&self) -> (FrequencyF64, FrequencyValueF64) {
+        *self
+            .data
+            .iter()
+            .max_by_key(|(_fr, val)| *val)
+            .expect("spectrum is never empty")
+    }
+
+    /// Returns the (frequency, frequency value)-pair with the minimum
+    /// frequency value.
+    #[must_use]
+    pub fn min(&self) -> (FrequencyF64, FrequencyValueF64) {
+        *self
+            .data
+            .iter()
+            .min_by_key(|(_fr, val)| *val)
+            .expect("spectrum is never empty")
+    }
+
+    /// Returns the average frequency value.
+    #[must_use]
+    pub fn average(&self) -> FrequencyValueF64 {
+        let sum = self.data.iter().map(|(_fr, val)| val.val()).sum::<f64>();
+        (sum / self.data.len() as f64).into()
+    }
+}
+
+/// Like [`crate::samples_fft_to_spectrum`] but with `f64` precision for the
+/// samples, the FFT, and the spectrum.
+///
+/// ## Parameters
+/// * `samples` Samples. The length must be a power of two.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::double::samples_fft_to_spectrum_f64;
+/// use spectrum_analyzer::FrequencyLimit;
+/// let samples = [0.0, 1.0, 0.0, -1.0];
+/// let spectrum = samples_fft_to_spectrum_f64(&samples, 4, FrequencyLimit::All).unwrap();
+/// assert_eq!(spectrum.max().0.val(), 1.0);
+/// ```
+///
+/// ## Errors
+/// * [`SpectrumAnalyzerError::TooFewSamples`] if `samples.len() < 2`,
+/// * [`SpectrumAnalyzerError::NaNValuesNotSupported`] if a sample is `NaN`,
+/// * [`SpectrumAnalyzerError::InfinityValuesNotSupported`] if a sample is infinite,
+/// * [`SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo`] if `samples.len()` isn't a power of two,
+/// * [`SpectrumAnalyzerError::InvalidFrequencyLimit`] if the frequency limit is invalid,
+/// * [`SpectrumAnalyzerError::InvalidFrequencyResolution`] if no frequency is
+///   inside the frequency limit.
+pub fn samples_fft_to_spectrum_f64(
+    samples: &[f64],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+) -> Result<FrequencySpectrumF64, SpectrumAnalyzerError> {
+    if samples.len() < 2 {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    if samples.iter().any(|x| x.is_nan()) {
+        return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+    }
+    if samples.iter().any(|x| x.is_infinite()) {
+        return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+    }
+    if !samples.len().is_power_of_two() {
+        return Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo);
+    }
+    validate_frequency_limit(sampling_rate, &frequency_limit)?;
+
+    let frequency_resolution = f64::from(sampling_rate) / samples.len() as f64;
+    let data = fft(samples)
+        .into_iter()
+        .take(samples.len() / 2 + 1)
+        .enumerate()
+        .map(|(i, (re, im))| (i as f64 * frequency_resolution, libm::hypot(re, im)))
+        .filter(|(fr, _val)| frequency_limit.contains(*fr as f32))
+        .map(|(fr, val)| (fr.into(), val.into()))
+        .collect::<Vec<_>>();
+    if data.is_empty() {
+        return Err(SpectrumAnalyzerError::InvalidFrequencyResolution(
+            frequency_resolution as f32,
+        ));
+    }
+
+    Ok(FrequencySpectrumF64 {
+        data,
+        frequency_resolution,
+        samples_len: samples.len() as u32,
+    })
+}
+
+/// Iterative radix-2 FFT of real samples. The length must be a power of two.
+/// Returns `(re, im)`-pairs of the full (mirrored) FFT result.
+fn fft(samples: &[f64]) -> Vec<(f64, f64)> {
+    let len = samples.len();
+    let bits = len.trailing_zeros();
+    let mut buffer = vec![(0.0, 0.0); len];
+    for (i, sample) in samples.iter().enumerate() {
+        buffer[i.reverse_bits() >> (usize::BITS - bits)] = (*sample, 0.0);
+    }
+
+    let mut size = 2;
+    while size <= len {
+        let half = size / 2;
+        for k in 0..half {
+            let angle = -2.0 * PI * k as f64 / size as f64;
+            let (w_re, w_im) = (libm::cos(angle), libm::sin(angle));
+            for start in (0..len).step_by(size) {
+                let (a_re, a_im) = buffer[start + k];
+                let (b_re, b_im) = buffer[start + k + half];
+                let t_re = b_re * w_re - b_im * w_im;
+                let t_im = b_re * w_im + b_im * w_re;
+                buffer[start + k] = (a_re + t_re, a_im + t_im);
+                buffer[start + k + half] = (a_re - t_re, a_im - t_im);
+            }
+        }
+        size *= 2;
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_same_result_as_f32_path() {
+        let samples = (0..1024)
+            .map(|i| libm::sin(i as f64 * 0.3) + 0.2 * libm::cos(i as f64 * 1.7))
+            .collect::<Vec<_>>();
+        let samples_f32 = samples.iter().map(|x| *x as f32).collect::<Vec<_>>();
+        let limit = FrequencyLimit::Range(100.0, 10000.0);
+        let spectrum = samples_fft_to_spectrum_f64(&samples, 44100, limit).unwrap();
+        let expected = samples_fft_to_spectrum(&samples_f32, 44100, limit, None).unwrap();
+
+        assert_eq!(spectrum.data().len(), expected.data().len());
+        for ((fr_a, val_a), (fr_b, val_b)) in spectrum.data().iter().zip(expected.data()) {
+            assert!((fr_a.val() - f64::from(fr_b.val())).abs() < 1e-3);
+            assert!((val_a.val() - f64::from(val_b.val())).abs() < 1e-2);
+        }
+        assert_eq!(
+            spectrum.max().0.val() as f32,
+            expected.max().0.val(),
+            "same peak frequency"
+        );
+    }
+
+    #[test]
+    fn test_high_dynamic_range() {
+        // the small sine is far below the precision of f32 relative to the big one
+        let samples = (0..64)
+            .map(|i| {
+                let phase = 2.0 * PI * i as f64 / 64.0;
+                1e9 * libm::sin(4.0 * phase) + 1e-3 * libm::sin(16.0 * phase)
+            })
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum_f64(&samples, 64, FrequencyLimit::All).unwrap();
+        let small = spectrum.data()[16].1.val();
+        assert!((small - 0.032).abs() < 1e-3, "{small}");
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            samples_fft_to_spectrum_f64(&[0.0; 3], 44100, FrequencyLimit::All),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+        assert!(matches!(
+            samples_fft_to_spectrum_f64(&[0.0, f64::NAN], 44100, FrequencyLimit::All),
+            Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+        ));
+    }
+}
@@ -28,10 +28,12 @@ SOFTWARE.
 //! located in submodules.
 
 use crate::limit::FrequencyLimitError;
+use crate::windows::Window;
 
 /// Describes main errors of the library. Almost all errors
 /// are caused by wrong input.
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SpectrumAnalyzerError {
     /// There must be at least two samples.
     TooFewSamples,
@@ -42,7 +44,32 @@ pub enum SpectrumAnalyzerError {
     /// See [`crate::limit::FrequencyLimitError`].
     InvalidFrequencyLimit(FrequencyLimitError),
     /// The number of samples must be a power of two in order for the FFT.
+    /// This is a requirement of the underlying FFT implementation
+    /// (`microfft`). Pad the samples with zeroes to the next power of two.
     SamplesLengthNotAPowerOfTwo,
+    /// The number of samples exceeds the biggest supported FFT length. See
+    /// [`crate::MAX_SAMPLES_LEN`].
+    TooManySamples,
+    /// The number of samples doesn't match the configured number of samples,
+    /// e.g. of a [`crate::SpectrumAnalyzer`]. Contains `(expected, actual)`.
+    SamplesLengthMismatch(usize, usize),
+    /// The overlap of two consecutive segments must be smaller than the
+    /// segment length, as otherwise the analysis would never progress.
+    /// Contains `(segment_len, overlap)`.
+    InvalidSegmentOverlap(usize, usize),
+    /// The hop length, i.e. the number of samples between the beginnings of
+    /// two consecutive frames, must not be zero, as otherwise the analysis
+    /// would never progress.
+    InvalidHopLength,
+    /// The requested frequency resolution must be a positive number that is
+    /// small enough to get at least two frequencies inside the frequency range.
+    InvalidFrequencyResolution(f32),
+    /// The coefficient of the pre-emphasis filter must be in range
+    /// `0.0..=1.0`. Contains the coefficient.
+    InvalidPreEmphasis(f32),
+    /// The parameter of the window is out of range, e.g. a negative `beta`
+    /// of [`Window::Kaiser`]. See [`Window::apply`]. Contains the window.
+    InvalidWindow(Window),
     /// After applying the scaling function on a specific item, the returned value is either
     /// infinity or NaN, according to IEEE-754. This is invalid. Check
     /// your scaling function!
@@ -0,0 +1,221 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Spectral features as commonly used in music information retrieval (MIR).
+//! They describe the shape of a spectrum with a single number each, e.g. its
+//! "brightness" ([`spectral_centroid`]) or its "noisiness"
+//! ([`spectral_flatness`]).
+//!
+//! All functions expect a spectrum with non-negative magnitudes, i.e. no
+//! decibel values. They return `None` if the spectrum is silent, i.e. if all
+//! values are zero, as the features are undefined in that case.
+
+use crate::frequency::Frequency;
+use crate::spectrum::FrequencySpectrum;
+
+/// Lower bound for values before the logarithm is applied. Prevents `-inf`.
+const MIN_LOG_VALUE: f32 = 1e-20;
+
+/// Calculates the spectral centroid, i.e. the magnitude-weighted mean
+/// frequency. This is the "center of mass" of the spectrum and correlates
+/// with the perceived brightness of a sound.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// use spectrum_analyzer::features::spectral_centroid;
+/// let samples = (0..1024)
+///     .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 100.0 * i as f32 / 1024.0))
+///     .collect::<Vec<_>>();
+/// let spectrum = samples_fft_to_spectrum(&samples, 1024, FrequencyLimit::All, None).unwrap();
+/// let centroid = spectral_centroid(&spectrum).unwrap();
+/// assert!((centroid.val() - 100.0).abs() < 1.0);
+/// # }
+/// ```
+#[must_use]
+pub fn spectral_centroid(spectrum: &FrequencySpectrum) -> Option<Frequency> {
+    let total = magnitude_sum(spectrum)?;
+    let weighted_sum = spectrum
+        .data()
+        .iter()
+        .map(|(fr, val)| fr.val() * val.val())
+        .sum::<f32>();
+    Some((weighted_sum / total).into())
+}
+
+/// Calculates the spectral spread (also called spectral bandwidth), i.e. the
+/// magnitude-weighted standard deviation of the frequencies around the
+/// [`spectral_centroid`].
+#[must_use]
+pub fn spectral_spread(spectrum: &FrequencySpectrum) -> Option<Frequency> {
+    let total = magnitude_sum(spectrum)?;
+    let centroid = spectral_centroid(spectrum)?.val();
+    let variance = spectrum
+        .data()
+        .iter()
+        .map(|(fr, val)| (fr.val() - centroid) * (fr.val() - centroid) * val.val())
+        .sum::<f32>()
+        / total;
+    Some(libm::sqrtf(variance).into())
+}
+
+/// Calculates the spectral rolloff, i.e. the lowest frequency below which
+/// the given percentage of the total magnitude is located.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum with magnitudes (not in decibel).
+/// * `percentage` Percentage in range `[0.0, 1.0]`. Typical values are
+///                `0.85` or `0.95`.
+///
+/// ## Panics
+/// If `percentage` is not in range `[0.0, 1.0]`.
+#[must_use]
+pub fn spectral_rolloff(spectrum: &FrequencySpectrum, percentage: f32) -> Option<Frequency> {
+    assert!(
+        (0.0..=1.0).contains(&percentage),
+        "percentage must be in range [0.0, 1.0]!"
+    );
+    let threshold = magnitude_sum(spectrum)? * percentage;
+    let mut cumulative_sum = 0.0;
+    spectrum
+        .data()
+        .iter()
+        .find(|(_fr, val)| {
+            cumulative_sum += val.val();
+            cumulative_sum >= threshold
+        })
+        .or_else(|| spectrum.data().last())
+        .map(|(fr, _val)| *fr)
+}
+
+/// Calculates the spectral flatness (also called Wiener entropy), i.e. the
+/// ratio of the geometric mean to the arithmetic mean of the values. It is
+/// in range `[0.0, 1.0]`, where `1.0` corresponds to white noise and values
+/// close to `0.0` to pure tones.
+#[must_use]
+pub fn spectral_flatness(spectrum: &FrequencySpectrum) -> Option<f32> {
+    let total = magnitude_sum(spectrum)?;
+    let len = spectrum.data().len() as f32;
+    let log_mean = spectrum
+        .data()
+        .iter()
+        .map(|(_fr, val)| libm::logf(val.val().max(MIN_LOG_VALUE)))
+        .sum::<f32>()
+        / len;
+    Some(libm::expf(log_mean) / (total / len))
+}
+
+/// Calculates the spectral crest factor, i.e. the ratio of the maximum value
+/// to the arithmetic mean of the values. High values indicate a tonal
+/// spectrum with a few dominant peaks.
+#[must_use]
+pub fn spectral_crest(spectrum: &FrequencySpectrum) -> Option<f32> {
+    let total = magnitude_sum(spectrum)?;
+    let len = spectrum.data().len() as f32;
+    Some(spectrum.max().1.val() / (total / len))
+}
+
+/// Returns the sum of all values or `None` if the sum is zero.
+fn magnitude_sum(spectrum: &FrequencySpectrum) -> Option<f32> {
+    let sum = spectrum
+        .data()
+        .iter()
+        .map(|(_fr, val)| val.val())
+        .sum::<f32>();
+    if sum > 0.0 {
+        Some(sum)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrequencyValue;
+    use alloc::vec::Vec;
+    use float_cmp::assert_approx_eq;
+
+    fn spectrum(values: &[f32]) -> FrequencySpectrum {
+        let data = values
+            .iter()
+            .enumerate()
+            .map(|(i, val)| (Frequency::from(i as f32 * 10.0), FrequencyValue::from(*val)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(data, 10.0, values.len() as u32 * 2, &mut working_buffer)
+    }
+
+    #[test]
+    fn test_centroid_and_spread() {
+        let flat = spectrum(&[1.0; 5]);
+        assert_approx_eq!(f32, spectral_centroid(&flat).unwrap().val(), 20.0);
+        // standard deviation of 0, 10, 20, 30, 40
+        assert_approx_eq!(
+            f32,
+            spectral_spread(&flat).unwrap().val(),
+            libm::sqrtf(200.0),
+            epsilon = 0.001
+        );
+
+        let tone = spectrum(&[0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_approx_eq!(f32, spectral_centroid(&tone).unwrap().val(), 30.0);
+        assert_approx_eq!(f32, spectral_spread(&tone).unwrap().val(), 0.0);
+    }
+
+    #[test]
+    fn test_rolloff() {
+        let spectrum = spectrum(&[4.0, 3.0, 2.0, 1.0, 0.0]);
+        assert_eq!(spectral_rolloff(&spectrum, 0.0).unwrap().val(), 0.0);
+        assert_eq!(spectral_rolloff(&spectrum, 0.7).unwrap().val(), 10.0);
+        assert_eq!(spectral_rolloff(&spectrum, 0.85).unwrap().val(), 20.0);
+        assert_eq!(spectral_rolloff(&spectrum, 1.0).unwrap().val(), 30.0);
+    }
+
+    #[test]
+    fn test_flatness_and_crest() {
+        let flat = spectrum(&[2.0; 8]);
+        assert_approx_eq!(
+            f32,
+            spectral_flatness(&flat).unwrap(),
+            1.0,
+            epsilon = 0.0001
+        );
+        assert_approx_eq!(f32, spectral_crest(&flat).unwrap(), 1.0);
+
+        let tone = spectrum(&[0.0, 0.0, 8.0, 0.0]);
+        assert!(spectral_flatness(&tone).unwrap() < 0.001);
+        assert_approx_eq!(f32, spectral_crest(&tone).unwrap(), 4.0);
+    }
+
+    #[test]
+    fn test_silence() {
+        let silence = spectrum(&[0.0; 4]);
+        assert!(spectral_centroid(&silence).is_none());
+        assert!(spectral_spread(&silence).is_none());
+        assert!(spectral_rolloff(&silence, 0.85).is_none());
+        assert!(spectral_flatness(&silence).is_none());
+        assert!(spectral_crest(&silence).is_none());
+    }
+}
@@ -27,37 +27,198 @@ SOFTWARE.
 //! example). The difference to a complex FFT, as with `rustfft` is, that the
 //! result vector contains less results as there are no mirrored frequencies.
 
+use crate::error::SpectrumAnalyzerError;
+use crate::validate_fft_len;
 use alloc::vec::Vec;
 use core::convert::TryInto;
-use microfft::real;
+use core::fmt::Debug;
+use microfft::{complex, inverse, real};
 
 /// The result of a FFT is always complex but because different FFT crates might
 /// use different versions of "num-complex", each implementation exports
 /// it's own version that gets used in lib.rs for binary compatibility.
 pub use microfft::Complex32;
 
-/// Calculates the real FFT by invoking the proper function corresponding to the
-/// buffer length.
-macro_rules! real_fft_n {
-    ($buffer:expr, $( $i:literal ),*) => {
-        match $buffer.len() {
+/// Calculates an FFT in-place by invoking the function `$module::$prefix<N>`
+/// of [`microfft`] that corresponds to the buffer length `N`, e.g.
+/// `real::rfft_1024`. All kernels of [`microfft`] take a reference to an
+/// array and return a reference to an array with the result, which gets
+/// turned into a slice. This is the only place that lists the supported
+/// FFT lengths, so that each kernel is only referenced once per kind of FFT.
+/// Lengths whose `size-*` feature is disabled are compiled out.
+///
+/// # Panics
+/// If the buffer length isn't one of the supported lengths.
+macro_rules! fft_in_place_n {
+    ($module:ident :: $prefix:ident, $buffer:expr) => {
+        fft_in_place_n!(
+            @dispatch $module::$prefix,
+            $buffer,
+            2,
+            4,
+            8 if "size-8",
+            16 if "size-16",
+            32 if "size-32",
+            64 if "size-64",
+            128 if "size-128",
+            256 if "size-256",
+            512 if "size-512",
+            1024 if "size-1024",
+            2048 if "size-2048",
+            4096 if "size-4096",
+            8192 if "size-8192",
+            16384 if "size-16384"
+        )
+    };
+    (
+        @dispatch $module:ident :: $prefix:ident,
+        $buffer:expr,
+        $( $i:literal $( if $feature:literal )? ),*
+    ) => {{
+        let buffer = $buffer;
+        match buffer.len() {
             $(
+                $( #[cfg(feature = $feature)] )?
                 $i => {
-                    let mut buffer: [_; $i] = $buffer.try_into().unwrap();
-                    paste::paste! (
-                        real::[<rfft_$i>]
-                    )(&mut buffer).to_vec()
+                    let buffer: &mut [_; $i] = buffer.try_into().unwrap();
+                    paste::paste!($module::[<$prefix $i>])(buffer).as_mut_slice()
                 }
             )*
-            _ => { unimplemented!("unexpected buffer len") }
+            _ => unimplemented!("unexpected buffer len"),
         }
-    };
+    }};
+}
+
+/// FFT implementation that is selectable at runtime, e.g. in
+/// [`crate::SpectrumAnalyzerBuilder::fft_backend`]. All backends produce the
+/// same spectrum (except for floating point inaccuracies). The `microfft`
+/// backends support powers of two up to [`crate::MAX_SAMPLES_LEN`], while
+/// `FftBackend::RustFft` (needs the `rustfft` feature) supports all lengths.
+/// `FftBackend::CmsisDsp` (needs the `cmsis-dsp` feature and an ARM target)
+/// supports powers of two from `32` up to `4096`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FftBackend {
+    /// Real FFT using [`microfft::real`]. This is the fastest option and
+    /// needs the least memory.
+    #[default]
+    MicrofftReal,
+    /// Complex FFT using [`microfft::complex`]. The samples are transformed to
+    /// complex numbers first. This needs twice the memory of
+    /// [`FftBackend::MicrofftReal`] and is slower but doesn't rely on the
+    /// packing of the Nyquist frequency. It supports the same lengths as
+    /// [`FftBackend::MicrofftReal`].
+    MicrofftComplex,
+    /// Complex FFT using `rustfft`, which supports any length via
+    /// mixed-radix and Bluestein's algorithm, e.g. `1000` or `44100`. Needs
+    /// the `std` library. The plans are cached per thread.
+    #[cfg(feature = "rustfft")]
+    RustFft,
+    /// Real FFT `arm_rfft_fast_f32` of the CMSIS-DSP library, which uses the
+    /// DSP instructions and the FPU of Cortex-M4/M7 targets. The binary must
+    /// link against the CMSIS-DSP library for the target, e.g.
+    /// `libarm_cortexM4lf_math.a`. Supports powers of two from `32` up to
+    /// `4096`.
+    #[cfg(all(feature = "cmsis-dsp", any(target_arch = "arm", test)))]
+    CmsisDsp,
+}
+
+/// Interface of an FFT implementation. Implement this to plug in your own
+/// FFT, e.g. a hardware-accelerated one, and reuse all the post-processing
+/// of this crate via [`crate::samples_fft_to_spectrum_with_fft`] or
+/// [`crate::SpectrumAnalyzerBuilder::fft_backend`].
+pub trait Fft: Debug {
+    /// Checks whether the FFT supports `len` samples. This is called before
+    /// [`Self::calc`], so that unsupported lengths are reported as error
+    /// instead of a panic. The default implementation accepts all lengths.
+    ///
+    /// # Errors
+    /// An error that describes why the length isn't supported, e.g.
+    /// [`SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo`] or
+    /// [`SpectrumAnalyzerError::TooManySamples`].
+    fn validate_len(&self, _len: usize) -> Result<(), SpectrumAnalyzerError> {
+        Ok(())
+    }
+
+    /// Calculates the FFT of the given real samples.
+    ///
+    /// # Parameters
+    /// - `samples`: At least two samples without NaN or infinite values. The
+    ///              length was accepted by [`Self::validate_len`].
+    ///
+    /// # Return value
+    /// Exactly `samples.len() / 2 + 1` complex values, where the first index
+    /// corresponds to the DC component and the last index to the Nyquist
+    /// frequency. The values must not be normalized.
+    fn calc(&self, samples: &[f32]) -> Vec<Complex32>;
+}
+
+impl Fft for FftBackend {
+    #[inline]
+    fn validate_len(&self, len: usize) -> Result<(), SpectrumAnalyzerError> {
+        match self {
+            Self::MicrofftReal | Self::MicrofftComplex => validate_fft_len(len),
+            #[cfg(feature = "rustfft")]
+            Self::RustFft => Ok(()),
+            #[cfg(all(feature = "cmsis-dsp", any(target_arch = "arm", test)))]
+            Self::CmsisDsp => crate::cmsis_dsp::validate_len(len),
+        }
+    }
+
+    #[inline]
+    fn calc(&self, samples: &[f32]) -> Vec<Complex32> {
+        match self {
+            Self::MicrofftReal => FftImpl::calc(samples),
+            Self::MicrofftComplex => FftImpl::calc_complex(samples),
+            #[cfg(feature = "rustfft")]
+            Self::RustFft => calc_rustfft(samples),
+            #[cfg(all(feature = "cmsis-dsp", any(target_arch = "arm", test)))]
+            Self::CmsisDsp => crate::cmsis_dsp::calc(samples),
+        }
+    }
+}
+
+/// Calculates the FFT of [`FftBackend::RustFft`]. The result has the length
+/// `samples.len() / 2 + 1`.
+#[cfg(feature = "rustfft")]
+fn calc_rustfft(samples: &[f32]) -> Vec<Complex32> {
+    std::thread_local! {
+        // caches the plans of all lengths that were used so far
+        static PLANNER: core::cell::RefCell<rustfft::FftPlanner<f32>> =
+            core::cell::RefCell::new(rustfft::FftPlanner::new());
+    }
+    let fft = PLANNER.with(|planner| planner.borrow_mut().plan_fft_forward(samples.len()));
+    let mut buffer = samples
+        .iter()
+        .map(|sample| Complex32::new(*sample, 0.0))
+        .collect::<Vec<_>>();
+    fft.process(&mut buffer);
+    buffer.truncate(samples.len() / 2 + 1);
+    buffer
+}
+
+/// Invokes the FFT and checks the length of the result, as the FFT might be
+/// implemented outside of this crate.
+///
+/// # Panics
+/// If the FFT doesn't return `samples.len() / 2 + 1` values.
+pub(crate) fn calc_checked(fft: &dyn Fft, samples: &[f32]) -> Vec<Complex32> {
+    let fft_res = fft.calc(samples);
+    assert_eq!(
+        fft_res.len(),
+        samples.len() / 2 + 1,
+        "the FFT must return samples.len() / 2 + 1 values"
+    );
+    fft_res
 }
 
 /// Real FFT using [`microfft::real`].
 pub struct FftImpl;
 
 impl FftImpl {
+    /// Biggest FFT length supported by [`microfft::real`] with the currently
+    /// activated `size-*` features.
+    pub(crate) const MAX_LEN: usize = max_len();
+
     /// Calculates the FFT For the given input samples and returns a Vector of
     /// of [`Complex32`] with length `samples.len() / 2 + 1`, where the first
     /// index corresponds to the DC component and the last index to the Nyquist
@@ -66,18 +227,123 @@ impl FftImpl {
     /// # Parameters
     /// - `samples`: Array with samples. Each value must be a regular floating
     ///              point number (no NaN or infinite) and the length must be
-    ///              a power of two. Otherwise, the function panics.
+    ///              a power of two not bigger than [`Self::MAX_LEN`]. Otherwise,
+    ///              the function panics.
     #[inline]
     pub(crate) fn calc(samples: &[f32]) -> Vec<Complex32> {
-        let mut fft_res: Vec<Complex32> =
-            real_fft_n!(samples, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384);
+        // in-place on the heap, as a copy of the biggest buffer would be too
+        // big for the stack
+        let mut buffer = samples.to_vec();
+        let fft_res = Self::calc_in_place(&mut buffer);
 
         // `microfft::real` documentation says: the Nyquist frequency real value
         // is packed inside the imaginary part of the DC component.
         let nyquist_fr_pos_val = fft_res[0].im;
         fft_res[0].im = 0.0;
+        let mut res = Vec::with_capacity(fft_res.len() + 1);
+        res.extend_from_slice(fft_res);
         // manually add the nyquist frequency
-        fft_res.push(Complex32::new(nyquist_fr_pos_val, 0.0));
+        res.push(Complex32::new(nyquist_fr_pos_val, 0.0));
+        res
+    }
+
+    /// Like [`Self::calc`] but calculates the FFT in-place without any heap
+    /// allocation. The returned slice of length `samples.len() / 2` reuses the
+    /// memory of `samples`. Attention: the real value of the Nyquist frequency
+    /// is packed inside the imaginary part of the DC component.
+    ///
+    /// # Parameters
+    /// - `samples`: See [`Self::calc`].
+    #[inline]
+    pub(crate) fn calc_in_place(samples: &mut [f32]) -> &mut [Complex32] {
+        debug_assert!(samples.len() <= Self::MAX_LEN);
+        fft_in_place_n!(real::rfft_, samples)
+    }
+
+    /// Like [`Self::calc`] but uses the complex FFT of [`microfft::complex`].
+    /// The mirrored frequencies of the result are dropped.
+    ///
+    /// # Parameters
+    /// - `samples`: See [`Self::calc`].
+    #[inline]
+    pub(crate) fn calc_complex(samples: &[f32]) -> Vec<Complex32> {
+        let samples = samples
+            .iter()
+            .map(|sample| Complex32::new(*sample, 0.0))
+            .collect::<Vec<_>>();
+        let mut fft_res = Self::calc_complex_input(samples);
+        fft_res.truncate(fft_res.len() / 2 + 1);
         fft_res
     }
+
+    /// Calculates the complex FFT of [`microfft::complex`] for complex
+    /// samples, e.g. IQ data, and returns the full result of the same length,
+    /// including the negative frequencies in the second half.
+    ///
+    /// # Parameters
+    /// - `samples`: Complex samples. The length must be a power of two not
+    ///              bigger than [`Self::MAX_LEN`]. Otherwise, the function
+    ///              panics.
+    #[inline]
+    pub(crate) fn calc_complex_input(mut samples: Vec<Complex32>) -> Vec<Complex32> {
+        debug_assert!(samples.len() <= Self::MAX_LEN);
+        // in-place, as a copy of the biggest buffer would be too big for the stack
+        let _ = fft_in_place_n!(complex::cfft_, samples.as_mut_slice());
+        samples
+    }
+
+    /// Calculates the inverse of [`Self::calc`], i.e. the real samples of
+    /// length `(fft_res.len() - 1) * 2` for the given FFT result from the DC
+    /// component up to the Nyquist frequency. The mirrored frequencies are
+    /// reconstructed from the complex conjugates.
+    ///
+    /// # Parameters
+    /// - `fft_res`: FFT result of length `N / 2 + 1`, where `N` is a power of
+    ///              two not bigger than [`Self::MAX_LEN`]. Otherwise, the
+    ///              function panics.
+    #[inline]
+    pub(crate) fn calc_inverse(fft_res: &[Complex32]) -> Vec<f32> {
+        let len = (fft_res.len() - 1) * 2;
+        debug_assert!(len <= Self::MAX_LEN);
+        let mut buffer = Vec::with_capacity(len);
+        buffer.extend_from_slice(fft_res);
+        buffer.extend(fft_res[1..fft_res.len() - 1].iter().rev().map(|c| c.conj()));
+
+        // in-place, as a copy of the biggest buffer would be too big for the stack
+        let _ = fft_in_place_n!(inverse::ifft_, buffer.as_mut_slice());
+        buffer.into_iter().map(|c| c.re).collect()
+    }
+}
+
+/// Returns the biggest FFT length enabled via the `size-*` features. As each
+/// feature enables all smaller lengths, all powers of two up to this length
+/// are supported.
+const fn max_len() -> usize {
+    if cfg!(feature = "size-16384") {
+        16384
+    } else if cfg!(feature = "size-8192") {
+        8192
+    } else if cfg!(feature = "size-4096") {
+        4096
+    } else if cfg!(feature = "size-2048") {
+        2048
+    } else if cfg!(feature = "size-1024") {
+        1024
+    } else if cfg!(feature = "size-512") {
+        512
+    } else if cfg!(feature = "size-256") {
+        256
+    } else if cfg!(feature = "size-128") {
+        128
+    } else if cfg!(feature = "size-64") {
+        64
+    } else if cfg!(feature = "size-32") {
+        32
+    } else if cfg!(feature = "size-16") {
+        16
+    } else if cfg!(feature = "size-8") {
+        8
+    } else {
+        4
+    }
 }
@@ -0,0 +1,298 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Compact binary frames of a [`FrequencySpectrum`], e.g. to stream spectra
+//! from a sensor node to a host over UART or BLE. See [`encode`] and
+//! [`decode`].
+//!
+//! All numbers are little endian. A frame consists of a header
+//!
+//! | Bytes | Type  | Content                                   |
+//! |-------|-------|-------------------------------------------|
+//! | 0     | `u8`  | Version, currently [`FRAME_VERSION`]      |
+//! | 1..5  | `f32` | Frequency resolution in Hertz             |
+//! | 5..9  | `u32` | Number of samples (after padding)         |
+//! | 9..13 | `u32` | Number of samples before padding          |
+//! | 13..15| `u16` | Number `n` of (frequency, value)-pairs    |
+//!
+//! followed by `n` pairs of the FFT bin index (`u16`) and the frequency value
+//! (`f32`). The frequency of each pair is `index * frequency resolution`.
+//! Hence, the sampling rate is `frequency resolution * number of samples`.
+
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// Version of the frame format written by [`encode`].
+pub const FRAME_VERSION: u8 = 1;
+
+/// Length of the header of a frame in bytes.
+const HEADER_LEN: usize = 15;
+
+/// Length of a single (frequency, value)-pair of a frame in bytes.
+const PAIR_LEN: usize = 6;
+
+/// Possible errors when encoding or decoding a frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameError {
+    /// The buffer is too small. Contains the required length.
+    BufferTooSmall(usize),
+    /// The frame was written with an unsupported version.
+    UnsupportedVersion(u8),
+    /// The spectrum can't be encoded, e.g. because it has more than
+    /// `u16::MAX` frequencies, or the frame contains invalid values.
+    InvalidData,
+}
+
+/// Returns the length of the frame of the given spectrum in bytes.
+#[must_use]
+pub fn encoded_len(spectrum: &FrequencySpectrum) -> usize {
+    HEADER_LEN + spectrum.data().len() * PAIR_LEN
+}
+
+/// Encodes the spectrum into the caller-provided buffer without any heap
+/// allocation. See the [module description](self) for the format.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum to encode.
+/// * `buffer` Buffer with at least [`encoded_len`] bytes.
+///
+/// ## Return value
+/// Number of written bytes.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// use spectrum_analyzer::frame::{decode, encode, encoded_len};
+/// let samples = [0.0, 1.0, 0.0, -1.0];
+/// let spectrum = samples_fft_to_spectrum(&samples, 4, FrequencyLimit::All, None).unwrap();
+/// let mut buffer = [0; 64];
+/// let len = encode(&spectrum, &mut buffer).unwrap();
+/// assert_eq!(len, encoded_len(&spectrum));
+/// let decoded = decode(&buffer[..len]).unwrap();
+/// assert_eq!(decoded.data(), spectrum.data());
+/// ```
+///
+/// ## Errors
+/// * [`FrameError::BufferTooSmall`] if the buffer is too small,
+/// * [`FrameError::InvalidData`] if the spectrum has more than `u16::MAX`
+///   frequencies or a frequency isn't a non-negative multiple of the
+///   frequency resolution below `u16::MAX`.
+pub fn encode(spectrum: &FrequencySpectrum, buffer: &mut [u8]) -> Result<usize, FrameError> {
+    let len = encoded_len(spectrum);
+    if buffer.len() < len {
+        return Err(FrameError::BufferTooSmall(len));
+    }
+    let n: u16 = spectrum
+        .data()
+        .len()
+        .try_into()
+        .map_err(|_| FrameError::InvalidData)?;
+
+    buffer[0] = FRAME_VERSION;
+    buffer[1..5].copy_from_slice(&spectrum.frequency_resolution().to_le_bytes());
+    buffer[5..9].copy_from_slice(&spectrum.samples_len().to_le_bytes());
+    buffer[9..13].copy_from_slice(&spectrum.unpadded_samples_len().to_le_bytes());
+    buffer[13..15].copy_from_slice(&n.to_le_bytes());
+
+    let pairs = buffer[HEADER_LEN..len].chunks_exact_mut(PAIR_LEN);
+    for (chunk, (fr, fr_val)) in pairs.zip(spectrum.data()) {
+        if fr.val() < 0.0 {
+            return Err(FrameError::InvalidData);
+        }
+        let index: u16 = spectrum
+            .index_of_frequency(fr.val())
+            .try_into()
+            .map_err(|_| FrameError::InvalidData)?;
+        chunk[0..2].copy_from_slice(&index.to_le_bytes());
+        chunk[2..6].copy_from_slice(&fr_val.val().to_le_bytes());
+    }
+    Ok(len)
+}
+
+/// Decodes a frame written by [`encode`]. All statistics of the spectrum,
+/// such as [`FrequencySpectrum::max`], are recalculated.
+///
+/// ## Parameters
+/// * `buffer` Buffer that starts with the frame.
+///
+/// ## Errors
+/// * [`FrameError::BufferTooSmall`] if the buffer is shorter than the frame,
+/// * [`FrameError::UnsupportedVersion`] if the version is unknown,
+/// * [`FrameError::InvalidData`] if the frame contains less than two pairs,
+///   `NaN` or infinite values, an invalid frequency resolution, indices
+///   that are not strictly ascending, or a number of samples before padding
+///   that is zero or bigger than the number of samples.
+pub fn decode(buffer: &[u8]) -> Result<FrequencySpectrum, FrameError> {
+    if buffer.len() < HEADER_LEN {
+        return Err(FrameError::BufferTooSmall(HEADER_LEN));
+    }
+    if buffer[0] != FRAME_VERSION {
+        return Err(FrameError::UnsupportedVersion(buffer[0]));
+    }
+    let frequency_resolution = f32::from_le_bytes(buffer[1..5].try_into().unwrap());
+    let samples_len = u32::from_le_bytes(buffer[5..9].try_into().unwrap());
+    let unpadded_samples_len = u32::from_le_bytes(buffer[9..13].try_into().unwrap());
+    let n = u16::from_le_bytes(buffer[13..15].try_into().unwrap()) as usize;
+    let len = HEADER_LEN + n * PAIR_LEN;
+    if buffer.len() < len {
+        return Err(FrameError::BufferTooSmall(len));
+    }
+    if n < 2
+        || !frequency_resolution.is_finite()
+        || frequency_resolution <= 0.0
+        || unpadded_samples_len == 0
+        || unpadded_samples_len > samples_len
+    {
+        return Err(FrameError::InvalidData);
+    }
+
+    // the spectrum must be sorted by frequency without duplicates
+    let mut previous_index = None;
+    let data = buffer[HEADER_LEN..len]
+        .chunks_exact(PAIR_LEN)
+        .map(|chunk| {
+            let index = u16::from_le_bytes(chunk[0..2].try_into().unwrap());
+            let fr_val = f32::from_le_bytes(chunk[2..6].try_into().unwrap());
+            if !fr_val.is_finite() || previous_index.map_or(false, |previous| index <= previous) {
+                return Err(FrameError::InvalidData);
+            }
+            previous_index = Some(index);
+            Ok((
+                Frequency::from(f32::from(index) * frequency_resolution),
+                FrequencyValue::from(fr_val),
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut working_buffer = data.clone();
+    let mut spectrum =
+        FrequencySpectrum::new(data, frequency_resolution, samples_len, &mut working_buffer);
+    spectrum.set_unpadded_samples_len(unpadded_samples_len);
+    Ok(spectrum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::padding::Padding;
+    use crate::{samples_fft_to_spectrum_padded, FrequencyLimit};
+
+    fn spectrum() -> FrequencySpectrum {
+        let samples = (0..1000)
+            .map(|i| libm::sinf(i as f32 * 0.3))
+            .collect::<Vec<_>>();
+        samples_fft_to_spectrum_padded(
+            &samples,
+            44100,
+            FrequencyLimit::Range(100.0, 1000.0),
+            None,
+            Padding::ZeroToNextPowerOfTwo,
+        )
+        .unwrap()
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_roundtrip() {
+        let spectrum = spectrum();
+        let mut buffer = vec![0; encoded_len(&spectrum)];
+        assert_eq!(encode(&spectrum, &mut buffer), Ok(buffer.len()));
+
+        let decoded = decode(&buffer).unwrap();
+        assert_eq!(decoded.data(), spectrum.data());
+        assert_eq!(decoded.max(), spectrum.max());
+        assert_eq!(
+            decoded.frequency_resolution(),
+            spectrum.frequency_resolution()
+        );
+        assert_eq!(decoded.samples_len(), 1024);
+        assert_eq!(decoded.unpadded_samples_len(), 1000);
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_errors() {
+        let spectrum = spectrum();
+        let len = encoded_len(&spectrum);
+        let mut buffer = vec![0; len];
+        assert_eq!(
+            encode(&spectrum, &mut buffer[..len - 1]),
+            Err(FrameError::BufferTooSmall(len))
+        );
+
+        encode(&spectrum, &mut buffer).unwrap();
+        assert!(matches!(
+            decode(&buffer[..len - 1]),
+            Err(FrameError::BufferTooSmall(l)) if l == len
+        ));
+        buffer[HEADER_LEN + 2..HEADER_LEN + 6].copy_from_slice(&f32::NAN.to_le_bytes());
+        assert!(matches!(decode(&buffer), Err(FrameError::InvalidData)));
+        buffer[0] = 42;
+        assert!(matches!(
+            decode(&buffer),
+            Err(FrameError::UnsupportedVersion(42))
+        ));
+    }
+
+    /// Creates a frame with the given header values and bin indices.
+    fn frame(samples_len: u32, unpadded_samples_len: u32, indices: &[u16]) -> Vec<u8> {
+        let mut buffer = vec![FRAME_VERSION];
+        buffer.extend_from_slice(&1.0_f32.to_le_bytes());
+        buffer.extend_from_slice(&samples_len.to_le_bytes());
+        buffer.extend_from_slice(&unpadded_samples_len.to_le_bytes());
+        buffer.extend_from_slice(&(indices.len() as u16).to_le_bytes());
+        for index in indices {
+            buffer.extend_from_slice(&index.to_le_bytes());
+            buffer.extend_from_slice(&1.0_f32.to_le_bytes());
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        assert_eq!(decode(&frame(8, 8, &[0, 1, 3])).unwrap().data().len(), 3);
+        // unsorted indices
+        assert_eq!(
+            decode(&frame(8, 8, &[1, 0, 3])).unwrap_err(),
+            FrameError::InvalidData
+        );
+        // duplicate indices
+        assert_eq!(
+            decode(&frame(8, 8, &[0, 1, 1])).unwrap_err(),
+            FrameError::InvalidData
+        );
+        // no samples before padding
+        assert_eq!(
+            decode(&frame(8, 0, &[0, 1])).unwrap_err(),
+            FrameError::InvalidData
+        );
+        // more samples before than after padding
+        assert_eq!(
+            decode(&frame(8, 9, &[0, 1])).unwrap_err(),
+            FrameError::InvalidData
+        );
+    }
+}
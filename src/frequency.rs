@@ -21,11 +21,13 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-//! Module for the struct [`OrderableF32`] and the two
-//! convenient type definitions [`Frequency`] and [`FrequencyValue`].
+//! Module for the structs [`OrderableF32`] and [`OrderableF64`] and the
+//! convenient type definitions [`Frequency`] and [`FrequencyValue`] as well
+//! as their `f64` variants [`FrequencyF64`] and [`FrequencyValueF64`], and
+//! the [`DisplayUnit`] extension trait.
 
 use core::cmp::Ordering;
-use core::fmt::{Display, Formatter, Result};
+use core::fmt::{Display, Formatter, LowerExp, Result};
 use core::ops::{Add, Div, Mul, Sub};
 
 /// A frequency in Hertz. A convenient wrapper type around `f32`.
@@ -34,100 +36,210 @@ pub type Frequency = OrderableF32;
 /// magnitude.
 pub type FrequencyValue = OrderableF32;
 
-/// Wrapper around [`f32`] that guarantees a valid number, hence, the number is
-/// neither `NaN` or `infinite`. This makes the number orderable and sortable.
-#[derive(Debug, Copy, Clone, Default)]
-pub struct OrderableF32(f32);
+/// Like [`Frequency`] but with `f64` precision.
+pub type FrequencyF64 = OrderableF64;
+/// Like [`FrequencyValue`] but with `f64` precision.
+pub type FrequencyValueF64 = OrderableF64;
 
-impl OrderableF32 {
-    #[inline]
-    pub const fn val(&self) -> f32 {
-        self.0
-    }
-}
+/// Implements an orderable wrapper type around a primitive float type.
+macro_rules! orderable_float {
+    ($(#[$meta:meta])* $name:ident, $float:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, Default)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(transparent))]
+        pub struct $name($float);
 
-impl From<f32> for OrderableF32 {
-    #[inline]
-    fn from(val: f32) -> Self {
-        debug_assert!(!val.is_nan(), "NaN-values are not supported!");
-        debug_assert!(!val.is_infinite(), "Infinite-values are not supported!");
-        Self(val)
-    }
-}
+        impl $name {
+            #[inline]
+            pub const fn val(&self) -> $float {
+                self.0
+            }
+        }
 
-impl Display for OrderableF32 {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}", self.0)
-    }
-}
+        impl From<$float> for $name {
+            #[inline]
+            fn from(val: $float) -> Self {
+                debug_assert!(!val.is_nan(), "NaN-values are not supported!");
+                debug_assert!(!val.is_infinite(), "Infinite-values are not supported!");
+                Self(val)
+            }
+        }
 
-impl Ord for OrderableF32 {
-    #[inline]
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
+        /// Formats the plain number. Formatting options, such as the
+        /// precision, are supported.
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+                Display::fmt(&self.0, f)
+            }
+        }
 
-impl Eq for OrderableF32 {}
+        /// Formats the plain number.
+        #[cfg(feature = "defmt")]
+        impl defmt::Format for $name {
+            fn format(&self, f: defmt::Formatter<'_>) {
+                defmt::Format::format(&self.0, f);
+            }
+        }
 
-impl PartialEq for OrderableF32 {
-    #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        matches!(self.cmp(other), Ordering::Equal)
-    }
-}
+        impl LowerExp for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+                LowerExp::fmt(&self.0, f)
+            }
+        }
 
-impl PartialOrd for OrderableF32 {
-    #[allow(clippy::float_cmp)]
-    #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // self.cmp(other).is_eq()
-        Some(if self.val() < other.val() {
-            Ordering::Less
-        } else if self.val() == other.val() {
-            Ordering::Equal
-        } else {
-            Ordering::Greater
-        })
-    }
+        impl Ord for $name {
+            #[allow(clippy::float_cmp)]
+            #[inline]
+            fn cmp(&self, other: &Self) -> Ordering {
+                if self.val() < other.val() {
+                    Ordering::Less
+                } else if self.val() == other.val() {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater
+                }
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialEq for $name {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                matches!(self.cmp(other), Ordering::Equal)
+            }
+        }
+
+        impl PartialOrd for $name {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            #[inline]
+            fn add(self, other: Self) -> Self::Output {
+                (self.val() + other.val()).into()
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            #[inline]
+            fn sub(self, other: Self) -> Self::Output {
+                (self.val() - other.val()).into()
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, other: Self) -> Self::Output {
+                (self.val() * other.val()).into()
+            }
+        }
+
+        impl Div for $name {
+            type Output = Self;
+
+            #[inline]
+            fn div(self, other: Self) -> Self::Output {
+                let quotient = self.val() / other.val();
+                debug_assert!(!quotient.is_nan(), "NaN is not allowed");
+                debug_assert!(!quotient.is_infinite(), "INFINITY is not allowed");
+                quotient.into()
+            }
+        }
+    };
 }
 
-impl Add for OrderableF32 {
-    type Output = Self;
+orderable_float!(
+    /// Wrapper around [`f32`] that guarantees a valid number, hence, the number is
+    /// neither `NaN` or `infinite`. This makes the number orderable and sortable.
+    OrderableF32,
+    f32
+);
 
-    #[inline]
-    fn add(self, other: Self) -> Self::Output {
-        (self.val() + other.val()).into()
-    }
+orderable_float!(
+    /// Like [`OrderableF32`] but wraps an [`f64`].
+    OrderableF64,
+    f64
+);
+
+/// Extension trait to display a [`Frequency`] or a [`FrequencyValue`] with
+/// a unit.
+pub trait DisplayUnit {
+    /// Returns a wrapper that displays the value as frequency with an
+    /// automatic unit, i.e. in `Hz` below 1000 Hz and in `kHz` otherwise.
+    /// The precision of the formatter applies to the number in the chosen
+    /// unit.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::{DisplayUnit, Frequency};
+    /// assert_eq!(format!("{}", Frequency::from(440.0).display_hz()), "440 Hz");
+    /// assert_eq!(format!("{:.2}", Frequency::from(1500.0).display_hz()), "1.50 kHz");
+    /// ```
+    #[must_use]
+    fn display_hz(self) -> DisplayHz;
+
+    /// Returns a wrapper that displays the value as magnitude in decibel,
+    /// i.e. `20 * log10(val)`. The precision of the formatter applies to the
+    /// number.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::{DisplayUnit, FrequencyValue};
+    /// assert_eq!(format!("{:.1}", FrequencyValue::from(0.5).display_db()), "-6.0 dB");
+    /// ```
+    #[must_use]
+    fn display_db(self) -> DisplayDb;
 }
 
-impl Sub for OrderableF32 {
-    type Output = Self;
+impl DisplayUnit for OrderableF32 {
+    #[inline]
+    fn display_hz(self) -> DisplayHz {
+        DisplayHz(self.0)
+    }
 
     #[inline]
-    fn sub(self, other: Self) -> Self::Output {
-        (self.val() - other.val()).into()
+    fn display_db(self) -> DisplayDb {
+        DisplayDb(self.0)
     }
 }
 
-impl Mul for OrderableF32 {
-    type Output = Self;
+/// Displays a frequency with an automatic unit. See
+/// [`DisplayUnit::display_hz`].
+#[derive(Debug, Copy, Clone)]
+pub struct DisplayHz(f32);
 
-    #[inline]
-    fn mul(self, other: Self) -> Self::Output {
-        (self.val() * other.val()).into()
+impl Display for DisplayHz {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let (val, unit) = if libm::fabsf(self.0) >= 1000.0 {
+            (self.0 / 1000.0, "kHz")
+        } else {
+            (self.0, "Hz")
+        };
+        Display::fmt(&val, f)?;
+        write!(f, " {}", unit)
     }
 }
 
-impl Div for OrderableF32 {
-    type Output = Self;
+/// Displays a magnitude in decibel. See [`DisplayUnit::display_db`].
+#[derive(Debug, Copy, Clone)]
+pub struct DisplayDb(f32);
 
-    #[inline]
-    fn div(self, other: Self) -> Self::Output {
-        let quotient = self.val() / other.val();
-        debug_assert!(!quotient.is_nan(), "NaN is not allowed");
-        debug_assert!(!quotient.is_infinite(), "INFINITY is not allowed");
-        quotient.into()
+impl Display for DisplayDb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // the magnitude 0.0 results in negative infinity
+        Display::fmt(&(20.0 * libm::log10f(libm::fabsf(self.0))), f)?;
+        write!(f, " dB")
     }
 }
 
@@ -152,4 +264,37 @@ mod tests {
             assert_eq!(f1, f1, "Equal must work");
         }
     }
+
+    #[test]
+    fn test_display() {
+        use alloc::format;
+        let fr = OrderableF32::from(1234.5);
+        assert_eq!(format!("{}", fr), "1234.5");
+        assert_eq!(format!("{:.0}", fr), "1234");
+        assert_eq!(format!("{:.2e}", fr), "1.23e3");
+        assert_eq!(format!("{:e}", OrderableF64::from(0.001)), "1e-3");
+        assert_eq!(format!("{}", fr.display_hz()), "1.2345 kHz");
+        assert_eq!(
+            format!("{:.1}", OrderableF32::from(99.96).display_hz()),
+            "100.0 Hz"
+        );
+        assert_eq!(
+            format!("{}", OrderableF32::from(0.1).display_db()),
+            "-20 dB"
+        );
+        assert_eq!(
+            format!("{}", OrderableF32::from(0.0).display_db()),
+            "-inf dB"
+        );
+    }
+
+    #[test]
+    fn test_orderablef64() {
+        let f1: OrderableF64 = 2.0.into();
+        let f2: OrderableF64 = 1e-300.into();
+
+        assert_eq!(2.0, (f1 + f2).val(), "add must work");
+        assert!(f2 < f1, "Compare must work");
+        assert!(f2 > 0.0.into(), "f64 precision must be preserved");
+    }
 }
@@ -0,0 +1,223 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [ERB] (equivalent rectangular bandwidth) scale and a
+//! gammatone filterbank that reduces a [`FrequencySpectrum`] to an auditory
+//! spectrum. Gammatone filters model the frequency selectivity of the
+//! cochlea and are used in hearing models and for cochlear-inspired
+//! features.
+//!
+//! The filters are applied in the frequency domain, i.e. each filter weights
+//! the frequencies of the spectrum with the magnitude response of a
+//! 4th-order gammatone filter according to Patterson and Holdsworth.
+//!
+//! [ERB]: https://en.wikipedia.org/wiki/Equivalent_rectangular_bandwidth
+
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// Order of the gammatone filters.
+const ORDER: i32 = 4;
+
+/// Factor between the bandwidth parameter of a 4th-order gammatone filter and
+/// the ERB of its center frequency.
+const BANDWIDTH_FACTOR: f32 = 1.019;
+
+/// Returns the equivalent rectangular bandwidth in Hertz of the auditory
+/// filter at the given frequency according to Glasberg and Moore.
+#[must_use]
+pub fn erb(hz: f32) -> f32 {
+    24.7 * (4.37 * hz / 1000.0 + 1.0)
+}
+
+/// Converts hertz to the ERB-rate scale, i.e. the number of ERBs below the
+/// given frequency.
+#[must_use]
+pub fn hertz_to_erb_rate(hz: f32) -> f32 {
+    assert!(hz >= 0.0);
+    21.4 * libm::log10f(1.0 + 4.37 * hz / 1000.0)
+}
+
+/// Converts the ERB-rate scale to hertz. This is the inverse of
+/// [`hertz_to_erb_rate`].
+#[must_use]
+pub fn erb_rate_to_hertz(erb_rate: f32) -> f32 {
+    assert!(erb_rate >= 0.0);
+    (libm::powf(10.0, erb_rate / 21.4) - 1.0) * 1000.0 / 4.37
+}
+
+/// A single filter of a [`GammatoneFilterbank`]. All values are in Hertz.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GammatoneFilter {
+    /// Center frequency of the filter, i.e. where the gain is `1`.
+    pub center: f32,
+    /// Bandwidth parameter of the filter, i.e. `1.019 * ERB(center)`.
+    pub bandwidth: f32,
+}
+
+impl GammatoneFilter {
+    /// Creates the filter with the given center frequency in Hertz.
+    #[must_use]
+    pub fn new(center: f32) -> Self {
+        Self {
+            center,
+            bandwidth: BANDWIDTH_FACTOR * erb(center),
+        }
+    }
+
+    /// Returns the magnitude response of the filter for the given frequency.
+    #[inline]
+    #[must_use]
+    pub fn gain(&self, fr: f32) -> f32 {
+        let x = (fr - self.center) / self.bandwidth;
+        libm::powf(1.0 + x * x, -(ORDER as f32) / 2.0)
+    }
+
+    /// Applies the filter to the spectrum and returns the energy of the
+    /// filter output, i.e. the sum of the squared, weighted values.
+    #[inline]
+    #[must_use]
+    pub fn apply(&self, spectrum: &FrequencySpectrum) -> f32 {
+        spectrum
+            .data()
+            .iter()
+            .map(|(fr, fr_val)| {
+                let val = self.gain(fr.val()) * fr_val.val();
+                val * val
+            })
+            .sum()
+    }
+}
+
+/// Filterbank of gammatone filters whose center frequencies are equidistant
+/// on the ERB-rate scale. The filterbank doesn't depend on the FFT length or
+/// the sampling rate, hence, it can be created once and applied to many
+/// spectra. The values of the spectrum are expected to be magnitudes.
+#[derive(Debug, Clone)]
+pub struct GammatoneFilterbank {
+    filters: Vec<GammatoneFilter>,
+}
+
+impl GammatoneFilterbank {
+    /// Creates a new filterbank with `n_bands` gammatone filters whose
+    /// center frequencies are between `f_min` and `f_max` (inclusive).
+    ///
+    /// ## Parameters
+    /// - `n_bands` Number of bands.
+    /// - `f_min` Center frequency of the first filter in Hertz.
+    /// - `f_max` Center frequency of the last filter in Hertz.
+    ///
+    /// ## Panics
+    /// If `n_bands` is zero or if not `0 <= f_min < f_max`.
+    #[must_use]
+    pub fn new(n_bands: usize, f_min: f32, f_max: f32) -> Self {
+        assert!(n_bands > 0, "At least one band is required!");
+        assert!(
+            0.0 <= f_min && f_min < f_max,
+            "Invalid frequency range [{}; {}]!",
+            f_min,
+            f_max
+        );
+
+        let erb_min = hertz_to_erb_rate(f_min);
+        let erb_max = hertz_to_erb_rate(f_max);
+        let erb_step = (erb_max - erb_min) / (n_bands - 1).max(1) as f32;
+        let filters = (0..n_bands)
+            .map(|i| GammatoneFilter::new(erb_rate_to_hertz(erb_min + i as f32 * erb_step)))
+            .collect();
+
+        Self { filters }
+    }
+
+    /// Returns all filters of this filterbank, sorted from the lowest to the
+    /// highest band.
+    #[inline]
+    #[must_use]
+    pub fn filters(&self) -> &[GammatoneFilter] {
+        &self.filters
+    }
+
+    /// Applies the filterbank to the spectrum.
+    ///
+    /// ## Return
+    /// `(center frequency, band energy)`-pairs, sorted from the lowest to
+    /// the highest band.
+    #[must_use]
+    pub fn apply(&self, spectrum: &FrequencySpectrum) -> Vec<(Frequency, FrequencyValue)> {
+        self.filters
+            .iter()
+            .map(|filter| (filter.center.into(), filter.apply(spectrum).into()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erb() {
+        assert!((erb(1000.0) - 132.639).abs() < 1e-3);
+        for hz in [0.0, 100.0, 1000.0, 10000.0] {
+            assert!((erb_rate_to_hertz(hertz_to_erb_rate(hz)) - hz).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = GammatoneFilter::new(1000.0);
+        assert_eq!(filter.gain(1000.0), 1.0);
+        // -12 dB at one bandwidth distance
+        assert!((filter.gain(1000.0 + filter.bandwidth) - 0.25).abs() < 1e-6);
+        assert!(filter.gain(500.0) < 0.01);
+    }
+
+    #[test]
+    fn test_filterbank() {
+        let filterbank = GammatoneFilterbank::new(10, 100.0, 8000.0);
+        let filters = filterbank.filters();
+        assert_eq!(filters.len(), 10);
+        assert!((filters[0].center - 100.0).abs() < 0.01);
+        assert!((filters[9].center - 8000.0).abs() < 0.5);
+        let steps = filters
+            .windows(2)
+            .map(|pair| hertz_to_erb_rate(pair[1].center) - hertz_to_erb_rate(pair[0].center))
+            .collect::<Vec<_>>();
+        assert!(steps.iter().all(|step| (step - steps[0]).abs() < 1e-3));
+
+        // 0, 100, ..., 1500 Hz with a single tone at 1000 Hz
+        let data = (0..16)
+            .map(|i| {
+                let val = if i == 10 { 1.0 } else { 0.0 };
+                (Frequency::from(i as f32 * 100.0), FrequencyValue::from(val))
+            })
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        let spectrum = FrequencySpectrum::new(data, 100.0, 32, &mut working_buffer);
+        let bands = GammatoneFilterbank::new(3, 500.0, 1000.0).apply(&spectrum);
+        assert_eq!(bands[2].1.val(), 1.0);
+        assert!(bands[0].1 < bands[1].1);
+        assert_eq!(spectrum.to_gammatone_bands(3, 500.0, 1000.0), bands);
+    }
+}
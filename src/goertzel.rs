@@ -0,0 +1,196 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [Goertzel algorithm]. It calculates the magnitude of
+//! single frequencies without a full FFT. If you are only interested in a
+//! few frequencies, e.g. for DTMF detection or pilot-tone monitoring, this is
+//! much cheaper than [`crate::samples_fft_to_spectrum`]. Furthermore, the
+//! number of samples doesn't need to be a power of two and the frequencies
+//! don't need to match the frequency grid of an FFT.
+//!
+//! The magnitudes have the same scale as the values of
+//! [`crate::samples_fft_to_spectrum`] without scaling function.
+//!
+//! [Goertzel algorithm]: https://en.wikipedia.org/wiki/Goertzel_algorithm
+
+use crate::error::SpectrumAnalyzerError;
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::limit::FrequencyLimitError;
+use crate::validate_samples;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// Calculates the magnitude of a single frequency in the given samples using
+/// the Goertzel algorithm.
+///
+/// ## Parameters
+/// * `samples` raw audio, e.g. 16bit audio data but as f32. Any length of at
+///             least two samples is supported.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency` The frequency of interest in Hertz. Must be in the interval
+///               `[0; sampling_rate / 2]`.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::goertzel::goertzel;
+/// let samples = vec![0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0];
+/// let magnitude = goertzel(&samples, 8, 2.0).unwrap();
+/// ```
+///
+/// ## Errors
+/// See [`crate::samples_fft_to_spectrum`]. If `frequency` is out of range,
+/// [`SpectrumAnalyzerError::InvalidFrequencyLimit`] is returned.
+pub fn goertzel(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency: f32,
+) -> Result<FrequencyValue, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    verify_frequency(sampling_rate, frequency)?;
+    Ok(goertzel_magnitude(samples, sampling_rate, frequency).into())
+}
+
+/// Like [`goertzel`] but for multiple frequencies.
+///
+/// ## Return value
+/// `(frequency, magnitude)`-pairs in the same order as `frequencies`.
+///
+/// ## Errors
+/// See [`goertzel`].
+pub fn goertzel_multiple(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequencies: &[f32],
+) -> Result<Vec<(Frequency, FrequencyValue)>, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    for frequency in frequencies {
+        verify_frequency(sampling_rate, *frequency)?;
+    }
+    Ok(frequencies
+        .iter()
+        .map(|fr| {
+            (
+                Frequency::from(*fr),
+                FrequencyValue::from(goertzel_magnitude(samples, sampling_rate, *fr)),
+            )
+        })
+        .collect())
+}
+
+/// Verifies that `frequency` is between `0` and the Nyquist frequency.
+pub(crate) fn verify_frequency(
+    sampling_rate: u32,
+    frequency: f32,
+) -> Result<(), SpectrumAnalyzerError> {
+    if frequency < 0.0 {
+        Err(SpectrumAnalyzerError::InvalidFrequencyLimit(
+            FrequencyLimitError::ValueBelowMinimum(frequency),
+        ))
+    } else if frequency > sampling_rate as f32 / 2.0 {
+        Err(SpectrumAnalyzerError::InvalidFrequencyLimit(
+            FrequencyLimitError::ValueAboveNyquist(frequency),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// The actual Goertzel algorithm. Works for arbitrary (non-integer) bin
+/// indices `k = frequency * N / sampling_rate`.
+pub(crate) fn goertzel_magnitude(samples: &[f32], sampling_rate: u32, frequency: f32) -> f32 {
+    let omega = 2.0 * PI * frequency / sampling_rate as f32;
+    let coeff = 2.0 * libm::cosf(omega);
+
+    let mut s_prev = 0.0;
+    let mut s_prev2 = 0.0;
+    for sample in samples {
+        let s = sample + coeff * s_prev - s_prev2;
+        s_prev2 = s_prev;
+        s_prev = s;
+    }
+
+    let power = s_prev * s_prev + s_prev2 * s_prev2 - coeff * s_prev * s_prev2;
+    // rounding errors might result in tiny negative values
+    libm::sqrtf(power.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    fn sine(frequency: f32, sampling_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| libm::sinf(2.0 * PI * frequency * i as f32 / sampling_rate as f32))
+            .collect()
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_matches_fft() {
+        let samples = sine(1000.0, 8000, 256);
+        let spectrum = samples_fft_to_spectrum(&samples, 8000, FrequencyLimit::All, None).unwrap();
+        for fr in [0.0, 500.0, 1000.0, 1250.0, 4000.0] {
+            float_cmp::assert_approx_eq!(
+                f32,
+                goertzel(&samples, 8000, fr).unwrap().val(),
+                spectrum.freq_val_exact(fr).val(),
+                epsilon = 0.01
+            );
+        }
+    }
+
+    #[test]
+    fn test_dtmf() {
+        // DTMF "5": 770 Hz + 1336 Hz
+        let samples = sine(770.0, 8000, 205)
+            .iter()
+            .zip(sine(1336.0, 8000, 205))
+            .map(|(a, b)| a + b)
+            .collect::<Vec<_>>();
+        let rows = goertzel_multiple(&samples, 8000, &[697.0, 770.0, 852.0, 941.0]).unwrap();
+        let cols = goertzel_multiple(&samples, 8000, &[1209.0, 1336.0, 1477.0]).unwrap();
+        let strongest = |vals: &[(Frequency, FrequencyValue)]| {
+            vals.iter().max_by_key(|(_fr, val)| *val).unwrap().0.val()
+        };
+        assert_eq!(strongest(&rows), 770.0);
+        assert_eq!(strongest(&cols), 1336.0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let samples = sine(100.0, 1000, 100);
+        assert!(matches!(
+            goertzel(&samples, 1000, 501.0).unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyLimit(_)
+        ));
+        assert!(matches!(
+            goertzel_multiple(&samples, 1000, &[100.0, -1.0]).unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyLimit(_)
+        ));
+        assert!(matches!(
+            goertzel(&[1.0, f32::NAN], 1000, 100.0).unwrap_err(),
+            SpectrumAnalyzerError::NaNValuesNotSupported
+        ));
+    }
+}
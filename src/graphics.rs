@@ -0,0 +1,323 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Rendering of a spectrum onto any `embedded-graphics` [`DrawTarget`], e.g.
+//! an SSD1306 or ILI9341 display of an embedded gadget. See
+//! [`draw_spectrum`]. Needs the `embedded-graphics` feature.
+
+use crate::bars::{BarReduction, FrequencyScale};
+use crate::spectrum::FrequencySpectrum;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+/// How [`draw_spectrum`] draws the spectrum.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpectrumStyle {
+    /// Equally wide bars with the maximum value of their frequency range.
+    /// Pixels that don't fit evenly are left empty on the right.
+    Bars {
+        /// Number of bars.
+        count: u32,
+        /// Number of empty pixels between two bars.
+        gap: u32,
+    },
+    /// A connected line with one point per pixel column.
+    Line,
+}
+
+/// Scale of the values, i.e. of the vertical axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AmplitudeScale {
+    /// The values are scaled relative to the maximum of the spectrum.
+    Linear,
+    /// The values are mapped to dB. `min_db` is drawn at the bottom and
+    /// `max_db` at the top of the area. Levels outside are clamped.
+    Db {
+        /// Level in dB at the bottom of the area.
+        min_db: f32,
+        /// Level in dB at the top of the area.
+        max_db: f32,
+        /// Whether the values of the spectrum are already in dB. Otherwise,
+        /// they are treated as magnitudes and converted to dB with
+        /// `20 * log10(val)`.
+        values_in_db: bool,
+    },
+}
+
+/// Configuration for [`draw_spectrum`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpectrumDrawConfig {
+    /// Bars or line.
+    pub style: SpectrumStyle,
+    /// Scale of the frequency axis. [`FrequencyScale::Log`] matches the
+    /// perception of pitch best.
+    pub frequency_scale: FrequencyScale,
+    /// Scale of the values.
+    pub amplitude_scale: AmplitudeScale,
+}
+
+impl Default for SpectrumDrawConfig {
+    /// 16 bars with a gap of one pixel on a logarithmic frequency axis,
+    /// scaled relative to the maximum of the spectrum.
+    fn default() -> Self {
+        Self {
+            style: SpectrumStyle::Bars { count: 16, gap: 1 },
+            frequency_scale: FrequencyScale::Log,
+            amplitude_scale: AmplitudeScale::Linear,
+        }
+    }
+}
+
+/// Draws the spectrum into `area` of the draw target, with the lowest
+/// frequency on the left and the highest frequency on the right. Only the
+/// bars or the line are drawn in `color`; clear the area before, if needed.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum to draw. See [`FrequencySpectrum::to_bars`] for the
+///              mapping of the frequencies to bars or pixel columns.
+/// * `target` Draw target, e.g. a display driver.
+/// * `area` Area of the target to draw into.
+/// * `color` Color of the bars or the line.
+/// * `config` See [`SpectrumDrawConfig`].
+///
+/// ## Example
+/// ```rust
+/// use embedded_graphics_core::draw_target::DrawTarget;
+/// use embedded_graphics_core::geometry::Dimensions;
+/// use embedded_graphics_core::pixelcolor::BinaryColor;
+/// use spectrum_analyzer::graphics::{draw_spectrum, SpectrumDrawConfig};
+/// use spectrum_analyzer::FrequencySpectrum;
+///
+/// fn show<D: DrawTarget<Color = BinaryColor>>(
+///     display: &mut D,
+///     spectrum: &FrequencySpectrum,
+/// ) -> Result<(), D::Error> {
+///     display.clear(BinaryColor::Off)?;
+///     let area = display.bounding_box();
+///     draw_spectrum(spectrum, display, area, BinaryColor::On, &SpectrumDrawConfig::default())
+/// }
+/// ```
+///
+/// ## Errors
+/// Errors of the draw target.
+///
+/// ## Panics
+/// If `area` is empty, if there are no bars or the bars don't fit into
+/// `area`, if `min_db` is not smaller than `max_db`, or if the spectrum
+/// doesn't cover a frequency range.
+pub fn draw_spectrum<D: DrawTarget>(
+    spectrum: &FrequencySpectrum,
+    target: &mut D,
+    area: Rectangle,
+    color: D::Color,
+    config: &SpectrumDrawConfig,
+) -> Result<(), D::Error> {
+    let Size { width, height } = area.size;
+    assert!(width > 0 && height > 0, "The area must not be empty!");
+    if let AmplitudeScale::Db { min_db, max_db, .. } = config.amplitude_scale {
+        assert!(min_db < max_db, "min_db must be smaller than max_db!");
+    }
+    let columns = match config.style {
+        SpectrumStyle::Bars { count, gap } => {
+            assert!(count > 0, "The number of bars must not be zero!");
+            assert!(
+                u64::from(count) + u64::from(count - 1) * u64::from(gap) <= u64::from(width),
+                "The bars don't fit into the area!"
+            );
+            count
+        }
+        SpectrumStyle::Line => width,
+    };
+
+    let bars = spectrum.to_bars(columns as usize, config.frequency_scale, BarReduction::Max);
+    let max = spectrum.max().1.val();
+    // value in `[0.0; 1.0]` from the bottom to the top of the area
+    let level = |val: f32| {
+        let normalized = match config.amplitude_scale {
+            AmplitudeScale::Linear if max > 0.0 => val / max,
+            AmplitudeScale::Linear => 0.0,
+            AmplitudeScale::Db {
+                min_db,
+                max_db,
+                values_in_db,
+            } => {
+                let db = if values_in_db {
+                    val
+                } else {
+                    20.0 * libm::log10f(val.max(f32::MIN_POSITIVE))
+                };
+                (db - min_db) / (max_db - min_db)
+            }
+        };
+        normalized.clamp(0.0, 1.0)
+    };
+    // exclusive
+    let bottom = area.top_left.y + height as i32;
+
+    match config.style {
+        SpectrumStyle::Bars { count, gap } => {
+            let bar_width = (width - (count - 1) * gap) / count;
+            for (i, bar) in bars.iter().enumerate() {
+                let bar_height = libm::roundf(level(bar.value.val()) * height as f32) as u32;
+                let x = area.top_left.x + (i as u32 * (bar_width + gap)) as i32;
+                target.fill_solid(
+                    &Rectangle::new(
+                        Point::new(x, bottom - bar_height as i32),
+                        Size::new(bar_width, bar_height),
+                    ),
+                    color,
+                )?;
+            }
+        }
+        SpectrumStyle::Line => {
+            // the points of neighbouring columns are connected with vertical
+            // segments
+            let mut previous_y = None;
+            for (i, bar) in bars.iter().enumerate() {
+                let offset = libm::roundf(level(bar.value.val()) * (height - 1) as f32) as i32;
+                let y = bottom - 1 - offset;
+                let (from, to) = previous_y.map_or((y, y), |previous_y: i32| {
+                    (previous_y.min(y), previous_y.max(y))
+                });
+                target.fill_solid(
+                    &Rectangle::new(
+                        Point::new(area.top_left.x + i as i32, from),
+                        Size::new(1, (to - from + 1) as u32),
+                    ),
+                    color,
+                )?;
+                previous_y = Some(y);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frequency::{Frequency, FrequencyValue};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+    use embedded_graphics_core::geometry::{Dimensions, OriginDimensions};
+    use embedded_graphics_core::pixelcolor::BinaryColor;
+    use embedded_graphics_core::Pixel;
+
+    /// Monochrome display of 8x5 pixels.
+    struct Display([[bool; 8]; 5]);
+
+    impl Display {
+        /// Renders the pixels as rows of `#` and ` `, separated by `\n`.
+        fn render(&self) -> String {
+            self.0
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&on| if on { '#' } else { ' ' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            Size::new(8, 5)
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Color = BinaryColor;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.0[point.y as usize][point.x as usize] = color.is_on();
+            }
+            Ok(())
+        }
+    }
+
+    /// Spectrum with the frequencies 0, 100, ..., 700 Hz.
+    fn spectrum() -> FrequencySpectrum {
+        let data = [0.0, 1.0, 4.0, 2.0, 0.0, 0.0, 0.0, 3.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &val)| (Frequency::from(i as f32 * 100.0), FrequencyValue::from(val)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(data, 100.0, 16, &mut working_buffer)
+    }
+
+    #[test]
+    fn test_draw_bars() {
+        let mut display = Display([[false; 8]; 5]);
+        let config = SpectrumDrawConfig {
+            style: SpectrumStyle::Bars { count: 3, gap: 1 },
+            frequency_scale: FrequencyScale::Linear,
+            amplitude_scale: AmplitudeScale::Linear,
+        };
+        // bottom four rows
+        let area = Rectangle::new(Point::new(0, 1), Size::new(8, 4));
+        draw_spectrum(&spectrum(), &mut display, area, BinaryColor::On, &config).unwrap();
+        // bars of 2 pixels with the maxima 4, 2, and 3
+        let expected = "        \n##      \n##    ##\n## ## ##\n## ## ##";
+        assert_eq!(display.render(), expected);
+    }
+
+    #[test]
+    fn test_draw_line() {
+        let mut display = Display([[false; 8]; 5]);
+        let config = SpectrumDrawConfig {
+            style: SpectrumStyle::Line,
+            frequency_scale: FrequencyScale::Linear,
+            amplitude_scale: AmplitudeScale::Db {
+                min_db: 0.0,
+                max_db: 4.0,
+                values_in_db: true,
+            },
+        };
+        let area = display.bounding_box();
+        draw_spectrum(&spectrum(), &mut display, area, BinaryColor::On, &config).unwrap();
+        let expected = "  ##    \n  ##   #\n  ###  #\n ## #  #\n##  ####";
+        assert_eq!(display.render(), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bars_dont_fit() {
+        let mut display = Display([[false; 8]; 5]);
+        let config = SpectrumDrawConfig {
+            style: SpectrumStyle::Bars { count: 5, gap: 1 },
+            ..SpectrumDrawConfig::default()
+        };
+        let area = display.bounding_box();
+        let _ = draw_spectrum(&spectrum(), &mut display, area, BinaryColor::On, &config);
+    }
+}
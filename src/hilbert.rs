@@ -0,0 +1,228 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! FFT-based [Hilbert transform] to obtain the analytic signal of real
+//! samples, and from it the envelope and the instantaneous frequency.
+//!
+//! The envelope spectrum, see [`envelope_spectrum`], is a common tool for
+//! the diagnosis of rolling bearings: a defect modulates the amplitude of a
+//! high-frequency resonance, and the modulation frequency reveals the
+//! defect.
+//!
+//! [Hilbert transform]: https://en.wikipedia.org/wiki/Hilbert_transform
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::{Complex32, FftImpl};
+use crate::scaling::SpectrumScalingFunction;
+use crate::{
+    samples_fft_to_spectrum, validate_fft_len, validate_samples, FrequencyLimit, FrequencySpectrum,
+};
+use alloc::vec::Vec;
+use core::f32::consts::TAU;
+
+/// Calculates the analytic signal `x + i * H(x)` of the samples, where
+/// `H(x)` is the Hilbert transform. The negative frequencies of the FFT
+/// are removed and the positive frequencies are doubled before the inverse
+/// FFT.
+///
+/// ## Parameters
+/// * `samples` Real samples. The length must be a power of two.
+///
+/// ## Return value
+/// Complex samples of the same length. The real part equals the input.
+///
+/// ## Errors
+/// * [`SpectrumAnalyzerError::TooFewSamples`],
+///   [`SpectrumAnalyzerError::NaNValuesNotSupported`], or
+///   [`SpectrumAnalyzerError::InfinityValuesNotSupported`] for invalid
+///   samples,
+/// * [`SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo`] or
+///   [`SpectrumAnalyzerError::TooManySamples`] for an invalid length.
+pub fn analytic_signal(samples: &[f32]) -> Result<Vec<Complex32>, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    validate_fft_len(samples.len())?;
+
+    let len = samples.len();
+    let samples = samples
+        .iter()
+        .map(|sample| Complex32::new(*sample, 0.0))
+        .collect::<Vec<_>>();
+    let mut fft_res = FftImpl::calc_complex_input(samples);
+    // DC and Nyquist stay as they are; they exist only once
+    for (i, val) in fft_res.iter_mut().enumerate() {
+        if i > 0 && i < len / 2 {
+            *val *= 2.0;
+        } else if i > len / 2 {
+            *val = Complex32::default();
+        }
+    }
+
+    // inverse FFT via the forward FFT: ifft(x) = conj(fft(conj(x))) / N
+    fft_res.iter_mut().for_each(|val| *val = val.conj());
+    let mut analytic = FftImpl::calc_complex_input(fft_res);
+    analytic
+        .iter_mut()
+        .for_each(|val| *val = val.conj() / len as f32);
+    Ok(analytic)
+}
+
+/// Calculates the envelope of the samples, i.e. the magnitude of the
+/// [`analytic_signal`].
+///
+/// ## Errors
+/// See [`analytic_signal`].
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-64")] {
+/// use spectrum_analyzer::hilbert::envelope;
+/// // 1000 Hz carrier, amplitude-modulated by 125 Hz
+/// let samples = (0..64)
+///     .map(|i| {
+///         let t = i as f32 / 8000.0;
+///         let modulation = 1.0 + 0.5 * (2.0 * core::f32::consts::PI * 125.0 * t).cos();
+///         modulation * (2.0 * core::f32::consts::PI * 1000.0 * t).sin()
+///     })
+///     .collect::<Vec<_>>();
+/// let envelope = envelope(&samples).unwrap();
+/// assert!((envelope[0] - 1.5).abs() < 0.01);
+/// assert!((envelope[32] - 0.5).abs() < 0.01);
+/// # }
+/// ```
+pub fn envelope(samples: &[f32]) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    Ok(analytic_signal(samples)?
+        .iter()
+        .map(|val| libm::sqrtf(val.norm_sqr()))
+        .collect())
+}
+
+/// Calculates the instantaneous frequency in Hertz from the derivative of
+/// the unwrapped phase of the [`analytic_signal`].
+///
+/// ## Parameters
+/// * `samples` See [`analytic_signal`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+///
+/// ## Return value
+/// `samples.len() - 1` values, where index `i` is the frequency between
+/// sample `i` and `i + 1`.
+///
+/// ## Errors
+/// See [`analytic_signal`].
+pub fn instantaneous_frequency(
+    samples: &[f32],
+    sampling_rate: u32,
+) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    let analytic = analytic_signal(samples)?;
+    Ok(analytic
+        .windows(2)
+        .map(|pair| {
+            // phase difference in the interval [-PI; PI]; no unwrapping needed
+            let diff = pair[1] * pair[0].conj();
+            libm::atan2f(diff.im, diff.re) / TAU * sampling_rate as f32
+        })
+        .collect())
+}
+
+/// Calculates the spectrum of the [`envelope`] of the samples. The mean of
+/// the envelope is removed beforehand, so that the DC component doesn't
+/// hide the modulation frequencies. Usually, the samples are band-pass
+/// filtered around the resonance of interest beforehand.
+///
+/// ## Parameters
+/// * `samples` See [`analytic_signal`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+/// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
+///
+/// ## Errors
+/// See [`analytic_signal`] and [`crate::samples_fft_to_spectrum`].
+pub fn envelope_spectrum(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    let mut envelope = envelope(samples)?;
+    let mean = envelope.iter().sum::<f32>() / envelope.len() as f32;
+    envelope.iter_mut().for_each(|val| *val -= mean);
+    samples_fft_to_spectrum(&envelope, sampling_rate, frequency_limit, scaling_fn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    /// 2000 Hz carrier that is amplitude-modulated with 250 Hz.
+    fn modulated(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / 16000.0;
+                let modulation = 1.0 + 0.5 * libm::cosf(2.0 * PI * 250.0 * t);
+                modulation * libm::sinf(2.0 * PI * 2000.0 * t)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    fn test_analytic_signal() {
+        let samples = (0..64)
+            .map(|i| libm::cosf(2.0 * PI * i as f32 / 16.0))
+            .collect::<Vec<_>>();
+        let analytic = analytic_signal(&samples).unwrap();
+        for (i, val) in analytic.iter().enumerate() {
+            // the Hilbert transform of the cosine is the sine
+            let expected = libm::sinf(2.0 * PI * i as f32 / 16.0);
+            assert!((val.re - samples[i]).abs() < 1e-5);
+            assert!((val.im - expected).abs() < 1e-5);
+        }
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_envelope_and_instantaneous_frequency() {
+        let samples = modulated(1024);
+        let envelope = envelope(&samples).unwrap();
+        for (i, val) in envelope.iter().enumerate() {
+            let expected = 1.0 + 0.5 * libm::cosf(2.0 * PI * 250.0 * i as f32 / 16000.0);
+            assert!((val - expected).abs() < 1e-3, "{i}: {val} != {expected}");
+        }
+        let frequencies = instantaneous_frequency(&samples, 16000).unwrap();
+        assert_eq!(frequencies.len(), 1023);
+        assert!(frequencies.iter().all(|fr| (fr - 2000.0).abs() < 1.0));
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_envelope_spectrum() {
+        let spectrum =
+            envelope_spectrum(&modulated(1024), 16000, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectrum.max().0.val(), 250.0);
+        assert!(matches!(
+            envelope(&[0.0; 3]),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+    }
+}
@@ -0,0 +1,302 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Mapping of spectra to the colors of an LED strip, e.g. of WS2812
+//! ("NeoPixel") LEDs driven by a microcontroller. Each LED shows one bar of
+//! the spectrum (see [`crate::FrequencySpectrum::to_bars`]). See
+//! [`LedStrip`].
+//!
+//! The colors are `[r, g, b]` arrays, which can be passed to the usual
+//! smart-LED drivers. Drivers that expect another channel order, such as
+//! GRB, reorder the channels themselves.
+
+use crate::bars::{BarReduction, FrequencyScale};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// Colors of the LEDs of a [`LedStrip`] at full intensity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LedPalette {
+    /// Hue from red (lowest band) to violet (highest band).
+    Rainbow,
+    /// All LEDs have the same color.
+    Single([u8; 3]),
+}
+
+impl LedPalette {
+    /// Returns the color of the LED with the given index.
+    fn color(self, index: usize, n_leds: usize) -> [u8; 3] {
+        match self {
+            Self::Rainbow => {
+                // 0° (red) to 270° (violet)
+                let hue = 270.0 * index as f32 / (n_leds - 1).max(1) as f32;
+                hue_to_rgb(hue)
+            }
+            Self::Single(color) => color,
+        }
+    }
+}
+
+/// Configuration of a [`LedStrip`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LedStripConfig {
+    /// Scale on which the bars of the LEDs are equally wide.
+    pub scale: FrequencyScale,
+    /// Level in dB (relative to the magnitude `1.0`) at which the LEDs are
+    /// off.
+    pub min_db: f32,
+    /// Level in dB (relative to the magnitude `1.0`) at which the LEDs have
+    /// the full intensity.
+    pub max_db: f32,
+    /// Gamma of the LEDs. The intensity is raised to this power, as the
+    /// perceived brightness of LEDs is not linear. `1.0` disables the gamma
+    /// correction.
+    pub gamma: f32,
+    /// Maximum brightness, e.g. to limit the current of the strip.
+    pub brightness: u8,
+    /// Colors of the LEDs.
+    pub palette: LedPalette,
+    /// Color of the peak dot, i.e. the LED of the loudest band, or `None` to
+    /// disable the peak dot.
+    pub peak_color: Option<[u8; 3]>,
+    /// Decrease of the held peak level in dB per update. The peak dot moves
+    /// to another LED when its level exceeds the held peak level.
+    pub peak_decay_db: f32,
+}
+
+impl Default for LedStripConfig {
+    fn default() -> Self {
+        Self {
+            scale: FrequencyScale::Log,
+            min_db: -60.0,
+            max_db: 0.0,
+            gamma: 2.2,
+            brightness: 255,
+            palette: LedPalette::Rainbow,
+            peak_color: Some([255, 255, 255]),
+            peak_decay_db: 1.0,
+        }
+    }
+}
+
+/// Maps consecutive spectra to the colors of an LED strip. The values of the
+/// spectra are expected to be magnitudes, e.g. scaled with
+/// [`crate::scaling::divide_by_N`].
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-16")] {
+/// use spectrum_analyzer::led::{LedStrip, LedStripConfig};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+/// let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// let mut strip = LedStrip::new(3, LedStripConfig::default());
+/// let colors: &[[u8; 3]] = strip.update(&spectrum);
+/// assert_eq!(colors.len(), 3);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LedStrip {
+    config: LedStripConfig,
+    colors: Vec<[u8; 3]>,
+    /// Index of the LED of the peak dot and its held level in dB.
+    peak: Option<(usize, f32)>,
+}
+
+impl LedStrip {
+    /// Creates a new LED strip mapping with `n_leds` LEDs.
+    ///
+    /// ## Panics
+    /// If `n_leds` is zero or `config.min_db` is not smaller than
+    /// `config.max_db`.
+    #[must_use]
+    pub fn new(n_leds: usize, config: LedStripConfig) -> Self {
+        assert!(n_leds > 0, "n_leds must not be zero!");
+        assert!(
+            config.min_db < config.max_db,
+            "min_db must be smaller than max_db!"
+        );
+        Self {
+            config,
+            colors: alloc::vec![[0; 3]; n_leds],
+            peak: None,
+        }
+    }
+
+    /// Returns the configuration.
+    #[must_use]
+    pub const fn config(&self) -> &LedStripConfig {
+        &self.config
+    }
+
+    /// Returns the colors of the last update, from the lowest to the highest
+    /// band.
+    #[must_use]
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    /// Maps the spectrum to the colors of the LEDs and returns them, from
+    /// the lowest to the highest band.
+    ///
+    /// ## Panics
+    /// See [`crate::FrequencySpectrum::to_bars`].
+    pub fn update(&mut self, spectrum: &FrequencySpectrum) -> &[[u8; 3]] {
+        let n_leds = self.colors.len();
+        let bars = spectrum.to_bars(n_leds, self.config.scale, BarReduction::Max);
+        let range_db = self.config.max_db - self.config.min_db;
+
+        let mut loudest: Option<(usize, f32)> = None;
+        for (index, (color, bar)) in self.colors.iter_mut().zip(&bars).enumerate() {
+            let db = 20.0 * libm::log10f(bar.value.val().max(f32::MIN_POSITIVE));
+            if loudest.map_or(true, |(_, loudest_db)| db > loudest_db) {
+                loudest = Some((index, db));
+            }
+            let level = ((db - self.config.min_db) / range_db).clamp(0.0, 1.0);
+            let intensity =
+                libm::powf(level, self.config.gamma) * f32::from(self.config.brightness);
+            let full_color = self.config.palette.color(index, n_leds);
+            *color = full_color
+                .map(|channel| libm::roundf(f32::from(channel) * intensity / 255.0) as u8);
+        }
+
+        if let Some(peak_color) = self.config.peak_color {
+            let decayed = self
+                .peak
+                .map(|(index, db)| (index, db - self.config.peak_decay_db));
+            self.peak = match (decayed, loudest) {
+                (Some(decayed), Some(loudest)) if decayed.1 >= loudest.1 => Some(decayed),
+                (_, loudest) => loudest,
+            };
+            if let Some((index, db)) = self.peak {
+                if db > self.config.min_db {
+                    let scale = f32::from(self.config.brightness) / 255.0;
+                    self.colors[index] =
+                        peak_color.map(|channel| libm::roundf(f32::from(channel) * scale) as u8);
+                }
+            }
+        }
+
+        &self.colors
+    }
+
+    /// Turns all LEDs off and resets the peak dot.
+    pub fn reset(&mut self) {
+        self.colors.fill([0; 3]);
+        self.peak = None;
+    }
+}
+
+/// Converts a hue in degrees with full saturation and value to RGB.
+fn hue_to_rgb(hue: f32) -> [u8; 3] {
+    let sector = hue / 60.0;
+    let x = 1.0 - libm::fabsf(sector % 2.0 - 1.0);
+    let (r, g, b) = match sector as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    [r, g, b].map(|channel: f32| libm::roundf(channel * 255.0) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frequency::{Frequency, FrequencyValue};
+
+    /// Spectrum from 0 to 1500 Hz with the given values for the bands
+    /// 0..500 Hz, 500..1000 Hz, and 1000..1500 Hz.
+    fn spectrum(bands: [f32; 3]) -> FrequencySpectrum {
+        let data = (0..16)
+            .map(|i| {
+                (
+                    Frequency::from(i as f32 * 100.0),
+                    FrequencyValue::from(bands[(i / 5).min(2)]),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        FrequencySpectrum::new(data, 100.0, 32, &mut working_buffer)
+    }
+
+    #[test]
+    fn test_hue_to_rgb() {
+        assert_eq!(hue_to_rgb(0.0), [255, 0, 0]);
+        assert_eq!(hue_to_rgb(120.0), [0, 255, 0]);
+        assert_eq!(hue_to_rgb(240.0), [0, 0, 255]);
+        assert_eq!(hue_to_rgb(270.0), [128, 0, 255]);
+    }
+
+    #[test]
+    fn test_led_strip() {
+        let config = LedStripConfig {
+            scale: FrequencyScale::Linear,
+            min_db: -40.0,
+            max_db: 0.0,
+            gamma: 1.0,
+            palette: LedPalette::Single([200, 100, 0]),
+            peak_color: None,
+            ..LedStripConfig::default()
+        };
+        let mut strip = LedStrip::new(3, config);
+        // 0 dB, -20 dB, -60 dB
+        let colors = strip.update(&spectrum([1.0, 0.1, 0.001]));
+        assert_eq!(colors, [[200, 100, 0], [100, 50, 0], [0, 0, 0]]);
+
+        let mut strip = LedStrip::new(
+            3,
+            LedStripConfig {
+                gamma: 2.0,
+                ..config
+            },
+        );
+        let colors = strip.update(&spectrum([1.0, 0.1, 0.001]));
+        assert_eq!(colors[1], [50, 25, 0]);
+    }
+
+    #[test]
+    fn test_peak_dot() {
+        let config = LedStripConfig {
+            scale: FrequencyScale::Linear,
+            min_db: -40.0,
+            peak_decay_db: 10.0,
+            ..LedStripConfig::default()
+        };
+        let mut strip = LedStrip::new(3, config);
+        let colors = strip.update(&spectrum([0.01, 1.0, 0.01]));
+        assert_eq!(colors[1], [255, 255, 255]);
+        // the peak is held at -10 dB
+        let colors = strip.update(&spectrum([0.01, 0.01, 0.1]));
+        assert_eq!(colors[1], [255, 255, 255]);
+        assert_ne!(colors[2], [255, 255, 255]);
+        // -14 dB on the last LED exceeds the decayed peak of -20 dB
+        let colors = strip.update(&spectrum([0.01, 0.01, 0.2]));
+        assert_eq!(colors[2], [255, 255, 255]);
+
+        strip.reset();
+        assert_eq!(strip.colors(), [[0; 3]; 3]);
+    }
+}
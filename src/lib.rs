@@ -66,16 +66,21 @@ SOFTWARE.
     clippy::suboptimal_flops,
     clippy::redundant_pub_crate,
     clippy::fallible_impl_from,
-    clippy::float_cmp
+    clippy::float_cmp,
+    clippy::too_long_first_doc_paragraph,
+    clippy::doc_overindented_list_items
 )]
+// tests that need bigger FFT lengths than enabled via the `size-*` features
+// are compiled out, which leaves some of their helpers unused
+#![cfg_attr(all(test, not(feature = "size-16384")), allow(unused))]
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 #![deny(rustdoc::all)]
 #![no_std]
 
-// enable std in tests (println!() for example)
+// enable std in tests (println!() for example) and with the "std" feature
 #[cfg_attr(test, macro_use)]
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
 // We use alloc crate, because this is no_std
@@ -83,28 +88,95 @@ extern crate std;
 #[macro_use]
 extern crate alloc;
 
+use alloc::borrow::Cow;
 use alloc::vec::Vec;
 
+pub use crate::analyzer::{SpectrumAnalyzer, SpectrumAnalyzerBuilder};
+pub use crate::complex_spectrum::ComplexFrequencySpectrum;
 use crate::error::SpectrumAnalyzerError;
-use crate::fft::{Complex32, FftImpl};
-pub use crate::frequency::{Frequency, FrequencyValue};
+use crate::fft::{calc_checked, FftImpl};
+pub use crate::fft::{Complex32, Fft, FftBackend};
+pub use crate::frequency::{
+    DisplayDb, DisplayHz, DisplayUnit, Frequency, FrequencyF64, FrequencyValue, FrequencyValueF64,
+};
 pub use crate::limit::FrequencyLimit;
 pub use crate::limit::FrequencyLimitError;
+use crate::padding::Padding;
+pub use crate::peak::{Peak, PeakConfig};
+use crate::sample::Sample;
 use crate::scaling::SpectrumScalingFunction;
 pub use crate::spectrum::FrequencySpectrum;
+pub use crate::streaming::StreamingAnalyzer;
+use crate::windows::Window;
 
+mod analyzer;
+pub mod ascii;
+#[cfg(feature = "symphonia")]
+pub mod audio_file;
+pub mod averaging;
+pub mod bark;
+pub mod bars;
+pub mod calibration;
+// other targets only compile it for the tests with a mock of CMSIS-DSP
+#[cfg(all(feature = "cmsis-dsp", any(target_arch = "arm", test)))]
+mod cmsis_dsp;
+mod complex_spectrum;
+pub mod cqt;
+pub mod detrend;
+pub mod double;
 pub mod error;
+pub mod features;
 mod fft;
+pub mod frame;
 mod frequency;
+pub mod gammatone;
+pub mod goertzel;
+#[cfg(feature = "embedded-graphics")]
+pub mod graphics;
+pub mod hilbert;
+pub mod led;
 mod limit;
+#[cfg(feature = "live")]
+pub mod live;
+pub mod measurement;
+pub mod mel;
+pub mod multichannel;
+pub mod no_alloc;
+pub mod note;
+pub mod onset;
+pub mod padding;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+mod peak;
+pub mod pitch;
+pub mod pre_emphasis;
+pub mod psd;
+pub mod q15;
+pub mod sample;
 pub mod scaling;
+pub mod smoothing;
+pub mod sound_level;
+pub mod spectrogram;
+pub mod spectrogram_image;
 mod spectrum;
+mod streaming;
+pub mod transfer;
+pub mod waterfall;
+pub mod wav;
+pub mod weighting;
 pub mod windows;
+pub mod zoom;
 
 // test module for large "integration"-like tests
 #[cfg(test)]
 mod tests;
 
+/// The maximum number of samples that can be passed into
+/// [`samples_fft_to_spectrum`]. This is limited by the underlying FFT
+/// implementation and the enabled `size-*` features, e.g. `1024` with
+/// `default-features = false, features = ["size-1024"]`.
+pub const MAX_SAMPLES_LEN: usize = FftImpl::MAX_LEN;
+
 /// Takes an array of samples (length must be a power of 2),
 /// e.g. 2048, applies an FFT (using the specified FFT implementation) on it
 /// and returns all frequencies with their volume/magnitude.
@@ -114,14 +186,16 @@ mod tests;
 /// the FFT algorithm, except that complex numbers are transformed
 /// to their magnitude.
 ///
-/// * `samples` raw audio, e.g. 16bit audio data but as f32.
+/// * `samples` raw audio, e.g. 16bit audio data but as f32. For other sample
+///             formats, such as `i16`, see [`samples_fft_to_spectrum_generic`].
 ///             You should apply an window function (like Hann) on the data first.
 ///             The final frequency resolution is `sample_rate / (N / 2)`
 ///             e.g. `44100/(16384/2) == 5.383Hz`, i.e. more samples =>
 ///             better accuracy/frequency resolution. The amount of samples must
-///             be a power of 2. If you don't have enough data, provide zeroes.
+///             be a power of 2 and at most [`MAX_SAMPLES_LEN`]. If you don't have
+///             enough data, provide zeroes.
 /// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
-/// * `frequency_limit` Frequency limit. See [`FrequencyLimit´]
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`]
 /// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`] for details.
 ///
 /// ## Returns value
@@ -154,33 +228,295 @@ mod tests;
 ///  );
 /// ```
 ///
-/// ## Panics
-/// * When `samples.len()` isn't a power of two less than or equal to `16384` and `microfft` is used
+/// ## Errors
+/// This function never panics on bad input. Instead, it returns
+/// * [`SpectrumAnalyzerError::TooFewSamples`] if `samples.len() < 2` (this includes empty input),
+/// * [`SpectrumAnalyzerError::NaNValuesNotSupported`] if a sample is `NaN`,
+/// * [`SpectrumAnalyzerError::InfinityValuesNotSupported`] if a sample is infinite,
+/// * [`SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo`] if `samples.len()` isn't a power of two,
+/// * [`SpectrumAnalyzerError::TooManySamples`] if `samples.len() > MAX_SAMPLES_LEN`,
+/// * [`SpectrumAnalyzerError::InvalidFrequencyLimit`] if the frequency limit is invalid,
+/// * [`SpectrumAnalyzerError::ScalingError`] if the scaling function produced invalid values.
+///
+/// With the `rustfft` feature, the two length errors can't occur, as all
+/// other lengths are calculated with `FftBackend::RustFft`.
 pub fn samples_fft_to_spectrum(
     samples: &[f32],
     sampling_rate: u32,
     frequency_limit: FrequencyLimit,
     scaling_fn: Option<&SpectrumScalingFunction>,
 ) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
-    // everything below two samples is unreasonable
-    if samples.len() < 2 {
-        return Err(SpectrumAnalyzerError::TooFewSamples);
+    samples_fft_to_spectrum_in_place(
+        Cow::Borrowed(samples),
+        sampling_rate,
+        frequency_limit,
+        scaling_fn,
+    )
+}
+
+/// Like [`samples_fft_to_spectrum`] but accepts other sample formats, such as
+/// `i16`, `i32`, `u8`, or `f64`. They are normalized to `f32` first. See
+/// [`sample::Sample`]. `f32` samples aren't copied.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_spectrum_generic, FrequencyLimit};
+/// // e.g. 16 bit PCM from a WAV file
+/// let samples: [i16; 4] = [0, 16384, 0, -16384];
+/// let res = samples_fft_to_spectrum_generic(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// assert_eq!(res.max().1.val(), 1.0);
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`].
+pub fn samples_fft_to_spectrum_generic<S: Sample>(
+    samples: &[S],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    samples_fft_to_spectrum_in_place(
+        S::to_f32_samples(samples),
+        sampling_rate,
+        frequency_limit,
+        scaling_fn,
+    )
+}
+
+/// Like [`samples_fft_to_spectrum`] but applies the given window to the
+/// samples first. The caller's samples stay unchanged. Unlike applying a
+/// window function beforehand, this makes at most one copy of the samples,
+/// and none for samples that need a conversion to `f32` anyway.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `window` Optional window. See [`windows::Window`].
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+/// * `scaling_fn` See [`samples_fft_to_spectrum`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_spectrum_windowed, FrequencyLimit};
+/// use spectrum_analyzer::windows::Window;
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let spectrum = samples_fft_to_spectrum_windowed(
+///     &samples,
+///     44100,
+///     Some(Window::Hann),
+///     FrequencyLimit::All,
+///     None,
+/// )
+/// .unwrap();
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`].
+///
+/// ## Panics
+/// See [`windows::Window::apply`].
+pub fn samples_fft_to_spectrum_windowed<S: Sample>(
+    samples: &[S],
+    sampling_rate: u32,
+    window: Option<Window>,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    let mut samples = S::to_f32_samples(samples);
+    if let Some(window) = window {
+        // copies the samples only if they are borrowed
+        window.apply_in_place(samples.to_mut());
     }
-    // do several checks on input data
-    if samples.iter().any(|x| x.is_nan()) {
-        return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+    samples_fft_to_spectrum_in_place(samples, sampling_rate, frequency_limit, scaling_fn)
+}
+
+/// Like [`samples_fft_to_spectrum`] but returns one spectrum for each of the
+/// given (possibly disjoint) frequency ranges, e.g.
+/// `&[(50.0, 200.0), (1000.0, 2000.0)]`. The FFT is only calculated once.
+/// Each spectrum is the same as the one of [`samples_fft_to_spectrum`] with
+/// the corresponding [`FrequencyLimit::Range`], i.e. it only contains the
+/// frequencies of its range and the scaling function sees the statistics of
+/// its range.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `ranges` Inclusive `(min, max)` frequency ranges in Hertz. Each range
+///            has the same semantics as [`FrequencyLimit::Range`].
+/// * `scaling_fn` See [`samples_fft_to_spectrum`].
+///
+/// ## Return value
+/// One spectrum per range, in the order of `ranges`. Hence, no spectrum if
+/// `ranges` is empty.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-4096")] {
+/// use spectrum_analyzer::samples_fft_to_spectra_in_ranges;
+/// let samples = vec![0.0; 4096];
+/// let spectra =
+///     samples_fft_to_spectra_in_ranges(&samples, 4096, &[(50.0, 200.0), (1000.0, 2000.0)], None)
+///         .unwrap();
+/// assert_eq!(spectra[0].data().len(), 151);
+/// assert_eq!(spectra[1].data().len(), 1001);
+/// # }
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`]. Additionally,
+/// [`SpectrumAnalyzerError::InvalidFrequencyResolution`] if less than two
+/// frequencies are inside a range.
+pub fn samples_fft_to_spectra_in_ranges<S: Sample>(
+    samples: &[S],
+    sampling_rate: u32,
+    ranges: &[(f32, f32)],
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<Vec<FrequencySpectrum>, SpectrumAnalyzerError> {
+    let samples = S::to_f32_samples(samples);
+    validate_input(&samples, sampling_rate, &FrequencyLimit::All)?;
+    if ranges.is_empty() {
+        return Ok(Vec::new());
     }
-    if samples.iter().any(|x| x.is_infinite()) {
-        return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples.len() as u32);
+    for (min, max) in ranges {
+        validate_frequency_limit(sampling_rate, &FrequencyLimit::Range(*min, *max))?;
+        let frequencies_in_range = libm::floorf(max / frequency_resolution)
+            - libm::ceilf(min / frequency_resolution)
+            + 1.0;
+        if frequencies_in_range < 2.0 {
+            return Err(SpectrumAnalyzerError::InvalidFrequencyResolution(
+                frequency_resolution,
+            ));
+        }
     }
-    if !samples.len().is_power_of_two() {
-        return Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo);
+    // one contiguous spectrum that covers all ranges
+    let min = ranges
+        .iter()
+        .map(|(min, _)| *min)
+        .fold(f32::INFINITY, f32::min);
+    let max = ranges.iter().map(|(_, max)| *max).fold(0.0, f32::max);
+    let spectrum = samples_fft_to_spectrum_in_place(
+        samples,
+        sampling_rate,
+        FrequencyLimit::Range(min, max),
+        None,
+    )?;
+
+    ranges
+        .iter()
+        .map(|(min, max)| {
+            let mut sub = spectrum
+                .sub_spectrum(FrequencyLimit::Range(*min, *max))
+                .ok_or(SpectrumAnalyzerError::InvalidFrequencyResolution(
+                    frequency_resolution,
+                ))?;
+            if let Some(scaling_fn) = scaling_fn {
+                let mut working_buffer = sub.data().to_vec();
+                sub.apply_scaling_fn(scaling_fn, &mut working_buffer)?;
+            }
+            Ok(sub)
+        })
+        .collect()
+}
+
+/// Like [`samples_fft_to_spectrum_with_fft`] with [`FftBackend::MicrofftReal`]
+/// but calculates the FFT in-place in the memory of `samples` and writes the
+/// magnitudes directly into the data of the spectrum. Unlike
+/// [`FftImpl::calc`], this needs neither a copy of the samples on the stack nor
+/// an intermediate vector with the complex FFT result, which reduces the peak
+/// memory usage for big FFTs considerably. The samples are only copied if they
+/// are borrowed. With the `rustfft` feature, lengths that `microfft` doesn't
+/// support are calculated with `FftBackend::RustFft` instead.
+fn samples_fft_to_spectrum_in_place(
+    mut samples: Cow<'_, [f32]>,
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    #[cfg(feature = "rustfft")]
+    if validate_fft_len(samples.len()).is_err() {
+        return samples_fft_to_spectrum_with_fft(
+            &samples,
+            sampling_rate,
+            frequency_limit,
+            scaling_fn,
+            &FftBackend::RustFft,
+        );
     }
-    let max_detectable_frequency = sampling_rate as f32 / 2.0;
-    // verify frequency limit: unwrap error or else ok
-    frequency_limit
-        .verify(max_detectable_frequency)
-        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+    validate_input(&samples, sampling_rate, &frequency_limit)?;
+
+    let samples_len = samples.len();
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples_len as u32);
+    let fft_res = FftImpl::calc_in_place(samples.to_mut());
+    // the real value of the Nyquist frequency is packed inside the DC
+    // component
+    let nyquist_fr_val = fft_res[0].im;
+    fft_res[0].im = 0.0;
+
+    let frequency_vec = (0..=samples_len / 2)
+        .map(|index| (index, index as f32 * frequency_resolution))
+        .filter(|(_index, fr)| frequency_limit.contains(*fr))
+        .map(|(index, fr)| {
+            let magnitude = if index == samples_len / 2 {
+                libm::fabsf(nyquist_fr_val)
+            } else {
+                complex_to_magnitude(&fft_res[index])
+            };
+            (Frequency::from(fr), FrequencyValue::from(magnitude))
+        })
+        .collect::<Vec<_>>();
+
+    finish_spectrum(frequency_vec, frequency_resolution, samples_len, scaling_fn)
+}
+
+/// Like [`samples_fft_to_spectrum`] but uses the given FFT implementation,
+/// e.g. one of [`FftBackend`] or a custom implementation of [`Fft`]. The
+/// supported numbers of samples are up to the FFT, see [`Fft::validate_len`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_spectrum_with_fft, Complex32, Fft, FrequencyLimit};
+///
+/// /// Naive DFT, just to show how to plug in an own FFT.
+/// #[derive(Debug)]
+/// struct Dft;
+///
+/// impl Fft for Dft {
+///     fn calc(&self, samples: &[f32]) -> Vec<Complex32> {
+///         let n = samples.len();
+///         (0..=n / 2)
+///             .map(|k| {
+///                 samples.iter().enumerate().fold(Complex32::new(0.0, 0.0), |acc, (i, x)| {
+///                     let angle = -2.0 * core::f32::consts::PI * (k * i) as f32 / n as f32;
+///                     acc + Complex32::new(angle.cos(), angle.sin()) * *x
+///                 })
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// // the naive DFT supports any number of samples, e.g. 6 samples of 2 Hz
+/// let samples = [1.0, -0.5, -0.5, 1.0, -0.5, -0.5];
+/// let spectrum = samples_fft_to_spectrum_with_fft(&samples, 6, FrequencyLimit::All, None, &Dft).unwrap();
+/// assert_eq!(spectrum.max().0.val(), 2.0);
+/// ```
+///
+/// ## Errors
+/// The error of [`Fft::validate_len`] if the FFT doesn't support the number
+/// of samples. Otherwise, see [`samples_fft_to_spectrum`].
+///
+/// ## Panics
+/// If the FFT doesn't return `samples.len() / 2 + 1` values.
+pub fn samples_fft_to_spectrum_with_fft(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    fft: &dyn Fft,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    fft.validate_len(samples.len())?;
+    validate_frequency_limit(sampling_rate, &frequency_limit)?;
 
     // With FFT we transform an array of time-domain waveform samples
     // into an array of frequency-domain spectrum samples
@@ -193,7 +529,7 @@ pub fn samples_fft_to_spectrum(
     // chosen at compile time (via Cargo feature).
     // If a complex FFT implementation was chosen, this will internally
     // transform all data to Complex numbers.
-    let fft_res = FftImpl::calc(samples);
+    let fft_res = calc_checked(fft, samples);
 
     // This function:
     // 1) calculates the corresponding frequency of each index in the FFT result
@@ -210,6 +546,298 @@ pub fn samples_fft_to_spectrum(
     )
 }
 
+/// Like [`samples_fft_to_spectrum`] but returns the full two-sided spectrum
+/// with all `N` frequencies from `-sampling_rate / 2` (inclusive) up to
+/// `sampling_rate / 2` (exclusive), sorted from the lowest to the highest
+/// frequency. As the samples are real, the magnitudes of the negative
+/// frequencies mirror the ones of the positive frequencies. This is useful to
+/// verify the symmetry or to feed DSP code that expects the full spectrum.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`] for details.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::samples_fft_to_two_sided_spectrum;
+/// let samples = [0.0, 1.0, 0.0, -1.0];
+/// let spectrum = samples_fft_to_two_sided_spectrum(&samples, 4, None).unwrap();
+/// assert_eq!(spectrum.data().len(), 4);
+/// assert_eq!(spectrum.data()[0].0.val(), -2.0);
+/// assert_eq!(spectrum.freq_val_exact(-1.0), spectrum.freq_val_exact(1.0));
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`]. [`SpectrumAnalyzerError::InvalidFrequencyLimit`]
+/// can't occur.
+pub fn samples_fft_to_two_sided_spectrum(
+    samples: &[f32],
+    sampling_rate: u32,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    validate_fft_len(samples.len())?;
+
+    let len = samples.len();
+    let fft_res = FftImpl::calc(samples);
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, len as u32);
+    let data = (0..len)
+        .map(|i| {
+            let index = i as isize - (len / 2) as isize;
+            (
+                Frequency::from(index as f32 * frequency_resolution),
+                // negative frequencies are the complex conjugates of the
+                // positive ones, i.e. they have the same magnitude
+                FrequencyValue::from(complex_to_magnitude(&fft_res[index.unsigned_abs()])),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut working_buffer = data.clone();
+    let mut spectrum =
+        FrequencySpectrum::new(data, frequency_resolution, len as u32, &mut working_buffer);
+    if let Some(scaling_fn) = scaling_fn {
+        spectrum.apply_scaling_fn(scaling_fn, &mut working_buffer)?;
+    }
+    Ok(spectrum)
+}
+
+/// Calculates the spectrum of complex samples, e.g. IQ data of a
+/// software-defined radio (SDR), with a complex FFT. Unlike for real samples,
+/// the spectrum isn't symmetric. Hence, it contains all `N` frequencies from
+/// `-sampling_rate / 2` (inclusive) up to `sampling_rate / 2` (exclusive),
+/// sorted from the lowest to the highest frequency (i.e., "fftshift" is
+/// applied).
+///
+/// ## Parameters
+/// * `samples` Complex samples. The length must be a power of two and at most
+///             [`MAX_SAMPLES_LEN`].
+/// * `sampling_rate` sampling_rate, e.g. `2_000_000 [Hz]`
+/// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`] for details.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::{complex_samples_fft_to_spectrum, Complex32};
+/// // complex exponential at -1/4 of the sampling rate
+/// let samples = [
+///     Complex32::new(1.0, 0.0),
+///     Complex32::new(0.0, -1.0),
+///     Complex32::new(-1.0, 0.0),
+///     Complex32::new(0.0, 1.0),
+/// ];
+/// let spectrum = complex_samples_fft_to_spectrum(&samples, 1000, None).unwrap();
+/// assert_eq!(spectrum.max().0.val(), -250.0);
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`]. [`SpectrumAnalyzerError::InvalidFrequencyLimit`]
+/// can't occur.
+pub fn complex_samples_fft_to_spectrum(
+    samples: &[Complex32],
+    sampling_rate: u32,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    if samples.len() < 2 {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    if samples.iter().any(|x| x.re.is_nan() || x.im.is_nan()) {
+        return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+    }
+    if samples
+        .iter()
+        .any(|x| x.re.is_infinite() || x.im.is_infinite())
+    {
+        return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+    }
+    validate_fft_len(samples.len())?;
+
+    let len = samples.len();
+    let mut fft_res = FftImpl::calc_complex_input(samples.to_vec());
+    // fftshift: the negative frequencies of the second half come first
+    fft_res.rotate_left(len / 2);
+
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, len as u32);
+    let data = fft_res
+        .iter()
+        .enumerate()
+        .map(|(i, complex_res)| {
+            let fr = (i as f32 - (len / 2) as f32) * frequency_resolution;
+            (
+                Frequency::from(fr),
+                FrequencyValue::from(complex_to_magnitude(complex_res)),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut working_buffer = data.clone();
+    let mut spectrum =
+        FrequencySpectrum::new(data, frequency_resolution, len as u32, &mut working_buffer);
+    if let Some(scaling_fn) = scaling_fn {
+        spectrum.apply_scaling_fn(scaling_fn, &mut working_buffer)?;
+    }
+    Ok(spectrum)
+}
+
+/// Like [`samples_fft_to_spectrum`] but pads the samples with zeroes first,
+/// e.g. to the next power of two. Hence, sample buffers of any length, e.g.
+/// 1000 samples, can be analyzed. The frequency resolution of the spectrum
+/// corresponds to the padded length, e.g. `sampling_rate / 1024`, and
+/// [`FrequencySpectrum::samples_len`] returns the padded length. See
+/// [`FrequencySpectrum::true_frequency_resolution`] for the resolution
+/// without padding.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`]. After the padding, the
+///             length must be a power of two and at most [`MAX_SAMPLES_LEN`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`]
+/// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`] for details.
+/// * `padding` See [`Padding`].
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::{samples_fft_to_spectrum_padded, FrequencyLimit};
+/// use spectrum_analyzer::padding::Padding;
+/// let samples = vec![0.5; 1000];
+/// let spectrum = samples_fft_to_spectrum_padded(
+///     &samples,
+///     1024,
+///     FrequencyLimit::All,
+///     None,
+///     Padding::ZeroToNextPowerOfTwo,
+/// ).unwrap();
+/// assert_eq!(spectrum.frequency_resolution(), 1.0);
+/// # }
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`]. The length checks apply to the padded
+/// samples.
+///
+/// ## Panics
+/// See [`Padding::padded_len`].
+pub fn samples_fft_to_spectrum_padded(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+    padding: Padding,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    // check before the padding, as a single sample must be rejected
+    validate_samples(samples)?;
+    let padded_samples = padding.apply(samples);
+    let mut spectrum =
+        samples_fft_to_spectrum(&padded_samples, sampling_rate, frequency_limit, scaling_fn)?;
+    spectrum.set_unpadded_samples_len(samples.len() as u32);
+    Ok(spectrum)
+}
+
+/// Like [`samples_fft_to_spectrum`] but keeps the complex FFT result for
+/// each frequency. Hence, the phase information is not lost. No scaling
+/// is applied to the values.
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`]
+///
+/// ## Returns value
+/// New object of type [`ComplexFrequencySpectrum`].
+///
+/// ## Examples
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_complex_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let spectrum = samples_fft_to_complex_spectrum(&samples, 44100, FrequencyLimit::All).unwrap();
+/// let _phase = spectrum.phase_at(11025.0);
+/// let _magnitude = spectrum.magnitude_at(11025.0);
+/// ```
+///
+/// ## Errors
+/// See [`samples_fft_to_spectrum`].
+pub fn samples_fft_to_complex_spectrum(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+) -> Result<ComplexFrequencySpectrum, SpectrumAnalyzerError> {
+    validate_input(samples, sampling_rate, &frequency_limit)?;
+
+    let fft_res = FftImpl::calc(samples);
+
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples.len() as u32);
+    let frequency_vec = fft_result_with_frequencies(
+        samples.len(),
+        &fft_res,
+        frequency_resolution,
+        frequency_limit,
+    )
+    .map(|(fr, complex_res)| (Frequency::from(fr), *complex_res))
+    .collect::<Vec<(Frequency, Complex32)>>();
+
+    Ok(ComplexFrequencySpectrum::new(
+        frequency_vec,
+        frequency_resolution,
+        samples.len() as u32,
+    ))
+}
+
+/// Performs all sanity checks on the input of the public analysis functions,
+/// such as [`samples_fft_to_spectrum`].
+///
+/// ## Parameters
+/// * `samples` See [`samples_fft_to_spectrum`].
+/// * `sampling_rate` See [`samples_fft_to_spectrum`].
+/// * `frequency_limit` See [`samples_fft_to_spectrum`].
+fn validate_input(
+    samples: &[f32],
+    sampling_rate: u32,
+    frequency_limit: &FrequencyLimit,
+) -> Result<(), SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    validate_fft_len(samples.len())?;
+    validate_frequency_limit(sampling_rate, frequency_limit)
+}
+
+/// Checks that the FFT implementation supports the given length.
+const fn validate_fft_len(len: usize) -> Result<(), SpectrumAnalyzerError> {
+    if !len.is_power_of_two() {
+        return Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo);
+    }
+    if len > MAX_SAMPLES_LEN {
+        return Err(SpectrumAnalyzerError::TooManySamples);
+    }
+    Ok(())
+}
+
+/// Checks that the frequency limit is valid for the given sampling rate.
+fn validate_frequency_limit(
+    sampling_rate: u32,
+    frequency_limit: &FrequencyLimit,
+) -> Result<(), SpectrumAnalyzerError> {
+    // verify frequency limit: unwrap error or else ok
+    frequency_limit
+        .verify_for_sampling_rate(sampling_rate)
+        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)
+}
+
+/// Checks that there are at least two samples and that all samples are
+/// regular floating point numbers, i.e. neither `NaN` nor infinite. Unlike
+/// [`validate_input`], this doesn't check the length for the FFT.
+fn validate_samples(samples: &[f32]) -> Result<(), SpectrumAnalyzerError> {
+    // everything below two samples is unreasonable
+    if samples.len() < 2 {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    // do several checks on input data
+    if samples.iter().any(|x| x.is_nan()) {
+        return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+    }
+    if samples.iter().any(|x| x.is_infinite()) {
+        return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+    }
+    Ok(())
+}
+
 /// Transforms the FFT result into the spectrum by calculating the corresponding frequency of each
 /// FFT result index and optionally calculating the magnitudes of the complex numbers if a complex
 /// FFT implementation is chosen.
@@ -220,7 +848,7 @@ pub fn samples_fft_to_spectrum(
 ///                 `fft_result.len()` in real and complex FFT algorithms.
 /// * `fft_result` Result buffer from FFT. Has the same length as the samples array.
 /// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
-/// * `frequency_limit` Frequency limit. See [`FrequencyLimit´]
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`]
 /// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
 ///
 /// ## Return value
@@ -233,13 +861,73 @@ fn fft_result_to_spectrum(
     frequency_limit: FrequencyLimit,
     scaling_fn: Option<&SpectrumScalingFunction>,
 ) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
-    let maybe_min = frequency_limit.maybe_min();
-    let maybe_max = frequency_limit.maybe_max();
-
     let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples_len as u32);
 
     // collect frequency => frequency value in Vector of Pairs/Tuples
-    let frequency_vec = fft_result
+    let frequency_vec = fft_result_with_frequencies(
+        samples_len,
+        fft_result,
+        frequency_resolution,
+        frequency_limit,
+    )
+    // FFT result is always complex: calc magnitude
+    //   sqrt(re*re + im*im) (re: real part, im: imaginary part)
+    .map(|(fr, complex_res)| (fr, complex_to_magnitude(complex_res)))
+    // transform to my thin convenient orderable f32 wrappers
+    .map(|(fr, val)| (Frequency::from(fr), FrequencyValue::from(val)))
+    // collect all into an sorted vector (from lowest frequency to highest)
+    .collect::<Vec<(Frequency, FrequencyValue)>>();
+
+    finish_spectrum(frequency_vec, frequency_resolution, samples_len, scaling_fn)
+}
+
+/// Creates the [`FrequencySpectrum`] from the collected data and optionally
+/// scales it. The working buffer for the median is only allocated if it is
+/// actually needed for scaling.
+fn finish_spectrum(
+    frequency_vec: Vec<(Frequency, FrequencyValue)>,
+    frequency_resolution: f32,
+    samples_len: usize,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    // create spectrum object
+    let mut spectrum = FrequencySpectrum::new(
+        frequency_vec,
+        frequency_resolution,
+        samples_len as u32,
+        &mut [],
+    );
+
+    // optionally scale
+    if let Some(scaling_fn) = scaling_fn {
+        let mut working_buffer = vec![(0.0.into(), 0.0.into()); spectrum.data().len()];
+        spectrum.apply_scaling_fn(scaling_fn, &mut working_buffer)?
+    }
+
+    Ok(spectrum)
+}
+
+/// Maps each relevant index of the FFT result to its corresponding frequency
+/// and filters out all frequencies that are not covered by `frequency_limit`.
+///
+/// ## Parameters
+/// * `samples_len` Length of samples. See [`fft_result_to_spectrum`].
+/// * `fft_result` Result buffer from FFT or any buffer derived from it
+///                with the same indices, e.g. power values.
+/// * `frequency_resolution` See [`fft_calc_frequency_resolution`].
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`]
+///
+/// ## Return value
+/// Iterator over `(frequency, FFT result)`-pairs, sorted from the
+/// lowest to the highest frequency.
+#[inline]
+fn fft_result_with_frequencies<T>(
+    samples_len: usize,
+    fft_result: &[T],
+    frequency_resolution: f32,
+    frequency_limit: FrequencyLimit,
+) -> impl Iterator<Item = (f32, &T)> {
+    fft_result
         .iter()
         // See https://stackoverflow.com/a/4371627/2891595 for more information as well as
         // https://www.gaussianwaves.com/2015/11/interpreting-fft-results-complex-dft-frequency-bins-and-fftshift/
@@ -255,7 +943,7 @@ fn fft_result_to_spectrum(
         // to (index, fft-result)-pairs
         .enumerate()
         // calc index => corresponding frequency
-        .map(|(fft_index, fft_result)| {
+        .map(move |(fft_index, fft_result)| {
             (
                 // Calculate corresponding frequency of each index of FFT result.
                 //
@@ -284,52 +972,13 @@ fn fft_result_to_spectrum(
         })
         // #######################
         // ### BEGIN filtering: results in lower calculation and memory overhead!
-        // check lower bound frequency (inclusive)
-        .filter(|(fr, _fft_result)| {
-            maybe_min.map_or(true, |min_fr| {
-                // inclusive!
-                // attention: due to the frequency resolution, we do not necessarily hit
-                //            exactly the frequency, that a user requested
-                //            e.g. 1416.8 < limit < 1425.15
-                *fr >= min_fr
-            })
-        })
-        // check upper bound frequency (inclusive)
-        .filter(|(fr, _fft_result)| {
-            maybe_max.map_or(true, |max_fr| {
-                // inclusive!
-                // attention: due to the frequency resolution, we do not necessarily hit
-                //            exactly the frequency, that a user requested
-                //            e.g. 1416.8 < limit < 1425.15
-                *fr <= max_fr
-            })
-        })
-        // ### END filtering
-        // #######################
-        // FFT result is always complex: calc magnitude
-        //   sqrt(re*re + im*im) (re: real part, im: imaginary part)
-        .map(|(fr, complex_res)| (fr, complex_to_magnitude(complex_res)))
-        // transform to my thin convenient orderable f32 wrappers
-        .map(|(fr, val)| (Frequency::from(fr), FrequencyValue::from(val)))
-        // collect all into an sorted vector (from lowest frequency to highest)
-        .collect::<Vec<(Frequency, FrequencyValue)>>();
-
-    let mut working_buffer = vec![(0.0.into(), 0.0.into()); frequency_vec.len()];
-
-    // create spectrum object
-    let mut spectrum = FrequencySpectrum::new(
-        frequency_vec,
-        frequency_resolution,
-        samples_len as u32,
-        &mut working_buffer,
-    );
-
-    // optionally scale
-    if let Some(scaling_fn) = scaling_fn {
-        spectrum.apply_scaling_fn(scaling_fn, &mut working_buffer)?
-    }
-
-    Ok(spectrum)
+        // check the frequency limit (inclusive)
+        // attention: due to the frequency resolution, we do not necessarily hit
+        //            exactly the frequency, that a user requested
+        //            e.g. 1416.8 < limit < 1425.15
+        .filter(move |(fr, _fft_result)| frequency_limit.contains(*fr))
+    // ### END filtering
+    // #######################
 }
 
 /// Calculate the frequency resolution of the FFT. It is determined by the sampling rate
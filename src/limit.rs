@@ -23,12 +23,16 @@ SOFTWARE.
 */
 //! Module for the struct [`FrequencyLimit`].
 
+use core::fmt::{Display, Formatter};
+
 /// Can be used to specify a desired frequency limit. If you know that you only
 /// need frequencies `f <= 1000Hz`, `1000 <= f <= 6777`, or `10000 <= f`, then this
 /// can help you to accelerate overall computation speed and memory usage.
 ///
 /// Please note that due to frequency inaccuracies the FFT result may not contain
 /// a value for `1000Hz` but for `998.76Hz`!
+///
+/// For multiple disjoint ranges, see [`crate::samples_fft_to_spectra_in_ranges`].
 #[derive(Debug, Copy, Clone)]
 pub enum FrequencyLimit {
     /// Interested in all frequencies. [0, sampling_rate/2] (Nyquist theorem).
@@ -70,6 +74,19 @@ impl FrequencyLimit {
         }
     }
 
+    /// Returns whether the frequency is inside the frequency limit. All
+    /// limits are inclusive.
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, frequency: f32) -> bool {
+        match self {
+            Self::All => true,
+            Self::Min(min) => frequency >= *min,
+            Self::Max(max) => frequency <= *max,
+            Self::Range(min, max) => frequency >= *min && frequency <= *max,
+        }
+    }
+
     /// Returns the minimum value, panics if it's none.
     /// Unwrapped version of [`Self::maybe_min`].
     #[inline]
@@ -86,13 +103,100 @@ impl FrequencyLimit {
         self.maybe_max().expect("Must contain a value!")
     }
 
+    /// Creates a [`FrequencyLimit::Range`] of the given total bandwidth around
+    /// the center frequency, e.g. ±50 Hz around 1 kHz for a bandwidth of
+    /// 100 Hz. The lower limit is clamped to `0.0`.
+    ///
+    /// ## Parameters
+    /// * `center` Center frequency in Hertz.
+    /// * `bandwidth` Total width of the range in Hertz.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::FrequencyLimit;
+    /// let limit = FrequencyLimit::centered(1000.0, 100.0);
+    /// assert_eq!(limit.min(), 950.0);
+    /// assert_eq!(limit.max(), 1050.0);
+    /// ```
+    ///
+    /// ## Panics
+    /// If `bandwidth` is negative.
+    #[must_use]
+    pub fn centered(center: f32, bandwidth: f32) -> Self {
+        assert!(bandwidth >= 0.0, "bandwidth must not be negative!");
+        Self::Range(
+            (center - bandwidth / 2.0).max(0.0),
+            center + bandwidth / 2.0,
+        )
+    }
+
+    /// Like [`Self::centered`] but the bandwidth is given by the quality
+    /// factor `q`, i.e. the bandwidth is `center / q`.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::FrequencyLimit;
+    /// let limit = FrequencyLimit::centered_q(1000.0, 10.0);
+    /// assert_eq!(limit.min(), 950.0);
+    /// assert_eq!(limit.max(), 1050.0);
+    /// ```
+    ///
+    /// ## Panics
+    /// If `q` isn't positive.
+    #[must_use]
+    pub fn centered_q(center: f32, q: f32) -> Self {
+        assert!(q > 0.0, "q must be positive!");
+        Self::centered(center, center / q)
+    }
+
+    /// Creates a [`FrequencyLimit::Range`] and verifies that both values are
+    /// valid, i.e., not negative or `NaN`, and that `min <= max`. Use
+    /// [`Self::verify_for_sampling_rate`] to check the Nyquist frequency as well.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::FrequencyLimit;
+    /// let limit = FrequencyLimit::try_range(100.0, 1000.0).unwrap();
+    /// assert!(FrequencyLimit::try_range(1000.0, 100.0).is_err());
+    /// assert!(limit.verify_for_sampling_rate(1000).is_err());
+    /// ```
+    ///
+    /// ## Errors
+    /// * [`FrequencyLimitError::NaNValue`] if a value is `NaN`,
+    /// * [`FrequencyLimitError::ValueBelowMinimum`] if a value is negative,
+    /// * [`FrequencyLimitError::InvalidRange`] if `min > max`.
+    pub fn try_range(min: f32, max: f32) -> Result<Self, FrequencyLimitError> {
+        let limit = Self::Range(min, max);
+        limit.verify(f32::INFINITY)?;
+        Ok(limit)
+    }
+
+    /// Like [`Self::verify`] but calculates the maximum detectable frequency
+    /// (Nyquist frequency) from the sampling rate.
+    ///
+    /// ## Errors
+    /// See [`Self::verify`].
+    pub fn verify_for_sampling_rate(&self, sampling_rate: u32) -> Result<(), FrequencyLimitError> {
+        self.verify(sampling_rate as f32 / 2.0)
+    }
+
     /// Verifies that the frequency limit has sane values and takes the maximum possible
     /// frequency into account.
+    ///
+    /// ## Errors
+    /// * [`FrequencyLimitError::NaNValue`] if a value is `NaN`,
+    /// * [`FrequencyLimitError::ValueBelowMinimum`] if a value is negative,
+    /// * [`FrequencyLimitError::ValueAboveNyquist`] if a value is above the
+    ///   maximum detectable frequency,
+    /// * [`FrequencyLimitError::InvalidRange`] if the minimum of a range is
+    ///   bigger than its maximum.
     pub fn verify(&self, max_detectable_frequency: f32) -> Result<(), FrequencyLimitError> {
         match self {
             Self::All => Ok(()),
             Self::Min(x) | Self::Max(x) => {
-                if *x < 0.0 {
+                if x.is_nan() {
+                    Err(FrequencyLimitError::NaNValue)
+                } else if *x < 0.0 {
                     Err(FrequencyLimitError::ValueBelowMinimum(*x))
                 } else if *x > max_detectable_frequency {
                     Err(FrequencyLimitError::ValueAboveNyquist(*x))
@@ -114,7 +218,9 @@ impl FrequencyLimit {
 }
 
 /// Possible errors when creating a [`FrequencyLimit`]-object.
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum FrequencyLimitError {
     /// If the minimum value is below 0. Negative frequencies are not supported.
     ValueBelowMinimum(f32),
@@ -124,11 +230,33 @@ pub enum FrequencyLimitError {
     /// Either the corresponding value is below or above the minimum/maximum or the
     /// first member of the tuple is bigger than the second.
     InvalidRange(f32, f32),
+    /// If a value is `NaN`.
+    NaNValue,
+}
+
+impl Display for FrequencyLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ValueBelowMinimum(x) => {
+                write!(f, "frequency limit {x} Hz is negative")
+            }
+            Self::ValueAboveNyquist(x) => {
+                write!(f, "frequency limit {x} Hz is above the Nyquist frequency")
+            }
+            Self::InvalidRange(min, max) => {
+                write!(
+                    f,
+                    "frequency range {min} Hz..={max} Hz is invalid: minimum is bigger than maximum"
+                )
+            }
+            Self::NaNValue => write!(f, "frequency limit is NaN"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::FrequencyLimit;
+    use crate::{FrequencyLimit, FrequencyLimitError};
 
     #[test]
     fn test_panic_min_below_minimum() {
@@ -173,4 +301,51 @@ mod tests {
         FrequencyLimit::Range(50.0, 50.0).verify(100.0).unwrap();
         FrequencyLimit::Range(50.0, 70.0).verify(100.0).unwrap();
     }
+
+    #[test]
+    fn test_centered() {
+        let limit = FrequencyLimit::centered(100.0, 300.0);
+        assert_eq!(limit.min(), 0.0);
+        assert_eq!(limit.max(), 250.0);
+        let limit = FrequencyLimit::centered_q(440.0, 4.0);
+        assert_eq!(limit.min(), 385.0);
+        assert_eq!(limit.max(), 495.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_centered_q_zero() {
+        let _ = FrequencyLimit::centered_q(440.0, 0.0);
+    }
+
+    #[test]
+    fn test_try_range() {
+        assert!(matches!(
+            FrequencyLimit::try_range(100.0, 200.0),
+            Ok(FrequencyLimit::Range(min, max)) if min == 100.0 && max == 200.0
+        ));
+        assert_eq!(
+            FrequencyLimit::try_range(200.0, 100.0).unwrap_err(),
+            FrequencyLimitError::InvalidRange(200.0, 100.0)
+        );
+        assert_eq!(
+            FrequencyLimit::try_range(-1.0, 100.0).unwrap_err(),
+            FrequencyLimitError::ValueBelowMinimum(-1.0)
+        );
+        assert_eq!(
+            FrequencyLimit::try_range(f32::NAN, 100.0).unwrap_err(),
+            FrequencyLimitError::NaNValue
+        );
+        assert_eq!(
+            FrequencyLimit::try_range(100.0, 30000.0)
+                .unwrap()
+                .verify_for_sampling_rate(44100)
+                .unwrap_err(),
+            FrequencyLimitError::ValueAboveNyquist(30000.0)
+        );
+        assert_eq!(
+            std::format!("{}", FrequencyLimitError::InvalidRange(2.0, 1.0)),
+            "frequency range 2 Hz..=1 Hz is invalid: minimum is bigger than maximum"
+        );
+    }
 }
@@ -0,0 +1,403 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Live analysis of audio input devices via [`cpal`]. Needs the `live`
+//! feature.
+//!
+//! [`start_live_analysis`] connects to an input device, mixes all channels
+//! down to mono, feeds them into a [`StreamingAnalyzer`] and invokes a
+//! callback with the spectrum of each new frame, or with the error why a
+//! frame couldn't be analyzed.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use spectrum_analyzer::live::cpal::traits::HostTrait;
+//! use spectrum_analyzer::live::{cpal, start_live_analysis, LiveConfig};
+//! use spectrum_analyzer::windows::Window;
+//!
+//! let device = cpal::default_host().default_input_device().unwrap();
+//! let config = LiveConfig {
+//!     window: Some(Window::Hann),
+//!     ..LiveConfig::default()
+//! };
+//! let _stream = start_live_analysis(
+//!     &device,
+//!     config,
+//!     |spectrum| match spectrum {
+//!         Ok(spectrum) => println!("loudest frequency: {}", spectrum.max().0),
+//!         Err(err) => eprintln!("analysis error: {err}"),
+//!     },
+//!     |err| eprintln!("stream error: {err}"),
+//! )
+//! .unwrap();
+//! // the analysis stops when the stream is dropped
+//! std::thread::sleep(std::time::Duration::from_secs(10));
+//! ```
+
+pub use cpal;
+
+use crate::error::SpectrumAnalyzerError;
+use crate::multichannel::downmix_into;
+use crate::sample::Sample;
+use crate::windows::Window;
+use crate::{FrequencyLimit, FrequencySpectrum, SpectrumAnalyzerBuilder, StreamingAnalyzer};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{SampleFormat, SizedSample, Stream, StreamConfig};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+/// Maximum number of blocks of samples that wait for the analysis. If the
+/// analysis or the callback is too slow, further blocks are dropped instead of
+/// queueing up more and more memory.
+const BLOCK_QUEUE_LEN: usize = 8;
+
+/// Configuration of [`start_live_analysis`].
+#[derive(Debug, Clone, Copy)]
+pub struct LiveConfig {
+    /// Number of samples of each frame. Must be a power of two.
+    pub frame_len: usize,
+    /// Number of new samples between two frames, e.g. `frame_len / 2` for
+    /// an overlap of 50%. Must not be zero.
+    pub hop_len: usize,
+    /// Optional window that is applied to each frame. See [`Window`].
+    pub window: Option<Window>,
+    /// Whether the magnitudes are divided by the coherent gain of the
+    /// window. See [`SpectrumAnalyzerBuilder::compensate_window_gain`].
+    pub compensate_window_gain: bool,
+    /// Frequency limit. See [`FrequencyLimit`].
+    pub frequency_limit: FrequencyLimit,
+}
+
+impl Default for LiveConfig {
+    /// Frames of 2048 samples with an overlap of 50%, no window,
+    /// and no frequency limit.
+    fn default() -> Self {
+        Self {
+            frame_len: 2048,
+            hop_len: 1024,
+            window: None,
+            compensate_window_gain: false,
+            frequency_limit: FrequencyLimit::All,
+        }
+    }
+}
+
+/// Possible errors of [`start_live_analysis`].
+#[derive(Debug)]
+pub enum LiveError {
+    /// The [`LiveConfig`] is invalid for the sampling rate of the device.
+    InvalidConfig(SpectrumAnalyzerError),
+    /// The default input config of the device can't be queried.
+    DeviceConfig(cpal::DefaultStreamConfigError),
+    /// The sample format of the device is not supported.
+    UnsupportedSampleFormat(SampleFormat),
+    /// The input stream can't be built.
+    BuildStream(cpal::BuildStreamError),
+    /// The input stream can't be started.
+    PlayStream(cpal::PlayStreamError),
+}
+
+impl Display for LiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidConfig(e) => write!(f, "invalid config: {e:?}"),
+            Self::DeviceConfig(e) => write!(f, "can't get device config: {e}"),
+            Self::UnsupportedSampleFormat(format) => {
+                write!(f, "unsupported sample format: {format}")
+            }
+            Self::BuildStream(e) => write!(f, "can't build input stream: {e}"),
+            Self::PlayStream(e) => write!(f, "can't start input stream: {e}"),
+        }
+    }
+}
+
+/// Errors that are passed to the callback of [`start_live_analysis`] while
+/// the analysis runs. The analysis continues afterwards.
+#[derive(Debug)]
+pub enum LiveAnalysisError {
+    /// A block of samples or a frame can't be analyzed, e.g. because it
+    /// contains NaN values.
+    Analysis(SpectrumAnalyzerError),
+    /// The analysis can't keep up with the device, hence, the given number
+    /// of blocks of samples was dropped. The analysis starts with a new frame
+    /// afterwards.
+    Overrun(usize),
+}
+
+impl Display for LiveAnalysisError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Analysis(e) => write!(f, "analysis failed: {e:?}"),
+            Self::Overrun(blocks) => write!(f, "dropped {blocks} blocks of samples"),
+        }
+    }
+}
+
+impl std::error::Error for LiveAnalysisError {}
+
+/// Starts the analysis of the default input config of `device`. The
+/// channels are mixed down to mono and `callback` is invoked with the
+/// spectrum of each new frame from a dedicated worker thread, so the audio
+/// thread of [`cpal`] is not blocked by the analysis. The audio thread only
+/// drops blocks of samples if the worker thread falls behind, which is
+/// reported as [`LiveAnalysisError::Overrun`]. Frames that can't be analyzed,
+/// for example because they contain NaN values, are reported as
+/// [`LiveAnalysisError::Analysis`].
+///
+/// The analysis runs until the returned [`Stream`] is dropped.
+///
+/// ## Parameters
+/// * `device` Input device, e.g. `cpal::default_host().default_input_device()`.
+/// * `config` See [`LiveConfig`].
+/// * `callback` Invoked with the spectrum of each frame or with an error.
+/// * `error_callback` Invoked when an error occurs on the stream.
+///
+/// ## Errors
+/// See [`LiveError`].
+pub fn start_live_analysis(
+    device: &cpal::Device,
+    config: LiveConfig,
+    callback: impl FnMut(Result<FrequencySpectrum, LiveAnalysisError>) + Send + 'static,
+    error_callback: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<Stream, LiveError> {
+    let supported_config = device
+        .default_input_config()
+        .map_err(LiveError::DeviceConfig)?;
+    let stream_config = supported_config.config();
+    build_analyzer(&config, stream_config.sample_rate.0).map_err(LiveError::InvalidConfig)?;
+
+    let stream = match supported_config.sample_format() {
+        SampleFormat::F32 => {
+            build_stream::<f32>(device, &stream_config, config, callback, error_callback)
+        }
+        SampleFormat::F64 => {
+            build_stream::<f64>(device, &stream_config, config, callback, error_callback)
+        }
+        SampleFormat::I16 => {
+            build_stream::<i16>(device, &stream_config, config, callback, error_callback)
+        }
+        SampleFormat::I32 => {
+            build_stream::<i32>(device, &stream_config, config, callback, error_callback)
+        }
+        SampleFormat::U8 => {
+            build_stream::<u8>(device, &stream_config, config, callback, error_callback)
+        }
+        format => return Err(LiveError::UnsupportedSampleFormat(format)),
+    }
+    .map_err(LiveError::BuildStream)?;
+    stream.play().map_err(LiveError::PlayStream)?;
+    Ok(stream)
+}
+
+/// Builds the analyzer for the config and the given sampling rate.
+fn build_analyzer(
+    config: &LiveConfig,
+    sampling_rate: u32,
+) -> Result<StreamingAnalyzer, SpectrumAnalyzerError> {
+    if config.hop_len == 0 {
+        return Err(SpectrumAnalyzerError::InvalidHopLength);
+    }
+    let mut builder = SpectrumAnalyzerBuilder::new(config.frame_len, sampling_rate)
+        .frequency_limit(config.frequency_limit)
+        .compensate_window_gain(config.compensate_window_gain);
+    if let Some(window) = config.window {
+        builder = builder.window(window);
+    }
+    Ok(StreamingAnalyzer::new(builder.build()?, config.hop_len))
+}
+
+/// Builds the input stream for the sample type `T`. The audio thread only
+/// mixes the samples down and sends them to a worker thread, which runs the
+/// analysis and the callback. The worker thread ends with the stream.
+fn build_stream<T: SizedSample + Sample>(
+    device: &cpal::Device,
+    stream_config: &StreamConfig,
+    config: LiveConfig,
+    callback: impl FnMut(Result<FrequencySpectrum, LiveAnalysisError>) + Send + 'static,
+    error_callback: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<Stream, cpal::BuildStreamError> {
+    let channels = usize::from(stream_config.channels);
+    let sampling_rate = stream_config.sample_rate.0;
+    let (sender, receiver) = mpsc::sync_channel(BLOCK_QUEUE_LEN);
+    // the worker thread returns the blocks, so that their allocations are
+    // reused
+    let (recycler, recycled) = mpsc::sync_channel(BLOCK_QUEUE_LEN);
+    let mut block_sender = BlockSender {
+        sender,
+        recycled,
+        spare: None,
+        dropped: 0,
+    };
+    let stream = device.build_input_stream(
+        stream_config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| block_sender.send(data, channels),
+        error_callback,
+        None,
+    )?;
+    thread::spawn(move || {
+        let analyzer = build_analyzer(&config, sampling_rate).expect("config was verified");
+        run_analysis(analyzer, &receiver, &recycler, callback);
+    });
+    Ok(stream)
+}
+
+/// A block of mono samples and the number of blocks that were dropped right
+/// before it.
+type Block = (Vec<f32>, usize);
+
+/// Audio thread side of the bounded queue to the worker thread. It never
+/// blocks and only allocates until enough blocks are in circulation.
+struct BlockSender {
+    sender: SyncSender<Block>,
+    recycled: Receiver<Vec<f32>>,
+    /// Block that couldn't be sent and is reused for the next samples.
+    spare: Option<Vec<f32>>,
+    /// Number of blocks that were dropped since the last sent block.
+    dropped: usize,
+}
+
+impl BlockSender {
+    /// Mixes the interleaved samples down and sends them to the worker
+    /// thread. Drops them if the queue is full.
+    fn send<T: Sample>(&mut self, data: &[T], channels: usize) {
+        let mut block = self
+            .spare
+            .take()
+            .or_else(|| self.recycled.try_recv().ok())
+            .unwrap_or_default();
+        downmix_into(data, channels, &mut block);
+        match self.sender.try_send((block, self.dropped)) {
+            Ok(()) => self.dropped = 0,
+            Err(TrySendError::Full((block, _))) => {
+                self.dropped += 1;
+                self.spare = Some(block);
+            }
+            // the worker thread ended
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+/// Worker thread side of the queue. Analyzes all blocks until the sender is
+/// dropped and returns them to the audio thread afterwards.
+fn run_analysis(
+    mut analyzer: StreamingAnalyzer,
+    receiver: &Receiver<Block>,
+    recycler: &SyncSender<Vec<f32>>,
+    mut callback: impl FnMut(Result<FrequencySpectrum, LiveAnalysisError>),
+) {
+    for (samples, dropped) in receiver {
+        if dropped > 0 {
+            // the frame must not span the gap
+            analyzer.reset();
+            callback(Err(LiveAnalysisError::Overrun(dropped)));
+        }
+        match analyzer.push(&samples) {
+            Ok(spectra) => spectra
+                .into_iter()
+                .for_each(|spectrum| callback(spectrum.map_err(LiveAnalysisError::Analysis))),
+            Err(e) => callback(Err(LiveAnalysisError::Analysis(e))),
+        }
+        // the audio thread allocates a new block if this one is lost
+        let _ = recycler.try_send(samples);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_analyzer() {
+        let analyzer = build_analyzer(&LiveConfig::default(), 44100).unwrap();
+        assert_eq!(analyzer.hop_len(), 1024);
+        assert_eq!(analyzer.analyzer().samples_len(), 2048);
+        let config = LiveConfig {
+            frame_len: 1000,
+            ..LiveConfig::default()
+        };
+        assert!(matches!(
+            build_analyzer(&config, 44100),
+            Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+        ));
+        let config = LiveConfig {
+            frequency_limit: FrequencyLimit::Max(30000.0),
+            ..LiveConfig::default()
+        };
+        assert!(build_analyzer(&config, 44100).is_err());
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_queue() {
+        let config = LiveConfig {
+            frame_len: 8,
+            hop_len: 8,
+            ..LiveConfig::default()
+        };
+        let (sender, receiver) = mpsc::sync_channel(2);
+        let (recycler, recycled) = mpsc::sync_channel(2);
+        let mut block_sender = BlockSender {
+            sender,
+            recycled,
+            spare: None,
+            dropped: 0,
+        };
+        // stereo, the third block doesn't fit into the queue anymore
+        block_sender.send(&[1.0_f32; 16], 2);
+        block_sender.send(&[f32::NAN; 16], 2);
+        block_sender.send(&[1.0_f32; 16], 2);
+        assert_eq!(block_sender.dropped, 1);
+
+        let mut results = Vec::new();
+        let worker = thread::spawn(move || {
+            let analyzer = build_analyzer(&config, 44100).unwrap();
+            run_analysis(analyzer, &receiver, &recycler, |result| {
+                results.push(result);
+            });
+            results
+        });
+        // retry until the worker thread made room in the queue
+        loop {
+            block_sender.send(&[1.0_f32; 16], 2);
+            if block_sender.dropped == 0 {
+                break;
+            }
+        }
+        drop(block_sender);
+        let results = worker.join().unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap().data().len(), 5);
+        assert!(matches!(
+            results[1],
+            Err(LiveAnalysisError::Analysis(
+                SpectrumAnalyzerError::NaNValuesNotSupported
+            ))
+        ));
+        assert!(matches!(results[2], Err(LiveAnalysisError::Overrun(_))));
+        assert!(results[3].is_ok());
+    }
+}
@@ -0,0 +1,258 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Audio measurements, such as the total harmonic distortion (THD) of a
+//! device under test, that is stimulated with a pure sine. See
+//! [`analyze_distortion`].
+//!
+//! All calculations are based on the power of the frequency values, hence,
+//! the spectrum must contain magnitudes (and not decibel values). As a window
+//! function spreads the power of a tone over several neighbouring
+//! frequencies, each tone is measured over a notch around its frequency. See
+//! [`DistortionConfig::notch_width`].
+
+use crate::frequency::Frequency;
+use crate::spectrum::FrequencySpectrum;
+
+/// Configuration for [`analyze_distortion`].
+#[derive(Debug, Copy, Clone)]
+pub struct DistortionConfig {
+    /// Number of harmonics that are considered for the THD, starting with
+    /// the second harmonic. Harmonics above the highest frequency of the
+    /// spectrum are ignored.
+    pub harmonics: usize,
+    /// Number of neighbouring frequencies on each side of a tone that belong
+    /// to the tone. `0` only works without a window function and if the
+    /// frequency of the tone exactly matches a frequency of the spectrum.
+    /// `3` is a good value for the Hann window.
+    pub notch_width: usize,
+}
+
+impl Default for DistortionConfig {
+    fn default() -> Self {
+        Self {
+            harmonics: 5,
+            notch_width: 3,
+        }
+    }
+}
+
+/// Result of [`analyze_distortion`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DistortionAnalysis {
+    /// Frequency of the fundamental.
+    pub fundamental: Frequency,
+    /// Total harmonic distortion as ratio, i.e. the RMS of the harmonics
+    /// relative to the fundamental. Multiply with 100 to get percent.
+    pub thd: f32,
+    /// Total harmonic distortion plus noise as ratio, i.e. the RMS of
+    /// everything except the fundamental (and the DC component) relative to
+    /// the fundamental.
+    pub thd_n: f32,
+    /// Signal to noise and distortion ratio in decibel. `None` if there is
+    /// no power besides the fundamental, i.e. if the ratio is infinite.
+    pub sinad: Option<f32>,
+}
+
+/// Measures the harmonic distortion of a sine in the spectrum.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum with magnitudes (not in decibel). It should cover
+///              all frequencies from `0 Hz`, as the power of all
+///              frequencies contributes to the noise.
+/// * `fundamental` Frequency of the stimulus. If `None`, the strongest
+///                 frequency of the spectrum is used.
+/// * `config` See [`DistortionConfig`].
+///
+/// ## Return value
+/// `None` if the power of the fundamental is zero.
+///
+/// ## Panics
+/// If `fundamental` is not inside the spectrum.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-4096")] {
+/// use spectrum_analyzer::measurement::{analyze_distortion, DistortionConfig};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// use spectrum_analyzer::windows::hann_window;
+/// # let samples = (0..4096)
+/// #    .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 48000.0))
+/// #    .collect::<Vec<_>>();
+/// let spectrum = samples_fft_to_spectrum(&hann_window(&samples), 48000, FrequencyLimit::All, None).unwrap();
+/// let analysis = analyze_distortion(&spectrum, Some(1000.0), &DistortionConfig::default()).unwrap();
+/// println!("THD: {}%", analysis.thd * 100.0);
+/// # }
+/// ```
+#[must_use]
+pub fn analyze_distortion(
+    spectrum: &FrequencySpectrum,
+    fundamental: Option<f32>,
+    config: &DistortionConfig,
+) -> Option<DistortionAnalysis> {
+    let fundamental = fundamental.map_or_else(|| spectrum.max().0, Frequency::from);
+    let fundamental_index = closest_index(spectrum, fundamental.val());
+
+    let fundamental_power = notch_power(spectrum, fundamental_index, config.notch_width);
+    if fundamental_power <= 0.0 {
+        return None;
+    }
+
+    let harmonics_power = (2..config.harmonics + 2)
+        .map(|h| fundamental.val() * h as f32)
+        .take_while(|&fr| fr <= spectrum.max_fr().val())
+        .map(|fr| notch_power(spectrum, closest_index(spectrum, fr), config.notch_width))
+        .sum::<f32>();
+
+    let total_power = total_power_without_dc(spectrum, config.notch_width);
+    let noise_and_distortion_power = (total_power - fundamental_power).max(0.0);
+
+    Some(DistortionAnalysis {
+        fundamental,
+        thd: libm::sqrtf(harmonics_power / fundamental_power),
+        thd_n: libm::sqrtf(noise_and_distortion_power / fundamental_power),
+        sinad: (noise_and_distortion_power > 0.0)
+            .then(|| 10.0 * libm::log10f(total_power / noise_and_distortion_power)),
+    })
+}
+
+/// Returns the index of the frequency in the spectrum that is the closest
+/// to `fr`.
+pub(crate) fn closest_index(spectrum: &FrequencySpectrum, fr: f32) -> usize {
+    let min_fr = spectrum.min_fr().val();
+    assert!(
+        fr >= min_fr && fr <= spectrum.max_fr().val(),
+        "Frequency {}Hz is out of bounds [{}; {}]!",
+        fr,
+        min_fr,
+        spectrum.max_fr()
+    );
+    let index = libm::roundf((fr - min_fr) / spectrum.frequency_resolution()) as usize;
+    index.min(spectrum.data().len() - 1)
+}
+
+/// Returns the power, i.e. the sum of the squared values, of the frequencies
+/// in the notch around `index`.
+pub(crate) fn notch_power(spectrum: &FrequencySpectrum, index: usize, notch_width: usize) -> f32 {
+    let data = spectrum.data();
+    let from = index.saturating_sub(notch_width);
+    let to = (index + notch_width).min(data.len() - 1);
+    data[from..=to]
+        .iter()
+        .map(|(_fr, val)| val.val() * val.val())
+        .sum()
+}
+
+/// Returns the power of all frequencies, except the DC component and its
+/// notch.
+pub(crate) fn total_power_without_dc(spectrum: &FrequencySpectrum, notch_width: usize) -> f32 {
+    let skip = if spectrum.dc_component().is_some() {
+        notch_width + 1
+    } else {
+        0
+    };
+    spectrum
+        .data()
+        .iter()
+        .skip(skip)
+        .map(|(_fr, val)| val.val() * val.val())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit, FrequencyValue};
+    use alloc::vec::Vec;
+    use core::f32::consts::PI;
+
+    /// Sine of 1000 Hz with two harmonics that exactly match frequencies of
+    /// the spectrum.
+    fn spectrum(noise: bool) -> FrequencySpectrum {
+        let samples = (0..1024)
+            .map(|i| {
+                let t = i as f32 / 8192.0;
+                let noise = if noise && i % 2 == 0 { 0.001 } else { 0.0 };
+                libm::sinf(2.0 * PI * 1000.0 * t)
+                    + 0.01 * libm::sinf(2.0 * PI * 2000.0 * t)
+                    + 0.01 * libm::sinf(2.0 * PI * 3000.0 * t)
+                    + noise
+            })
+            .collect::<Vec<_>>();
+        samples_fft_to_spectrum(&samples, 8192, FrequencyLimit::All, None).unwrap()
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_thd() {
+        let config = DistortionConfig {
+            harmonics: 5,
+            notch_width: 0,
+        };
+        let analysis = analyze_distortion(&spectrum(false), None, &config).unwrap();
+        assert_eq!(analysis.fundamental.val(), 1000.0);
+        float_cmp::assert_approx_eq!(f32, analysis.thd, libm::sqrtf(0.0002), epsilon = 0.0001);
+        float_cmp::assert_approx_eq!(f32, analysis.thd_n, analysis.thd, epsilon = 0.0001);
+        float_cmp::assert_approx_eq!(f32, analysis.sinad.unwrap(), 37.0, epsilon = 0.1);
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_thd_n() {
+        let config = DistortionConfig {
+            harmonics: 5,
+            notch_width: 0,
+        };
+        let analysis = analyze_distortion(&spectrum(true), Some(1000.0), &config).unwrap();
+        // noise at the Nyquist frequency
+        float_cmp::assert_approx_eq!(f32, analysis.thd, libm::sqrtf(0.0002), epsilon = 0.0001);
+        assert!(analysis.thd_n > analysis.thd);
+    }
+
+    #[test]
+    fn test_without_noise() {
+        let mut data = (0..8)
+            .map(|i| (Frequency::from(i as f32 * 100.0), FrequencyValue::from(0.0)))
+            .collect::<Vec<_>>();
+        data[2].1 = 1.0.into();
+        let mut working_buffer = data.clone();
+        let spectrum = FrequencySpectrum::new(data, 100.0, 16, &mut working_buffer);
+        let config = DistortionConfig {
+            harmonics: 5,
+            notch_width: 0,
+        };
+        let analysis = analyze_distortion(&spectrum, None, &config).unwrap();
+        assert_eq!(analysis.fundamental.val(), 200.0);
+        assert_eq!(analysis.thd, 0.0);
+        assert_eq!(analysis.thd_n, 0.0);
+        assert_eq!(analysis.sinad, None);
+    }
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    fn test_silence() {
+        let spectrum =
+            samples_fft_to_spectrum(&[0.0; 64], 8192, FrequencyLimit::All, None).unwrap();
+        assert!(analyze_distortion(&spectrum, None, &DistortionConfig::default()).is_none());
+    }
+}
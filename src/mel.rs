@@ -0,0 +1,343 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [mel scale] and a triangular mel filterbank that reduces a
+//! [`FrequencySpectrum`] to mel bands. This is the common front-end for
+//! speech processing and machine learning on audio. On top of that, this
+//! module calculates [MFCCs] (mel-frequency cepstral coefficients).
+//!
+//! [MFCCs]: https://en.wikipedia.org/wiki/Mel-frequency_cepstrum
+//!
+//! [mel scale]: https://en.wikipedia.org/wiki/Mel_scale
+
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// Number of mel bands used by [`mfcc`].
+pub const DEFAULT_MFCC_BANDS: usize = 26;
+
+/// Lower bound for band energies before the logarithm is applied in
+/// [`MelFilterbank::mfcc`]. Prevents `-inf` for silent bands.
+const MIN_LOG_ENERGY: f32 = 1e-10;
+
+/// Converts hertz to [mel](https://en.wikipedia.org/wiki/Mel_scale).
+#[must_use]
+pub fn hertz_to_mel(hz: f32) -> f32 {
+    assert!(hz >= 0.0);
+    2595.0 * libm::log10f(1.0 + (hz / 700.0))
+}
+
+/// Converts [mel](https://en.wikipedia.org/wiki/Mel_scale) to hertz.
+#[must_use]
+pub fn mel_to_hertz(mel: f32) -> f32 {
+    assert!(mel >= 0.0);
+    700.0 * (libm::powf(10.0, mel / 2595.0) - 1.0)
+}
+
+/// A single triangular filter of a [`MelFilterbank`]. The weight rises
+/// linearly from `0` at `lower` to `1` at `center` and falls linearly back
+/// to `0` at `upper`. All values are in Hertz.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TriangularFilter {
+    /// Lower edge of the filter.
+    pub lower: f32,
+    /// Center of the filter, i.e. where the weight is `1`.
+    pub center: f32,
+    /// Upper edge of the filter.
+    pub upper: f32,
+}
+
+impl TriangularFilter {
+    /// Returns the weight of the filter for the given frequency.
+    #[inline]
+    #[must_use]
+    pub fn weight(&self, fr: f32) -> f32 {
+        if fr <= self.lower || fr >= self.upper {
+            0.0
+        } else if fr <= self.center {
+            (fr - self.lower) / (self.center - self.lower)
+        } else {
+            (self.upper - fr) / (self.upper - self.center)
+        }
+    }
+
+    /// Applies the filter to the spectrum and returns the weighted sum of all
+    /// frequency values.
+    #[inline]
+    #[must_use]
+    pub fn apply(&self, spectrum: &FrequencySpectrum) -> f32 {
+        spectrum
+            .data()
+            .iter()
+            .map(|(fr, fr_val)| self.weight(fr.val()) * fr_val.val())
+            .sum()
+    }
+}
+
+/// Filterbank of triangular filters whose center frequencies are
+/// equidistant on the [mel scale]. Neighboring filters overlap by half.
+/// The filterbank doesn't depend on the FFT length or the sampling rate,
+/// hence, it can be created once and applied to many spectra.
+///
+/// The values of the spectrum are weighted and summed up as they are. For
+/// the classic mel spectrogram, create the spectrum with power values
+/// (squared magnitudes).
+///
+/// [mel scale]: https://en.wikipedia.org/wiki/Mel_scale
+#[derive(Debug, Clone)]
+pub struct MelFilterbank {
+    filters: Vec<TriangularFilter>,
+}
+
+impl MelFilterbank {
+    /// Creates a new filterbank with `n_bands` triangular filters between
+    /// `f_min` and `f_max`.
+    ///
+    /// ## Parameters
+    /// - `n_bands` Number of mel bands.
+    /// - `f_min` Lower edge of the first filter in Hertz.
+    /// - `f_max` Upper edge of the last filter in Hertz.
+    ///
+    /// ## Panics
+    /// If `n_bands` is zero or if not `0 <= f_min < f_max`.
+    #[must_use]
+    pub fn new(n_bands: usize, f_min: f32, f_max: f32) -> Self {
+        assert!(n_bands > 0, "At least one mel band is required!");
+        assert!(
+            0.0 <= f_min && f_min < f_max,
+            "Invalid frequency range [{}; {}]!",
+            f_min,
+            f_max
+        );
+
+        let mel_min = hertz_to_mel(f_min);
+        let mel_max = hertz_to_mel(f_max);
+        let mel_step = (mel_max - mel_min) / (n_bands + 1) as f32;
+        // n_bands + 2 edges: each filter spans from edge i to edge i + 2
+        let edges = (0..n_bands + 2)
+            .map(|i| mel_to_hertz(mel_min + i as f32 * mel_step))
+            .collect::<Vec<_>>();
+
+        let filters = edges
+            .windows(3)
+            .map(|edges| TriangularFilter {
+                lower: edges[0],
+                center: edges[1],
+                upper: edges[2],
+            })
+            .collect();
+
+        Self { filters }
+    }
+
+    /// Returns all filters of this filterbank, sorted from the lowest to the
+    /// highest band.
+    #[inline]
+    #[must_use]
+    pub fn filters(&self) -> &[TriangularFilter] {
+        &self.filters
+    }
+
+    /// Applies the filterbank to the spectrum.
+    ///
+    /// ## Return
+    /// `(center frequency, band value)`-pairs, sorted from the lowest to the
+    /// highest band.
+    #[must_use]
+    pub fn apply(&self, spectrum: &FrequencySpectrum) -> Vec<(Frequency, FrequencyValue)> {
+        self.filters
+            .iter()
+            .map(|filter| (filter.center.into(), filter.apply(spectrum).into()))
+            .collect()
+    }
+
+    /// Calculates the first `n_coeffs` mel-frequency cepstral coefficients
+    /// (MFCCs) of the spectrum. Therefore, the filterbank is applied to the
+    /// spectrum, the natural logarithm of each band energy is taken, and a
+    /// DCT (type II, orthonormal) is applied on the log energies.
+    ///
+    /// Usually, the spectrum should contain power values, e.g., obtained by
+    /// [`crate::psd::welch_psd`] or by squaring the magnitudes.
+    ///
+    /// ## Panics
+    /// If `n_coeffs` is bigger than the number of bands.
+    #[must_use]
+    pub fn mfcc(&self, spectrum: &FrequencySpectrum, n_coeffs: usize) -> Vec<f32> {
+        assert!(
+            n_coeffs <= self.filters.len(),
+            "Can't calculate {} coefficients from {} mel bands!",
+            n_coeffs,
+            self.filters.len()
+        );
+        let log_energies = self
+            .filters
+            .iter()
+            .map(|filter| libm::logf(filter.apply(spectrum).max(MIN_LOG_ENERGY)))
+            .collect::<Vec<_>>();
+        dct_ii(&log_energies, n_coeffs)
+    }
+}
+
+/// Calculates the first `n_coeffs` mel-frequency cepstral coefficients
+/// (MFCCs) of the spectrum using a filterbank of [`DEFAULT_MFCC_BANDS`] mel
+/// bands that covers the whole spectrum. Use [`MelFilterbank::mfcc`] for
+/// more control.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// use spectrum_analyzer::mel::mfcc;
+/// let samples = vec![0.0; 512];
+/// let spectrum = samples_fft_to_spectrum(&samples, 16000, FrequencyLimit::All, None).unwrap();
+/// let coefficients = mfcc(&spectrum, 13);
+/// assert_eq!(coefficients.len(), 13);
+/// # }
+/// ```
+///
+/// ## Panics
+/// If `n_coeffs` is bigger than [`DEFAULT_MFCC_BANDS`].
+#[must_use]
+pub fn mfcc(spectrum: &FrequencySpectrum, n_coeffs: usize) -> Vec<f32> {
+    MelFilterbank::new(
+        DEFAULT_MFCC_BANDS,
+        spectrum.min_fr().val(),
+        spectrum.max_fr().val(),
+    )
+    .mfcc(spectrum, n_coeffs)
+}
+
+/// Calculates the first `n_coeffs` coefficients of the orthonormal
+/// discrete cosine transform of type II.
+///
+/// More information: <https://en.wikipedia.org/wiki/Discrete_cosine_transform#DCT-II>
+fn dct_ii(input: &[f32], n_coeffs: usize) -> Vec<f32> {
+    let len = input.len() as f32;
+    (0..n_coeffs)
+        .map(|k| {
+            let sum = input
+                .iter()
+                .enumerate()
+                .map(|(n, x)| x * libm::cosf(PI * k as f32 * (n as f32 + 0.5) / len))
+                .sum::<f32>();
+            let normalization = if k == 0 {
+                libm::sqrtf(1.0 / len)
+            } else {
+                libm::sqrtf(2.0 / len)
+            };
+            sum * normalization
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mel() {
+        float_cmp::assert_approx_eq!(f32, hertz_to_mel(0.0), 0.0, epsilon = 0.1);
+        float_cmp::assert_approx_eq!(f32, hertz_to_mel(500.0), 607.4, epsilon = 0.1);
+        float_cmp::assert_approx_eq!(f32, hertz_to_mel(5000.0), 2363.5, epsilon = 0.1);
+
+        let conv = |hz: f32| mel_to_hertz(hertz_to_mel(hz));
+
+        float_cmp::assert_approx_eq!(f32, conv(0.0), 0.0, epsilon = 0.1);
+        float_cmp::assert_approx_eq!(f32, conv(1000.0), 1000.0, epsilon = 0.1);
+        float_cmp::assert_approx_eq!(f32, conv(10000.0), 10000.0, epsilon = 0.1);
+    }
+
+    #[test]
+    fn test_triangular_filter() {
+        let filter = TriangularFilter {
+            lower: 100.0,
+            center: 200.0,
+            upper: 400.0,
+        };
+        assert_eq!(filter.weight(50.0), 0.0);
+        assert_eq!(filter.weight(150.0), 0.5);
+        assert_eq!(filter.weight(200.0), 1.0);
+        assert_eq!(filter.weight(300.0), 0.5);
+        assert_eq!(filter.weight(400.0), 0.0);
+    }
+
+    #[test]
+    fn test_filterbank() {
+        let filterbank = MelFilterbank::new(10, 0.0, 8000.0);
+        let filters = filterbank.filters();
+        assert_eq!(filters.len(), 10);
+        float_cmp::assert_approx_eq!(f32, filters[0].lower, 0.0, epsilon = 0.01);
+        float_cmp::assert_approx_eq!(f32, filters[9].upper, 8000.0, epsilon = 0.5);
+        for pair in filters.windows(2) {
+            // neighbors overlap by half
+            assert_eq!(pair[0].center, pair[1].lower);
+            assert_eq!(pair[0].upper, pair[1].center);
+            // bands get wider for higher frequencies
+            assert!(pair[1].upper - pair[1].lower > pair[0].upper - pair[0].lower);
+        }
+    }
+
+    #[test]
+    fn test_dct_ii() {
+        // a constant input only has a DC coefficient
+        let coefficients = dct_ii(&[2.0; 8], 4);
+        float_cmp::assert_approx_eq!(f32, coefficients[0], 2.0 * libm::sqrtf(8.0), ulps = 4);
+        for c in &coefficients[1..] {
+            float_cmp::assert_approx_eq!(f32, *c, 0.0, epsilon = 0.00001);
+        }
+        // orthonormal: energy is preserved
+        let input = [1.0, -2.0, 3.0, 0.5];
+        let energy_in = input.iter().map(|x| x * x).sum::<f32>();
+        let energy_out = dct_ii(&input, 4).iter().map(|x| x * x).sum::<f32>();
+        float_cmp::assert_approx_eq!(f32, energy_in, energy_out, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_mfcc() {
+        let mut data = (0..=80)
+            .map(|i| ((i as f32 * 100.0).into(), 1.0.into()))
+            .collect::<Vec<(Frequency, FrequencyValue)>>();
+        let spectrum = FrequencySpectrum::new(data.clone(), 100.0, 160, &mut data);
+        let coefficients = mfcc(&spectrum, 13);
+        assert_eq!(coefficients.len(), 13);
+        assert!(coefficients.iter().all(|c| c.is_finite()));
+    }
+
+    #[test]
+    fn test_apply() {
+        // 100 Hz resolution, constant value of 1.0
+        let mut data = (0..=80)
+            .map(|i| ((i as f32 * 100.0).into(), 1.0.into()))
+            .collect::<Vec<(Frequency, FrequencyValue)>>();
+        let spectrum = FrequencySpectrum::new(data.clone(), 100.0, 160, &mut data);
+        let bands = spectrum.to_mel_bands(4, 0.0, 8000.0);
+        assert_eq!(bands.len(), 4);
+        // wider filters cover more bins
+        for pair in bands.windows(2) {
+            assert!(pair[0].0 < pair[1].0);
+            assert!(pair[0].1 < pair[1].1);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Analysis of interleaved multi-channel samples, e.g. stereo audio in the
+//! layout `[l0, r0, l1, r1, ...]`.
+//!
+//! [`samples_fft_to_channel_spectra`] calculates one spectrum per channel
+//! and [`samples_fft_to_mixed_spectrum`] calculates a single spectrum of a
+//! combination of the channels, see [`ChannelMix`]. To average the spectra
+//! of all channels instead, pass the per-channel spectra to a
+//! [`crate::averaging::SpectrumAverager`].
+
+use crate::error::SpectrumAnalyzerError;
+use crate::sample::Sample;
+use crate::scaling::SpectrumScalingFunction;
+use crate::{samples_fft_to_spectrum, FrequencyLimit, FrequencySpectrum};
+use alloc::vec::Vec;
+
+/// Combination of the channels for [`samples_fft_to_mixed_spectrum`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChannelMix {
+    /// The average of all channels.
+    Mono,
+    /// The mid signal `(L + R) / 2` of a stereo signal. Equivalent to
+    /// [`Self::Mono`] for two channels.
+    Mid,
+    /// The side signal `(L - R) / 2` of a stereo signal, i.e. the
+    /// difference between both channels.
+    Side,
+}
+
+impl ChannelMix {
+    /// Applies the mix to the interleaved samples.
+    ///
+    /// ## Panics
+    /// If `channels` is zero, or if the mix is [`Self::Mid`] or
+    /// [`Self::Side`] and `channels` is not two.
+    #[must_use]
+    pub fn apply<S: Sample>(self, samples: &[S], channels: usize) -> Vec<f32> {
+        match self {
+            Self::Mono => downmix(samples, channels),
+            Self::Mid | Self::Side => {
+                assert_eq!(channels, 2, "Mid/side needs a stereo signal!");
+                let sign = if self == Self::Mid { 1.0 } else { -1.0 };
+                samples
+                    .chunks_exact(2)
+                    .map(|frame| (frame[0].to_f32() + sign * frame[1].to_f32()) / 2.0)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Splits interleaved samples into one vector per channel. Incomplete frames
+/// at the end are ignored.
+///
+/// ## Panics
+/// If `channels` is zero.
+#[must_use]
+pub fn deinterleave<S: Sample>(samples: &[S], channels: usize) -> Vec<Vec<f32>> {
+    assert_ne!(channels, 0, "There must be at least one channel!");
+    (0..channels)
+        .map(|channel| {
+            samples
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .take(samples.len() / channels)
+                .map(|x| x.to_f32())
+                .collect()
+        })
+        .collect()
+}
+
+/// Mixes interleaved samples down to mono by averaging all channels.
+/// Incomplete frames at the end are ignored.
+///
+/// ## Panics
+/// If `channels` is zero.
+#[must_use]
+pub fn downmix<S: Sample>(samples: &[S], channels: usize) -> Vec<f32> {
+    let mut mono = Vec::with_capacity(samples.len() / channels.max(1));
+    downmix_into(samples, channels, &mut mono);
+    mono
+}
+
+/// Like [`downmix`] but writes the mono samples into `mono`, which is cleared
+/// first. This reuses the allocation of `mono`, e.g. in an audio callback.
+///
+/// ## Panics
+/// If `channels` is zero.
+pub fn downmix_into<S: Sample>(samples: &[S], channels: usize, mono: &mut Vec<f32>) {
+    assert_ne!(channels, 0, "There must be at least one channel!");
+    mono.clear();
+    mono.extend(
+        samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().map(|x| x.to_f32()).sum::<f32>() / channels as f32),
+    );
+}
+
+/// Calculates the spectrum of each channel of the interleaved samples. See
+/// [`crate::samples_fft_to_spectrum`].
+///
+/// ## Parameters
+/// * `samples` Interleaved samples. The number of samples per channel must
+///             be a power of two.
+/// * `channels` Number of channels, e.g. `2` for stereo.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+/// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
+///
+/// ## Return value
+/// One spectrum per channel, in the order of the channels.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::multichannel::samples_fft_to_channel_spectra;
+/// use spectrum_analyzer::FrequencyLimit;
+/// // stereo: [l0, r0, l1, r1, ...]
+/// let samples = [0.0, 1.0, 1.0, 0.0, 0.0, -1.0, -1.0, 0.0];
+/// let spectra = samples_fft_to_channel_spectra(&samples, 2, 44100, FrequencyLimit::All, None)
+///     .unwrap();
+/// assert_eq!(spectra.len(), 2);
+/// ```
+///
+/// ## Errors
+/// [`SpectrumAnalyzerError::SamplesLengthMismatch`] if the number of samples
+/// is not a multiple of `channels`. Otherwise, see
+/// [`crate::samples_fft_to_spectrum`].
+///
+/// ## Panics
+/// If `channels` is zero.
+pub fn samples_fft_to_channel_spectra<S: Sample>(
+    samples: &[S],
+    channels: usize,
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<Vec<FrequencySpectrum>, SpectrumAnalyzerError> {
+    validate_channels(samples.len(), channels)?;
+    deinterleave(samples, channels)
+        .iter()
+        .map(|channel| samples_fft_to_spectrum(channel, sampling_rate, frequency_limit, scaling_fn))
+        .collect()
+}
+
+/// Calculates the spectrum of a combination of the channels of the
+/// interleaved samples, e.g. of the mono downmix or of the side signal of a
+/// stereo signal. See [`ChannelMix`] and [`crate::samples_fft_to_spectrum`].
+///
+/// ## Parameters
+/// * `samples` Interleaved samples. The number of samples per channel must
+///             be a power of two.
+/// * `channels` Number of channels, e.g. `2` for stereo.
+/// * `mix` Combination of the channels. See [`ChannelMix`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+/// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
+///
+/// ## Errors
+/// See [`samples_fft_to_channel_spectra`].
+///
+/// ## Panics
+/// See [`ChannelMix::apply`].
+pub fn samples_fft_to_mixed_spectrum<S: Sample>(
+    samples: &[S],
+    channels: usize,
+    mix: ChannelMix,
+    sampling_rate: u32,
+    frequency_limit: FrequencyLimit,
+    scaling_fn: Option<&SpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    validate_channels(samples.len(), channels)?;
+    samples_fft_to_spectrum(
+        &mix.apply(samples, channels),
+        sampling_rate,
+        frequency_limit,
+        scaling_fn,
+    )
+}
+
+/// Checks that the samples consist of complete frames of all channels.
+fn validate_channels(samples_len: usize, channels: usize) -> Result<(), SpectrumAnalyzerError> {
+    assert_ne!(channels, 0, "There must be at least one channel!");
+    if samples_len % channels != 0 {
+        return Err(SpectrumAnalyzerError::SamplesLengthMismatch(
+            samples_len - samples_len % channels,
+            samples_len,
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    /// Stereo signal with 1000 Hz on both channels and additional 3000 Hz
+    /// only on the left channel.
+    fn stereo() -> Vec<f32> {
+        (0..1024)
+            .flat_map(|i| {
+                let t = i as f32 / 16000.0;
+                let both = libm::sinf(2.0 * PI * 1000.0 * t);
+                let left_only = libm::sinf(2.0 * PI * 3000.0 * t);
+                [both + left_only, both]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_deinterleave_and_downmix() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        assert_eq!(
+            deinterleave(&samples, 2),
+            vec![vec![1.0, 3.0, 5.0], vec![2.0, 4.0, 6.0]]
+        );
+        assert_eq!(downmix(&samples, 2), vec![1.5, 3.5, 5.5]);
+        let mut mono = vec![9.0; 8];
+        downmix_into(&samples, 2, &mut mono);
+        assert_eq!(mono, vec![1.5, 3.5, 5.5]);
+        assert_eq!(ChannelMix::Side.apply(&samples, 2), vec![-0.5; 3]);
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_channel_spectra() {
+        let spectra =
+            samples_fft_to_channel_spectra(&stereo(), 2, 16000, FrequencyLimit::All, None).unwrap();
+        let at = |spectrum: &FrequencySpectrum, fr: f32| spectrum.freq_val_exact(fr).val();
+        assert!(at(&spectra[0], 3000.0) > 100.0 * at(&spectra[1], 3000.0));
+        assert!((at(&spectra[0], 1000.0) - at(&spectra[1], 1000.0)).abs() < 0.01);
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_mid_side() {
+        let samples = stereo();
+        let mid = samples_fft_to_mixed_spectrum(
+            &samples,
+            2,
+            ChannelMix::Mid,
+            16000,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+        let side = samples_fft_to_mixed_spectrum(
+            &samples,
+            2,
+            ChannelMix::Side,
+            16000,
+            FrequencyLimit::All,
+            None,
+        )
+        .unwrap();
+        assert_eq!(mid.max().0.val(), 1000.0);
+        // the common 1000 Hz cancel out in the side signal
+        assert_eq!(side.max().0.val(), 3000.0);
+        assert!(side.freq_val_exact(1000.0).val() < 0.01);
+
+        assert!(matches!(
+            samples_fft_to_mixed_spectrum(
+                &samples[1..],
+                2,
+                ChannelMix::Mono,
+                16000,
+                FrequencyLimit::All,
+                None
+            ),
+            Err(SpectrumAnalyzerError::SamplesLengthMismatch(2046, 2047))
+        ));
+    }
+}
@@ -0,0 +1,220 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Spectrum analysis without any heap allocation, backed by fixed-size arrays
+//! whose sizes are known at compile time. See
+//! [`samples_fft_to_static_spectrum`]. Use
+//! [`crate::windows::Window::apply_in_place`] to apply a window function on
+//! the array of samples beforehand, which doesn't allocate either.
+//!
+//! This path doesn't allocate at runtime, but the crate still links the
+//! `alloc` crate. Hence, targets still need a global allocator, even if it is
+//! never used, e.g. one that always fails. Targets without any global
+//! allocator are not supported.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::FftImpl;
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::{
+    complex_to_magnitude, fft_calc_frequency_resolution, validate_fft_len, validate_samples,
+};
+
+/// Compile-time check that `M` is `N / 2 + 1`. Referencing [`Self::OK`] fails
+/// to compile for other values.
+struct AssertSpectrumLen<const N: usize, const M: usize>;
+
+impl<const N: usize, const M: usize> AssertSpectrumLen<N, M> {
+    const OK: () = assert!(M == N / 2 + 1, "M must be N / 2 + 1!");
+}
+
+/// Like [`crate::FrequencySpectrum`] but backed by an array of `M`
+/// (frequency, frequency value)-pairs, where `M` is `N / 2 + 1` for `N`
+/// samples. It covers all frequencies from the DC component to the Nyquist
+/// frequency.
+#[derive(Debug, Copy, Clone)]
+pub struct StaticFrequencySpectrum<const M: usize> {
+    data: [(Frequency, FrequencyValue); M],
+    frequency_resolution: f32,
+    samples_len: u32,
+}
+
+impl<const M: usize> StaticFrequencySpectrum<M> {
+    /// Returns the underlying data, sorted from the lowest to the highest
+    /// frequency.
+    #[inline]
+    #[must_use]
+    pub const fn data(&self) -> &[(Frequency, FrequencyValue); M] {
+        &self.data
+    }
+
+    /// Returns the frequency resolution of this spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn frequency_resolution(&self) -> f32 {
+        self.frequency_resolution
+    }
+
+    /// Returns the number of samples used to obtain this spectrum.
+    #[inline]
+    #[must_use]
+    pub const fn samples_len(&self) -> u32 {
+        self.samples_len
+    }
+
+    /// Returns the (frequency, frequency value)-pair with the maximum
+    /// frequency value. If multiple pairs share the maximum, the one with
+    /// the highest frequency is returned.
+    #[must_use]
+    pub fn max(&self) -> (Frequency, FrequencyValue) {
+        *self
+            .data
+            .iter()
+            .max_by_key(|(_fr, val)| *val)
+            .expect("spectrum is never empty")
+    }
+
+    /// Returns the (frequency, frequency value)-pair with the minimum
+    /// frequency value. If multiple pairs share the minimum, the one with
+    /// the lowest frequency is returned.
+    #[must_use]
+    pub fn min(&self) -> (Frequency, FrequencyValue) {
+        *self
+            .data
+            .iter()
+            .min_by_key(|(_fr, val)| *val)
+            .expect("spectrum is never empty")
+    }
+
+    /// Returns the average frequency value.
+    #[must_use]
+    pub fn average(&self) -> FrequencyValue {
+        let sum = self.data.iter().map(|(_fr, val)| val.val()).sum::<f32>();
+        (sum / M as f32).into()
+    }
+}
+
+/// Like [`crate::samples_fft_to_spectrum`] but without any heap allocation.
+/// The FFT is calculated in-place on a copy of the samples on the stack.
+/// Apply a window function on the samples before, if desired, e.g. with
+/// [`crate::windows::Window::apply_in_place`]. Frequency limits and scaling
+/// functions are not supported.
+///
+/// ## Parameters
+/// * `samples` Array of `N` samples. `N` must be a power of two and at most
+///             [`crate::MAX_SAMPLES_LEN`].
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+///
+/// ## Return value
+/// Spectrum with `M = N / 2 + 1` frequencies.
+///
+/// `M` is checked at compile time. Other values don't compile:
+/// ```rust,compile_fail
+/// use spectrum_analyzer::no_alloc::{samples_fft_to_static_spectrum, StaticFrequencySpectrum};
+/// let spectrum: StaticFrequencySpectrum<4> = samples_fft_to_static_spectrum(&[0.0; 8], 44100).unwrap();
+/// ```
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-16")] {
+/// use spectrum_analyzer::no_alloc::{samples_fft_to_static_spectrum, StaticFrequencySpectrum};
+/// let samples = [0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+/// let spectrum: StaticFrequencySpectrum<5> = samples_fft_to_static_spectrum(&samples, 44100).unwrap();
+/// assert_eq!(spectrum.data().len(), 5);
+/// assert_eq!(spectrum.frequency_resolution(), 5512.5);
+/// # }
+/// ```
+///
+/// ## Errors
+/// See [`crate::samples_fft_to_spectrum`].
+pub fn samples_fft_to_static_spectrum<const N: usize, const M: usize>(
+    samples: &[f32; N],
+    sampling_rate: u32,
+) -> Result<StaticFrequencySpectrum<M>, SpectrumAnalyzerError> {
+    #[allow(clippy::let_unit_value)]
+    let () = AssertSpectrumLen::<N, M>::OK;
+    validate_samples(samples)?;
+    validate_fft_len(N)?;
+
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, N as u32);
+    let mut buffer = *samples;
+    let fft_res = FftImpl::calc_in_place(&mut buffer);
+    // the real value of the Nyquist frequency is packed inside the DC
+    // component
+    let nyquist_fr_val = fft_res[0].im;
+    fft_res[0].im = 0.0;
+
+    let mut data = [(Frequency::default(), FrequencyValue::default()); M];
+    for (index, pair) in data.iter_mut().enumerate() {
+        let magnitude = if index == N / 2 {
+            libm::fabsf(nyquist_fr_val)
+        } else {
+            complex_to_magnitude(&fft_res[index])
+        };
+        *pair = (
+            (index as f32 * frequency_resolution).into(),
+            magnitude.into(),
+        );
+    }
+
+    Ok(StaticFrequencySpectrum {
+        data,
+        frequency_resolution,
+        samples_len: N as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    fn test_same_result_as_dynamic_spectrum() {
+        let mut samples = [0.0; 64];
+        for (i, sample) in samples.iter_mut().enumerate() {
+            *sample = libm::sinf(i as f32 * 0.7) + 0.5;
+        }
+        let spectrum: StaticFrequencySpectrum<33> =
+            samples_fft_to_static_spectrum(&samples, 64).unwrap();
+        let expected = samples_fft_to_spectrum(&samples, 64, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectrum.data().as_slice(), expected.data());
+        assert_eq!(spectrum.max(), expected.max());
+        assert_eq!(spectrum.frequency_resolution(), 1.0);
+        float_cmp::assert_approx_eq!(
+            f32,
+            spectrum.average().val(),
+            expected.average().val(),
+            ulps = 4
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let err = samples_fft_to_static_spectrum::<6, 4>(&[0.0; 6], 44100).unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Mapping between frequencies and musical notes of the twelve-tone equal
+//! temperament, e.g. for tuner applications. Notes are numbered in
+//! [scientific pitch notation], i.e. `A4` is the reference pitch (usually
+//! 440 Hz) and `C4` is the middle C. See [`FrequencyNote`] for the mapping
+//! of a [`Frequency`].
+//!
+//! [scientific pitch notation]: https://en.wikipedia.org/wiki/Scientific_pitch_notation
+
+use crate::frequency::Frequency;
+use crate::spectrum::FrequencySpectrum;
+use core::fmt::{Display, Formatter, Result};
+
+/// Standard concert pitch of `A4` in Hertz.
+pub const DEFAULT_REFERENCE_A4: f32 = 440.0;
+
+/// MIDI note number of `A4`.
+const MIDI_A4: i32 = 69;
+
+/// Name of a note within an octave. Only sharps are used, i.e. `D♭` is
+/// represented as [`NoteName::CSharp`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NoteName {
+    /// C
+    C,
+    /// C♯ / D♭
+    CSharp,
+    /// D
+    D,
+    /// D♯ / E♭
+    DSharp,
+    /// E
+    E,
+    /// F
+    F,
+    /// F♯ / G♭
+    FSharp,
+    /// G
+    G,
+    /// G♯ / A♭
+    GSharp,
+    /// A
+    A,
+    /// A♯ / B♭
+    ASharp,
+    /// B
+    B,
+}
+
+impl NoteName {
+    const ALL: [Self; 12] = [
+        Self::C,
+        Self::CSharp,
+        Self::D,
+        Self::DSharp,
+        Self::E,
+        Self::F,
+        Self::FSharp,
+        Self::G,
+        Self::GSharp,
+        Self::A,
+        Self::ASharp,
+        Self::B,
+    ];
+
+    /// Number of semitones above `C` in the same octave.
+    #[must_use]
+    pub const fn semitone(self) -> i32 {
+        self as i32
+    }
+}
+
+impl Display for NoteName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let name = match self {
+            Self::C => "C",
+            Self::CSharp => "C#",
+            Self::D => "D",
+            Self::DSharp => "D#",
+            Self::E => "E",
+            Self::F => "F",
+            Self::FSharp => "F#",
+            Self::G => "G",
+            Self::GSharp => "G#",
+            Self::A => "A",
+            Self::ASharp => "A#",
+            Self::B => "B",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The nearest note of a frequency together with the deviation from the
+/// exact pitch of that note.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Note {
+    /// Name of the note.
+    pub name: NoteName,
+    /// Octave in scientific pitch notation. `A4` has octave `4`.
+    pub octave: i32,
+    /// Deviation from the exact pitch of the note in cents, i.e. hundredths
+    /// of a semitone. In range `[-50, 50]`. Positive values mean that the
+    /// frequency is too high (sharp).
+    pub cents: f32,
+}
+
+impl Note {
+    /// Returns the exact frequency of the note, ignoring [`Note::cents`].
+    ///
+    /// ## Parameters
+    /// * `reference_a4` Frequency of `A4` in Hertz, see [`DEFAULT_REFERENCE_A4`].
+    #[must_use]
+    pub fn frequency(&self, reference_a4: f32) -> Frequency {
+        Frequency::from_note(self.name, self.octave, reference_a4)
+    }
+}
+
+impl Display for Note {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}{} ({:+.1} ct)", self.name, self.octave, self.cents)
+    }
+}
+
+/// Extension trait to map a [`Frequency`] to the nearest note and a note back
+/// to its frequency.
+pub trait FrequencyNote {
+    /// Maps the frequency to the nearest note of the twelve-tone equal
+    /// temperament.
+    ///
+    /// ## Parameters
+    /// * `reference_a4` Frequency of `A4` in Hertz, see [`DEFAULT_REFERENCE_A4`].
+    ///
+    /// ## Panics
+    /// If the frequency or `reference_a4` is not bigger than zero.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::Frequency;
+    /// use spectrum_analyzer::note::{FrequencyNote, NoteName, DEFAULT_REFERENCE_A4};
+    /// let note = Frequency::from(261.63).to_note(DEFAULT_REFERENCE_A4);
+    /// assert_eq!(NoteName::C, note.name);
+    /// assert_eq!(4, note.octave);
+    /// ```
+    #[must_use]
+    fn to_note(self, reference_a4: f32) -> Note;
+
+    /// Returns the exact frequency of a note of the twelve-tone equal
+    /// temperament. This is the reverse of [`FrequencyNote::to_note`].
+    ///
+    /// ## Parameters
+    /// * `name` Name of the note.
+    /// * `octave` Octave in scientific pitch notation.
+    /// * `reference_a4` Frequency of `A4` in Hertz, see [`DEFAULT_REFERENCE_A4`].
+    #[must_use]
+    fn from_note(name: NoteName, octave: i32, reference_a4: f32) -> Self;
+}
+
+impl FrequencyNote for Frequency {
+    fn to_note(self, reference_a4: f32) -> Note {
+        assert!(self.val() > 0.0, "Frequency must be bigger than 0 Hz!");
+        assert!(reference_a4 > 0.0, "Reference must be bigger than 0 Hz!");
+        let midi = MIDI_A4 as f32 + 12.0 * libm::log2f(self.val() / reference_a4);
+        let nearest = libm::roundf(midi);
+        let nearest_int = nearest as i32;
+        Note {
+            name: NoteName::ALL[nearest_int.rem_euclid(12) as usize],
+            octave: nearest_int.div_euclid(12) - 1,
+            cents: (midi - nearest) * 100.0,
+        }
+    }
+
+    fn from_note(name: NoteName, octave: i32, reference_a4: f32) -> Self {
+        let midi = (octave + 1) * 12 + name.semitone();
+        let fr = reference_a4 * libm::powf(2.0, (midi - MIDI_A4) as f32 / 12.0);
+        fr.into()
+    }
+}
+
+/// Maps the strongest frequency of the spectrum (see
+/// [`FrequencySpectrum::max`]) to the nearest note.
+///
+/// Note that the strongest frequency is not necessarily the fundamental,
+/// see [`crate::pitch`] for better pitch estimates.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum, e.g. of an instrument.
+/// * `reference_a4` Frequency of `A4` in Hertz, see [`DEFAULT_REFERENCE_A4`].
+///
+/// ## Return value
+/// `None` if the strongest frequency is `0 Hz`, e.g. for silence.
+#[must_use]
+pub fn tuner(spectrum: &FrequencySpectrum, reference_a4: f32) -> Option<Note> {
+    let (fr, _val) = spectrum.max();
+    if fr.val() > 0.0 {
+        Some(fr.to_note(reference_a4))
+    } else {
+        None
+    }
+}
+
+/// Implementation of [`FrequencySpectrum::to_chroma`].
+pub(crate) fn chroma(spectrum: &FrequencySpectrum, reference_a4: f32) -> [f32; 12] {
+    assert!(reference_a4 > 0.0, "Reference must be bigger than 0 Hz!");
+    let mut chroma = [0.0; 12];
+    for (fr, fr_val) in spectrum.data() {
+        if fr.val() <= 0.0 {
+            continue;
+        }
+        let midi = MIDI_A4 as f32 + 12.0 * libm::log2f(fr.val() / reference_a4);
+        let pitch_class = (libm::roundf(midi) as i32).rem_euclid(12) as usize;
+        chroma[pitch_class] += fr_val.val() * fr_val.val();
+    }
+    chroma
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn test_chroma() {
+        use crate::frequency::FrequencyValue;
+        use alloc::vec::Vec;
+        // A4, E5, and A5 (C#6 is above the spectrum)
+        let data = [(0.0, 5.0), (440.0, 1.0), (659.26, 2.0), (880.0, 1.0)]
+            .iter()
+            .map(|&(fr, val)| (Frequency::from(fr), FrequencyValue::from(val)))
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        let spectrum = FrequencySpectrum::new(data, 1.0, 2048, &mut working_buffer);
+        let chroma = spectrum.to_chroma(DEFAULT_REFERENCE_A4);
+        assert_eq!(chroma[NoteName::A.semitone() as usize], 2.0);
+        assert_eq!(chroma[NoteName::E.semitone() as usize], 4.0);
+        assert_eq!(chroma.iter().sum::<f32>(), 6.0);
+
+        // a reference of 466.16 Hz shifts everything by a semitone down
+        let chroma = spectrum.to_chroma(466.16);
+        assert_eq!(chroma[NoteName::GSharp.semitone() as usize], 2.0);
+    }
+
+    #[test]
+    fn test_to_note() {
+        let note = Frequency::from(440.0).to_note(DEFAULT_REFERENCE_A4);
+        assert_eq!(NoteName::A, note.name);
+        assert_eq!(4, note.octave);
+        assert!(approx_eq!(f32, 0.0, note.cents, epsilon = 0.01));
+
+        let note = Frequency::from(82.41).to_note(DEFAULT_REFERENCE_A4);
+        assert_eq!(NoteName::E, note.name);
+        assert_eq!(2, note.octave);
+
+        // B3 is right below C4
+        let note = Frequency::from(246.94).to_note(DEFAULT_REFERENCE_A4);
+        assert_eq!(NoteName::B, note.name);
+        assert_eq!(3, note.octave);
+
+        // a quarter tone above A4
+        let note = Frequency::from(452.89).to_note(DEFAULT_REFERENCE_A4);
+        assert_eq!(NoteName::A, note.name);
+        assert!(approx_eq!(f32, 50.0, note.cents, epsilon = 0.1));
+
+        let note = Frequency::from(432.0).to_note(432.0);
+        assert_eq!(NoteName::A, note.name);
+        assert_eq!("A4 (+0.0 ct)", note.to_string());
+    }
+
+    #[test]
+    fn test_from_note() {
+        let fr = Frequency::from_note(NoteName::A, 4, DEFAULT_REFERENCE_A4);
+        assert!(approx_eq!(f32, 440.0, fr.val(), epsilon = 0.001));
+        let fr = Frequency::from_note(NoteName::C, 4, DEFAULT_REFERENCE_A4);
+        assert!(approx_eq!(f32, 261.6256, fr.val(), epsilon = 0.01));
+        let fr = Frequency::from_note(NoteName::A, 0, DEFAULT_REFERENCE_A4);
+        assert!(approx_eq!(f32, 27.5, fr.val(), epsilon = 0.001));
+
+        for octave in -1..9 {
+            for name in NoteName::ALL {
+                let note = Frequency::from_note(name, octave, DEFAULT_REFERENCE_A4)
+                    .to_note(DEFAULT_REFERENCE_A4);
+                assert_eq!(name, note.name);
+                assert_eq!(octave, note.octave);
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Onset detection across consecutive spectra, e.g. to detect beats or notes
+//! in a stream of audio. See [`OnsetDetector`].
+//!
+//! The detection function is the half-wave-rectified [spectral flux], i.e.
+//! the sum of all magnitude increases between two frames. Decreases, e.g.
+//! from a decaying note, are ignored.
+//!
+//! [spectral flux]: https://en.wikipedia.org/wiki/Spectral_flux
+
+use crate::spectrum::FrequencySpectrum;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/// Configuration for an [`OnsetDetector`].
+#[derive(Debug, Copy, Clone)]
+pub struct OnsetConfig {
+    /// Number of previous flux values the adaptive threshold is calculated
+    /// from.
+    pub history_len: usize,
+    /// The flux must exceed the mean of the previous flux values multiplied
+    /// with this value.
+    pub threshold_multiplier: f32,
+    /// Minimum flux an onset must have. Prevents onsets caused by noise
+    /// after a period of silence.
+    pub min_flux: f32,
+}
+
+impl Default for OnsetConfig {
+    fn default() -> Self {
+        Self {
+            history_len: 10,
+            threshold_multiplier: 1.5,
+            min_flux: 0.0,
+        }
+    }
+}
+
+/// An onset detected by [`OnsetDetector::process`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Onset {
+    /// Index of the frame, i.e. the number of frames processed before.
+    pub frame: usize,
+    /// Spectral flux of the frame.
+    pub flux: f32,
+    /// Adaptive threshold the flux exceeded.
+    pub threshold: f32,
+}
+
+/// Detects onsets in a stream of spectra. Feed consecutive frames of the
+/// same size (and frequency limit) into [`OnsetDetector::process`].
+///
+/// A frame is an onset if its spectral flux is higher than the flux of the
+/// previous frame and exceeds an adaptive threshold derived from the mean of
+/// the recent flux values. See [`OnsetConfig`].
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::onset::{OnsetConfig, OnsetDetector};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let mut detector = OnsetDetector::new(OnsetConfig::default());
+/// # let frames: Vec<Vec<f32>> = vec![vec![0.0; 1024]; 4];
+/// for frame in frames {
+///     let spectrum = samples_fft_to_spectrum(&frame, 44100, FrequencyLimit::All, None).unwrap();
+///     if let Some(onset) = detector.process(&spectrum) {
+///         println!("onset in frame {}", onset.frame);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OnsetDetector {
+    config: OnsetConfig,
+    previous_values: Option<Vec<f32>>,
+    flux_history: VecDeque<f32>,
+    frame: usize,
+}
+
+impl OnsetDetector {
+    /// Creates a new detector.
+    ///
+    /// ## Panics
+    /// If `config.history_len` is zero.
+    #[must_use]
+    pub fn new(config: OnsetConfig) -> Self {
+        assert!(config.history_len > 0, "history_len must not be zero!");
+        Self {
+            config,
+            previous_values: None,
+            flux_history: VecDeque::with_capacity(config.history_len),
+            frame: 0,
+        }
+    }
+
+    /// Processes the next frame. The first frame never is an onset, as there
+    /// is no previous frame to compare with.
+    ///
+    /// ## Return value
+    /// `Some` if the frame is an onset.
+    ///
+    /// ## Panics
+    /// If the spectrum has a different length than the previous one.
+    pub fn process(&mut self, spectrum: &FrequencySpectrum) -> Option<Onset> {
+        let values = spectrum
+            .data()
+            .iter()
+            .map(|(_fr, val)| val.val())
+            .collect::<Vec<_>>();
+        let frame = self.frame;
+        self.frame += 1;
+
+        let flux = self
+            .previous_values
+            .as_ref()
+            .map(|previous_values| spectral_flux(previous_values, &values));
+        self.previous_values = Some(values);
+        let flux = flux?;
+
+        let threshold = if self.flux_history.is_empty() {
+            self.config.min_flux
+        } else {
+            let mean = self.flux_history.iter().sum::<f32>() / self.flux_history.len() as f32;
+            (mean * self.config.threshold_multiplier).max(self.config.min_flux)
+        };
+        let is_rising = self.flux_history.back().map_or(true, |&last| flux > last);
+
+        if self.flux_history.len() == self.config.history_len {
+            self.flux_history.pop_front();
+        }
+        self.flux_history.push_back(flux);
+
+        if is_rising && flux > threshold {
+            Some(Onset {
+                frame,
+                flux,
+                threshold,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Resets the detector to its initial state, e.g. after a pause in the
+    /// audio stream.
+    pub fn reset(&mut self) {
+        self.previous_values = None;
+        self.flux_history.clear();
+        self.frame = 0;
+    }
+}
+
+/// Calculates the half-wave-rectified spectral flux between two frames, i.e.
+/// the sum of all positive differences `current[i] - previous[i]`.
+///
+/// ## Panics
+/// If the frames have different lengths.
+#[must_use]
+pub fn spectral_flux(previous: &[f32], current: &[f32]) -> f32 {
+    assert_eq!(
+        previous.len(),
+        current.len(),
+        "frames must have the same length!"
+    );
+    previous
+        .iter()
+        .zip(current)
+        .map(|(previous, current)| (current - previous).max(0.0))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    fn frame(amplitude: f32) -> FrequencySpectrum {
+        let samples = (0..256)
+            .map(|i| amplitude * libm::sinf(2.0 * PI * 64.0 * i as f32 / 1024.0))
+            .collect::<Vec<_>>();
+        samples_fft_to_spectrum(&samples, 1024, FrequencyLimit::All, None).unwrap()
+    }
+
+    #[test]
+    fn test_spectral_flux() {
+        assert_eq!(spectral_flux(&[1.0, 2.0, 3.0], &[2.0, 1.0, 5.0]), 3.0);
+        assert_eq!(spectral_flux(&[1.0, 2.0], &[0.0, 0.0]), 0.0);
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_onset_detector() {
+        let amplitudes = [0.0, 0.0, 0.0, 1.0, 0.8, 0.6, 0.5, 0.0, 0.0, 2.0, 1.5];
+        let mut detector = OnsetDetector::new(OnsetConfig {
+            min_flux: 1.0,
+            ..OnsetConfig::default()
+        });
+        let onsets = amplitudes
+            .iter()
+            .filter_map(|&amplitude| detector.process(&frame(amplitude)))
+            .map(|onset| onset.frame)
+            .collect::<Vec<_>>();
+        assert_eq!(onsets, [3, 9]);
+
+        detector.reset();
+        assert!(detector.process(&frame(1.0)).is_none());
+    }
+}
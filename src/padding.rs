@@ -0,0 +1,106 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Zero-padding of samples to a length the FFT supports. See [`Padding`] and
+//! [`crate::samples_fft_to_spectrum_padded`].
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+/// How samples are padded with zeroes before the FFT is applied.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Padding {
+    /// No padding. The number of samples must be a power of two.
+    #[default]
+    None,
+    /// Appends zeroes until the length is the next power of two, e.g. 1000
+    /// samples are padded to 1024 samples. The frequency resolution of the
+    /// spectrum is `sampling_rate / 1024` then.
+    ZeroToNextPowerOfTwo,
+    /// Pads to the next power of two and then further by the given factor,
+    /// e.g. `4` pads 1000 samples to 4096 samples. The factor must be a
+    /// power of two. This interpolates the spectrum, which results in
+    /// smoother plots and a finer peak localization, but it doesn't improve
+    /// the true frequency resolution, i.e. the ability to separate two close
+    /// frequencies. See
+    /// [`crate::FrequencySpectrum::true_frequency_resolution`].
+    Factor(usize),
+}
+
+impl Padding {
+    /// Returns the number of samples after the padding.
+    ///
+    /// ## Panics
+    /// If the factor of [`Self::Factor`] isn't a power of two.
+    #[must_use]
+    pub const fn padded_len(self, samples_len: usize) -> usize {
+        match self {
+            Self::None => samples_len,
+            Self::ZeroToNextPowerOfTwo => samples_len.next_power_of_two(),
+            Self::Factor(factor) => {
+                assert!(factor.is_power_of_two(), "factor must be a power of two!");
+                samples_len.next_power_of_two() * factor
+            }
+        }
+    }
+
+    /// Pads the samples. Only allocates if padding is required.
+    pub(crate) fn apply(self, samples: &[f32]) -> Cow<'_, [f32]> {
+        let padded_len = self.padded_len(samples.len());
+        if padded_len == samples.len() {
+            Cow::Borrowed(samples)
+        } else {
+            let mut padded = Vec::with_capacity(padded_len);
+            padded.extend_from_slice(samples);
+            padded.resize(padded_len, 0.0);
+            Cow::Owned(padded)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(Padding::None.padded_len(1000), 1000);
+        assert_eq!(Padding::ZeroToNextPowerOfTwo.padded_len(1000), 1024);
+        assert_eq!(Padding::ZeroToNextPowerOfTwo.padded_len(1024), 1024);
+        assert_eq!(Padding::Factor(1).padded_len(1000), 1024);
+        assert_eq!(Padding::Factor(4).padded_len(1000), 4096);
+
+        let samples = [1.0, 2.0, 3.0];
+        assert!(matches!(Padding::None.apply(&samples), Cow::Borrowed(_)));
+        assert_eq!(
+            Padding::ZeroToNextPowerOfTwo.apply(&samples).as_ref(),
+            [1.0, 2.0, 3.0, 0.0]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_factor() {
+        let _ = Padding::Factor(3).padded_len(1024);
+    }
+}
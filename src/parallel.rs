@@ -0,0 +1,251 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Parallel analysis of many frames with [`rayon`], e.g. for the offline
+//! processing of long recordings, where a single-threaded short-time
+//! Fourier transform (STFT) is the bottleneck. Needs the `rayon` feature.
+//!
+//! See [`samples_to_spectra_parallel`] and [`spectrogram_parallel`].
+
+use crate::error::SpectrumAnalyzerError;
+use crate::sample::Sample;
+use crate::scaling::{SpectrumDataStats, SpectrumScalingFunction};
+use crate::spectrogram::Spectrogram;
+use crate::windows::Window;
+use crate::{samples_fft_to_spectrum_windowed, FrequencyLimit, FrequencySpectrum};
+use alloc::vec::Vec;
+use rayon::prelude::*;
+
+/// Scaling function that can be shared between threads. See
+/// [`SpectrumScalingFunction`].
+pub type SyncSpectrumScalingFunction = dyn Fn(f32, &SpectrumDataStats) -> f32 + Sync;
+
+/// Configuration for the analysis of each frame. See
+/// [`samples_fft_to_spectrum_windowed`].
+#[derive(Debug, Copy, Clone)]
+pub struct ParallelConfig {
+    /// Sampling rate, e.g. `44100 [Hz]`.
+    pub sampling_rate: u32,
+    /// Optional window that is applied to each frame.
+    pub window: Option<Window>,
+    /// Frequency limit. See [`FrequencyLimit`].
+    pub frequency_limit: FrequencyLimit,
+}
+
+impl ParallelConfig {
+    /// Creates a new configuration without a window and without a frequency
+    /// limit.
+    #[must_use]
+    pub const fn new(sampling_rate: u32) -> Self {
+        Self {
+            sampling_rate,
+            window: None,
+            frequency_limit: FrequencyLimit::All,
+        }
+    }
+}
+
+/// Analyzes all frames in parallel on the global [`rayon`] thread pool.
+///
+/// ## Parameters
+/// * `frames` Frames of samples. Each frame must fulfill the requirements of
+///            [`crate::samples_fft_to_spectrum`].
+/// * `config` See [`ParallelConfig`].
+/// * `scaling_fn` Optional scaling function. See
+///                [`crate::samples_fft_to_spectrum`].
+///
+/// ## Return value
+/// The spectrum of each frame, in the order of the frames.
+///
+/// ## Errors
+/// The error of the first frame that can't be analyzed, in the order of the
+/// frames. Hence, the error doesn't depend on the scheduling of the threads.
+/// See [`crate::samples_fft_to_spectrum`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::parallel::{samples_to_spectra_parallel, ParallelConfig};
+/// use spectrum_analyzer::windows::Window;
+/// let frames = vec![vec![0.0_f32, 1.1, 5.5, -5.5]; 16];
+/// let config = ParallelConfig {
+///     window: Some(Window::Hann),
+///     ..ParallelConfig::new(44100)
+/// };
+/// let spectra = samples_to_spectra_parallel(&frames, &config, None).unwrap();
+/// assert_eq!(spectra.len(), 16);
+/// ```
+pub fn samples_to_spectra_parallel<F, S>(
+    frames: &[F],
+    config: &ParallelConfig,
+    scaling_fn: Option<&SyncSpectrumScalingFunction>,
+) -> Result<Vec<FrequencySpectrum>, SpectrumAnalyzerError>
+where
+    F: AsRef<[S]> + Sync,
+    S: Sample + Sync,
+{
+    let results = frames
+        .par_iter()
+        .map(|frame| analyze(frame.as_ref(), config, scaling_fn))
+        .collect::<Vec<_>>();
+    first_error(results)
+}
+
+/// Splits the samples into (overlapping) frames of `frame_len` samples and
+/// analyzes them in parallel on the global [`rayon`] thread pool. An
+/// incomplete frame at the end is ignored.
+///
+/// ## Parameters
+/// * `samples` Samples, e.g. of a long recording.
+/// * `frame_len` Number of samples of each frame. Must be a power of two.
+/// * `hop_len` Number of samples between the beginnings of two frames, e.g.
+///             `frame_len / 2` for an overlap of 50%.
+/// * `config` See [`ParallelConfig`].
+/// * `scaling_fn` Optional scaling function. See
+///                [`crate::samples_fft_to_spectrum`].
+///
+/// ## Return value
+/// The spectrogram of all frames. The time of each frame is the time of its
+/// first sample.
+///
+/// ## Errors
+/// See [`samples_to_spectra_parallel`].
+///
+/// ## Panics
+/// If `hop_len` is zero.
+pub fn spectrogram_parallel<S: Sample + Sync>(
+    samples: &[S],
+    frame_len: usize,
+    hop_len: usize,
+    config: &ParallelConfig,
+    scaling_fn: Option<&SyncSpectrumScalingFunction>,
+) -> Result<Spectrogram, SpectrumAnalyzerError> {
+    assert!(hop_len > 0, "hop_len must not be zero!");
+    let n_frames = if samples.len() < frame_len {
+        0
+    } else {
+        (samples.len() - frame_len) / hop_len + 1
+    };
+    let results = (0..n_frames)
+        .into_par_iter()
+        .map(|i| {
+            let start = i * hop_len;
+            analyze(&samples[start..start + frame_len], config, scaling_fn)
+        })
+        .collect::<Vec<_>>();
+    let spectra = first_error(results)?;
+    Ok(Spectrogram::from_spectra(
+        spectra,
+        hop_len as f32 / config.sampling_rate as f32,
+    ))
+}
+
+/// Returns all spectra or the error of the frame with the lowest index.
+/// Collecting into a `Result` with [`rayon`] directly returns the error of
+/// whichever thread fails first.
+fn first_error(
+    results: Vec<Result<FrequencySpectrum, SpectrumAnalyzerError>>,
+) -> Result<Vec<FrequencySpectrum>, SpectrumAnalyzerError> {
+    results.into_iter().collect()
+}
+
+fn analyze<S: Sample>(
+    frame: &[S],
+    config: &ParallelConfig,
+    scaling_fn: Option<&SyncSpectrumScalingFunction>,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    samples_fft_to_spectrum_windowed(
+        frame,
+        config.sampling_rate,
+        config.window,
+        config.frequency_limit,
+        scaling_fn.map(|scaling_fn| scaling_fn as &SpectrumScalingFunction),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaling::divide_by_N;
+    use core::f32::consts::PI;
+
+    #[test]
+    fn test_samples_to_spectra_parallel() {
+        let frames = (1..=8)
+            .map(|i| {
+                (0..256)
+                    .map(|j| libm::sinf(2.0 * PI * i as f32 * 1000.0 * j as f32 / 16000.0))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let config = ParallelConfig::new(16000);
+        let spectra = samples_to_spectra_parallel(&frames, &config, Some(&divide_by_N)).unwrap();
+        for (i, (frame, spectrum)) in frames.iter().zip(&spectra).enumerate() {
+            assert_eq!(spectrum.max().0.val(), (i + 1) as f32 * 1000.0);
+            let sequential = crate::samples_fft_to_spectrum(
+                frame,
+                16000,
+                FrequencyLimit::All,
+                Some(&divide_by_N),
+            )
+            .unwrap();
+            assert_eq!(spectrum.data(), sequential.data());
+        }
+
+        let invalid = [vec![0.0_f32; 4], vec![0.0; 1]];
+        assert!(matches!(
+            samples_to_spectra_parallel(&invalid, &config, None),
+            Err(SpectrumAnalyzerError::TooFewSamples)
+        ));
+
+        // always the error of the first invalid frame
+        let mut invalid = vec![vec![0.0_f32; 4]; 64];
+        invalid[10][0] = f32::NAN;
+        for frame in &mut invalid[11..] {
+            frame[0] = f32::INFINITY;
+        }
+        for _ in 0..10 {
+            assert!(matches!(
+                samples_to_spectra_parallel(&invalid, &config, None),
+                Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_spectrogram_parallel() {
+        let samples = (0..1000).map(|i| i as i16).collect::<Vec<_>>();
+        let config = ParallelConfig {
+            window: Some(Window::Hann),
+            ..ParallelConfig::new(1000)
+        };
+        let spectrogram = spectrogram_parallel(&samples, 256, 128, &config, None).unwrap();
+        // 0, 128, ..., 640 (the frame at 768 is incomplete)
+        assert_eq!(spectrogram.len(), 6);
+        assert!((spectrogram.times()[5] - 0.64).abs() < 1e-6);
+        assert!(
+            spectrogram_parallel(&samples[..100], 256, 128, &config, None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,210 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for peak detection inside a [`crate::FrequencySpectrum`]. See
+//! [`crate::FrequencySpectrum::find_peaks`].
+
+use crate::frequency::{Frequency, FrequencyValue};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// Configuration for [`crate::FrequencySpectrum::find_peaks`]. The default
+/// configuration reports every local maximum.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PeakConfig {
+    /// Minimum frequency value/magnitude a peak must have (inclusive).
+    pub threshold: f32,
+    /// Minimum [prominence] a peak must have (inclusive), i.e. how much the
+    /// peak stands out from the surrounding baseline.
+    ///
+    /// [prominence]: https://en.wikipedia.org/wiki/Topographic_prominence
+    pub min_prominence: f32,
+    /// Minimum distance in Hertz between two peaks. If two peaks are closer,
+    /// only the higher one is kept.
+    pub min_distance: f32,
+}
+
+/// A peak, i.e. a local maximum, inside a [`crate::FrequencySpectrum`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Peak {
+    /// Index of the peak in [`crate::FrequencySpectrum::data`].
+    pub index: usize,
+    /// Frequency of the peak.
+    pub frequency: Frequency,
+    /// Frequency value/magnitude of the peak.
+    pub value: FrequencyValue,
+    /// Prominence of the peak. See [`PeakConfig::min_prominence`].
+    pub prominence: f32,
+}
+
+/// Finds all peaks inside `data` that match the given configuration. The
+/// first and the last element are never considered as peaks, as they only
+/// have one neighbor.
+///
+/// ## Return value
+/// Peaks sorted by frequency, from the lowest to the highest.
+pub(crate) fn find_peaks(data: &[(Frequency, FrequencyValue)], config: &PeakConfig) -> Vec<Peak> {
+    let mut peaks = local_maxima(data)
+        .into_iter()
+        .filter(|&index| data[index].1.val() >= config.threshold)
+        .map(|index| Peak {
+            index,
+            frequency: data[index].0,
+            value: data[index].1,
+            prominence: prominence(data, index),
+        })
+        .filter(|peak| peak.prominence >= config.min_prominence)
+        .collect::<Vec<_>>();
+
+    if config.min_distance > 0.0 {
+        peaks = filter_by_distance(peaks, config.min_distance);
+    }
+    peaks
+}
+
+/// Returns the indices of all local maxima. For plateaus, i.e. multiple
+/// neighbors with the same value, the index in the middle is reported.
+fn local_maxima(data: &[(Frequency, FrequencyValue)]) -> Vec<usize> {
+    let mut maxima = Vec::new();
+    if data.len() < 3 {
+        return maxima;
+    }
+    let mut i = 1;
+    while i < data.len() - 1 {
+        if data[i - 1].1 < data[i].1 {
+            // find the end of a potential plateau
+            let mut plateau_end = i;
+            while plateau_end + 1 < data.len() - 1 && data[plateau_end + 1].1 == data[i].1 {
+                plateau_end += 1;
+            }
+            if data[plateau_end + 1].1 < data[i].1 {
+                maxima.push((i + plateau_end) / 2);
+            }
+            i = plateau_end + 1;
+        } else {
+            i += 1;
+        }
+    }
+    maxima
+}
+
+/// Calculates the prominence of the peak at `index`. Therefore, the lowest
+/// value on each side is searched until a higher value or the border is
+/// reached. The higher of both minimums is the reference level of the peak.
+fn prominence(data: &[(Frequency, FrequencyValue)], index: usize) -> f32 {
+    let peak_val = data[index].1;
+    let left_min = data[..index]
+        .iter()
+        .rev()
+        .take_while(|(_fr, val)| *val <= peak_val)
+        .map(|(_fr, val)| *val)
+        .min()
+        .unwrap_or(peak_val);
+    let right_min = data[index + 1..]
+        .iter()
+        .take_while(|(_fr, val)| *val <= peak_val)
+        .map(|(_fr, val)| *val)
+        .min()
+        .unwrap_or(peak_val);
+    (peak_val - left_min.max(right_min)).val()
+}
+
+/// Removes all peaks that are closer than `min_distance` to a higher peak.
+fn filter_by_distance(peaks: Vec<Peak>, min_distance: f32) -> Vec<Peak> {
+    let mut by_value = peaks;
+    // highest peaks first
+    by_value.sort_by_key(|peak| Reverse(peak.value));
+
+    let mut kept: Vec<Peak> = Vec::with_capacity(by_value.len());
+    for peak in by_value {
+        let too_close = kept
+            .iter()
+            .any(|other| (other.frequency - peak.frequency).val().abs() < min_distance);
+        if !too_close {
+            kept.push(peak);
+        }
+    }
+    kept.sort_by_key(|peak| peak.frequency);
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(values: &[f32]) -> Vec<(Frequency, FrequencyValue)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, val)| ((i as f32 * 10.0).into(), (*val).into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_local_maxima() {
+        let data = data(&[5.0, 1.0, 3.0, 2.0, 4.0, 4.0, 4.0, 1.0, 2.0]);
+        // borders are not reported
+        assert_eq!(local_maxima(&data), vec![2, 5]);
+        assert!(local_maxima(&data[0..2]).is_empty());
+    }
+
+    #[test]
+    fn test_find_peaks() {
+        let data = data(&[0.0, 1.0, 0.5, 10.0, 0.0, 2.0, 1.5, 8.0, 0.0]);
+        let all = find_peaks(&data, &PeakConfig::default());
+        let frequencies = all.iter().map(|p| p.frequency.val()).collect::<Vec<_>>();
+        assert_eq!(frequencies, vec![10.0, 30.0, 50.0, 70.0]);
+        assert_eq!(all[0].prominence, 0.5);
+        assert_eq!(all[1].prominence, 10.0);
+        assert_eq!(all[2].prominence, 0.5);
+        assert_eq!(all[3].prominence, 8.0);
+
+        let config = PeakConfig {
+            threshold: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(find_peaks(&data, &config).len(), 4);
+        let config = PeakConfig {
+            threshold: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(find_peaks(&data, &config).len(), 3);
+
+        let config = PeakConfig {
+            min_prominence: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(find_peaks(&data, &config).len(), 2);
+
+        let config = PeakConfig {
+            min_distance: 35.0,
+            ..Default::default()
+        };
+        let frequencies = find_peaks(&data, &config)
+            .iter()
+            .map(|p| p.frequency.val())
+            .collect::<Vec<_>>();
+        assert_eq!(frequencies, vec![30.0, 70.0]);
+    }
+}
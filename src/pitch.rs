@@ -0,0 +1,285 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for pitch detection, i.e. estimating the fundamental frequency of
+//! a (musical) signal. The fundamental is not necessarily the strongest
+//! frequency in the spectrum, as harmonics may dominate, which is why
+//! [`crate::FrequencySpectrum::max`] is often not a good pitch estimate.
+//!
+//! There are two approaches:
+//! - [`detect_fundamental`] works on a spectrum,
+//! - [`detect_fundamental_yin`] works on the raw samples in the time domain.
+//!   This is more reliable for low frequencies with small buffers, where the
+//!   frequency resolution of an FFT is too coarse.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::math::parabolic_interpolation;
+use crate::spectrum::FrequencySpectrum;
+use crate::validate_samples;
+use alloc::vec::Vec;
+
+/// Number of harmonics used by [`detect_fundamental`].
+pub const DEFAULT_HPS_HARMONICS: usize = 5;
+
+/// Recommended threshold for [`detect_fundamental_yin`].
+pub const DEFAULT_YIN_THRESHOLD: f32 = 0.15;
+
+/// Lower bound for values before the logarithm is applied. Prevents `-inf`.
+const MIN_LOG_VALUE: f32 = 1e-20;
+
+/// Estimates the fundamental frequency of the spectrum using the
+/// [Harmonic Product Spectrum] (HPS) with [`DEFAULT_HPS_HARMONICS`]
+/// harmonics. See [`detect_fundamental_hps`].
+///
+/// [Harmonic Product Spectrum]: http://musicweb.ucsd.edu/~trsmyth/analysis/Harmonic_Product_Spectrum.html
+#[must_use]
+pub fn detect_fundamental(spectrum: &FrequencySpectrum) -> Option<Frequency> {
+    detect_fundamental_hps(spectrum, DEFAULT_HPS_HARMONICS)
+}
+
+/// Estimates the fundamental frequency of the spectrum using the
+/// [Harmonic Product Spectrum] (HPS). For each candidate frequency `f`, the
+/// values at `f, 2f, ..., harmonics * f` are multiplied. The candidate with
+/// the highest product is the fundamental. The product is calculated as sum
+/// of logarithms to prevent overflows.
+///
+/// Only candidates whose highest harmonic is still inside the spectrum are
+/// considered. Hence, the spectrum should cover a frequency range of at least
+/// `harmonics` times the highest expected fundamental. The bins of the
+/// harmonics are looked up by their frequency, so the spectrum may have gaps,
+/// e.g. after [`FrequencySpectrum::sub_spectrum`]. Harmonics without a bin
+/// are skipped. The DC component is never reported.
+///
+/// ## Parameters
+/// * `spectrum` Spectrum with magnitudes (not in decibel).
+/// * `harmonics` Number of harmonics (including the fundamental itself).
+///
+/// ## Return value
+/// `None` if there is no candidate or if the spectrum is silent.
+///
+/// [Harmonic Product Spectrum]: http://musicweb.ucsd.edu/~trsmyth/analysis/Harmonic_Product_Spectrum.html
+#[must_use]
+pub fn detect_fundamental_hps(spectrum: &FrequencySpectrum, harmonics: usize) -> Option<Frequency> {
+    let data = spectrum.data();
+    let max_fr = spectrum.max_fr().val();
+    let resolution = spectrum.frequency_resolution();
+
+    if data.iter().all(|(_fr, val)| val.val() == 0.0) {
+        return None;
+    }
+
+    data.iter()
+        .map(|(fr, _val)| *fr)
+        .filter(|fr| fr.val() > 0.0)
+        .take_while(|fr| fr.val() * harmonics as f32 <= max_fr)
+        .map(|fr| {
+            let log_product = (1..=harmonics)
+                .filter_map(|h| value_at(data, fr.val() * h as f32, resolution))
+                .map(|val| libm::logf(val.max(MIN_LOG_VALUE)))
+                .sum::<f32>();
+            (fr, log_product)
+        })
+        .fold(
+            None,
+            |best: Option<(Frequency, f32)>, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate),
+            },
+        )
+        .map(|(fr, _log_product)| fr)
+}
+
+/// Returns the value of the bin at the frequency `fr`, if `data` has a bin
+/// within half the frequency resolution. `data` must be sorted by frequency
+/// but may have gaps.
+fn value_at(data: &[(Frequency, FrequencyValue)], fr: f32, resolution: f32) -> Option<f32> {
+    let index = data.partition_point(|(bin_fr, _val)| bin_fr.val() < fr);
+    [index.checked_sub(1), Some(index)]
+        .into_iter()
+        .flatten()
+        .filter_map(|i| data.get(i))
+        .map(|(bin_fr, val)| ((bin_fr.val() - fr).abs(), val.val()))
+        .filter(|(distance, _val)| *distance <= resolution / 2.0)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_distance, val)| val)
+}
+
+/// Estimates the fundamental frequency of the samples in the time domain
+/// using the [YIN] algorithm by de Cheveigné and Kawahara (2002). The
+/// lowest detectable frequency is `2 * sampling_rate / samples.len()`, e.g.
+/// `~43 Hz` for 2048 samples at 44.1 kHz.
+///
+/// ## Parameters
+/// * `samples` raw audio, e.g. 16bit audio data but as f32. Don't apply a
+///             window function. Any length is supported.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `threshold` Threshold for the cumulative mean normalized difference
+///               function. Lower values are more strict. See
+///               [`DEFAULT_YIN_THRESHOLD`].
+///
+/// ## Return value
+/// `None` if no periodicity is found, e.g. for noise or silence.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::pitch::{detect_fundamental_yin, DEFAULT_YIN_THRESHOLD};
+/// let samples = (0..2048)
+///     .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 82.4 * i as f32 / 44100.0))
+///     .collect::<Vec<_>>();
+/// let pitch = detect_fundamental_yin(&samples, 44100, DEFAULT_YIN_THRESHOLD).unwrap();
+/// ```
+///
+/// ## Errors
+/// If the samples are invalid, e.g. contain `NaN`. See
+/// [`crate::samples_fft_to_spectrum`].
+///
+/// [YIN]: http://audition.ens.fr/adc/pdf/2002_JASA_YIN.pdf
+pub fn detect_fundamental_yin(
+    samples: &[f32],
+    sampling_rate: u32,
+    threshold: f32,
+) -> Result<Option<Frequency>, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+
+    let cmnd = cumulative_mean_normalized_difference(samples);
+
+    // absolute threshold: first dip below the threshold ...
+    let tau = match (2..cmnd.len()).find(|&tau| cmnd[tau] < threshold) {
+        Some(tau) => tau,
+        None => return Ok(None),
+    };
+    // ... and then the local minimum of that dip
+    let tau = (tau..cmnd.len() - 1)
+        .find(|&tau| cmnd[tau + 1] >= cmnd[tau])
+        .unwrap_or(cmnd.len() - 1);
+
+    let refined_tau = if tau + 1 < cmnd.len() {
+        let (offset, _) = parabolic_interpolation(cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+        tau as f32 + offset
+    } else {
+        tau as f32
+    };
+
+    Ok(Some((sampling_rate as f32 / refined_tau).into()))
+}
+
+/// Calculates the cumulative mean normalized difference function (step 2 and
+/// step 3 of YIN) for all lags `0 <= tau < samples.len() / 2`.
+fn cumulative_mean_normalized_difference(samples: &[f32]) -> Vec<f32> {
+    let window_len = samples.len() / 2;
+    let mut cmnd = Vec::with_capacity(window_len);
+    cmnd.push(1.0);
+    let mut running_sum = 0.0;
+    for tau in 1..window_len {
+        let difference = samples[..window_len]
+            .iter()
+            .zip(&samples[tau..tau + window_len])
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>();
+        running_sum += difference;
+        cmnd.push(if running_sum == 0.0 {
+            1.0
+        } else {
+            difference * tau as f32 / running_sum
+        });
+    }
+    cmnd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    #[cfg(feature = "size-4096")]
+    #[test]
+    fn test_dominant_harmonic() {
+        // fundamental 200 Hz is weak, the 2nd harmonic dominates
+        let amplitudes = [0.3, 1.0, 0.6, 0.4, 0.3];
+        let samples = (0..4096)
+            .map(|i| {
+                let t = i as f32 / 4096.0;
+                amplitudes
+                    .iter()
+                    .enumerate()
+                    .map(|(h, a)| a * libm::sinf(2.0 * PI * 200.0 * (h + 1) as f32 * t))
+                    .sum::<f32>()
+            })
+            .collect::<Vec<_>>();
+        let spectrum = samples_fft_to_spectrum(&samples, 4096, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectrum.max().0.val(), 400.0);
+        assert_eq!(detect_fundamental(&spectrum).unwrap().val(), 200.0);
+    }
+
+    #[test]
+    fn test_hps_with_gap() {
+        // harmonics of 200 Hz, but the bins from 300 to 450 Hz are missing
+        let mut data = (2..=20)
+            .map(|i| i as f32 * 50.0)
+            .filter(|fr| !(300.0..=450.0).contains(fr))
+            .map(|fr| {
+                let val = if fr % 200.0 == 0.0 { 1.0 } else { 0.01 };
+                (Frequency::from(fr), FrequencyValue::from(val))
+            })
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        let spectrum = FrequencySpectrum::new(data.clone(), 50.0, 40, &mut working_buffer);
+        assert_eq!(detect_fundamental_hps(&spectrum, 4).unwrap().val(), 200.0);
+
+        assert_eq!(value_at(&data, 400.0, 50.0), None);
+        assert_eq!(value_at(&data, 610.0, 50.0), Some(1.0));
+        assert_eq!(value_at(&data, 2000.0, 50.0), None);
+        data.clear();
+        assert_eq!(value_at(&data, 100.0, 50.0), None);
+    }
+
+    #[test]
+    fn test_yin() {
+        // the FFT resolution would be 8 Hz, which is too coarse
+        let samples = (0..1024)
+            .map(|i| libm::sinf(2.0 * PI * 82.4 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        let pitch = detect_fundamental_yin(&samples, 8192, DEFAULT_YIN_THRESHOLD)
+            .unwrap()
+            .unwrap();
+        float_cmp::assert_approx_eq!(f32, pitch.val(), 82.4, epsilon = 0.2);
+
+        let silence = vec![0.0; 1024];
+        assert!(
+            detect_fundamental_yin(&silence, 8192, DEFAULT_YIN_THRESHOLD)
+                .unwrap()
+                .is_none()
+        );
+        assert!(detect_fundamental_yin(&[0.0], 8192, DEFAULT_YIN_THRESHOLD).is_err());
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_silence() {
+        let samples = vec![0.0; 256];
+        let spectrum = samples_fft_to_spectrum(&samples, 4096, FrequencyLimit::All, None).unwrap();
+        assert!(detect_fundamental(&spectrum).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! First-order pre-emphasis filter `y[n] = x[n] - alpha * x[n - 1]`. It
+//! boosts the high frequencies by about 6 dB per octave, which compensates
+//! the spectral tilt of voiced speech. Hence, it is a standard step of
+//! speech front-ends before the FFT, e.g. for [`crate::mel`] features. See
+//! [`crate::SpectrumAnalyzerBuilder::pre_emphasis`].
+
+/// Commonly used coefficient for speech.
+pub const DEFAULT_PRE_EMPHASIS: f32 = 0.97;
+
+/// Applies the pre-emphasis filter `y[n] = x[n] - alpha * x[n - 1]` to the
+/// samples in-place. The first sample stays unchanged, as there is no
+/// previous sample.
+///
+/// ## Parameters
+/// * `samples` Samples without NaN or infinite values.
+/// * `alpha` Coefficient in range `0.0..=1.0`, usually between `0.9` and
+///           `0.97`. `0.0` leaves the samples unchanged.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::pre_emphasis::pre_emphasis;
+/// let mut samples = [1.0, 1.0, 1.0];
+/// pre_emphasis(&mut samples, 0.5);
+/// assert_eq!(samples, [1.0, 0.5, 0.5]);
+/// ```
+///
+/// ## Panics
+/// If `alpha` isn't in range `0.0..=1.0`.
+pub fn pre_emphasis(samples: &mut [f32], alpha: f32) {
+    assert!(
+        (0.0..=1.0).contains(&alpha),
+        "alpha must be in range 0.0..=1.0!"
+    );
+    // backwards, so that each previous sample is still the original one
+    for i in (1..samples.len()).rev() {
+        samples[i] -= alpha * samples[i - 1];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_pre_emphasis() {
+        let samples = [1.0, 2.0, 4.0, 3.0];
+        let mut filtered = samples;
+        pre_emphasis(&mut filtered, 0.5);
+        assert_eq!(filtered, [1.0, 1.5, 3.0, 1.0]);
+
+        let mut unchanged = samples;
+        pre_emphasis(&mut unchanged, 0.0);
+        assert_eq!(unchanged, samples);
+
+        let mut empty: [f32; 0] = [];
+        pre_emphasis(&mut empty, DEFAULT_PRE_EMPHASIS);
+    }
+
+    #[test]
+    fn test_pre_emphasis_boosts_high_frequencies() {
+        let sine = |fr: f32| {
+            (0..256)
+                .map(|i| libm::sinf(2.0 * core::f32::consts::PI * fr * i as f32 / 8000.0))
+                .collect::<Vec<_>>()
+        };
+        let energy = |samples: &[f32]| samples[1..].iter().map(|x| x * x).sum::<f32>();
+        let mut low = sine(100.0);
+        let mut high = sine(3000.0);
+        let (low_before, high_before) = (energy(&low), energy(&high));
+        pre_emphasis(&mut low, DEFAULT_PRE_EMPHASIS);
+        pre_emphasis(&mut high, DEFAULT_PRE_EMPHASIS);
+        assert!(energy(&low) < low_before / 10.0);
+        assert!(energy(&high) > high_before * 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_alpha() {
+        pre_emphasis(&mut [1.0, 2.0], 1.5);
+    }
+}
@@ -0,0 +1,221 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Power spectral density (PSD) estimation using [Welch's method].
+//!
+//! In contrast to [`crate::samples_fft_to_spectrum`], which calculates a
+//! single-shot magnitude spectrum, Welch's method splits the signal into
+//! (overlapping) segments, applies a window on each segment and averages the
+//! resulting periodograms. This reduces the variance of the estimate, which
+//! is what you want for noise measurements.
+//!
+//! [Welch's method]: https://en.wikipedia.org/wiki/Welch%27s_method
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::FftImpl;
+use crate::windows::WindowFunction;
+use crate::{
+    fft_calc_frequency_resolution, fft_result_with_frequencies, validate_input, validate_samples,
+    Frequency, FrequencyLimit, FrequencySpectrum, FrequencyValue,
+};
+use alloc::vec::Vec;
+
+/// Estimates the one-sided power spectral density (PSD) of `samples` using
+/// Welch's method.
+///
+/// The result is normalized to `V²/Hz` (or `unit²/Hz`, depending on the unit
+/// of the samples). The normalization divides by `sampling_rate * sum(w[n]²)`,
+/// which includes the correction for the equivalent noise bandwidth (ENBW)
+/// of the window. Hence, summing all values multiplied with the frequency
+/// resolution yields the mean power (mean square) of the signal.
+///
+/// ## Parameters
+/// * `samples` raw audio, e.g. 16bit audio data but as f32. Must contain at
+///             least `segment_len` samples.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `segment_len` Length of each segment. Must be a power of two. This
+///                 determines the frequency resolution.
+/// * `overlap` Number of samples two consecutive segments overlap, e.g.
+///             `segment_len / 2`. Must be smaller than `segment_len`.
+/// * `window_fn` Optional window function that is applied to each segment,
+///               e.g. [`crate::windows::hann_window`]. If `None`, a
+///               rectangular window is used.
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+///
+/// ## Return value
+/// New object of type [`FrequencySpectrum`] with PSD values in `V²/Hz`.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::psd::welch_psd;
+/// use spectrum_analyzer::windows::hann_window;
+/// use spectrum_analyzer::FrequencyLimit;
+/// let samples = vec![0.0, 1.1, 5.5, -5.5, 2.2, 0.7, -1.0, 3.3];
+/// let psd = welch_psd(&samples, 44100, 4, 2, Some(&hann_window), FrequencyLimit::All).unwrap();
+/// ```
+///
+/// ## Errors
+/// See [`crate::samples_fft_to_spectrum`]. Furthermore,
+/// [`SpectrumAnalyzerError::InvalidSegmentOverlap`] is returned if `overlap`
+/// is not smaller than `segment_len` and
+/// [`SpectrumAnalyzerError::TooFewSamples`] if there are less samples than
+/// `segment_len`.
+pub fn welch_psd(
+    samples: &[f32],
+    sampling_rate: u32,
+    segment_len: usize,
+    overlap: usize,
+    window_fn: Option<&WindowFunction>,
+    frequency_limit: FrequencyLimit,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    if overlap >= segment_len {
+        return Err(SpectrumAnalyzerError::InvalidSegmentOverlap(
+            segment_len,
+            overlap,
+        ));
+    }
+    if samples.len() < segment_len {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    // checks the first segment for a valid length and the frequency limit
+    validate_input(&samples[0..segment_len], sampling_rate, &frequency_limit)?;
+    // checks all remaining samples for NaN and infinity
+    validate_samples(samples)?;
+
+    // Window coefficients w[n] are obtained by windowing a signal of ones.
+    let window = {
+        let ones = vec![1.0; segment_len];
+        window_fn.map_or_else(|| ones.clone(), |window_fn| window_fn(&ones))
+    };
+    // sum(w[n]²): incorporates the ENBW of the window into the normalization
+    let window_power_sum = window.iter().map(|w| w * w).sum::<f32>();
+
+    let hop = segment_len - overlap;
+    let mut power_acc = vec![0.0_f32; segment_len / 2 + 1];
+    let mut segments_count = 0;
+    let mut segment = vec![0.0; segment_len];
+    let mut start = 0;
+    while start + segment_len <= samples.len() {
+        for ((dst, sample), w) in segment
+            .iter_mut()
+            .zip(&samples[start..start + segment_len])
+            .zip(&window)
+        {
+            *dst = sample * w;
+        }
+        let fft_res = FftImpl::calc(&segment);
+        for (acc, complex) in power_acc.iter_mut().zip(fft_res.iter()) {
+            *acc += complex.re * complex.re + complex.im * complex.im;
+        }
+        segments_count += 1;
+        start += hop;
+    }
+
+    let normalization = segments_count as f32 * sampling_rate as f32 * window_power_sum;
+    let nyquist_index = segment_len / 2;
+    for (i, power) in power_acc.iter_mut().enumerate() {
+        *power /= normalization;
+        // one-sided spectrum: the energy of the negative frequencies is
+        // added to the positive ones; DC and Nyquist exist only once
+        if i != 0 && i != nyquist_index {
+            *power *= 2.0;
+        }
+    }
+
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, segment_len as u32);
+    let data = fft_result_with_frequencies(
+        segment_len,
+        &power_acc,
+        frequency_resolution,
+        frequency_limit,
+    )
+    .map(|(fr, power)| (Frequency::from(fr), FrequencyValue::from(*power)))
+    .collect::<Vec<(Frequency, FrequencyValue)>>();
+
+    let mut working_buffer = data.clone();
+    Ok(FrequencySpectrum::new(
+        data,
+        frequency_resolution,
+        segment_len as u32,
+        &mut working_buffer,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use core::f32::consts::PI;
+
+    /// Sums up the power of all frequencies. Must equal the mean square
+    /// of the signal.
+    fn total_power(psd: &FrequencySpectrum) -> f32 {
+        psd.data().iter().map(|(_fr, val)| val.val()).sum::<f32>() * psd.frequency_resolution()
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_dc_signal() {
+        let samples = vec![2.0; 1024];
+        let psd = welch_psd(&samples, 1024, 256, 128, None, FrequencyLimit::All).unwrap();
+        float_cmp::assert_approx_eq!(f32, total_power(&psd), 4.0, epsilon = 0.001);
+        assert_eq!(psd.max().0.val(), 0.0);
+    }
+
+    #[cfg(feature = "size-512")]
+    #[test]
+    fn test_sine_power() {
+        // sine with amplitude 3 at 64Hz has a power of 3²/2 = 4.5
+        let samples = (0..4096)
+            .map(|i| 3.0 * libm::sinf(2.0 * PI * 64.0 * i as f32 / 1024.0))
+            .collect::<Vec<_>>();
+        let psd = welch_psd(
+            &samples,
+            1024,
+            512,
+            256,
+            Some(&hann_window),
+            FrequencyLimit::All,
+        )
+        .unwrap();
+        float_cmp::assert_approx_eq!(f32, total_power(&psd), 4.5, epsilon = 0.05);
+        assert_eq!(psd.max().0.val(), 64.0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let samples = vec![0.0; 64];
+        let err = welch_psd(&samples, 1024, 16, 16, None, FrequencyLimit::All).unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::InvalidSegmentOverlap(16, 16)
+        ));
+        let err = welch_psd(&samples, 1024, 128, 0, None, FrequencyLimit::All).unwrap_err();
+        assert!(matches!(err, SpectrumAnalyzerError::TooFewSamples));
+        let err = welch_psd(&samples, 1024, 12, 0, None, FrequencyLimit::All).unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+        ));
+    }
+}
@@ -0,0 +1,338 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Fixed-point (q15) analysis path for microcontrollers without FPU, e.g.
+//! Cortex-M0, where the `f32` soft-float path is too slow for real-time audio.
+//! See [`Q15Analyzer`].
+//!
+//! In q15, an `i16` value `x` represents the number `x / 32768`. The window
+//! application, the FFT, and the magnitude calculation of
+//! [`Q15Analyzer::analyze`] only use integer arithmetic. Floating point
+//! numbers are only used once when the analyzer is created.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft_calc_frequency_resolution;
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::spectrum::FrequencySpectrum;
+use crate::windows::WindowFunction;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// Converts a floating point number in range `[-1.0, 1.0]` to q15. Values
+/// outside are saturated.
+fn f32_to_q15(val: f32) -> i16 {
+    libm::roundf(val * 32768.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Multiplies two q15 numbers stored in an `i32`.
+const fn mul_q15(a: i32, b: i32) -> i32 {
+    (a * b) >> 15
+}
+
+/// Integer square root (rounded down).
+const fn isqrt(val: u32) -> u32 {
+    let mut res = 0;
+    let mut bit = 1 << 30;
+    let mut val = val;
+    while bit > val {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if val >= res + bit {
+            val -= res + bit;
+            res = (res >> 1) + bit;
+        } else {
+            res >>= 1;
+        }
+        bit >>= 2;
+    }
+    res
+}
+
+/// Reusable fixed-point spectrum analyzer for q15 samples. The window and
+/// the twiddle factors of the FFT are precalculated.
+///
+/// To prevent overflows, the FFT scales down by two in each stage. Hence, the
+/// magnitudes are the magnitudes of the regular FFT divided by the number of
+/// samples, in q15. A full-scale sine wave results in a magnitude of
+/// about `16384` at its frequency.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::q15::Q15Analyzer;
+/// use spectrum_analyzer::windows::hann_window;
+/// let mut analyzer = Q15Analyzer::new(256, Some(&hann_window));
+/// let samples = [0_i16; 256];
+/// let magnitudes = analyzer.analyze(&samples).unwrap();
+/// assert_eq!(magnitudes.len(), 129);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Q15Analyzer {
+    /// Window coefficients in q15.
+    window: Option<Vec<i16>>,
+    /// `(cos, -sin)` of `2 * PI * k / N` for `k` in `0..N / 2`, in q15.
+    twiddles: Vec<(i16, i16)>,
+    /// Complex FFT buffer, in q15.
+    buffer: Vec<(i32, i32)>,
+    /// Magnitudes from the DC component up to the Nyquist frequency.
+    magnitudes: Vec<u16>,
+}
+
+impl Q15Analyzer {
+    /// Creates a new analyzer.
+    ///
+    /// ## Parameters
+    /// * `samples_len` Number of samples passed to each
+    ///                 [`Q15Analyzer::analyze`] call.
+    /// * `window_fn` Optional window function. The window is calculated once
+    ///               and converted to q15.
+    ///
+    /// ## Panics
+    /// If `samples_len` is smaller than two or isn't a power of two. The q15
+    /// FFT doesn't use `microfft`, so [`crate::MAX_SAMPLES_LEN`] doesn't
+    /// apply.
+    #[must_use]
+    pub fn new(samples_len: usize, window_fn: Option<&WindowFunction>) -> Self {
+        assert!(
+            samples_len >= 2 && samples_len.is_power_of_two(),
+            "samples_len must be a power of two bigger than one!"
+        );
+        let window = window_fn.map(|window_fn| {
+            window_fn(&vec![1.0; samples_len])
+                .into_iter()
+                .map(f32_to_q15)
+                .collect()
+        });
+        let twiddles = (0..samples_len / 2)
+            .map(|k| {
+                let angle = 2.0 * PI * k as f32 / samples_len as f32;
+                (
+                    f32_to_q15(libm::cosf(angle)),
+                    f32_to_q15(-libm::sinf(angle)),
+                )
+            })
+            .collect();
+        Self {
+            window,
+            twiddles,
+            buffer: vec![(0, 0); samples_len],
+            magnitudes: vec![0; samples_len / 2 + 1],
+        }
+    }
+
+    /// Returns the number of samples expected by [`Self::analyze`].
+    #[must_use]
+    pub fn samples_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Applies the window and the fixed-point FFT to the samples and
+    /// calculates the magnitudes. This only uses integer arithmetic and
+    /// performs no heap allocation.
+    ///
+    /// ## Parameters
+    /// * `samples` q15 samples. The length must equal [`Self::samples_len`].
+    ///
+    /// ## Return value
+    /// `samples.len() / 2 + 1` magnitudes from the DC component up to the
+    /// Nyquist frequency. See [`Q15Analyzer`] for the scaling.
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::SamplesLengthMismatch`] if the number of
+    /// samples doesn't match.
+    pub fn analyze(&mut self, samples: &[i16]) -> Result<&[u16], SpectrumAnalyzerError> {
+        let len = self.buffer.len();
+        if samples.len() != len {
+            return Err(SpectrumAnalyzerError::SamplesLengthMismatch(
+                len,
+                samples.len(),
+            ));
+        }
+
+        // window and bit-reversal permutation
+        let bits = len.trailing_zeros();
+        for (i, sample) in samples.iter().enumerate() {
+            let sample = i32::from(*sample);
+            let sample = self
+                .window
+                .as_ref()
+                .map_or(sample, |window| mul_q15(sample, i32::from(window[i])));
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            self.buffer[j] = (sample, 0);
+        }
+
+        // iterative radix-2 FFT; each butterfly halves the values
+        let mut size = 2;
+        while size <= len {
+            let half = size / 2;
+            let step = len / size;
+            for k in 0..half {
+                let (w_re, w_im) = self.twiddles[k * step];
+                let (w_re, w_im) = (i32::from(w_re), i32::from(w_im));
+                for start in (0..len).step_by(size) {
+                    let (a_re, a_im) = self.buffer[start + k];
+                    let (b_re, b_im) = self.buffer[start + k + half];
+                    let t_re = mul_q15(b_re, w_re) - mul_q15(b_im, w_im);
+                    let t_im = mul_q15(b_re, w_im) + mul_q15(b_im, w_re);
+                    self.buffer[start + k] = ((a_re + t_re) >> 1, (a_im + t_im) >> 1);
+                    self.buffer[start + k + half] = ((a_re - t_re) >> 1, (a_im - t_im) >> 1);
+                }
+            }
+            size *= 2;
+        }
+
+        for (magnitude, (re, im)) in self.magnitudes.iter_mut().zip(&self.buffer) {
+            let power = re.unsigned_abs().pow(2) + im.unsigned_abs().pow(2);
+            *magnitude = isqrt(power) as u16;
+        }
+        Ok(&self.magnitudes)
+    }
+
+    /// Like [`Self::analyze`] but returns a [`FrequencySpectrum`] whose
+    /// frequency values are the (scaled) integer magnitudes. This needs
+    /// floating point arithmetic to create the spectrum.
+    ///
+    /// ## Parameters
+    /// * `samples` See [`Self::analyze`].
+    /// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+    ///
+    /// ## Errors
+    /// See [`Self::analyze`].
+    pub fn analyze_to_spectrum(
+        &mut self,
+        samples: &[i16],
+        sampling_rate: u32,
+    ) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+        let samples_len = samples.len() as u32;
+        let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, samples_len);
+        let data = self
+            .analyze(samples)?
+            .iter()
+            .enumerate()
+            .map(|(i, magnitude)| {
+                (
+                    Frequency::from(i as f32 * frequency_resolution),
+                    FrequencyValue::from(f32::from(*magnitude)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut working_buffer = data.clone();
+        Ok(FrequencySpectrum::new(
+            data,
+            frequency_resolution,
+            samples_len,
+            &mut working_buffer,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    fn sine(len: usize, bin: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * libm::sinf(2.0 * PI * (bin * i) as f32 / len as f32))
+            .collect()
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for val in [0, 1, 2, 3, 4, 15, 16, 17, 1 << 30, u32::MAX] {
+            let expected = libm::floor(libm::sqrt(f64::from(val))) as u32;
+            assert_eq!(isqrt(val), expected, "isqrt({val})");
+        }
+    }
+
+    #[test]
+    fn test_full_scale_sine() {
+        let samples = sine(256, 8, 32767.0)
+            .into_iter()
+            .map(|x| x as i16)
+            .collect::<Vec<_>>();
+        let mut analyzer = Q15Analyzer::new(256, None);
+        let magnitudes = analyzer.analyze(&samples).unwrap();
+        let (max_bin, max) = magnitudes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, magnitude)| **magnitude)
+            .unwrap();
+        assert_eq!(max_bin, 8);
+        assert!((16300..=16400).contains(max), "{max}");
+    }
+
+    #[cfg(feature = "size-512")]
+    #[test]
+    fn test_same_result_as_f32_path() {
+        let samples_f32 = sine(512, 20, 0.5)
+            .into_iter()
+            .zip(sine(512, 100, 0.25))
+            .map(|(a, b)| a + b)
+            .collect::<Vec<_>>();
+        let samples = samples_f32
+            .iter()
+            .map(|x| f32_to_q15(*x))
+            .collect::<Vec<_>>();
+        let expected =
+            samples_fft_to_spectrum(&hann_window(&samples_f32), 512, FrequencyLimit::All, None)
+                .unwrap();
+        let mut analyzer = Q15Analyzer::new(512, Some(&hann_window));
+        let spectrum = analyzer.analyze_to_spectrum(&samples, 512).unwrap();
+
+        assert_eq!(spectrum.data().len(), expected.data().len());
+        for ((fr_a, val_a), (fr_b, val_b)) in spectrum.data().iter().zip(expected.data()) {
+            assert_eq!(fr_a, fr_b);
+            // convert the float result to the q15 scaling
+            let expected = val_b.val() / 512.0 * 32768.0;
+            assert!(
+                (val_a.val() - expected).abs() < 20.0,
+                "{val_a} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_samples_len_mismatch() {
+        let mut analyzer = Q15Analyzer::new(8, None);
+        assert!(matches!(
+            analyzer.analyze(&[0; 4]),
+            Err(SpectrumAnalyzerError::SamplesLengthMismatch(8, 4))
+        ));
+    }
+
+    /// The q15 FFT isn't limited by the `size-*` features of `microfft`.
+    #[test]
+    fn test_len_above_max_samples_len() {
+        let len = crate::MAX_SAMPLES_LEN * 2;
+        let mut analyzer = Q15Analyzer::new(len, None);
+        assert_eq!(analyzer.analyze(&vec![0; len]).unwrap().len(), len / 2 + 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_len() {
+        let _ = Q15Analyzer::new(100, None);
+    }
+}
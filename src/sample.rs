@@ -0,0 +1,116 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [`Sample`] trait that allows to pass samples of common
+//! formats, such as `i16` of WAV files, directly into
+//! [`crate::samples_fft_to_spectrum`].
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+/// A sample that can be converted to a `f32` sample. Integer samples are
+/// normalized to the range `[-1.0, 1.0)`, e.g. `i16::MIN` becomes `-1.0`.
+/// Unsigned samples, such as 8-bit PCM, are centered around zero first.
+pub trait Sample: Copy {
+    /// Converts the sample to `f32`.
+    fn to_f32(self) -> f32;
+
+    /// Converts all samples to `f32`. This only allocates if a conversion is
+    /// necessary.
+    #[must_use]
+    fn to_f32_samples(samples: &[Self]) -> Cow<'_, [f32]> {
+        Cow::Owned(
+            samples
+                .iter()
+                .map(|sample| sample.to_f32())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl Sample for f32 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    #[inline]
+    fn to_f32_samples(samples: &[Self]) -> Cow<'_, [f32]> {
+        Cow::Borrowed(samples)
+    }
+}
+
+impl Sample for f64 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+impl Sample for i16 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        f32::from(self) / 32768.0
+    }
+}
+
+impl Sample for i32 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        (f64::from(self) / 2_147_483_648.0) as f32
+    }
+}
+
+impl Sample for u8 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        (f32::from(self) - 128.0) / 128.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(i16::MIN.to_f32(), -1.0);
+        assert_eq!(0_i16.to_f32(), 0.0);
+        assert_eq!(16384_i16.to_f32(), 0.5);
+        assert_eq!(i32::MIN.to_f32(), -1.0);
+        assert_eq!((1_i32 << 30).to_f32(), 0.5);
+        assert_eq!(0_u8.to_f32(), -1.0);
+        assert_eq!(128_u8.to_f32(), 0.0);
+        assert_eq!(192_u8.to_f32(), 0.5);
+        assert_eq!(0.25_f64.to_f32(), 0.25);
+    }
+
+    #[test]
+    fn test_f32_is_borrowed() {
+        let samples = [0.1_f32, 0.2];
+        assert!(matches!(
+            f32::to_f32_samples(&samples),
+            Cow::Borrowed(borrowed) if borrowed == samples
+        ));
+    }
+}
@@ -26,7 +26,12 @@ SOFTWARE.
 //! frequency value (the FFT result). They act as "idea/inspiration". Feel free
 //! to either compose them or create your own derivation from them.
 
+use crate::error::SpectrumAnalyzerError;
+use crate::spectrum::FrequencySpectrum;
+use crate::windows::WindowCoefficients;
 use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
 
 /// Helper struct for [`SpectrumScalingFunction`] that is passed into the
 /// scaling function together with the current frequency value. This structure
@@ -35,7 +40,11 @@ use alloc::boxed::Box;
 ///
 /// This uses `f32` in favor of [`crate::FrequencyValue`] because the latter led to
 /// some implementation problems.
+///
+/// More statistics may be added in the future. Hence, the struct is
+/// `#[non_exhaustive]` and only created by this crate.
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct SpectrumDataStats {
     /// Minimal frequency value in spectrum.
     pub min: f32,
@@ -45,6 +54,12 @@ pub struct SpectrumDataStats {
     pub average: f32,
     /// Median frequency value in spectrum.
     pub median: f32,
+    /// Sum of all frequency values in spectrum.
+    pub sum: f32,
+    /// Sum of the squares of all frequency values in spectrum.
+    pub sum_of_squares: f32,
+    /// Population standard deviation of the frequency values in spectrum.
+    pub std_dev: f32,
     /// Number of samples (`samples.len()`). Already casted to f32, to avoid
     /// repeatedly casting in a loop for each value.
     pub n: f32,
@@ -66,6 +81,29 @@ pub struct SpectrumDataStats {
 /// some implementation problems.
 pub type SpectrumScalingFunction = dyn Fn(f32, &SpectrumDataStats) -> f32;
 
+/// Like [`SpectrumScalingFunction`] but the function additionally receives the
+/// frequency in Hertz of the current value as first parameter. The parameters
+/// are `(frequency, frequency value, stats)`. This allows frequency dependent
+/// scaling, e.g. weighting curves or a microphone calibration.
+///
+/// Use it with [`crate::FrequencySpectrum::apply_frequency_scaling_fn`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let mut spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// let mut working_buffer = spectrum.data().to_vec();
+/// // +3 dB/octave tilt relative to 1 kHz
+/// spectrum
+///     .apply_frequency_scaling_fn(
+///         &|fr, val, _stats| val * libm::sqrtf(fr / 1000.0),
+///         &mut working_buffer,
+///     )
+///     .unwrap();
+/// ```
+pub type SpectrumFrequencyScalingFunction = dyn Fn(f32, f32, &SpectrumDataStats) -> f32;
+
 /// Calculates the base 10 logarithm of each frequency magnitude and
 /// multiplies it with 20. This scaling is quite common, you can
 /// find more information for example here:
@@ -142,6 +180,71 @@ pub fn divide_by_N_sqrt(fr_val: f32, stats: &SpectrumDataStats) -> f32 {
     }
 }
 
+/// Scales each frequency value/amplitude in the spectrum linearly to
+/// interval `[0.0; 1.0]`, so that [`SpectrumDataStats::min`] becomes `0.0` and
+/// [`SpectrumDataStats::max`] becomes `1.0`. Unlike [`scale_to_zero_to_one`],
+/// this also works for negative values, such as values in dB. If all values
+/// are equal, each value becomes `0.0`.
+/// Function is of type [`SpectrumScalingFunction`].
+#[must_use]
+pub fn scale_min_max(fr_val: f32, stats: &SpectrumDataStats) -> f32 {
+    debug_assert!(!fr_val.is_infinite());
+    debug_assert!(!fr_val.is_nan());
+    let range = stats.max - stats.min;
+    if range != 0.0 {
+        (fr_val - stats.min) / range
+    } else {
+        0.0
+    }
+}
+
+/// Divides each frequency value/amplitude by the sum of all values, so that
+/// the values sum up to `1.0` afterwards. Useful to treat the spectrum as a
+/// distribution. Expects that [`SpectrumDataStats::min`] is not negative.
+/// Function is of type [`SpectrumScalingFunction`].
+#[must_use]
+pub fn normalize_to_unit_sum(fr_val: f32, stats: &SpectrumDataStats) -> f32 {
+    debug_assert!(!fr_val.is_infinite());
+    debug_assert!(!fr_val.is_nan());
+    debug_assert!(fr_val >= 0.0);
+    if stats.sum != 0.0 {
+        fr_val / stats.sum
+    } else {
+        0.0
+    }
+}
+
+/// Divides each frequency value/amplitude by the square root of the sum of
+/// all squared values, so that the power (sum of squares) of the spectrum is
+/// `1.0` afterwards.
+/// Function is of type [`SpectrumScalingFunction`].
+#[must_use]
+pub fn normalize_to_unit_power(fr_val: f32, stats: &SpectrumDataStats) -> f32 {
+    debug_assert!(!fr_val.is_infinite());
+    debug_assert!(!fr_val.is_nan());
+    if stats.sum_of_squares != 0.0 {
+        fr_val / libm::sqrtf(stats.sum_of_squares)
+    } else {
+        0.0
+    }
+}
+
+/// Standardizes each frequency value/amplitude, i.e., subtracts the average
+/// and divides by the standard deviation (z-score). Afterwards, the values
+/// have an average of `0.0` and a standard deviation of `1.0`. If all values
+/// are equal, each value becomes `0.0`.
+/// Function is of type [`SpectrumScalingFunction`].
+#[must_use]
+pub fn z_score(fr_val: f32, stats: &SpectrumDataStats) -> f32 {
+    debug_assert!(!fr_val.is_infinite());
+    debug_assert!(!fr_val.is_nan());
+    if stats.std_dev != 0.0 {
+        (fr_val - stats.average) / stats.std_dev
+    } else {
+        0.0
+    }
+}
+
 /// Combines several scaling functions into a new single one.
 ///
 /// Currently there is the limitation that the functions need to have
@@ -162,6 +265,326 @@ pub fn combined(fncs: &'static [&SpectrumScalingFunction]) -> Box<SpectrumScalin
     })
 }
 
+/// Creates a scaling function that converts each magnitude into decibels
+/// relative to `reference`, i.e. `20 * log10(fr_val / reference)`. Values
+/// below `floor_db`, including zero magnitudes that would result in negative
+/// infinity, are clamped to `floor_db`. Unlike [`scale_20_times_log10`],
+/// a magnitude of zero doesn't result in `0 dB`.
+///
+/// ## Parameters
+/// * `reference` Magnitude that corresponds to `0 dB`. Must be positive.
+/// * `floor_db` Lowest value in dB, e.g. `-120.0`.
+///
+/// ## Panics
+/// If `reference` is not positive.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::scaling::to_db;
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let spectrum =
+///     samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, Some(&to_db(1.0, -120.0)))
+///         .unwrap();
+/// ```
+#[must_use]
+pub fn to_db(reference: f32, floor_db: f32) -> Box<SpectrumScalingFunction> {
+    assert!(reference > 0.0, "The reference must be positive!");
+    Box::new(move |fr_val, _stats| amplitude_to_db(fr_val / reference, floor_db))
+}
+
+/// Creates a scaling function that converts each magnitude into decibels
+/// relative to full scale (dBFS). A sine with the full-scale amplitude `1.0`
+/// at the frequency of a bin results in `0 dBFS`. This expects unscaled
+/// magnitudes of samples normalized to `[-1.0, 1.0]`, e.g. of integer
+/// samples converted by [`crate::sample::Sample`]. Values below `floor_db`
+/// are clamped to `floor_db`.
+///
+/// ## Parameters
+/// * `samples_len` Number of samples before zero-padding. Padding doesn't
+///                 increase the magnitude of a sine, so [`SpectrumDataStats::n`]
+///                 can't be used for padded samples.
+/// * `floor_db` Lowest value in dB, e.g. `-120.0`.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::scaling::to_dbfs;
+/// use spectrum_analyzer::{samples_fft_to_spectrum_generic, FrequencyLimit};
+/// // full-scale sine at 11025 Hz
+/// let samples = [0_i16, i16::MAX, 0, -i16::MAX];
+/// let spectrum =
+///     samples_fft_to_spectrum_generic(
+///         &samples,
+///         44100,
+///         FrequencyLimit::All,
+///         Some(&to_dbfs(samples.len(), -120.0)),
+///     )
+///     .unwrap();
+/// assert!(spectrum.freq_val_exact(11025.0).val().abs() < 0.001);
+/// assert_eq!(spectrum.freq_val_exact(0.0).val(), -120.0);
+/// ```
+///
+/// ## Panics
+/// If `samples_len` is zero.
+#[must_use]
+pub fn to_dbfs(samples_len: usize, floor_db: f32) -> Box<SpectrumScalingFunction> {
+    assert_ne!(samples_len, 0, "There must be at least one sample!");
+    // the magnitude of a full-scale sine is N / 2
+    let full_scale = samples_len as f32 / 2.0;
+    Box::new(move |fr_val, _stats| amplitude_to_db(fr_val / full_scale, floor_db))
+}
+
+/// Converts an amplitude ratio into decibels, clamped to `floor_db`.
+fn amplitude_to_db(ratio: f32, floor_db: f32) -> f32 {
+    if ratio <= 0.0 {
+        floor_db
+    } else {
+        (20.0 * libm::log10f(ratio)).max(floor_db)
+    }
+}
+
+/// Creates a scaling function that converts the magnitudes of a windowed
+/// spectrum into the true amplitudes of sines, i.e. `2 * |X| / sum(w[n])`.
+/// The sum of the window equals `L * CG`, where `L` is the length of the
+/// window and `CG` is the [`WindowCoefficients::coherent_gain`]. As `L` is
+/// the number of samples before zero-padding, this also works for padded
+/// samples. The factor `2` accounts for the energy of the negative
+/// frequencies. Hence, the value of the DC component and the Nyquist
+/// frequency is twice their amplitude.
+///
+/// ## Parameters
+/// * `coefficients` Coefficients of the window that was applied to the
+///                  samples.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::scaling::window_amplitude_correction;
+/// use spectrum_analyzer::windows::{Window, WindowCoefficients};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = (0..1024)
+///     .map(|i| 0.7 * (2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 8192.0).sin())
+///     .collect::<Vec<_>>();
+/// let coefficients = WindowCoefficients::new(Window::Hann, samples.len());
+/// let spectrum = samples_fft_to_spectrum(
+///     &Window::Hann.apply(&samples),
+///     8192,
+///     FrequencyLimit::All,
+///     Some(&window_amplitude_correction(&coefficients)),
+/// )
+/// .unwrap();
+/// assert!((spectrum.max().1.val() - 0.7).abs() < 0.001);
+/// # }
+/// ```
+#[must_use]
+pub fn window_amplitude_correction(
+    coefficients: &WindowCoefficients,
+) -> Box<SpectrumScalingFunction> {
+    let coefficient_sum = coefficients.as_slice().iter().sum::<f32>();
+    Box::new(move |fr_val, _stats| 2.0 * fr_val / coefficient_sum)
+}
+
+/// Creates a scaling function that converts the magnitudes of a windowed
+/// spectrum into a one-sided power spectral density in `V²/Hz` (or
+/// `unit²/Hz`, depending on the unit of the samples), i.e.
+/// `2 * |X|² / (fs * sum(w[n]²))`. This incorporates the
+/// [`WindowCoefficients::enbw`] of the window, so that the noise density is
+/// independent of the window. See also [`crate::psd::welch_psd`], which
+/// additionally averages several segments.
+///
+/// ## Parameters
+/// * `coefficients` Coefficients of the window that was applied to the
+///                  samples.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+#[must_use]
+pub fn window_power_density_correction(
+    coefficients: &WindowCoefficients,
+    sampling_rate: u32,
+) -> Box<SpectrumScalingFunction> {
+    let power_sum = coefficients.as_slice().iter().map(|w| w * w).sum::<f32>();
+    let normalization = sampling_rate as f32 * power_sum;
+    Box::new(move |fr_val, _stats| 2.0 * fr_val * fr_val / normalization)
+}
+
+/// Creates a frequency dependent scaling function that applies a tilt of
+/// `db_per_octave` to magnitudes. The magnitude at `reference_frequency`
+/// stays the same. For example, a tilt of `+3 dB/octave` compensates the
+/// slope of pink noise and many music spectra, so that they appear flat in a
+/// visualization. The value at `0 Hz` is not changed, as it has no octave.
+///
+/// The values of the spectrum are expected to be magnitudes (and not in
+/// decibel). Use [`tilt_db`] for values in decibel.
+///
+/// ## Parameters
+/// * `db_per_octave` Gain in dB per octave. Positive values boost high
+///                   frequencies.
+/// * `reference_frequency` Frequency in Hertz with a gain of `0 dB`.
+///
+/// ## Panics
+/// If `reference_frequency` is not positive.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::scaling::tilt;
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let mut spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// let mut working_buffer = spectrum.data().to_vec();
+/// spectrum
+///     .apply_frequency_scaling_fn(&tilt(3.0, 1000.0), &mut working_buffer)
+///     .unwrap();
+/// ```
+#[must_use]
+pub fn tilt(db_per_octave: f32, reference_frequency: f32) -> Box<SpectrumFrequencyScalingFunction> {
+    assert!(
+        reference_frequency > 0.0,
+        "The reference frequency must be positive!"
+    );
+    // (fr / reference)^exponent == 10^(db_per_octave * log2(fr / reference) / 20)
+    let exponent = db_per_octave / (20.0 * libm::log10f(2.0));
+    Box::new(move |fr, fr_val, _stats| {
+        if fr > 0.0 {
+            fr_val * libm::powf(fr / reference_frequency, exponent)
+        } else {
+            fr_val
+        }
+    })
+}
+
+/// Like [`tilt`] but for values in decibel. The tilt is added to each value.
+///
+/// ## Parameters
+/// * `db_per_octave` Gain in dB per octave. Positive values boost high
+///                   frequencies.
+/// * `reference_frequency` Frequency in Hertz with a gain of `0 dB`.
+///
+/// ## Panics
+/// If `reference_frequency` is not positive.
+#[must_use]
+pub fn tilt_db(
+    db_per_octave: f32,
+    reference_frequency: f32,
+) -> Box<SpectrumFrequencyScalingFunction> {
+    assert!(
+        reference_frequency > 0.0,
+        "The reference frequency must be positive!"
+    );
+    Box::new(move |fr, fr_val, _stats| {
+        if fr > 0.0 {
+            fr_val + db_per_octave * libm::log2f(fr / reference_frequency)
+        } else {
+            fr_val
+        }
+    })
+}
+
+/// Calculates the population standard deviation for
+/// [`SpectrumDataStats::std_dev`].
+pub(crate) fn std_dev(sum_of_squares: f32, average: f32, len: usize) -> f32 {
+    // clamp, as rounding errors may lead to a slightly negative variance
+    let variance = (sum_of_squares / len as f32 - average * average).max(0.0);
+    libm::sqrtf(variance)
+}
+
+/// A step of a [`ScalingChain`].
+enum ScalingStep {
+    Value(Box<SpectrumScalingFunction>),
+    Frequency(Box<SpectrumFrequencyScalingFunction>),
+}
+
+/// Sequence of scaling steps that are applied to a spectrum one after
+/// another. Unlike [`combined`], the [`SpectrumDataStats`] are updated after
+/// each step, so that each step sees the statistics of the values it
+/// scales. For example, normalizing to the maximum after a conversion to dB
+/// uses the maximum in dB. The steps can be any mix of
+/// [`SpectrumScalingFunction`] and [`SpectrumFrequencyScalingFunction`].
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::scaling::{divide_by_N, to_db, ScalingChain};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0, 1.1, 5.5, -5.5];
+/// let mut spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+/// let chain = ScalingChain::new()
+///     .then(divide_by_N)
+///     .then(to_db(1.0, -120.0))
+///     // relative to the maximum in dB
+///     .then(|val, stats| val - stats.max);
+/// chain.apply(&mut spectrum).unwrap();
+/// assert_eq!(spectrum.max().1.val(), 0.0);
+/// ```
+#[derive(Default)]
+pub struct ScalingChain {
+    steps: Vec<ScalingStep>,
+}
+
+impl Debug for ScalingChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScalingChain")
+            .field("steps", &self.steps.len())
+            .finish()
+    }
+}
+
+impl ScalingChain {
+    /// Creates an empty chain.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Appends a [`SpectrumScalingFunction`] to the chain.
+    #[must_use]
+    pub fn then(mut self, scaling_fn: impl Fn(f32, &SpectrumDataStats) -> f32 + 'static) -> Self {
+        self.steps.push(ScalingStep::Value(Box::new(scaling_fn)));
+        self
+    }
+
+    /// Appends a [`SpectrumFrequencyScalingFunction`] to the chain.
+    #[must_use]
+    pub fn then_frequency(
+        mut self,
+        scaling_fn: impl Fn(f32, f32, &SpectrumDataStats) -> f32 + 'static,
+    ) -> Self {
+        self.steps
+            .push(ScalingStep::Frequency(Box::new(scaling_fn)));
+        self
+    }
+
+    /// Returns the number of steps.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns whether the chain has no steps.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Applies all steps to the spectrum in the order they were appended.
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::ScalingError`] if a step produced invalid
+    /// values. The remaining steps are not applied then.
+    pub fn apply(&self, spectrum: &mut FrequencySpectrum) -> Result<(), SpectrumAnalyzerError> {
+        let mut working_buffer = spectrum.data().to_vec();
+        for step in &self.steps {
+            match step {
+                ScalingStep::Value(scaling_fn) => {
+                    spectrum.apply_scaling_fn(scaling_fn.as_ref(), &mut working_buffer)?;
+                }
+                ScalingStep::Frequency(scaling_fn) => {
+                    spectrum
+                        .apply_frequency_scaling_fn(scaling_fn.as_ref(), &mut working_buffer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,6 +598,9 @@ mod tests {
             max: data[data.len() - 1],
             average: data.iter().sum::<f32>() / data.len() as f32,
             median: (2.2 + 3.3) / 2.0,
+            sum: 16.5,
+            sum_of_squares: 66.55,
+            std_dev: 1.878,
             n: data.len() as f32,
         };
         // check that type matches
@@ -183,12 +609,199 @@ mod tests {
             .into_iter()
             .map(|x| scaling_fn(x, &stats))
             .collect::<Vec<_>>();
-        let expected = vec![0.0_f32, 0.2, 0.4, 0.6, 0.8, 1.0];
+        let expected = [0.0_f32, 0.2, 0.4, 0.6, 0.8, 1.0];
         for (expected_val, actual_val) in expected.iter().zip(scaled_data.iter()) {
             float_cmp::approx_eq!(f32, *expected_val, *actual_val, ulps = 3);
         }
     }
 
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_window_power_density_correction() {
+        use crate::windows::Window;
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        // sine with amplitude 2.0, i.e. power 2.0
+        let samples = (0..1024)
+            .map(|i| 2.0 * libm::sinf(2.0 * core::f32::consts::PI * 1003.0 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        for window in [Window::Rectangular, Window::Hann, Window::FlatTop] {
+            let coefficients = WindowCoefficients::new(window, samples.len());
+            let psd = samples_fft_to_spectrum(
+                &window.apply(&samples),
+                8192,
+                FrequencyLimit::All,
+                Some(&window_power_density_correction(&coefficients, 8192)),
+            )
+            .unwrap();
+            let power = psd.data().iter().map(|(_fr, val)| val.val()).sum::<f32>()
+                * psd.frequency_resolution();
+            assert!((power - 2.0).abs() < 0.01, "{window:?}: {power}");
+        }
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_amplitude_correction_of_padded_samples() {
+        use crate::windows::Window;
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        let mut samples = (0..512)
+            .map(|i| 0.7 * libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        let coefficients = WindowCoefficients::new(Window::Hann, samples.len());
+        coefficients.apply(&mut samples);
+        // zero-padding to 1024 samples
+        samples.resize(1024, 0.0);
+        let spectrum = samples_fft_to_spectrum(
+            &samples,
+            8192,
+            FrequencyLimit::All,
+            Some(&window_amplitude_correction(&coefficients)),
+        )
+        .unwrap();
+        assert!((spectrum.max().1.val() - 0.7).abs() < 0.001);
+
+        let mut samples = (0..512)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        samples.resize(1024, 0.0);
+        let spectrum = samples_fft_to_spectrum(
+            &samples,
+            8192,
+            FrequencyLimit::All,
+            Some(&to_dbfs(512, -120.0)),
+        )
+        .unwrap();
+        assert!(spectrum.max().1.val().abs() < 0.001);
+    }
+
+    #[test]
+    fn test_to_db() {
+        let stats = SpectrumDataStats {
+            min: 0.0,
+            max: 10.0,
+            average: 5.0,
+            median: 5.0,
+            sum: 20.0,
+            sum_of_squares: 100.0,
+            std_dev: 5.0,
+            n: 8.0,
+        };
+        let to_db = to_db(0.1, -120.0);
+        assert_eq!(to_db(1.0, &stats), 20.0);
+        assert_eq!(to_db(0.1, &stats), 0.0);
+        assert_eq!(to_db(0.0, &stats), -120.0);
+        assert_eq!(to_db(1e-10, &stats), -120.0);
+        let to_dbfs = to_dbfs(8, -90.0);
+        assert_eq!(to_dbfs(4.0, &stats), 0.0);
+        assert!((to_dbfs(2.0, &stats) + 6.020_6).abs() < 1e-4);
+        assert_eq!(to_dbfs(0.0, &stats), -90.0);
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_normalize() {
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        let samples = [0.0, 1.1, 5.5, -5.5, 3.3, 0.0, -1.0, 2.0];
+        let values = |scaling_fn: &SpectrumScalingFunction| {
+            samples_fft_to_spectrum(&samples, 8, FrequencyLimit::All, Some(scaling_fn))
+                .unwrap()
+                .data()
+                .iter()
+                .map(|(_fr, val)| val.val())
+                .collect::<Vec<_>>()
+        };
+
+        let min_max = values(&scale_min_max);
+        let min = min_max.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = min_max.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert_eq!((min, max), (0.0, 1.0));
+
+        let unit_sum = values(&normalize_to_unit_sum).iter().sum::<f32>();
+        assert!((unit_sum - 1.0).abs() < 1e-6);
+
+        let unit_power = values(&normalize_to_unit_power)
+            .iter()
+            .map(|val| val * val)
+            .sum::<f32>();
+        assert!((unit_power - 1.0).abs() < 1e-6);
+
+        let z_scores = values(&z_score);
+        let len = z_scores.len() as f32;
+        let average = z_scores.iter().sum::<f32>() / len;
+        let variance = z_scores.iter().map(|val| val * val).sum::<f32>() / len;
+        assert!(average.abs() < 1e-6);
+        assert!((variance - 1.0).abs() < 1e-5);
+
+        // equal values
+        let stats = SpectrumDataStats {
+            min: 2.0,
+            max: 2.0,
+            average: 2.0,
+            median: 2.0,
+            sum: 8.0,
+            sum_of_squares: 16.0,
+            std_dev: 0.0,
+            n: 8.0,
+        };
+        assert_eq!(scale_min_max(2.0, &stats), 0.0);
+        assert_eq!(z_score(2.0, &stats), 0.0);
+    }
+
+    #[test]
+    fn test_tilt() {
+        let stats = SpectrumDataStats {
+            min: 0.0,
+            max: 1.0,
+            average: 1.0,
+            median: 1.0,
+            sum: 4.0,
+            sum_of_squares: 4.0,
+            std_dev: 0.0,
+            n: 8.0,
+        };
+        let tilt = tilt(3.0, 1000.0);
+        let tilt_db = tilt_db(3.0, 1000.0);
+        assert_eq!(tilt(1000.0, 1.0, &stats), 1.0);
+        assert_eq!(tilt(0.0, 1.0, &stats), 1.0);
+        for (fr, expected_db) in [(2000.0, 3.0), (4000.0, 6.0), (500.0, -3.0), (125.0, -9.0)] {
+            let db = 20.0 * libm::log10f(tilt(fr, 1.0, &stats));
+            assert!((db - expected_db).abs() < 1e-4, "{fr}: {db}");
+            assert!((tilt_db(fr, -10.0, &stats) - (expected_db - 10.0)).abs() < 1e-4);
+        }
+        assert_eq!(tilt_db(0.0, -10.0, &stats), -10.0);
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_scaling_chain() {
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        let samples = [0.0, 1.1, 5.5, -5.5, 3.3, 0.0, -1.0, 2.0];
+        let mut spectrum = samples_fft_to_spectrum(&samples, 8, FrequencyLimit::All, None).unwrap();
+        let unscaled = spectrum.data().to_vec();
+        let chain = ScalingChain::new()
+            .then(divide_by_N)
+            .then_frequency(|fr, val, _stats| val * fr)
+            .then(scale_to_zero_to_one);
+        assert_eq!(chain.len(), 3);
+        chain.apply(&mut spectrum).unwrap();
+        // the last step sees the maximum after the second step
+        assert_eq!(spectrum.max().1.val(), 1.0);
+        let max_after_second_step = unscaled
+            .iter()
+            .map(|(fr, val)| val.val() / 8.0 * fr.val())
+            .fold(0.0, f32::max);
+        for ((fr, val), (_fr, unscaled)) in spectrum.data().iter().zip(&unscaled) {
+            let expected = unscaled.val() / 8.0 * fr.val() / max_after_second_step;
+            assert!((val.val() - expected).abs() < 1e-6);
+        }
+
+        let failing = ScalingChain::new().then(|_val, _stats| f32::NAN);
+        assert!(matches!(
+            failing.apply(&mut spectrum),
+            Err(SpectrumAnalyzerError::ScalingError(..))
+        ));
+    }
+
     // make sure this compiles
     #[test]
     fn test_combined_compiles() {
@@ -0,0 +1,152 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for fractional-octave smoothing of a spectrum, as used in
+//! loudspeaker and room measurement plots. See
+//! [`crate::FrequencySpectrum::smooth`].
+
+use crate::frequency::{Frequency, FrequencyValue};
+use alloc::vec::Vec;
+
+/// Width of the smoothing window in octaves. Each value is replaced by the
+/// average of all values within the window centered around its frequency.
+/// Hence, the window gets wider in Hertz with increasing frequency.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OctaveSmoothing {
+    /// 1/1 octave.
+    Full,
+    /// 1/2 octave.
+    Half,
+    /// 1/3 octave.
+    Third,
+    /// 1/6 octave.
+    Sixth,
+    /// 1/12 octave.
+    Twelfth,
+    /// 1/24 octave.
+    TwentyFourth,
+    /// 1/48 octave.
+    FortyEighth,
+}
+
+impl OctaveSmoothing {
+    /// Returns the width of the window in octaves, e.g. `1/3` for
+    /// [`Self::Third`].
+    #[must_use]
+    pub fn octaves(self) -> f32 {
+        let fraction = match self {
+            Self::Full => 1.0,
+            Self::Half => 2.0,
+            Self::Third => 3.0,
+            Self::Sixth => 6.0,
+            Self::Twelfth => 12.0,
+            Self::TwentyFourth => 24.0,
+            Self::FortyEighth => 48.0,
+        };
+        1.0 / fraction
+    }
+}
+
+/// Implementation of [`crate::FrequencySpectrum::smooth`]. Returns the
+/// smoothed values in the same order as `data`. The value at `0 Hz` is kept.
+pub(crate) fn smooth(data: &[(Frequency, FrequencyValue)], smoothing: OctaveSmoothing) -> Vec<f32> {
+    // prefix sums make the average of each window O(1)
+    let mut prefix_sums = Vec::with_capacity(data.len() + 1);
+    prefix_sums.push(0.0_f64);
+    for (_fr, val) in data {
+        let sum = prefix_sums[prefix_sums.len() - 1] + f64::from(val.val());
+        prefix_sums.push(sum);
+    }
+
+    let half_window_factor = libm::exp2f(smoothing.octaves() / 2.0);
+    let mut from = 0;
+    let mut to = 0;
+    data.iter()
+        .map(|(fr, val)| {
+            let fr = fr.val();
+            if fr <= 0.0 {
+                return val.val();
+            }
+            let lower = Frequency::from(fr / half_window_factor);
+            let upper = Frequency::from(fr * half_window_factor);
+            // both window edges only move forward
+            while data[from].0 < lower {
+                from += 1;
+            }
+            while to < data.len() && data[to].0 <= upper {
+                to += 1;
+            }
+            ((prefix_sums[to] - prefix_sums[from]) / (to - from) as f64) as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smooth() {
+        let data = [0.0, 5.0, 10.0, 15.0, 20.0, 25.0, 30.0, 35.0]
+            .iter()
+            .enumerate()
+            .map(|(i, val)| {
+                (
+                    Frequency::from(i as f32 * 100.0),
+                    FrequencyValue::from(*val),
+                )
+            })
+            .collect::<Vec<_>>();
+        let smoothed = smooth(&data, OctaveSmoothing::Full);
+        // DC is kept
+        assert_eq!(smoothed[0], 0.0);
+        // 100 Hz: window [70.7, 141.4]
+        assert_eq!(smoothed[1], 5.0);
+        // 200 Hz: window [141.4, 282.8]
+        assert_eq!(smoothed[2], 10.0);
+        // 400 Hz: window [282.8, 565.7]
+        assert_eq!(smoothed[4], 20.0);
+        // 600 Hz: window [424.3, 848.5]
+        assert_eq!(smoothed[6], 30.0);
+        // 700 Hz: window [495.0, 989.9]
+        assert_eq!(smoothed[7], 30.0);
+
+        let spiky = [1.0, 1.0, 1.0, 9.0, 1.0, 1.0, 1.0, 1.0]
+            .iter()
+            .enumerate()
+            .map(|(i, val)| {
+                (
+                    Frequency::from(i as f32 * 100.0),
+                    FrequencyValue::from(*val),
+                )
+            })
+            .collect::<Vec<_>>();
+        let smoothed = smooth(&spiky, OctaveSmoothing::Full);
+        // 300 Hz: window [212.1, 424.3]
+        assert_eq!(smoothed[3], 5.0);
+        // 200 Hz: window [141.4, 282.8]
+        assert_eq!(smoothed[2], 1.0);
+        let smoothed = smooth(&spiky, OctaveSmoothing::FortyEighth);
+        assert_eq!(smoothed[3], 9.0);
+    }
+}
@@ -0,0 +1,390 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Sound level meter on top of consecutive spectra, e.g. of a
+//! [`crate::StreamingAnalyzer`]. The [`SoundLevelMeter`] applies a frequency
+//! weighting, aggregates the power into octave or third-octave bands,
+//! applies the exponential time weighting (Fast or Slow) and integrates the
+//! equivalent continuous sound level (Leq) over time.
+//!
+//! The levels are absolute sound pressure levels in dB, based on an
+//! [`SplCalibration`].
+
+use crate::calibration::SplCalibration;
+use crate::spectrum::FrequencySpectrum;
+use crate::weighting::Weighting;
+use alloc::vec::Vec;
+
+/// Exponential time weighting of a sound level meter according to
+/// IEC 61672-1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeWeighting {
+    /// Time constant of `125 ms`.
+    Fast,
+    /// Time constant of `1 s`.
+    Slow,
+}
+
+impl TimeWeighting {
+    /// Returns the time constant in seconds.
+    #[must_use]
+    pub const fn time_constant(self) -> f32 {
+        match self {
+            Self::Fast => 0.125,
+            Self::Slow => 1.0,
+        }
+    }
+}
+
+/// Width of the bands of a [`SoundLevelMeter`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OctaveBands {
+    /// Bands with a width of one octave.
+    Octave,
+    /// Bands with a width of a third octave.
+    ThirdOctave,
+}
+
+impl OctaveBands {
+    /// Returns the number of bands per octave.
+    #[must_use]
+    pub const fn bands_per_octave(self) -> u8 {
+        match self {
+            Self::Octave => 1,
+            Self::ThirdOctave => 3,
+        }
+    }
+
+    /// Returns the exact (base 2) center frequencies of all bands between
+    /// `min_fr` and `max_fr`, relative to `1 kHz`, in ascending order. For
+    /// example, the octave bands between `20 Hz` and `20 kHz` are centered
+    /// at `31.25 Hz`, `62.5 Hz`, ..., `16 kHz`.
+    ///
+    /// ## Panics
+    /// If `min_fr` is not positive or bigger than `max_fr`.
+    #[must_use]
+    pub fn center_frequencies(self, min_fr: f32, max_fr: f32) -> Vec<f32> {
+        assert!(min_fr > 0.0, "min_fr must be bigger than 0 Hz!");
+        assert!(min_fr <= max_fr, "min_fr must not be bigger than max_fr!");
+        let bands_per_octave = f32::from(self.bands_per_octave());
+        let first = libm::ceilf(libm::log2f(min_fr / 1000.0) * bands_per_octave) as i32;
+        let last = libm::floorf(libm::log2f(max_fr / 1000.0) * bands_per_octave) as i32;
+        (first..=last)
+            .map(|k| 1000.0 * libm::exp2f(k as f32 / bands_per_octave))
+            .collect()
+    }
+}
+
+/// Configuration of a [`SoundLevelMeter`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SoundLevelMeterConfig {
+    /// Frequency weighting of all levels.
+    pub weighting: Weighting,
+    /// Time weighting of the levels returned by
+    /// [`SoundLevelMeter::process`].
+    pub time_weighting: TimeWeighting,
+    /// Width of the bands.
+    pub bands: OctaveBands,
+    /// Lowest center frequency of the bands in Hertz.
+    pub min_fr: f32,
+    /// Highest center frequency of the bands in Hertz.
+    pub max_fr: f32,
+}
+
+impl Default for SoundLevelMeterConfig {
+    fn default() -> Self {
+        Self {
+            weighting: Weighting::A,
+            time_weighting: TimeWeighting::Fast,
+            bands: OctaveBands::Octave,
+            min_fr: 20.0,
+            max_fr: 20000.0,
+        }
+    }
+}
+
+/// Sound pressure level of a single band.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BandLevel {
+    /// Center frequency of the band in Hertz.
+    pub center_fr: f32,
+    /// Sound pressure level in dB.
+    pub level_db: f32,
+}
+
+/// Sound pressure levels of a [`SoundLevelMeter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundLevels {
+    /// Overall sound pressure level of all frequencies in dB.
+    pub level_db: f32,
+    /// Sound pressure level of each band, from the lowest to the highest
+    /// band.
+    pub bands: Vec<BandLevel>,
+}
+
+/// Sound level meter that processes consecutive spectra. See the
+/// [module documentation](self).
+///
+/// All spectra must have the same settings as the reference of the
+/// [`SplCalibration`] and must contain magnitudes (and not decibel values).
+/// Power of a tone that a window function spreads into neighbouring bands is
+/// counted for these bands.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::calibration::SplCalibration;
+/// use spectrum_analyzer::sound_level::{SoundLevelMeter, SoundLevelMeterConfig};
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// # let samples = (0..4096)
+/// #    .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 48000.0))
+/// #    .collect::<Vec<_>>();
+/// let mut meter =
+///     SoundLevelMeter::new(SoundLevelMeterConfig::default(), SplCalibration::from_offset_db(94.0));
+/// for frame in samples.chunks_exact(1024) {
+///     let spectrum = samples_fft_to_spectrum(frame, 48000, FrequencyLimit::All, None).unwrap();
+///     let levels = meter.process(&spectrum, 1024.0 / 48000.0);
+///     println!("LAF: {} dB", levels.level_db);
+/// }
+/// println!("LAeq: {} dB", meter.leq().unwrap().level_db);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SoundLevelMeter {
+    config: SoundLevelMeterConfig,
+    calibration: SplCalibration,
+    center_frequencies: Vec<f32>,
+    /// Time weighted power of all frequencies, followed by the time weighted
+    /// power of each band. Empty before the first spectrum.
+    time_weighted_power: Vec<f32>,
+    /// Integrated energy in the same layout as `time_weighted_power`.
+    energy: Vec<f32>,
+    /// Integrated time in seconds.
+    duration: f32,
+}
+
+impl SoundLevelMeter {
+    /// Creates a new sound level meter.
+    ///
+    /// ## Parameters
+    /// * `config` See [`SoundLevelMeterConfig`].
+    /// * `calibration` See [`SplCalibration`].
+    ///
+    /// ## Panics
+    /// If `config.min_fr` is not positive or bigger than `config.max_fr`.
+    #[must_use]
+    pub fn new(config: SoundLevelMeterConfig, calibration: SplCalibration) -> Self {
+        let center_frequencies = config
+            .bands
+            .center_frequencies(config.min_fr, config.max_fr);
+        Self {
+            config,
+            calibration,
+            center_frequencies,
+            time_weighted_power: Vec::new(),
+            energy: Vec::new(),
+            duration: 0.0,
+        }
+    }
+
+    /// Returns the configuration.
+    #[must_use]
+    pub const fn config(&self) -> &SoundLevelMeterConfig {
+        &self.config
+    }
+
+    /// Returns the center frequencies of the bands in ascending order.
+    #[must_use]
+    pub fn center_frequencies(&self) -> &[f32] {
+        &self.center_frequencies
+    }
+
+    /// Processes the next spectrum and returns the time weighted levels.
+    /// The first spectrum initializes the time weighting.
+    ///
+    /// ## Parameters
+    /// * `spectrum` Spectrum of the next frame.
+    /// * `frame_duration` Time in seconds between the beginnings of this
+    ///                    and the previous frame, i.e. the hop length
+    ///                    divided by the sampling rate.
+    ///
+    /// ## Panics
+    /// If `frame_duration` is not positive.
+    pub fn process(&mut self, spectrum: &FrequencySpectrum, frame_duration: f32) -> SoundLevels {
+        assert!(frame_duration > 0.0, "frame_duration must be positive!");
+        let powers = self.band_powers(spectrum);
+
+        if self.time_weighted_power.is_empty() {
+            self.time_weighted_power = powers.clone();
+            self.energy = alloc::vec![0.0; powers.len()];
+        } else {
+            let alpha =
+                1.0 - libm::expf(-frame_duration / self.config.time_weighting.time_constant());
+            for (weighted, power) in self.time_weighted_power.iter_mut().zip(&powers) {
+                *weighted += alpha * (power - *weighted);
+            }
+        }
+
+        for (energy, power) in self.energy.iter_mut().zip(&powers) {
+            *energy += power * frame_duration;
+        }
+        self.duration += frame_duration;
+
+        self.levels(self.time_weighted_power.iter().copied())
+    }
+
+    /// Returns the equivalent continuous sound levels (Leq) of all spectra
+    /// since the creation or the last [`Self::reset`], i.e. the levels of
+    /// the average power. `None` if no spectrum was processed.
+    #[must_use]
+    pub fn leq(&self) -> Option<SoundLevels> {
+        if self.duration == 0.0 {
+            return None;
+        }
+        Some(self.levels(self.energy.iter().map(|energy| energy / self.duration)))
+    }
+
+    /// Resets the time weighting and the Leq integration.
+    pub fn reset(&mut self) {
+        self.time_weighted_power.clear();
+        self.energy.clear();
+        self.duration = 0.0;
+    }
+
+    /// Calculates the frequency weighted power of all frequencies (except the
+    /// DC component), followed by the power of each band.
+    fn band_powers(&self, spectrum: &FrequencySpectrum) -> Vec<f32> {
+        let bands_per_octave = f32::from(self.config.bands.bands_per_octave());
+        let half_band = libm::exp2f(1.0 / (2.0 * bands_per_octave));
+        let half_resolution = spectrum.frequency_resolution() / 2.0;
+
+        let mut powers = alloc::vec![0.0; self.center_frequencies.len() + 1];
+        for (fr, fr_val) in spectrum.data() {
+            let fr = fr.val();
+            if fr <= 0.0 {
+                continue;
+            }
+            let gain = self.config.weighting.gain(fr);
+            let power = fr_val.val() * fr_val.val() * gain * gain;
+            powers[0] += power;
+
+            // same as `FrequencySpectrum::band_energy`: values that are only
+            // partially inside a band contribute proportionally
+            let lower = fr - half_resolution;
+            let upper = fr + half_resolution;
+            for (band_power, center) in powers[1..].iter_mut().zip(&self.center_frequencies) {
+                let overlap =
+                    (upper.min(center * half_band) - lower.max(center / half_band)).max(0.0);
+                *band_power += power * overlap / spectrum.frequency_resolution();
+            }
+        }
+        powers
+    }
+
+    /// Converts powers in the layout of `time_weighted_power` to levels.
+    fn levels(&self, mut powers: impl Iterator<Item = f32>) -> SoundLevels {
+        let level_db = self
+            .calibration
+            .power_to_spl_db(powers.next().unwrap_or(0.0));
+        let bands = self
+            .center_frequencies
+            .iter()
+            .zip(powers)
+            .map(|(&center_fr, power)| BandLevel {
+                center_fr,
+                level_db: self.calibration.power_to_spl_db(power),
+            })
+            .collect();
+        SoundLevels { level_db, bands }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+    use core::f32::consts::PI;
+
+    /// Spectrum of a 1 kHz sine, that exactly matches a frequency of the
+    /// spectrum.
+    fn sine_spectrum(amplitude: f32) -> FrequencySpectrum {
+        let samples = (0..1024)
+            .map(|i| amplitude * libm::sinf(2.0 * PI * 1000.0 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        samples_fft_to_spectrum(&samples, 8192, FrequencyLimit::All, None).unwrap()
+    }
+
+    #[test]
+    fn test_center_frequencies() {
+        let octaves = OctaveBands::Octave.center_frequencies(20.0, 20000.0);
+        assert_eq!(octaves.len(), 10);
+        assert_eq!(octaves[0], 31.25);
+        assert_eq!(octaves[9], 16000.0);
+        let thirds = OctaveBands::ThirdOctave.center_frequencies(900.0, 1300.0);
+        assert_eq!(thirds.len(), 2);
+        assert_eq!(thirds[0], 1000.0);
+        assert!((thirds[1] - 1259.921).abs() < 1e-3);
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_sound_level_meter() {
+        let calibration =
+            SplCalibration::from_reference(&sine_spectrum(1.0), 1000.0, 94.0, 0).unwrap();
+        let mut meter = SoundLevelMeter::new(SoundLevelMeterConfig::default(), calibration);
+        assert!(meter.leq().is_none());
+
+        let levels = meter.process(&sine_spectrum(1.0), 0.125);
+        assert!((levels.level_db - 94.0).abs() < 0.01);
+        let band = levels
+            .bands
+            .iter()
+            .find(|band| band.center_fr == 1000.0)
+            .unwrap();
+        assert!((band.level_db - 94.0).abs() < 0.01);
+        assert!(levels.bands[0].level_db < 0.0);
+
+        // after one time constant, the power decays to 1/e
+        let silence = sine_spectrum(0.0);
+        let levels = meter.process(&silence, 0.125);
+        let expected = 94.0 + 10.0 * libm::log10f(libm::expf(-1.0));
+        assert!(
+            (levels.level_db - expected).abs() < 0.01,
+            "{}",
+            levels.level_db
+        );
+
+        // half of the time the full power, i.e. -3 dB
+        let leq = meter.leq().unwrap();
+        assert!(
+            (leq.level_db - (94.0 - 3.0103)).abs() < 0.01,
+            "{}",
+            leq.level_db
+        );
+
+        meter.reset();
+        assert!(meter.leq().is_none());
+        let levels = meter.process(&silence, 0.125);
+        assert_eq!(levels.level_db, f32::NEG_INFINITY);
+    }
+}
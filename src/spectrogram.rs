@@ -0,0 +1,311 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for [`Spectrogram`], a sequence of spectra over time, e.g. the
+//! result of a short-time Fourier transform (STFT) with the
+//! [`crate::StreamingAnalyzer`] or [`crate::wav::analyze_wav`].
+
+use crate::frequency::FrequencyValue;
+use crate::limit::FrequencyLimit;
+use crate::spectrum::FrequencySpectrum;
+use crate::wav::WavAnalysis;
+use alloc::vec::Vec;
+
+/// Sequence of spectra (frames), each with the time of its beginning in
+/// seconds. The timestamps are strictly ascending.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::spectrogram::Spectrogram;
+/// use spectrum_analyzer::{SpectrumAnalyzerBuilder, StreamingAnalyzer};
+/// # let samples = (0..8192)
+/// #    .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 48000.0))
+/// #    .collect::<Vec<_>>();
+/// let analyzer = SpectrumAnalyzerBuilder::new(1024, 48000).build().unwrap();
+/// let mut streaming = StreamingAnalyzer::new(analyzer, 512);
+/// let spectra = streaming
+///     .push(&samples)
+///     .unwrap()
+///     .into_iter()
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// let spectrogram = Spectrogram::from_spectra(spectra, 512.0 / 48000.0);
+/// let value = spectrogram.value_at(0.05, 1000.0).unwrap();
+/// # }
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spectrogram {
+    times: Vec<f32>,
+    spectra: Vec<FrequencySpectrum>,
+}
+
+impl Spectrogram {
+    /// Creates an empty spectrogram.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            times: Vec::new(),
+            spectra: Vec::new(),
+        }
+    }
+
+    /// Creates a spectrogram from consecutive spectra that are
+    /// `hop_duration` seconds apart. The first spectrum begins at `0 s`.
+    ///
+    /// ## Panics
+    /// If `hop_duration` is not positive.
+    #[must_use]
+    pub fn from_spectra(spectra: Vec<FrequencySpectrum>, hop_duration: f32) -> Self {
+        assert!(hop_duration > 0.0, "hop_duration must be positive!");
+        let times = (0..spectra.len())
+            .map(|i| i as f32 * hop_duration)
+            .collect();
+        Self { times, spectra }
+    }
+
+    /// Appends a spectrum that begins at `time` seconds.
+    ///
+    /// ## Panics
+    /// If `time` is not bigger than the time of the last frame.
+    pub fn push(&mut self, time: f32, spectrum: FrequencySpectrum) {
+        if let Some(&last) = self.times.last() {
+            assert!(
+                time > last,
+                "time {} must be bigger than the time of the last frame {}!",
+                time,
+                last
+            );
+        }
+        self.times.push(time);
+        self.spectra.push(spectrum);
+    }
+
+    /// Returns the number of frames.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.spectra.len()
+    }
+
+    /// Returns whether the spectrogram has no frames.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spectra.is_empty()
+    }
+
+    /// Returns the time of each frame in seconds.
+    #[must_use]
+    pub fn times(&self) -> &[f32] {
+        &self.times
+    }
+
+    /// Returns the spectrum of each frame.
+    #[must_use]
+    pub fn spectra(&self) -> &[FrequencySpectrum] {
+        &self.spectra
+    }
+
+    /// Returns the time and the spectrum of the frame with the given index.
+    #[must_use]
+    pub fn frame(&self, index: usize) -> Option<(f32, &FrequencySpectrum)> {
+        Some((*self.times.get(index)?, &self.spectra[index]))
+    }
+
+    /// Returns an iterator over the time and the spectrum of each frame.
+    pub fn iter(&self) -> impl Iterator<Item = (f32, &FrequencySpectrum)> {
+        self.times.iter().copied().zip(&self.spectra)
+    }
+
+    /// Returns the index of the frame whose time is the closest to `time`.
+    /// `None` if the spectrogram is empty.
+    #[must_use]
+    pub fn frame_index_at(&self, time: f32) -> Option<usize> {
+        let upper = self.times.partition_point(|&frame_time| frame_time < time);
+        if self.times.is_empty() {
+            None
+        } else if upper == 0 {
+            Some(0)
+        } else if upper == self.times.len()
+            || time - self.times[upper - 1] <= self.times[upper] - time
+        {
+            Some(upper - 1)
+        } else {
+            Some(upper)
+        }
+    }
+
+    /// Returns the value of the frequency `fr` in the frame whose time is
+    /// the closest to `time`. Between two frequencies of the spectrum, the
+    /// value is interpolated, see [`FrequencySpectrum::freq_val_exact`].
+    ///
+    /// ## Return value
+    /// `None` if the spectrogram is empty or `fr` is outside of the
+    /// spectrum.
+    #[must_use]
+    pub fn value_at(&self, time: f32, fr: f32) -> Option<FrequencyValue> {
+        let spectrum = &self.spectra[self.frame_index_at(time)?];
+        if fr < spectrum.min_fr().val() || fr > spectrum.max_fr().val() {
+            return None;
+        }
+        Some(spectrum.freq_val_exact(fr))
+    }
+
+    /// Returns the value of the frequency `fr` in each frame, together with
+    /// the time of the frame. See [`Self::value_at`].
+    ///
+    /// ## Return value
+    /// `None` if `fr` is outside of a spectrum.
+    #[must_use]
+    pub fn frequency_series(&self, fr: f32) -> Option<Vec<(f32, FrequencyValue)>> {
+        self.iter()
+            .map(|(time, spectrum)| {
+                if fr < spectrum.min_fr().val() || fr > spectrum.max_fr().val() {
+                    None
+                } else {
+                    Some((time, spectrum.freq_val_exact(fr)))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over all frames whose time is in `from..to`
+    /// (seconds).
+    pub fn time_slice(
+        &self,
+        from: f32,
+        to: f32,
+    ) -> impl Iterator<Item = (f32, &FrequencySpectrum)> {
+        let start = self.times.partition_point(|&time| time < from);
+        let end = self.times.partition_point(|&time| time < to).max(start);
+        self.times[start..end]
+            .iter()
+            .copied()
+            .zip(&self.spectra[start..end])
+    }
+
+    /// Returns a new spectrogram whose spectra only contain the frequencies
+    /// inside the frequency limit. See [`FrequencySpectrum::sub_spectrum`].
+    ///
+    /// ## Return value
+    /// `None` if less than two frequencies of a spectrum are inside the
+    /// frequency limit.
+    #[must_use]
+    pub fn frequency_slice(&self, frequency_limit: FrequencyLimit) -> Option<Self> {
+        let spectra = self
+            .spectra
+            .iter()
+            .map(|spectrum| spectrum.sub_spectrum(frequency_limit))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            times: self.times.clone(),
+            spectra,
+        })
+    }
+
+    /// Returns the spectrum of each frame.
+    #[must_use]
+    pub fn into_spectra(self) -> Vec<FrequencySpectrum> {
+        self.spectra
+    }
+}
+
+impl From<WavAnalysis> for Spectrogram {
+    fn from(analysis: WavAnalysis) -> Self {
+        let hop_duration = analysis.hop_len as f32 / analysis.sampling_rate as f32;
+        Self::from_spectra(analysis.spectra, hop_duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
+
+    /// Spectrogram of 8 frames that are 0.5 s apart. Frame `i` contains a
+    /// sine at `i * 100 Hz`.
+    fn spectrogram() -> Spectrogram {
+        let spectra = (0..8)
+            .map(|i| {
+                let samples = (0..64)
+                    .map(|j| {
+                        libm::sinf(
+                            2.0 * core::f32::consts::PI * i as f32 * 100.0 * j as f32 / 1600.0,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                samples_fft_to_spectrum(&samples, 1600, FrequencyLimit::All, None).unwrap()
+            })
+            .collect();
+        Spectrogram::from_spectra(spectra, 0.5)
+    }
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    fn test_spectrogram() {
+        let spectrogram = spectrogram();
+        assert_eq!(spectrogram.len(), 8);
+        assert_eq!(spectrogram.times()[7], 3.5);
+        assert_eq!(spectrogram.frame_index_at(-1.0), Some(0));
+        assert_eq!(spectrogram.frame_index_at(1.2), Some(2));
+        assert_eq!(spectrogram.frame_index_at(1.3), Some(3));
+        assert_eq!(spectrogram.frame_index_at(10.0), Some(7));
+        assert_eq!(Spectrogram::new().frame_index_at(1.0), None);
+
+        for (time, spectrum) in spectrogram.iter().skip(1) {
+            let expected_fr = time * 200.0;
+            assert_eq!(spectrum.max().0.val(), expected_fr);
+            let val = spectrogram.value_at(time, expected_fr).unwrap();
+            assert!((val.val() - spectrum.max().1.val()).abs() < 1e-4);
+        }
+        assert_eq!(spectrogram.value_at(0.0, 1000.0), None);
+
+        let series = spectrogram.frequency_series(300.0).unwrap();
+        assert_eq!(series.len(), 8);
+        let (time, _val) = series.iter().max_by(|(_, a), (_, b)| a.cmp(b)).unwrap();
+        assert_eq!(*time, 1.5);
+
+        let times = spectrogram
+            .time_slice(1.0, 2.5)
+            .map(|(time, _spectrum)| time)
+            .collect::<Vec<_>>();
+        assert_eq!(times, [1.0, 1.5, 2.0]);
+        assert_eq!(spectrogram.time_slice(2.0, 1.0).count(), 0);
+
+        let sliced = spectrogram
+            .frequency_slice(FrequencyLimit::Range(200.0, 400.0))
+            .unwrap();
+        assert_eq!(sliced.len(), 8);
+        assert_eq!(sliced.spectra()[0].min_fr().val(), 200.0);
+        assert_eq!(sliced.spectra()[0].max_fr().val(), 400.0);
+    }
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    #[should_panic(expected = "must be bigger than the time of the last frame")]
+    fn test_push_in_wrong_order() {
+        let mut spectrogram = spectrogram();
+        let spectrum = samples_fft_to_spectrum(&[0.0; 8], 1600, FrequencyLimit::All, None).unwrap();
+        spectrogram.push(3.5, spectrum);
+    }
+}
@@ -0,0 +1,360 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Rendering of a [`Spectrogram`] to an RGB image, e.g. for debugging and
+//! reports. Time runs from left to right (one column per frame) and the
+//! frequency from bottom to top. See [`render_spectrogram`], and
+//! `write_png` and `save_png` (need the `png` feature).
+
+use crate::spectrogram::Spectrogram;
+use alloc::vec::Vec;
+
+/// Colormap that maps normalized values in `[0.0; 1.0]` to colors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Colormap {
+    /// From black to white.
+    Grayscale,
+    /// Perceptually uniform colormap from dark purple over green to yellow,
+    /// known from matplotlib.
+    #[default]
+    Viridis,
+    /// Perceptually uniform colormap from black over red to light yellow,
+    /// known from matplotlib.
+    Inferno,
+}
+
+/// Equidistant samples of the viridis colormap.
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+/// Equidistant samples of the inferno colormap.
+const INFERNO: [[u8; 3]; 9] = [
+    [0, 0, 4],
+    [31, 12, 72],
+    [85, 15, 109],
+    [136, 34, 106],
+    [186, 54, 85],
+    [227, 89, 51],
+    [249, 140, 10],
+    [249, 201, 50],
+    [252, 255, 164],
+];
+
+impl Colormap {
+    /// Returns the RGB color of the normalized value. Values outside of
+    /// `[0.0; 1.0]` are clamped.
+    #[must_use]
+    pub fn color(self, val: f32) -> [u8; 3] {
+        let val = val.clamp(0.0, 1.0);
+        let samples = match self {
+            Self::Grayscale => {
+                let gray = libm::roundf(val * 255.0) as u8;
+                return [gray; 3];
+            }
+            Self::Viridis => &VIRIDIS,
+            Self::Inferno => &INFERNO,
+        };
+        // linear interpolation between the two neighbouring samples
+        let pos = val * (samples.len() - 1) as f32;
+        let index = (pos as usize).min(samples.len() - 2);
+        let t = pos - index as f32;
+        let mut color = [0; 3];
+        for (channel, (low, high)) in color
+            .iter_mut()
+            .zip(samples[index].iter().zip(&samples[index + 1]))
+        {
+            *channel =
+                libm::roundf(f32::from(*low) + t * (f32::from(*high) - f32::from(*low))) as u8;
+        }
+        color
+    }
+}
+
+/// Scale of the frequency axis of the image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FrequencyAxis {
+    /// All pixel rows cover the same number of Hertz.
+    #[default]
+    Linear,
+    /// All pixel rows cover the same number of octaves. The DC component is
+    /// not shown.
+    Logarithmic,
+}
+
+/// Configuration for [`render_spectrogram`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpectrogramImageConfig {
+    /// Colormap of the values.
+    pub colormap: Colormap,
+    /// Level in dB that is mapped to the lowest color. Lower levels are
+    /// clamped.
+    pub min_db: f32,
+    /// Level in dB that is mapped to the highest color. Higher levels are
+    /// clamped.
+    pub max_db: f32,
+    /// Scale of the frequency axis.
+    pub frequency_axis: FrequencyAxis,
+    /// Height of the image in pixels.
+    pub height: usize,
+    /// Whether the values of the spectra are already in dB. Otherwise, they
+    /// are treated as magnitudes and converted to dB with `20 * log10(val)`.
+    pub values_in_db: bool,
+}
+
+impl Default for SpectrogramImageConfig {
+    fn default() -> Self {
+        Self {
+            colormap: Colormap::default(),
+            min_db: -120.0,
+            max_db: 0.0,
+            frequency_axis: FrequencyAxis::default(),
+            height: 512,
+            values_in_db: false,
+        }
+    }
+}
+
+/// RGB image created by [`render_spectrogram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage {
+    /// Width in pixels.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    /// `width * height` RGB pixels in row-major order, starting at the top
+    /// left corner.
+    pub pixels: Vec<u8>,
+}
+
+/// Renders the spectrogram to an RGB image with one column per frame. The
+/// frequency range is the range of the first spectrum. Between two
+/// frequencies of a spectrum, the value is interpolated.
+///
+/// ## Panics
+/// If the spectrogram is empty, `config.height` is zero, or
+/// `config.min_db` is not smaller than `config.max_db`.
+#[must_use]
+pub fn render_spectrogram(spectrogram: &Spectrogram, config: &SpectrogramImageConfig) -> RgbImage {
+    assert!(
+        !spectrogram.is_empty(),
+        "The spectrogram must not be empty!"
+    );
+    assert!(config.height > 0, "The height must not be zero!");
+    assert!(
+        config.min_db < config.max_db,
+        "min_db must be smaller than max_db!"
+    );
+
+    let first = &spectrogram.spectra()[0];
+    let max_fr = first.max_fr().val();
+    let min_fr = match config.frequency_axis {
+        FrequencyAxis::Linear => first.min_fr().val(),
+        // skip the DC component
+        FrequencyAxis::Logarithmic => first.min_fr().val().max(first.frequency_resolution()),
+    };
+    // frequency of each pixel row, from top to bottom
+    let row_frequencies = (0..config.height)
+        .map(|y| {
+            let pos = 1.0 - (y as f32 + 0.5) / config.height as f32;
+            match config.frequency_axis {
+                FrequencyAxis::Linear => min_fr + pos * (max_fr - min_fr),
+                FrequencyAxis::Logarithmic => min_fr * libm::powf(max_fr / min_fr, pos),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let width = spectrogram.len();
+    let mut pixels = alloc::vec![0; width * config.height * 3];
+    for (x, spectrum) in spectrogram.spectra().iter().enumerate() {
+        let spectrum_min_fr = spectrum.min_fr().val();
+        let spectrum_max_fr = spectrum.max_fr().val();
+        for (y, fr) in row_frequencies.iter().enumerate() {
+            let fr = fr.clamp(spectrum_min_fr, spectrum_max_fr);
+            let val = spectrum.freq_val_exact(fr).val();
+            let db = if config.values_in_db {
+                val
+            } else {
+                20.0 * libm::log10f(val.max(f32::MIN_POSITIVE))
+            };
+            let normalized = (db - config.min_db) / (config.max_db - config.min_db);
+            let offset = (y * width + x) * 3;
+            pixels[offset..offset + 3].copy_from_slice(&config.colormap.color(normalized));
+        }
+    }
+
+    RgbImage {
+        width,
+        height: config.height,
+        pixels,
+    }
+}
+
+/// Possible errors of [`write_png`] and [`save_png`].
+#[cfg(feature = "png")]
+#[derive(Debug)]
+pub enum PngExportError {
+    /// The file couldn't be created.
+    Io(std::io::Error),
+    /// The PNG couldn't be encoded or written.
+    Encoding(png::EncodingError),
+}
+
+#[cfg(feature = "png")]
+impl core::fmt::Display for PngExportError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "can't create PNG file: {e}"),
+            Self::Encoding(e) => write!(f, "can't encode PNG: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for PngExportError {
+    fn from(e: png::EncodingError) -> Self {
+        Self::Encoding(e)
+    }
+}
+
+/// Renders the spectrogram (see [`render_spectrogram`]) and writes it as
+/// PNG to `writer`.
+///
+/// ## Errors
+/// [`PngExportError::Encoding`] if the PNG can't be encoded or written.
+///
+/// ## Panics
+/// See [`render_spectrogram`].
+#[cfg(feature = "png")]
+pub fn write_png(
+    spectrogram: &Spectrogram,
+    config: &SpectrogramImageConfig,
+    writer: impl std::io::Write,
+) -> Result<(), PngExportError> {
+    let image = render_spectrogram(spectrogram, config);
+    let mut encoder = png::Encoder::new(writer, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&image.pixels)?;
+    Ok(())
+}
+
+/// Renders the spectrogram (see [`render_spectrogram`]) and saves it as PNG
+/// file at `path`.
+///
+/// ## Errors
+/// [`PngExportError::Io`] if the file can't be created. Otherwise, see
+/// [`write_png`].
+///
+/// ## Panics
+/// See [`render_spectrogram`].
+#[cfg(feature = "png")]
+pub fn save_png(
+    spectrogram: &Spectrogram,
+    config: &SpectrogramImageConfig,
+    path: impl AsRef<std::path::Path>,
+) -> Result<(), PngExportError> {
+    let file = std::fs::File::create(path).map_err(PngExportError::Io)?;
+    write_png(spectrogram, config, std::io::BufWriter::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frequency::{Frequency, FrequencyValue};
+    use crate::FrequencySpectrum;
+
+    /// Spectrogram with two frames. Frequencies: 0, 100, ..., 700 Hz.
+    /// Frame 0 has `1.0` at 700 Hz, frame 1 has `1.0` at 100 Hz. All other
+    /// values are `0.001` (-60 dB).
+    fn spectrogram() -> Spectrogram {
+        let mut spectrogram = Spectrogram::new();
+        for (time, loud) in [(0.0, 7), (1.0, 1)] {
+            let data = (0..8)
+                .map(|i| {
+                    let val = if i == loud { 1.0 } else { 0.001 };
+                    (Frequency::from(i as f32 * 100.0), FrequencyValue::from(val))
+                })
+                .collect::<Vec<_>>();
+            let mut working_buffer = data.clone();
+            let spectrum = FrequencySpectrum::new(data, 100.0, 16, &mut working_buffer);
+            spectrogram.push(time, spectrum);
+        }
+        spectrogram
+    }
+
+    #[test]
+    fn test_colormap() {
+        assert_eq!(Colormap::Grayscale.color(0.5), [128; 3]);
+        assert_eq!(Colormap::Grayscale.color(2.0), [255; 3]);
+        assert_eq!(Colormap::Viridis.color(-1.0), VIRIDIS[0]);
+        assert_eq!(Colormap::Viridis.color(1.0), VIRIDIS[8]);
+        assert_eq!(Colormap::Inferno.color(0.5), INFERNO[4]);
+        assert_eq!(Colormap::Viridis.color(0.0625), [70, 23, 103]);
+    }
+
+    #[test]
+    fn test_render_spectrogram() {
+        let config = SpectrogramImageConfig {
+            colormap: Colormap::Grayscale,
+            min_db: -60.0,
+            max_db: 0.0,
+            height: 8,
+            ..SpectrogramImageConfig::default()
+        };
+        let image = render_spectrogram(&spectrogram(), &config);
+        assert_eq!((image.width, image.height), (2, 8));
+        assert_eq!(image.pixels.len(), 2 * 8 * 3);
+        let gray = |x: usize, y: usize| image.pixels[(y * 2 + x) * 3];
+        // top row: highest frequency (interpolated between 600 and 700 Hz)
+        assert!(gray(0, 0) > 200);
+        assert_eq!(gray(1, 0), 0);
+        assert!(gray(1, 6) > 128);
+        assert_eq!(gray(0, 6), 0);
+
+        let config = SpectrogramImageConfig {
+            frequency_axis: FrequencyAxis::Logarithmic,
+            ..config
+        };
+        let image = render_spectrogram(&spectrogram(), &config);
+        // bottom row: 100 Hz, the lowest frequency without the DC component
+        let gray = |x: usize, y: usize| image.pixels[(y * 2 + x) * 3];
+        assert!(gray(1, 7) > 200);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_write_png() {
+        let mut png = std::vec::Vec::new();
+        write_png(&spectrogram(), &SpectrogramImageConfig::default(), &mut png).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
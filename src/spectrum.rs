@@ -24,11 +24,42 @@ SOFTWARE.
 //! Module for the struct [`FrequencySpectrum`].
 
 use self::math::*;
+use crate::bark::to_bark_bands;
+use crate::bars::{to_bars, Bar, BarReduction, FrequencyScale};
+use crate::calibration::CalibrationCurve;
 use crate::error::SpectrumAnalyzerError;
-use crate::frequency::{Frequency, FrequencyValue};
-use crate::scaling::{SpectrumDataStats, SpectrumScalingFunction};
+use crate::frequency::{DisplayUnit, Frequency, FrequencyValue};
+use crate::gammatone::GammatoneFilterbank;
+use crate::limit::FrequencyLimit;
+use crate::mel::{hertz_to_mel, mel_to_hertz, MelFilterbank};
+use crate::note::chroma;
+use crate::peak::{find_peaks, Peak, PeakConfig};
+use crate::scaling::{
+    std_dev, SpectrumDataStats, SpectrumFrequencyScalingFunction, SpectrumScalingFunction,
+};
+use crate::smoothing::{smooth, OctaveSmoothing};
+use crate::weighting::Weighting;
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt::{Display, Formatter};
+use core::ops::RangeInclusive;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Percentile of all frequency values that is used as estimate for the noise
+/// floor. See [`FrequencySpectrum::noise_floor`].
+const NOISE_FLOOR_PERCENTILE: f32 = 0.25;
+
+/// Marks the cached median of a [`FrequencySpectrum`] as not calculated yet.
+/// This is a `NaN` bit pattern, hence, it is never a valid median.
+const MEDIAN_NOT_CALCULATED: u32 = u32::MAX;
+
+/// Returns the initial value of the cached median of a deserialized
+/// [`FrequencySpectrum`].
+#[cfg(feature = "serde")]
+const fn median_not_calculated() -> AtomicU32 {
+    AtomicU32::new(MEDIAN_NOT_CALCULATED)
+}
 
 /// Convenient wrapper around the processed FFT result which describes each
 /// frequency and its value/amplitude from the analyzed samples. It only
@@ -42,8 +73,12 @@ use alloc::vec::Vec;
 /// All results are related to the sampling rate provided to the library
 /// function which creates objects of this struct!
 ///
-/// This struct can be shared across thread boundaries.
+/// This struct can be shared across thread boundaries. Its only interior
+/// mutability is the atomic cache of the median. Hence, it implements `Send`
+/// and `Sync`. Hence, it can be put behind an
+/// `Arc`, e.g. to share it with a GUI thread.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrequencySpectrum {
     /// All (Frequency, FrequencyValue) data pairs sorted by lowest frequency
     /// to the highest frequency.Vector is sorted from lowest
@@ -57,12 +92,19 @@ pub struct FrequencySpectrum {
     /// Number of samples that were analyzed. Might be bigger than the length
     /// of `data`, if the spectrum was created with a [`crate::limit::FrequencyLimit`] .
     samples_len: u32,
+    /// Number of samples before zero-padding. Equals [`Self::samples_len`]
+    /// if the samples were not padded.
+    unpadded_samples_len: u32,
     /// Average value of frequency value/magnitude/amplitude
     /// corresponding to data in [`FrequencySpectrum::data`].
     average: FrequencyValue,
     /// Median value of frequency value/magnitude/amplitude
-    /// corresponding to data in [`FrequencySpectrum::data`].
-    median: FrequencyValue,
+    /// corresponding to data in [`FrequencySpectrum::data`]. The median is
+    /// expensive to calculate and only needed by a few users. Hence, it is
+    /// calculated lazily and cached as bits of a `f32`, or
+    /// [`MEDIAN_NOT_CALCULATED`]. An atomic keeps the struct `Sync`.
+    #[cfg_attr(feature = "serde", serde(skip, default = "median_not_calculated"))]
+    median: AtomicU32,
     /// Pair of (frequency, frequency value/magnitude/amplitude) where
     /// frequency value is **minimal** inside the spectrum.
     /// Corresponding to data in [`FrequencySpectrum::data`].
@@ -75,7 +117,7 @@ pub struct FrequencySpectrum {
 
 impl FrequencySpectrum {
     /// Creates a new object. Calculates several metrics from the data
-    /// in the given vector.
+    /// in the given vector. The median is only calculated when it is needed.
     ///
     /// ## Parameters
     /// * `data` Vector with all ([`Frequency`], [`FrequencyValue`])-tuples
@@ -83,15 +125,16 @@ impl FrequencySpectrum {
     ///                          `data[1].0 - data[0].0`.
     /// * `samples_len` Number of samples. Might be bigger than `data.len()`
     ///                 if the spectrum is obtained with a frequency limit.
-    /// * `working_buffer` Mutable buffer with the same length as `data`
-    ///                    required to calculate certain metrics.
+    /// * `working_buffer` Mutable buffer with the same length as `data`.
+    ///                    It is not used anymore, as the median is calculated
+    ///                    lazily, but kept for compatibility.
     #[inline]
     #[must_use]
     pub fn new(
         data: Vec<(Frequency, FrequencyValue)>,
         frequency_resolution: f32,
         samples_len: u32,
-        working_buffer: &mut [(Frequency, FrequencyValue)],
+        _working_buffer: &mut [(Frequency, FrequencyValue)],
     ) -> Self {
         debug_assert!(
             data.len() >= 2,
@@ -103,15 +146,16 @@ impl FrequencySpectrum {
             data,
             frequency_resolution,
             samples_len,
+            unpadded_samples_len: samples_len,
             // default/placeholder values
             average: FrequencyValue::from(-1.0),
-            median: FrequencyValue::from(-1.0),
+            median: AtomicU32::new(MEDIAN_NOT_CALCULATED),
             min: (Frequency::from(-1.0), FrequencyValue::from(-1.0)),
             max: (Frequency::from(-1.0), FrequencyValue::from(-1.0)),
         };
 
         // Important to call this once initially.
-        obj.calc_statistics(working_buffer);
+        obj.calc_statistics();
         obj
     }
 
@@ -123,22 +167,64 @@ impl FrequencySpectrum {
     ///
     /// ## Parameters
     /// * `scaling_fn` See [`crate::scaling::SpectrumScalingFunction`].
+    /// * `working_buffer` Mutable buffer with the same length as the data of
+    ///                    the spectrum required to calculate the median.
     #[inline]
     pub fn apply_scaling_fn(
         &mut self,
         scaling_fn: &SpectrumScalingFunction,
         working_buffer: &mut [(Frequency, FrequencyValue)],
+    ) -> Result<(), SpectrumAnalyzerError> {
+        self.apply_scaling(
+            |_fr, fr_val, stats| scaling_fn(fr_val, stats),
+            working_buffer,
+        )
+    }
+
+    /// Like [`Self::apply_scaling_fn`] but the scaling function additionally
+    /// receives the frequency of each value. This enables frequency
+    /// dependent corrections, such as weighting curves, a tilt, or a
+    /// microphone calibration.
+    ///
+    /// ## Parameters
+    /// * `scaling_fn` See [`crate::scaling::SpectrumFrequencyScalingFunction`].
+    #[inline]
+    pub fn apply_frequency_scaling_fn(
+        &mut self,
+        scaling_fn: &SpectrumFrequencyScalingFunction,
+        working_buffer: &mut [(Frequency, FrequencyValue)],
+    ) -> Result<(), SpectrumAnalyzerError> {
+        self.apply_scaling(scaling_fn, working_buffer)
+    }
+
+    /// Common implementation of [`Self::apply_scaling_fn`] and
+    /// [`Self::apply_frequency_scaling_fn`].
+    #[inline]
+    fn apply_scaling(
+        &mut self,
+        scaling_fn: impl Fn(f32, f32, &SpectrumDataStats) -> f32,
+        working_buffer: &mut [(Frequency, FrequencyValue)],
     ) -> Result<(), SpectrumAnalyzerError> {
         // This represents statistics about the spectrum in its current state
         // which a scaling function may use to scale values.
         //
         // On the first invocation of this function, these values represent the
         // statistics for the unscaled, hence initial, spectrum.
+        let (sum, sum_of_squares) = self
+            .data
+            .iter()
+            .map(|(_fr, fr_val)| fr_val.val())
+            .fold((0.0, 0.0), |(sum, sum_of_squares), val| {
+                (sum + val, sum_of_squares + val * val)
+            });
         let stats = SpectrumDataStats {
             min: self.min.1.val(),
             max: self.max.1.val(),
             average: self.average.val(),
-            median: self.median.val(),
+            median: self.median_with_buffer(working_buffer).val(),
+            sum,
+            sum_of_squares,
+            std_dev: std_dev(sum_of_squares, self.average.val(), self.data.len()),
             // attention! not necessarily `data.len()`!
             n: self.samples_len as f32,
         };
@@ -146,9 +232,9 @@ impl FrequencySpectrum {
         // Iterate over the whole spectrum and scale each frequency value.
         // I use a regular for loop instead of for_each(), so that I can
         // early return a result here
-        for (_fr, fr_val) in &mut self.data {
+        for (fr, fr_val) in &mut self.data {
             // scale value
-            let scaled_val: f32 = scaling_fn(fr_val.val(), &stats);
+            let scaled_val: f32 = scaling_fn(fr.val(), fr_val.val(), &stats);
 
             // sanity check
             if scaled_val.is_nan() || scaled_val.is_infinite() {
@@ -162,10 +248,112 @@ impl FrequencySpectrum {
             *fr_val = scaled_val.into()
         }
 
-        self.calc_statistics(working_buffer);
+        self.calc_statistics();
         Ok(())
     }
 
+    /// Applies a frequency weighting curve, such as A-weighting, to the
+    /// spectrum. Each value is multiplied with the linear gain of the
+    /// weighting at the corresponding frequency. Afterwards, several metrics
+    /// about the spectrum, such as `min` and `max`, are updated accordingly.
+    ///
+    /// The values of the spectrum are expected to be magnitudes (and not in
+    /// decibel). Apply the weighting before any logarithmic scaling.
+    ///
+    /// ## Parameters
+    /// * `weighting` See [`Weighting`].
+    #[inline]
+    pub fn apply_weighting(&mut self, weighting: Weighting) {
+        let mut working_buffer = self.data.clone();
+        self.apply_scaling(
+            |fr, fr_val, _stats| fr_val * weighting.gain(fr),
+            &mut working_buffer,
+        )
+        .expect("weighting gains are always finite");
+    }
+
+    /// Applies a calibration curve, e.g. of a measurement microphone, to the
+    /// spectrum. Each value is multiplied with the interpolated linear gain
+    /// of the calibration at the corresponding frequency. Afterwards, several
+    /// metrics about the spectrum, such as `min` and `max`, are updated
+    /// accordingly.
+    ///
+    /// The values of the spectrum are expected to be magnitudes (and not in
+    /// decibel). Apply the calibration before any logarithmic scaling.
+    ///
+    /// ## Parameters
+    /// * `calibration` See [`CalibrationCurve`].
+    #[inline]
+    pub fn apply_calibration(&mut self, calibration: &CalibrationCurve) {
+        let mut working_buffer = self.data.clone();
+        self.apply_scaling(
+            |fr, fr_val, _stats| fr_val * calibration.gain(fr),
+            &mut working_buffer,
+        )
+        .expect("calibration gains are always finite");
+    }
+
+    /// Applies fractional-octave smoothing, as used in loudspeaker and room
+    /// measurement plots. Each value is replaced by the average of all values
+    /// within a window of the given width in octaves, centered around its
+    /// frequency. The DC component is kept. Afterwards, several metrics about
+    /// the spectrum, such as `min` and `max`, are updated accordingly.
+    ///
+    /// ## Parameters
+    /// * `smoothing` See [`OctaveSmoothing`].
+    #[inline]
+    pub fn smooth(&mut self, smoothing: OctaveSmoothing) {
+        let smoothed = smooth(&self.data, smoothing);
+        for ((_fr, fr_val), smoothed_val) in self.data.iter_mut().zip(smoothed) {
+            *fr_val = smoothed_val.into();
+        }
+        self.calc_statistics();
+    }
+
+    /// Reduces stationary noise by [spectral subtraction]. The previously
+    /// measured `noise_profile`, e.g. a spectrum of the room without the
+    /// signal, is subtracted bin-wise. To prevent negative values and
+    /// "musical noise" artifacts, each value is at least `floor` times its
+    /// original value. Afterwards, several metrics about the spectrum, such
+    /// as `min` and `max`, are updated accordingly.
+    ///
+    /// Both spectra must be magnitudes (and not in decibel). Averaging
+    /// multiple noise spectra improves the result.
+    ///
+    /// ## Parameters
+    /// * `noise_profile` Spectrum of the noise. Must be obtained with the
+    ///                   same sampling rate, number of samples, frequency
+    ///                   limit, and window function as `self`.
+    /// * `floor` Spectral floor in range `[0.0, 1.0]`, e.g. `0.02`.
+    ///
+    /// ## Panics
+    /// If the frequencies of both spectra don't match or if `floor` is not in
+    /// range `[0.0, 1.0]`.
+    ///
+    /// [spectral subtraction]: https://en.wikipedia.org/wiki/Spectral_subtraction
+    #[inline]
+    pub fn subtract_noise_profile(&mut self, noise_profile: &Self, floor: f32) {
+        assert!(
+            (0.0..=1.0).contains(&floor),
+            "floor must be in range [0.0, 1.0]!"
+        );
+        assert!(
+            self.data.len() == noise_profile.data.len()
+                && self.min_fr() == noise_profile.min_fr()
+                && self.max_fr() == noise_profile.max_fr(),
+            "The noise profile must have the same frequencies as the spectrum!"
+        );
+
+        for ((_fr, fr_val), (_noise_fr, noise_val)) in
+            self.data.iter_mut().zip(noise_profile.data.iter())
+        {
+            let subtracted = fr_val.val() - noise_val.val();
+            *fr_val = subtracted.max(floor * fr_val.val()).into();
+        }
+
+        self.calc_statistics();
+    }
+
     /// Returns the average frequency value of the spectrum.
     #[inline]
     #[must_use]
@@ -173,11 +361,183 @@ impl FrequencySpectrum {
         self.average
     }
 
-    /// Returns the median frequency value of the spectrum.
+    /// Returns the median frequency value of the spectrum. It is calculated
+    /// on the first call and cached afterwards.
+    #[inline]
+    #[must_use]
+    pub fn median(&self) -> FrequencyValue {
+        self.cached_median()
+            .unwrap_or_else(|| self.median_with_buffer(&mut self.data.clone()))
+    }
+
+    /// Returns the cached median, if it was already calculated.
     #[inline]
+    fn cached_median(&self) -> Option<FrequencyValue> {
+        let bits = self.median.load(Ordering::Relaxed);
+        (bits != MEDIAN_NOT_CALCULATED).then(|| f32::from_bits(bits).into())
+    }
+
+    /// Like [`Self::median`] but uses the given working buffer with the same
+    /// length as [`Self::data`] to calculate the median, if necessary. The
+    /// data is copied into the working buffer where the element in the middle
+    /// is selected in `O(n)`. This is cheaper than sorting all values.
+    fn median_with_buffer(
+        &self,
+        working_buffer: &mut [(Frequency, FrequencyValue)],
+    ) -> FrequencyValue {
+        if let Some(median) = self.cached_median() {
+            return median;
+        }
+        assert_eq!(
+            self.data.len(),
+            working_buffer.len(),
+            "The working buffer must have the same length as `self.data`!"
+        );
+
+        working_buffer.copy_from_slice(&self.data);
+        let mid = working_buffer.len() / 2;
+        let (lower, upper, _) =
+            working_buffer.select_nth_unstable_by_key(mid, |(_fr, fr_val)| *fr_val);
+        let b = upper.1;
+        // the element `mid - 1` of the sorted values is the maximum of all
+        // elements before `mid`
+        let a = lower.iter().map(|(_fr, fr_val)| *fr_val).max().unwrap_or(b);
+        let median = (a + b) / 2.0.into();
+
+        self.median.store(median.val().to_bits(), Ordering::Relaxed);
+        median
+    }
+
+    /// Returns the (population) variance of all frequency values of the
+    /// spectrum.
+    #[must_use]
+    pub fn variance(&self) -> f32 {
+        let average = self.average.val();
+        let sum = self
+            .data
+            .iter()
+            .map(|(_fr, fr_val)| (fr_val.val() - average) * (fr_val.val() - average))
+            .sum::<f32>();
+        sum / self.data.len() as f32
+    }
+
+    /// Returns the standard deviation of all frequency values of the
+    /// spectrum, i.e. the square root of [`Self::variance`].
+    #[must_use]
+    pub fn std_dev(&self) -> f32 {
+        libm::sqrtf(self.variance())
+    }
+
+    /// Returns the root mean square (RMS) of all frequency values of the
+    /// spectrum.
+    #[must_use]
+    pub fn rms(&self) -> FrequencyValue {
+        let sum = self
+            .data
+            .iter()
+            .map(|(_fr, fr_val)| fr_val.val() * fr_val.val())
+            .sum::<f32>();
+        libm::sqrtf(sum / self.data.len() as f32).into()
+    }
+
+    /// Returns the energy of the analyzed samples, i.e. the sum of all squared
+    /// samples, calculated from the spectrum via [Parseval's theorem]. Each
+    /// frequency between the DC component and the Nyquist frequency accounts
+    /// for its mirrored negative frequency as well. Hence, it is counted
+    /// twice:
+    ///
+    /// `energy = (|X[0]|² + 2 * Σ |X[k]|² + |X[N/2]|²) / N`
+    ///
+    /// A two-sided spectrum, i.e. one that contains negative frequencies as
+    /// the ones of [`crate::samples_fft_to_two_sided_spectrum`] and
+    /// [`crate::complex_samples_fft_to_spectrum`], contains the negative
+    /// frequencies itself. Hence, each of its values is counted once:
+    ///
+    /// `energy = Σ |X[k]|² / N`
+    ///
+    /// This only matches the time domain if the values are the raw
+    /// magnitudes of the FFT, i.e. without any scaling function, and no
+    /// window function was applied. Frequencies outside of the frequency
+    /// limit don't contribute.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+    /// let samples = [1.0, 2.0, -3.0, 0.5];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 4, FrequencyLimit::All, None).unwrap();
+    /// let energy = samples.iter().map(|x| x * x).sum::<f32>();
+    /// assert!((spectrum.total_energy() - energy).abs() < 1e-4);
+    /// ```
+    ///
+    /// [Parseval's theorem]: https://en.wikipedia.org/wiki/Parseval%27s_theorem
+    #[must_use]
+    pub fn total_energy(&self) -> f32 {
+        let nyquist_index = self.samples_len as usize / 2;
+        let two_sided = self.min_fr().val() < 0.0;
+        let sum = self
+            .data
+            .iter()
+            .map(|(fr, fr_val)| {
+                let index = libm::roundf(fr.val() / self.frequency_resolution) as usize;
+                let factor = if two_sided || index == 0 || index == nyquist_index {
+                    1.0
+                } else {
+                    2.0
+                };
+                factor * fr_val.val() * fr_val.val()
+            })
+            .sum::<f32>();
+        sum / self.samples_len as f32
+    }
+
+    /// Returns the mean power of the analyzed samples, i.e. the mean of all
+    /// squared samples or the squared RMS of the signal in the time domain.
+    /// This is [`Self::total_energy`] divided by the number of samples
+    /// before padding. See [`Self::total_energy`] for the requirements.
     #[must_use]
-    pub const fn median(&self) -> FrequencyValue {
-        self.median
+    pub fn total_power(&self) -> f32 {
+        self.total_energy() / self.unpadded_samples_len as f32
+    }
+
+    /// Returns the frequency value at the given percentile of all frequency
+    /// values, e.g. `0.5` for the median. Values between two ranks are
+    /// linearly interpolated.
+    ///
+    /// ## Parameters
+    /// * `percentile` Percentile in range `[0.0, 1.0]`.
+    ///
+    /// ## Panics
+    /// If `percentile` is not in range `[0.0, 1.0]`.
+    #[must_use]
+    pub fn percentile(&self, percentile: f32) -> FrequencyValue {
+        assert!(
+            (0.0..=1.0).contains(&percentile),
+            "percentile must be in range [0.0, 1.0]!"
+        );
+        let mut values = self.data.iter().map(|(_fr, val)| *val).collect::<Vec<_>>();
+
+        let rank = percentile * (values.len() - 1) as f32;
+        let lower = libm::floorf(rank) as usize;
+        // select in O(n) instead of sorting all values
+        let (_, lower_val, greater) = values.select_nth_unstable(lower);
+        let lower_val = lower_val.val();
+        // the next value of the sorted values is the minimum of all greater
+        // elements
+        let upper_val = greater.iter().min().map_or(lower_val, |val| val.val());
+        (lower_val + (upper_val - lower_val) * (rank - lower as f32)).into()
+    }
+
+    /// Estimates the broadband noise floor of the spectrum, i.e. the level of
+    /// the values that don't belong to any tone. Peaks can be reported
+    /// relative to this value.
+    ///
+    /// The estimate is the lower quartile of all frequency values. In
+    /// contrast to the [`Self::median`], it stays robust if many narrowband
+    /// tones (and their leakage into neighbouring frequencies) are present, as
+    /// long as they cover less than 75% of the spectrum.
+    #[must_use]
+    pub fn noise_floor(&self) -> FrequencyValue {
+        self.percentile(NOISE_FLOOR_PERCENTILE)
     }
 
     /// Returns the maximum (frequency, frequency value)-pair of the spectrum
@@ -196,7 +556,7 @@ impl FrequencySpectrum {
         self.min
     }
 
-    /// Returns [`FrequencySpectrum::max().1`] - [`FrequencySpectrum::min().1`],
+    /// Returns `max().1 - min().1` (see [`FrequencySpectrum::max`] and [`FrequencySpectrum::min`]),
     /// i.e. the range of the frequency values (not the frequencies itself,
     /// but their amplitudes/values).
     #[inline]
@@ -212,20 +572,49 @@ impl FrequencySpectrum {
         &self.data
     }
 
-    /// Returns the frequency resolution of this spectrum.
+    /// Returns the frequency resolution of this spectrum, i.e. the
+    /// frequency steps between elements in [`Self::data`]. If the samples
+    /// were zero-padded, this is the effective resolution of the
+    /// interpolated spectrum. See [`Self::true_frequency_resolution`].
     #[inline]
     #[must_use]
     pub const fn frequency_resolution(&self) -> f32 {
         self.frequency_resolution
     }
 
-    /// Returns the number of samples used to obtain this spectrum.
+    /// Returns the true frequency resolution, i.e. the ability to separate
+    /// two frequencies, which only depends on the number of samples before
+    /// zero-padding. Zero-padding interpolates the spectrum but doesn't
+    /// improve the true resolution. Equals [`Self::frequency_resolution`] if
+    /// the samples were not padded.
+    #[inline]
+    #[must_use]
+    pub fn true_frequency_resolution(&self) -> f32 {
+        self.frequency_resolution * self.samples_len as f32 / self.unpadded_samples_len as f32
+    }
+
+    /// Returns the number of samples used to obtain this spectrum, including
+    /// zero-padding.
     #[inline]
     #[must_use]
     pub const fn samples_len(&self) -> u32 {
         self.samples_len
     }
 
+    /// Returns the number of samples before zero-padding.
+    #[inline]
+    #[must_use]
+    pub const fn unpadded_samples_len(&self) -> u32 {
+        self.unpadded_samples_len
+    }
+
+    /// Sets the number of samples before zero-padding.
+    #[inline]
+    pub(crate) fn set_unpadded_samples_len(&mut self, unpadded_samples_len: u32) {
+        debug_assert!(unpadded_samples_len <= self.samples_len);
+        self.unpadded_samples_len = unpadded_samples_len;
+    }
+
     /// Getter for the highest frequency that is captured inside this spectrum.
     /// Shortcut for `spectrum.data()[spectrum.data().len() - 1].0`.
     /// This corresponds to the [`crate::limit::FrequencyLimit`] of the spectrum.
@@ -354,6 +743,56 @@ impl FrequencySpectrum {
         panic!("Here be dragons");
     }
 
+    /// Returns the frequency of the FFT bin with the given index, i.e.
+    /// `index * frequency_resolution`. The bin doesn't need to be part of the
+    /// spectrum, e.g. if it is outside of the frequency limit.
+    ///
+    /// ## Parameters
+    /// * `index` Index of the FFT bin, where `0` is the DC component.
+    #[inline]
+    #[must_use]
+    pub fn frequency_of_index(&self, index: usize) -> Frequency {
+        (index as f32 * self.frequency_resolution).into()
+    }
+
+    /// Returns the index of the FFT bin closest to the given frequency. This
+    /// is the inverse of [`Self::frequency_of_index`]. Negative frequencies
+    /// are mapped to index `0`.
+    ///
+    /// ## Parameters
+    /// * `frequency` Frequency in Hertz.
+    #[inline]
+    #[must_use]
+    pub fn index_of_frequency(&self, frequency: f32) -> usize {
+        libm::roundf(frequency / self.frequency_resolution) as usize
+    }
+
+    /// Returns the frequency value of the FFT bin with the given index, if
+    /// the bin is part of the spectrum. This takes the frequency limit into
+    /// account, i.e. `index` is not an index into [`Self::data`].
+    ///
+    /// ## Parameters
+    /// * `index` Index of the FFT bin, where `0` is the DC component.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # #[cfg(feature = "size-1024")] {
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+    /// let samples = vec![0.0; 1024];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 1024, FrequencyLimit::Min(100.0), None).unwrap();
+    /// assert_eq!(spectrum.index_of_frequency(100.0), 100);
+    /// assert_eq!(spectrum.value_at_index(100), Some(spectrum.data()[0].1));
+    /// assert_eq!(spectrum.value_at_index(99), None);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn value_at_index(&self, index: usize) -> Option<FrequencyValue> {
+        self.data
+            .binary_search_by_key(&index, |(fr, _fr_val)| self.index_of_frequency(fr.val()))
+            .ok()
+            .map(|data_index| self.data[data_index].1)
+    }
+
     /// Returns the frequency closest to parameter `search_fr` in the spectrum. For example
     /// if the spectrum looks like this:
     /// ```text
@@ -444,6 +883,60 @@ impl FrequencySpectrum {
         self.freq_val_exact(hz)
     }
 
+    /// Consumes the spectrum and returns the underlying data without copying
+    /// it. See [`Self::data`].
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Vec<(Frequency, FrequencyValue)> {
+        self.data
+    }
+
+    /// Returns a copy of the underlying data. See [`Self::data`].
+    #[inline]
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<(Frequency, FrequencyValue)> {
+        self.data.clone()
+    }
+
+    /// Returns the frequencies (x) and the frequency values (y) as separate
+    /// vectors of plain `f32`, as expected by plotting libraries, such as
+    /// `plotters` or `egui_plot`.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+    /// let samples = [0.0, 1.0, 0.0, -1.0];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 4, FrequencyLimit::All, None).unwrap();
+    /// let (x, y) = spectrum.to_xy_vecs();
+    /// assert_eq!(x, vec![0.0, 1.0, 2.0]);
+    /// assert_eq!(y, vec![0.0, 2.0, 0.0]);
+    /// ```
+    #[must_use]
+    pub fn to_xy_vecs(&self) -> (Vec<f32>, Vec<f32>) {
+        self.data
+            .iter()
+            .map(|(fr, fr_val)| (fr.val(), fr_val.val()))
+            .unzip()
+    }
+
+    /// Like [`Self::to_map`] but keeps the exact frequencies as keys. Unlike
+    /// [`f32`], [`Frequency`] is [`Ord`]. Hence, no bins are merged, even if
+    /// the frequency resolution is below 1 Hz.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+    /// let samples = [0.0, 1.0, 0.0, -1.0];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 2, FrequencyLimit::All, None).unwrap();
+    /// let map = spectrum.to_ordered_map();
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map[&0.5.into()].val(), 2.0);
+    /// ```
+    #[must_use]
+    pub fn to_ordered_map(&self) -> BTreeMap<Frequency, FrequencyValue> {
+        self.data.iter().copied().collect()
+    }
+
     /// Returns a [`BTreeMap`] with all value pairs. The key is of type [`u32`]
     /// because [`f32`] is not [`Ord`].
     #[inline]
@@ -470,66 +963,463 @@ impl FrequencySpectrum {
             .collect()
     }
 
-    /// Calculates the `min`, `max`, `median`, and `average` of the frequency values/magnitudes/
-    /// amplitudes.
+    /// Reduces the spectrum to `n_bands` bands on the [mel] scale using a
+    /// triangular filterbank. Shortcut for creating a [`MelFilterbank`] and
+    /// calling [`MelFilterbank::apply`]. If you process many spectra, create
+    /// the filterbank once and reuse it.
+    ///
+    /// ## Parameters
+    /// - `n_bands` Number of mel bands.
+    /// - `f_min` Lowest frequency of the filterbank in Hertz.
+    /// - `f_max` Highest frequency of the filterbank in Hertz.
+    ///
+    /// ## Return
+    /// `(center frequency, band value)`-pairs, sorted from the lowest to the
+    /// highest band.
     ///
-    /// To do so, it needs to create a sorted copy of the data.
+    /// ## Panics
+    /// See [`MelFilterbank::new`].
+    ///
+    /// [mel]: https://en.wikipedia.org/wiki/Mel_scale
     #[inline]
-    fn calc_statistics(&mut self, working_buffer: &mut [(Frequency, FrequencyValue)]) {
-        // We create a copy with all data from `self.data` but we sort it by the
-        // frequency value and not the frequency. This way, we can easily find the
-        // median.
+    #[must_use]
+    pub fn to_mel_bands(
+        &self,
+        n_bands: usize,
+        f_min: f32,
+        f_max: f32,
+    ) -> Vec<(Frequency, FrequencyValue)> {
+        MelFilterbank::new(n_bands, f_min, f_max).apply(self)
+    }
 
-        let data_sorted_by_val = {
-            assert_eq!(
-                self.data.len(),
-                working_buffer.len(),
-                "The working buffer must have the same length as `self.data`!"
-            );
+    /// Folds the energy, i.e. the squared values, of all frequencies into the
+    /// 12 pitch classes of the twelve-tone equal temperament (chromagram or
+    /// pitch class profile), e.g. for key and chord detection. Each frequency
+    /// contributes to the pitch class of its nearest note, regardless of the
+    /// octave. The DC component is ignored.
+    ///
+    /// Low frequencies are coarsely resolved by the FFT and may be assigned
+    /// to wrong pitch classes. Limit the spectrum to the relevant range, e.g.
+    /// with [`Self::sub_spectrum`], or use a sufficiently long FFT.
+    ///
+    /// ## Parameters
+    /// * `reference_a4` Frequency of `A4` in Hertz, see
+    ///                  [`crate::note::DEFAULT_REFERENCE_A4`].
+    ///
+    /// ## Return
+    /// The energy of each pitch class, indexed by
+    /// [`crate::note::NoteName::semitone`], i.e. starting with `C`.
+    ///
+    /// ## Panics
+    /// If `reference_a4` is not bigger than zero.
+    #[inline]
+    #[must_use]
+    pub fn to_chroma(&self, reference_a4: f32) -> [f32; 12] {
+        chroma(self, reference_a4)
+    }
 
-            for (i, pair) in self.data.iter().enumerate() {
-                working_buffer[i] = *pair;
-            }
-            working_buffer.sort_by(|(_l_fr, l_fr_val), (_r_fr, r_fr_val)| {
-                // compare by frequency value, from min to max
-                l_fr_val.cmp(r_fr_val)
-            });
+    /// Reduces the spectrum to an auditory spectrum of `n_bands` bands on the
+    /// ERB-rate scale using a gammatone filterbank. Shortcut for creating a
+    /// [`GammatoneFilterbank`] and calling [`GammatoneFilterbank::apply`]. If
+    /// you process many spectra, create the filterbank once and reuse it.
+    ///
+    /// ## Parameters
+    /// - `n_bands` Number of bands.
+    /// - `f_min` Center frequency of the lowest band in Hertz.
+    /// - `f_max` Center frequency of the highest band in Hertz.
+    ///
+    /// ## Return
+    /// `(center frequency, band energy)`-pairs, sorted from the lowest to the
+    /// highest band.
+    ///
+    /// ## Panics
+    /// See [`GammatoneFilterbank::new`].
+    #[inline]
+    #[must_use]
+    pub fn to_gammatone_bands(
+        &self,
+        n_bands: usize,
+        f_min: f32,
+        f_max: f32,
+    ) -> Vec<(Frequency, FrequencyValue)> {
+        GammatoneFilterbank::new(n_bands, f_min, f_max).apply(self)
+    }
 
-            working_buffer
-        };
+    /// Reduces the spectrum to the energy, i.e. the sum of the squared
+    /// values, of each of the 24 critical bands of human hearing on the
+    /// [Bark] scale. See [`crate::bark`] and [`Self::band_energy`]. Bands
+    /// that start above the highest frequency of the spectrum are omitted.
+    ///
+    /// ## Return
+    /// `(center frequency, band energy)`-pairs, sorted from the lowest to the
+    /// highest band.
+    ///
+    /// [Bark]: https://en.wikipedia.org/wiki/Bark_scale
+    #[inline]
+    #[must_use]
+    pub fn to_bark_bands(&self) -> Vec<(Frequency, FrequencyValue)> {
+        to_bark_bands(self)
+    }
+
+    /// Groups the frequencies into `n_bars` bars that are equally wide on the
+    /// given frequency scale, e.g. for LED strips or GUI visualizers. The
+    /// bars cover the whole spectrum. Bars that are narrower than the
+    /// frequency resolution get the interpolated value at their center.
+    ///
+    /// ## Parameters
+    /// - `n_bars` Number of bars.
+    /// - `scale` See [`FrequencyScale`].
+    /// - `reduction` See [`BarReduction`].
+    ///
+    /// ## Return
+    /// Bars sorted from the lowest to the highest frequency.
+    ///
+    /// ## Panics
+    /// If `n_bars` is zero or if the spectrum covers no frequency range on
+    /// the given scale.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # #[cfg(feature = "size-16")] {
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+    /// use spectrum_analyzer::bars::{BarReduction, FrequencyScale};
+    /// let samples = vec![0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    /// let bars = spectrum.to_bars(2, FrequencyScale::Log, BarReduction::Max);
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn to_bars(
+        &self,
+        n_bars: usize,
+        scale: FrequencyScale,
+        reduction: BarReduction,
+    ) -> Vec<Bar> {
+        to_bars(self, n_bars, scale, reduction)
+    }
 
-        // sum of all frequency values
-        let sum: f32 = data_sorted_by_val
+    /// Like [`Self::max`] but refines the frequency and the value of the
+    /// maximum by fitting a parabola through the maximum and its two neighbors.
+    /// See [`Self::peak_interpolated`].
+    #[inline]
+    #[must_use]
+    pub fn max_interpolated(&self) -> (Frequency, FrequencyValue) {
+        let index = self
+            .data
             .iter()
-            .map(|fr_val| fr_val.1.val())
-            .fold(0.0, |a, b| a + b);
-
-        // average of all frequency values
-        let avg = sum / data_sorted_by_val.len() as f32;
-        let average: FrequencyValue = avg.into();
-
-        // median of all frequency values
-        let median = {
-            // we assume that data_sorted_by_val.length() is always even, because
-            // it must be a power of 2 (for FFT)
-            let a = data_sorted_by_val[data_sorted_by_val.len() / 2 - 1].1;
-            let b = data_sorted_by_val[data_sorted_by_val.len() / 2].1;
-            (a + b) / 2.0.into()
-        };
+            .position(|pair| *pair == self.max)
+            .expect("the maximum must be part of the data");
+        self.peak_interpolated(index)
+    }
+
+    /// Returns a refined estimation of the frequency and the value of the
+    /// peak at index `index` of [`Self::data`]. Due to the
+    /// [`Self::frequency_resolution`], the real frequency of a peak is
+    /// usually somewhere between two bins. This function fits a parabola
+    /// through the bin and its two neighbors and returns the vertex of
+    /// the parabola. This is also known as *quadratic interpolation*.
+    ///
+    /// If the bin is the first or the last of the spectrum or if it is not a
+    /// local maximum, the bin is returned unchanged.
+    ///
+    /// More information:
+    /// <https://ccrma.stanford.edu/~jos/sasp/Quadratic_Interpolation_Spectral_Peaks.html>
+    ///
+    /// ## Panics
+    /// If `index` is out of bounds.
+    ///
+    /// ## Parameters
+    /// - `index` Index of the peak in [`Self::data`], for example
+    ///           obtained by [`Self::find_peaks`].
+    #[inline]
+    #[must_use]
+    pub fn peak_interpolated(&self, index: usize) -> (Frequency, FrequencyValue) {
+        let (fr, fr_val) = self.data[index];
+        if index == 0 || index == self.data.len() - 1 {
+            return (fr, fr_val);
+        }
+        let left = self.data[index - 1].1;
+        let right = self.data[index + 1].1;
+        if left > fr_val || right > fr_val {
+            return (fr, fr_val);
+        }
+        let (offset, value) = parabolic_interpolation(left.val(), fr_val.val(), right.val());
+        (
+            (fr.val() + offset * self.frequency_resolution).into(),
+            value.into(),
+        )
+    }
+
+    /// Finds all peaks, i.e. local maxima, in the spectrum that match the
+    /// given configuration. The lowest and the highest frequency of the
+    /// spectrum are never reported as peak.
+    ///
+    /// ## Parameters
+    /// - `config` See [`PeakConfig`].
+    ///
+    /// ## Return
+    /// All peaks, sorted from the lowest to the highest frequency.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # #[cfg(feature = "size-16")] {
+    /// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit, PeakConfig};
+    /// let samples = vec![0.0, 1.1, 5.5, -5.5, 0.0, 1.1, 5.5, -5.5];
+    /// let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    /// let peaks = spectrum.find_peaks(&PeakConfig {
+    ///     threshold: 1.0,
+    ///     min_prominence: 0.5,
+    ///     min_distance: 100.0,
+    /// });
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn find_peaks(&self, config: &PeakConfig) -> Vec<Peak> {
+        find_peaks(&self.data, config)
+    }
+
+    /// Like [`Self::find_peaks`] but returns at most the `n` strongest peaks,
+    /// sorted by their frequency value from the highest to the lowest. Use
+    /// [`PeakConfig::min_distance`] to get distinct peaks, e.g. for chord
+    /// detection.
+    ///
+    /// ## Parameters
+    /// * `n` Maximum number of peaks.
+    /// * `config` See [`PeakConfig`].
+    #[must_use]
+    pub fn strongest_peaks(&self, n: usize, config: &PeakConfig) -> Vec<Peak> {
+        let mut peaks = find_peaks(&self.data, config);
+        peaks.sort_by_key(|peak| Reverse(peak.value));
+        peaks.truncate(n);
+        peaks
+    }
+
+    /// Returns the `n` (frequency, frequency value)-pairs with the highest
+    /// frequency values, sorted by their frequency value from the highest to
+    /// the lowest. Unlike [`Self::strongest_peaks`], neighbouring frequencies
+    /// of the same peak might be returned.
+    ///
+    /// ## Parameters
+    /// * `n` Number of pairs. If `n` is bigger than the length of
+    ///       [`Self::data`], all pairs are returned.
+    #[must_use]
+    pub fn max_n(&self, n: usize) -> Vec<(Frequency, FrequencyValue)> {
+        let mut data = self.data.clone();
+        let n = n.min(data.len());
+        if n == 0 {
+            return Vec::new();
+        }
+        // select in O(n) and only sort the `n` highest pairs
+        data.select_nth_unstable_by(n - 1, |(_l_fr, l_val), (_r_fr, r_val)| r_val.cmp(l_val));
+        data.truncate(n);
+        data.sort_by(|(_l_fr, l_val), (_r_fr, r_val)| r_val.cmp(l_val));
+        data
+    }
+
+    /// Calculates the energy, i.e. the sum of the squared values, between two
+    /// frequencies, e.g. for bass, mid, and treble meters. Each value covers
+    /// a band of [`Self::frequency_resolution`] centered around its
+    /// frequency. Values whose band is only partially inside the range
+    /// contribute proportionally. Parts of the range outside of the spectrum
+    /// contribute nothing.
+    ///
+    /// ## Parameters
+    /// * `from_hz` Lower edge of the range in Hertz.
+    /// * `to_hz` Upper edge of the range in Hertz.
+    ///
+    /// ## Panics
+    /// If `from_hz` is bigger than `to_hz`.
+    #[must_use]
+    pub fn band_energy(&self, from_hz: f32, to_hz: f32) -> f32 {
+        assert!(from_hz <= to_hz, "from_hz must not be bigger than to_hz!");
+        let half_resolution = self.frequency_resolution / 2.0;
+        self.data
+            .iter()
+            .map(|(fr, fr_val)| {
+                let lower = fr.val() - half_resolution;
+                let upper = fr.val() + half_resolution;
+                let overlap = (upper.min(to_hz) - lower.max(from_hz)).max(0.0);
+                fr_val.val() * fr_val.val() * overlap / self.frequency_resolution
+            })
+            .sum()
+    }
+
+    /// Returns a new spectrum that only contains the frequencies inside the
+    /// frequency limit. All statistics, such as [`Self::max`], are
+    /// recalculated. This is cheaper than another FFT if multiple consumers
+    /// need different bands of the same spectrum.
+    ///
+    /// ## Parameters
+    /// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+    ///
+    /// ## Return value
+    /// `None` if less than two frequencies are inside the frequency limit.
+    #[must_use]
+    pub fn sub_spectrum(&self, frequency_limit: FrequencyLimit) -> Option<Self> {
+        let data = self
+            .data
+            .iter()
+            .filter(|(fr, _fr_val)| frequency_limit.contains(fr.val()))
+            .copied()
+            .collect::<Vec<_>>();
+        if data.len() < 2 {
+            return None;
+        }
+        let mut working_buffer = data.clone();
+        let mut spectrum = Self::new(
+            data,
+            self.frequency_resolution,
+            self.samples_len,
+            &mut working_buffer,
+        );
+        spectrum.set_unpadded_samples_len(self.unpadded_samples_len);
+        Some(spectrum)
+    }
+
+    /// Calculates the signal-to-noise ratio (SNR) in decibel. The power of
+    /// the frequencies in `signal_bins` is the signal, the power of all
+    /// other frequencies, except the DC component, is the noise. The values
+    /// of the spectrum must be magnitudes (and not in decibel).
+    ///
+    /// ## Parameters
+    /// * `signal_bins` Indices in [`Self::data`] that belong to the signal,
+    ///                 e.g. the frequency of a test tone and a few
+    ///                 neighbours to include the leakage of the window
+    ///                 function.
+    ///
+    /// ## Return value
+    /// `None` if the power of the signal or of the noise is zero.
+    ///
+    /// ## Panics
+    /// If `signal_bins` is out of bounds.
+    #[must_use]
+    pub fn snr(&self, signal_bins: RangeInclusive<usize>) -> Option<f32> {
+        assert!(
+            *signal_bins.end() < self.data.len(),
+            "signal_bins out of bounds!"
+        );
+        let skip_dc = usize::from(self.dc_component().is_some());
+        let (signal_power, noise_power) = self
+            .data
+            .iter()
+            .enumerate()
+            .skip(skip_dc)
+            .map(|(i, (_fr, val))| (i, val.val() * val.val()))
+            .fold((0.0, 0.0), |(signal, noise), (i, power)| {
+                if signal_bins.contains(&i) {
+                    (signal + power, noise)
+                } else {
+                    (signal, noise + power)
+                }
+            });
+        if signal_power > 0.0 && noise_power > 0.0 {
+            Some(10.0 * libm::log10f(signal_power / noise_power))
+        } else {
+            None
+        }
+    }
+
+    /// Calculates the spurious-free dynamic range (SFDR) in decibel, i.e.
+    /// the ratio of the highest peak (the signal) to the second highest peak
+    /// (the strongest spur). The DC component and the highest frequency are
+    /// never considered as peak, see [`Self::find_peaks`]. The values of the
+    /// spectrum must be magnitudes (and not in decibel).
+    ///
+    /// Use a window function with low side lobes, such as
+    /// [`crate::windows::blackman_harris_4term`], as side lobes of the
+    /// signal are detected as spurs.
+    ///
+    /// ## Return value
+    /// `None` if there are less than two peaks.
+    #[must_use]
+    pub fn sfdr(&self) -> Option<f32> {
+        let mut peaks = self.find_peaks(&PeakConfig::default());
+        peaks.sort_by_key(|peak| core::cmp::Reverse(peak.value));
+        match peaks.as_slice() {
+            [signal, spur, ..] if spur.value.val() > 0.0 => {
+                Some(20.0 * libm::log10f(signal.value.val() / spur.value.val()))
+            }
+            _ => None,
+        }
+    }
 
-        // Because we sorted the vector from lowest to highest value, the
-        // following lines are correct, i.e., we get min/max value with
-        // the corresponding frequency.
-        let min = data_sorted_by_val[0];
-        let max = data_sorted_by_val[data_sorted_by_val.len() - 1];
+    /// Calculates the `min`, `max`, and `average` of the frequency
+    /// values/magnitudes/amplitudes in a single pass and invalidates the
+    /// cached `median`.
+    #[inline]
+    fn calc_statistics(&mut self) {
+        // single pass for min, max, and the sum of all frequency values
+        let mut min = self.data[0];
+        let mut max = self.data[0];
+        let mut sum = 0.0;
+        for pair in &self.data {
+            // first occurrence of the minimum, i.e. the lowest frequency
+            if pair.1 < min.1 {
+                min = *pair;
+            }
+            // last occurrence of the maximum, i.e. the highest frequency
+            if pair.1 >= max.1 {
+                max = *pair;
+            }
+            sum += pair.1.val();
+        }
 
         // check that I get the comparison right (and not from max to min)
         debug_assert!(min.1 <= max.1, "min must be <= max");
 
         self.min = min;
         self.max = max;
-        self.average = average;
-        self.median = median;
+        self.average = (sum / self.data.len() as f32).into();
+        // the median is calculated lazily
+        *self.median.get_mut() = MEDIAN_NOT_CALCULATED;
+    }
+}
+
+/// Human-readable summary of the spectrum with its frequency range, the
+/// frequency resolution, and the maximum, e.g.
+/// `0 Hz..22.05 kHz, 1025 frequencies, resolution 21.533 Hz, max 1 kHz: 0.5`.
+/// The precision of the formatter applies to all numbers.
+impl Display for FrequencySpectrum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let (max_fr, max_val) = self.max();
+        let precision = f.precision();
+        let write_fr = |f: &mut Formatter<'_>, fr: Frequency| match precision {
+            Some(precision) => write!(f, "{:.*}", precision, fr.display_hz()),
+            None => write!(f, "{}", fr.display_hz()),
+        };
+        write_fr(f, self.min_fr())?;
+        write!(f, "..")?;
+        write_fr(f, self.max_fr())?;
+        write!(f, ", {} frequencies, resolution ", self.data.len())?;
+        write_fr(f, self.frequency_resolution.into())?;
+        write!(f, ", max ")?;
+        write_fr(f, max_fr)?;
+        match precision {
+            Some(precision) => write!(f, ": {:.*}", precision, max_val),
+            None => write!(f, ": {}", max_val),
+        }
+    }
+}
+
+/// Compact summary of the spectrum for logging on embedded targets. Like the
+/// [`Display`] implementation but always in Hz, e.g.
+/// `0 Hz..22050 Hz, 1025 frequencies, resolution 21.533 Hz, max 1000 Hz: 0.5`.
+#[cfg(feature = "defmt")]
+impl defmt::Format for FrequencySpectrum {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        let (max_fr, max_val) = self.max();
+        defmt::write!(
+            f,
+            "{} Hz..{} Hz, {=usize} frequencies, resolution {=f32} Hz, max {} Hz: {}",
+            self.min_fr(),
+            self.max_fr(),
+            self.data.len(),
+            self.frequency_resolution,
+            max_fr,
+            max_val
+        );
     }
 }
 
@@ -548,7 +1438,7 @@ impl FrequencySpectrum {
     }
 }*/
 
-mod math {
+pub(crate) mod math {
     // use super::*;
 
     /// Calculates the y coordinate of Point C between two given points A and B
@@ -582,16 +1472,23 @@ mod math {
         slope * x_coord + c
     }
 
-    /// Converts hertz to [mel](https://en.wikipedia.org/wiki/Mel_scale).
-    pub fn hertz_to_mel(hz: f32) -> f32 {
-        assert!(hz >= 0.0);
-        2595.0 * libm::log10f(1.0 + (hz / 700.0))
-    }
-
-    /// Converts [mel](https://en.wikipedia.org/wiki/Mel_scale) to hertz.
-    pub fn mel_to_hertz(mel: f32) -> f32 {
-        assert!(mel >= 0.0);
-        700.0 * (libm::powf(10.0, mel / 2595.0) - 1.0)
+    /// Fits a parabola through three equidistant points with the y-coordinates
+    /// `left`, `center`, and `right` and returns the vertex of the parabola.
+    ///
+    /// ## Return Value
+    /// Tuple of the x-offset of the vertex relative to `center` in units of
+    /// the distance between two points (in interval `[-0.5; 0.5]` if `center`
+    /// is the maximum) and the y-coordinate of the vertex.
+    #[inline]
+    pub fn parabolic_interpolation(left: f32, center: f32, right: f32) -> (f32, f32) {
+        let denominator = left - 2.0 * center + right;
+        if denominator == 0.0 {
+            // all points on a line: nothing to interpolate
+            return (0.0, center);
+        }
+        let offset = 0.5 * (left - right) / denominator;
+        let value = center - 0.25 * (left - right) * offset;
+        (offset, value)
     }
 
     #[cfg(test)]
@@ -621,16 +1518,13 @@ mod math {
         }
 
         #[test]
-        fn test_mel() {
-            float_cmp::assert_approx_eq!(f32, hertz_to_mel(0.0), 0.0, epsilon = 0.1);
-            float_cmp::assert_approx_eq!(f32, hertz_to_mel(500.0), 607.4, epsilon = 0.1);
-            float_cmp::assert_approx_eq!(f32, hertz_to_mel(5000.0), 2363.5, epsilon = 0.1);
-
-            let conv = |hz: f32| mel_to_hertz(hertz_to_mel(hz));
-
-            float_cmp::assert_approx_eq!(f32, conv(0.0), 0.0, epsilon = 0.1);
-            float_cmp::assert_approx_eq!(f32, conv(1000.0), 1000.0, epsilon = 0.1);
-            float_cmp::assert_approx_eq!(f32, conv(10000.0), 10000.0, epsilon = 0.1);
+        fn test_parabolic_interpolation() {
+            // y = 10 - (x - 0.3)^2 at x = -1, 0, 1
+            let f = |x: f32| 10.0 - (x - 0.3) * (x - 0.3);
+            let (offset, value) = parabolic_interpolation(f(-1.0), f(0.0), f(1.0));
+            float_cmp::assert_approx_eq!(f32, offset, 0.3, epsilon = 0.0001);
+            float_cmp::assert_approx_eq!(f32, value, 10.0, epsilon = 0.0001);
+            assert_eq!(parabolic_interpolation(1.0, 1.0, 1.0), (0.0, 1.0));
         }
     }
 }
@@ -639,13 +1533,26 @@ mod math {
 mod tests {
     use super::*;
 
-    /// Test if a frequency spectrum can be sent to other threads.
+    /// Creates a spectrum of the given values at multiples of `res` Hertz,
+    /// starting at `0 Hz`.
+    fn spectrum_of(values: &[f32], res: f32) -> FrequencySpectrum {
+        let mut data = values
+            .iter()
+            .enumerate()
+            .map(|(i, val)| (Frequency::from(i as f32 * res), FrequencyValue::from(*val)))
+            .collect::<Vec<_>>();
+        FrequencySpectrum::new(data.clone(), res, data.len() as _, &mut data)
+    }
+
+    /// Test if a frequency spectrum can be sent to and shared with other
+    /// threads.
     #[test]
     const fn test_impl_send() {
         #[allow(unused)]
         // test if this compiles
         fn consume(s: FrequencySpectrum) {
             let _: &dyn Send = &s;
+            let _: &dyn Sync = &s;
         }
     }
 
@@ -982,6 +1889,205 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_apply_frequency_scaling_fn() {
+        let mut spectrum_vector: Vec<(Frequency, FrequencyValue)> = vec![
+            (0.0.into(), 1.0.into()),
+            (50.0.into(), 1.0.into()),
+            (100.0.into(), 1.0.into()),
+        ];
+        let mut spectrum = FrequencySpectrum::new(
+            spectrum_vector.clone(),
+            50.0,
+            spectrum_vector.len() as _,
+            &mut spectrum_vector,
+        );
+        spectrum
+            .apply_frequency_scaling_fn(&|fr, fr_val, _stats| fr_val * fr, &mut spectrum_vector)
+            .unwrap();
+        assert_eq!(spectrum.freq_val_exact(50.0).val(), 50.0);
+        assert_eq!(spectrum.max(), (100.0.into(), 100.0.into()));
+        assert_eq!(spectrum.min(), (0.0.into(), 0.0.into()));
+
+        spectrum
+            .apply_frequency_scaling_fn(&|fr, fr_val, _stats| fr_val / fr, &mut spectrum_vector)
+            .expect_err("division by zero must be detected");
+    }
+
+    #[test]
+    fn test_subtract_noise_profile() {
+        let mut spectrum = spectrum_of(&[2.0, 10.0, 1.0, 3.0], 50.0);
+        let noise_profile = spectrum_of(&[1.0, 1.0, 2.0, 1.0], 50.0);
+        spectrum.subtract_noise_profile(&noise_profile, 0.1);
+
+        let values = spectrum
+            .data()
+            .iter()
+            .map(|(_fr, val)| val.val())
+            .collect::<Vec<_>>();
+        // third value is the floor
+        assert_eq!(values, [1.0, 9.0, 0.1, 2.0]);
+        assert_eq!(spectrum.max(), (50.0.into(), 9.0.into()));
+        assert_eq!(spectrum.min(), (100.0.into(), 0.1.into()));
+    }
+
+    #[test]
+    fn test_noise_floor() {
+        // more tones than noise: the median is not a noise estimate anymore
+        let spectrum = spectrum_of(&[1.0, 10.0, 10.0, 2.0, 10.0, 10.0, 1.0, 10.0], 50.0);
+        assert_eq!(spectrum.median().val(), 10.0);
+        assert_eq!(spectrum.noise_floor().val(), 1.75);
+        assert_eq!(spectrum.percentile(0.0).val(), 1.0);
+        assert_eq!(spectrum.percentile(0.5), spectrum.median());
+        assert_eq!(spectrum.percentile(1.0), spectrum.max().1);
+    }
+
+    #[test]
+    fn test_lazy_median() {
+        let mut spectrum = spectrum_of(&[4.0, 1.0, 3.0, 2.0], 50.0);
+        assert!(spectrum.cached_median().is_none());
+        assert_eq!(spectrum.median().val(), 2.5);
+        assert_eq!(spectrum.cached_median().unwrap().val(), 2.5);
+
+        // the scaling function receives the median; afterwards, it is
+        // invalidated
+        let mut working_buffer = spectrum.to_vec();
+        spectrum
+            .apply_scaling_fn(&|val, stats| val * stats.median, &mut working_buffer)
+            .unwrap();
+        assert!(spectrum.cached_median().is_none());
+        assert_eq!(spectrum.median().val(), 6.25);
+        assert_eq!(spectrum.max(), (0.0.into(), 10.0.into()));
+    }
+
+    #[test]
+    fn test_variance_std_dev_rms() {
+        let spectrum = spectrum_of(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 50.0);
+        assert_eq!(spectrum.average().val(), 5.0);
+        assert_eq!(spectrum.variance(), 4.0);
+        assert_eq!(spectrum.std_dev(), 2.0);
+        float_cmp::assert_approx_eq!(
+            f32,
+            spectrum.rms().val(),
+            libm::sqrtf(29.0),
+            epsilon = 0.0001
+        );
+        assert_eq!(spectrum.percentile(0.95).val(), 8.3);
+    }
+
+    #[test]
+    fn test_max_n_and_strongest_peaks() {
+        let spectrum = spectrum_of(&[0.0, 3.0, 2.5, 0.0, 5.0, 0.0, 1.0, 0.0], 50.0);
+
+        let max_n = spectrum.max_n(3);
+        assert_eq!(
+            max_n,
+            vec![
+                (200.0.into(), 5.0.into()),
+                (50.0.into(), 3.0.into()),
+                (100.0.into(), 2.5.into())
+            ]
+        );
+        assert!(spectrum.max_n(0).is_empty());
+        assert_eq!(spectrum.max_n(100).len(), 8);
+
+        let peaks = spectrum.strongest_peaks(2, &PeakConfig::default());
+        let frequencies = peaks.iter().map(|p| p.frequency.val()).collect::<Vec<_>>();
+        assert_eq!(frequencies, vec![200.0, 50.0]);
+    }
+
+    #[test]
+    fn test_index_accessors() {
+        let mut spectrum_vector: Vec<(Frequency, FrequencyValue)> = vec![
+            (100.0.into(), 1.0.into()),
+            (150.0.into(), 2.0.into()),
+            (300.0.into(), 3.0.into()),
+        ];
+        let spectrum =
+            FrequencySpectrum::new(spectrum_vector.clone(), 50.0, 16, &mut spectrum_vector);
+        assert_eq!(spectrum.frequency_of_index(3).val(), 150.0);
+        assert_eq!(spectrum.index_of_frequency(160.0), 3);
+        assert_eq!(spectrum.index_of_frequency(-10.0), 0);
+        assert_eq!(spectrum.value_at_index(2).unwrap().val(), 1.0);
+        assert_eq!(spectrum.value_at_index(6).unwrap().val(), 3.0);
+        // gap and outside
+        assert_eq!(spectrum.value_at_index(4), None);
+        assert_eq!(spectrum.value_at_index(0), None);
+        assert_eq!(spectrum.value_at_index(7), None);
+    }
+
+    #[test]
+    fn test_owned_data() {
+        let mut spectrum_vector: Vec<(Frequency, FrequencyValue)> = vec![
+            (0.0.into(), 1.0.into()),
+            (50.0.into(), 2.0.into()),
+            (100.0.into(), 3.0.into()),
+        ];
+        let spectrum =
+            FrequencySpectrum::new(spectrum_vector.clone(), 50.0, 4, &mut spectrum_vector);
+        assert_eq!(spectrum.to_vec(), spectrum_vector);
+        assert_eq!(
+            spectrum.to_xy_vecs(),
+            (vec![0.0, 50.0, 100.0], vec![1.0, 2.0, 3.0])
+        );
+        assert_eq!(spectrum.into_inner(), spectrum_vector);
+    }
+
+    #[test]
+    fn test_sub_spectrum() {
+        let spectrum = spectrum_of(&[5.0, 0.1, 0.2, 10.0, 0.3, 1.0, 0.4, 0.5], 50.0);
+
+        let sub = spectrum
+            .sub_spectrum(FrequencyLimit::Range(200.0, 350.0))
+            .unwrap();
+        assert_eq!(sub.data(), &spectrum.data()[4..]);
+        assert_eq!(sub.max(), (250.0.into(), 1.0.into()));
+        assert_eq!(sub.min(), (200.0.into(), 0.3.into()));
+        assert_eq!(sub.frequency_resolution(), 50.0);
+        assert_eq!(sub.samples_len(), spectrum.samples_len());
+
+        assert!(spectrum
+            .sub_spectrum(FrequencyLimit::Range(10.0, 60.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_snr_and_sfdr() {
+        let spectrum = spectrum_of(&[5.0, 0.1, 0.1, 10.0, 0.1, 1.0, 0.1, 0.1], 50.0);
+        // noise power: 5 * 0.01 + 1.0 (the DC component is ignored)
+        float_cmp::assert_approx_eq!(
+            f32,
+            spectrum.snr(3..=3).unwrap(),
+            10.0 * libm::log10f(100.0 / 1.05),
+            epsilon = 0.0001
+        );
+        assert!(spectrum.snr(1..=7).is_none());
+        float_cmp::assert_approx_eq!(f32, spectrum.sfdr().unwrap(), 20.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_band_energy() {
+        let spectrum = spectrum_of(&[1.0, 2.0, 3.0, 4.0], 50.0);
+        assert_eq!(spectrum.band_energy(25.0, 125.0), 13.0);
+        assert_eq!(spectrum.band_energy(-100.0, 1000.0), 30.0);
+        // half of 50 Hz and half of 100 Hz
+        assert_eq!(spectrum.band_energy(50.0, 100.0), 6.5);
+        assert_eq!(spectrum.band_energy(60.0, 60.0), 0.0);
+    }
+
+    #[test]
+    fn test_max_interpolated() {
+        let spectrum = spectrum_of(&[0.0, 4.0, 8.0, 6.0, 0.0], 50.0);
+        let (fr, val) = spectrum.max_interpolated();
+        // vertex is between 100 Hz and 150 Hz
+        assert_eq!(fr.val(), 100.0 + 50.0 / 6.0);
+        assert!(val.val() > 8.0);
+        // borders are not interpolated
+        assert_eq!(spectrum.peak_interpolated(0), spectrum.data()[0]);
+        // no local maximum
+        assert_eq!(spectrum.peak_interpolated(1), spectrum.data()[1]);
+    }
+
     #[test]
     fn test_mel_getter() {
         let mut spectrum_vector = vec![
@@ -997,4 +2103,22 @@ mod tests {
         );
         let _ = spectrum.mel_val(450.0);
     }
+
+    #[test]
+    fn test_display() {
+        let mut data = vec![
+            (0.0_f32.into(), 5.0_f32.into()),
+            (1000.0.into(), 200.5.into()),
+            (2000.0.into(), 1.0.into()),
+        ];
+        let spectrum = FrequencySpectrum::new(data.clone(), 1000.0, 4, &mut data);
+        assert_eq!(
+            alloc::format!("{}", spectrum),
+            "0 Hz..2 kHz, 3 frequencies, resolution 1 kHz, max 1 kHz: 200.5"
+        );
+        assert_eq!(
+            alloc::format!("{:.1}", spectrum),
+            "0.0 Hz..2.0 kHz, 3 frequencies, resolution 1.0 kHz, max 1.0 kHz: 200.5"
+        );
+    }
 }
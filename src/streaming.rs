@@ -0,0 +1,271 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the [`StreamingAnalyzer`], which analyzes a continuous stream
+//! of samples that arrives in blocks of arbitrary size.
+
+use crate::analyzer::SpectrumAnalyzer;
+use crate::error::SpectrumAnalyzerError;
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Analyzes a continuous stream of samples with (overlapping) frames. Audio
+/// callbacks rarely deliver blocks of exactly the frame length, so this
+/// accepts blocks of any size via [`Self::push`]. The latest samples are kept
+/// in a ring buffer, and a new spectrum is emitted every `hop_len` samples
+/// once the first frame is complete.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::{SpectrumAnalyzerBuilder, StreamingAnalyzer};
+/// use spectrum_analyzer::windows::hann_window;
+/// let analyzer = SpectrumAnalyzerBuilder::new(1024, 44100)
+///     .window_fn(&hann_window)
+///     .build()
+///     .unwrap();
+/// // overlap of 75%
+/// let mut streaming = StreamingAnalyzer::with_overlap(analyzer, 0.75);
+/// assert_eq!(streaming.hop_len(), 256);
+/// // audio callback
+/// # let block = [0.0; 441];
+/// for spectrum in streaming.push(&block).unwrap() {
+///     let spectrum = spectrum.unwrap();
+///     // ...
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamingAnalyzer {
+    analyzer: SpectrumAnalyzer,
+    hop_len: usize,
+    /// The latest [`SpectrumAnalyzer::samples_len`] samples.
+    ring: Vec<f32>,
+    /// Index of the oldest sample in [`Self::ring`], i.e. the next index to
+    /// write to.
+    ring_pos: usize,
+    /// Number of samples to push until the next spectrum is emitted.
+    pending: usize,
+    /// Chronologically ordered copy of [`Self::ring`] for the analysis.
+    frame: Vec<f32>,
+}
+
+impl StreamingAnalyzer {
+    /// Creates a new streaming analyzer that emits a spectrum every
+    /// `hop_len` samples.
+    ///
+    /// ## Parameters
+    /// * `analyzer` Analyzer for each frame. The frame length is
+    ///              [`SpectrumAnalyzer::samples_len`].
+    /// * `hop_len` Number of new samples between two frames, e.g. half the
+    ///             frame length for an overlap of 50%. May be bigger than
+    ///             the frame length to skip samples.
+    ///
+    /// ## Panics
+    /// If `hop_len` is zero.
+    #[must_use]
+    pub fn new(analyzer: SpectrumAnalyzer, hop_len: usize) -> Self {
+        assert_ne!(hop_len, 0, "The hop length must not be zero!");
+        let frame_len = analyzer.samples_len();
+        Self {
+            analyzer,
+            hop_len,
+            ring: vec![0.0; frame_len],
+            ring_pos: 0,
+            pending: frame_len,
+            frame: vec![0.0; frame_len],
+        }
+    }
+
+    /// Like [`Self::new`] but calculates the hop length from the overlap of
+    /// two consecutive frames, e.g. `0.5` for 50% or `0.75` for 75%. The hop
+    /// length is rounded and at least one sample.
+    ///
+    /// ## Panics
+    /// If `overlap` is not in the range `[0.0, 1.0)`.
+    #[must_use]
+    pub fn with_overlap(analyzer: SpectrumAnalyzer, overlap: f32) -> Self {
+        assert!(
+            (0.0..1.0).contains(&overlap),
+            "The overlap must be in the range [0.0, 1.0)!"
+        );
+        let hop_len = libm::roundf(analyzer.samples_len() as f32 * (1.0 - overlap)) as usize;
+        Self::new(analyzer, hop_len.max(1))
+    }
+
+    /// Returns the number of samples between two emitted spectra.
+    #[must_use]
+    pub const fn hop_len(&self) -> usize {
+        self.hop_len
+    }
+
+    /// Returns the underlying analyzer.
+    #[must_use]
+    pub const fn analyzer(&self) -> &SpectrumAnalyzer {
+        &self.analyzer
+    }
+
+    /// Appends the samples to the stream and returns the spectra of all
+    /// frames that were completed by them, in chronological order. Usually,
+    /// this is none or one spectrum, unless `samples` is longer than the hop
+    /// length.
+    ///
+    /// ## Return value
+    /// One result per completed frame. A frame fails with
+    /// [`SpectrumAnalyzerError::ScalingError`] if the scaling function of the
+    /// analyzer produced invalid values. This doesn't affect the other frames,
+    /// as their samples can't be pushed again.
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::NaNValuesNotSupported`] or
+    /// [`SpectrumAnalyzerError::InfinityValuesNotSupported`] for invalid
+    /// samples. In this case, none of the samples are appended.
+    #[allow(clippy::type_complexity)]
+    pub fn push(
+        &mut self,
+        samples: &[f32],
+    ) -> Result<Vec<Result<FrequencySpectrum, SpectrumAnalyzerError>>, SpectrumAnalyzerError> {
+        if samples.iter().any(|x| x.is_nan()) {
+            return Err(SpectrumAnalyzerError::NaNValuesNotSupported);
+        }
+        if samples.iter().any(|x| x.is_infinite()) {
+            return Err(SpectrumAnalyzerError::InfinityValuesNotSupported);
+        }
+
+        let mut spectra = Vec::new();
+        for &sample in samples {
+            self.ring[self.ring_pos] = sample;
+            self.ring_pos = (self.ring_pos + 1) % self.ring.len();
+            self.pending -= 1;
+            if self.pending == 0 {
+                self.pending = self.hop_len;
+                let (newer, older) = self.ring.split_at(self.ring_pos);
+                let (frame_older, frame_newer) = self.frame.split_at_mut(older.len());
+                frame_older.copy_from_slice(older);
+                frame_newer.copy_from_slice(newer);
+                spectra.push(self.analyzer.analyze(&self.frame));
+            }
+        }
+        Ok(spectra)
+    }
+
+    /// Discards all buffered samples, so that the next spectrum is emitted
+    /// once a whole new frame was pushed.
+    pub fn reset(&mut self) {
+        self.ring.fill(0.0);
+        self.ring_pos = 0;
+        self.pending = self.ring.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit, SpectrumAnalyzerBuilder};
+
+    fn samples(len: usize) -> Vec<f32> {
+        (0..len).map(|i| libm::sinf(i as f32 * 0.3)).collect()
+    }
+
+    #[cfg(feature = "size-64")]
+    #[test]
+    fn test_push_arbitrary_blocks() {
+        let analyzer = SpectrumAnalyzerBuilder::new(64, 44100).build().unwrap();
+        let mut streaming = StreamingAnalyzer::with_overlap(analyzer, 0.5);
+        assert_eq!(streaming.hop_len(), 32);
+
+        let samples = samples(200);
+        let mut spectra = Vec::new();
+        for block in samples.chunks(37) {
+            spectra.extend(
+                streaming
+                    .push(block)
+                    .unwrap()
+                    .into_iter()
+                    .map(Result::unwrap),
+            );
+        }
+        // frames start at 0, 32, 64, 96, and 128
+        assert_eq!(spectra.len(), 5);
+        for (i, spectrum) in spectra.iter().enumerate() {
+            let expected = samples_fft_to_spectrum(
+                &samples[i * 32..i * 32 + 64],
+                44100,
+                FrequencyLimit::All,
+                None,
+            )
+            .unwrap();
+            assert_eq!(spectrum.data(), expected.data());
+        }
+    }
+
+    #[cfg(feature = "size-16")]
+    #[test]
+    fn test_hop_longer_than_frame() {
+        let analyzer = SpectrumAnalyzerBuilder::new(16, 44100).build().unwrap();
+        let mut streaming = StreamingAnalyzer::new(analyzer, 40);
+        // frames end at 16, 56, and 96
+        assert_eq!(streaming.push(&samples(100)).unwrap().len(), 3);
+
+        streaming.reset();
+        assert_eq!(streaming.push(&samples(15)).unwrap().len(), 0);
+        assert_eq!(streaming.push(&samples(1)).unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "size-16")]
+    #[test]
+    fn test_invalid_samples() {
+        let analyzer = SpectrumAnalyzerBuilder::new(16, 44100).build().unwrap();
+        let mut streaming = StreamingAnalyzer::new(analyzer, 8);
+        let mut samples = samples(16);
+        samples[3] = f32::NAN;
+        assert!(matches!(
+            streaming.push(&samples),
+            Err(SpectrumAnalyzerError::NaNValuesNotSupported)
+        ));
+        // nothing was appended
+        assert_eq!(streaming.push(&[0.0; 15]).unwrap().len(), 0);
+    }
+
+    #[cfg(feature = "size-16")]
+    #[test]
+    fn test_failing_frame_keeps_other_spectra() {
+        // fails for all frames with loud samples
+        let analyzer = SpectrumAnalyzerBuilder::new(16, 44100)
+            .scaling_fn(|val, stats| if stats.max > 100.0 { f32::NAN } else { val })
+            .build()
+            .unwrap();
+        let mut streaming = StreamingAnalyzer::new(analyzer, 16);
+        let mut samples = samples(48);
+        samples[16..32].fill(1000.0);
+        let spectra = streaming.push(&samples).unwrap();
+        assert_eq!(spectra.len(), 3);
+        assert!(spectra[0].is_ok());
+        assert!(matches!(
+            spectra[1],
+            Err(SpectrumAnalyzerError::ScalingError(..))
+        ));
+        assert!(spectra[2].is_ok());
+    }
+}
@@ -26,8 +26,8 @@ SOFTWARE.
 use crate::error::SpectrumAnalyzerError;
 use crate::scaling::{divide_by_N, scale_to_zero_to_one};
 use crate::tests::sine::sine_wave_audio_data_multiple;
-use crate::windows::{hamming_window, hann_window};
-use crate::{samples_fft_to_spectrum, FrequencyLimit};
+use crate::windows::{hamming_window, hann_window, Window};
+use crate::{samples_fft_to_spectrum, samples_fft_to_spectrum_windowed, FrequencyLimit};
 use alloc::vec::Vec;
 use audio_visualizer::spectrum::plotters_png_file::spectrum_static_plotters_png_visualize;
 use audio_visualizer::waveform::plotters_png_file::waveform_static_plotters_png_visualize;
@@ -41,6 +41,7 @@ const TEST_OUT_DIR: &str = "test/out";
 
 mod sine;
 
+#[cfg(feature = "size-4096")]
 #[test]
 fn test_spectrum_and_visualize_sine_waves_50_1000_3777hz() {
     let sine_audio = sine_wave_audio_data_multiple(&[50.0, 1000.0, 3777.0], 44100, 1000);
@@ -139,6 +140,7 @@ fn test_spectrum_and_visualize_sine_waves_50_1000_3777hz() {
 /// signal twice, but one time for twice the duration. If all FFT result values are
 /// divided by their corresponding N (length of samples), the values must match
 /// (with a small delta).
+#[cfg(feature = "size-4096")]
 #[test]
 fn test_spectrum_power() {
     let interesting_frequency = 2048.0;
@@ -213,6 +215,7 @@ fn test_spectrum_power() {
     //);
 }
 
+#[cfg(feature = "size-512")]
 #[test]
 fn test_spectrum_frequency_limit_inclusive() {
     let sampling_rate = 1024;
@@ -282,6 +285,7 @@ fn test_spectrum_frequency_limit_inclusive() {
 }
 
 /// Tests that the spectrum contains the Nyquist frequency.
+#[cfg(feature = "size-4096")]
 #[test]
 fn test_spectrum_nyquist_theorem() {
     let dummy_audio_samples = vec![0.0; 4096];
@@ -312,6 +316,7 @@ fn test_spectrum_nyquist_theorem() {
 }
 /// Tests that the spectrum contains the Nyquist frequency using a sine wave at almost Nyquist
 /// frequency.
+#[cfg(feature = "size-4096")]
 #[test]
 fn test_spectrum_nyquist_theorem2() {
     let sine_audio = sine_wave_audio_data_multiple(
@@ -397,13 +402,44 @@ fn test_invalid_input() {
         SpectrumAnalyzerError::InvalidFrequencyLimit(_)
     ));
 
-    // samples length not a power of two
-    let samples = vec![0.0; 3];
-    let err = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap_err();
-    assert!(matches!(
-        err,
-        SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
-    ));
+    // samples length not a power of two (supported by `rustfft`)
+    #[cfg(not(feature = "rustfft"))]
+    {
+        let samples = vec![0.0; 3];
+        let err = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+        ));
+    }
+
+    // empty input
+    let err = samples_fft_to_spectrum(&[], 44100, FrequencyLimit::All, None).unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::TooFewSamples));
+
+    // power of two, but too big for the FFT implementation (supported by
+    // `rustfft`)
+    #[cfg(not(feature = "rustfft"))]
+    {
+        let samples = vec![0.0; crate::MAX_SAMPLES_LEN * 2];
+        let err = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap_err();
+        assert!(matches!(err, SpectrumAnalyzerError::TooManySamples));
+    }
+}
+
+/// Lengths that `microfft` doesn't support fall back to `rustfft`.
+#[cfg(feature = "rustfft")]
+#[test]
+fn test_rustfft_fallback_for_arbitrary_lengths() {
+    // 480 samples of an Opus frame with a sine of 1 kHz at 48 kHz
+    let samples = (0..480)
+        .map(|i| libm::sinf(2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 48000.0))
+        .collect::<Vec<_>>();
+    let spectrum = samples_fft_to_spectrum(&samples, 48000, FrequencyLimit::All, None).unwrap();
+    assert_eq!(spectrum.data().len(), 241);
+    assert_eq!(spectrum.frequency_resolution(), 100.0);
+    assert_eq!(spectrum.max().0.val(), 1000.0);
+    assert!((spectrum.max().1.val() - 240.0).abs() < 0.01);
 }
 
 #[test]
@@ -425,6 +461,7 @@ fn test_scaling_produces_error() {
 }
 
 /// Test that the scaling actually has the effect that we expect it to have.
+#[cfg(feature = "size-1024")]
 #[test]
 fn test_divide_by_n_has_effect() {
     let audio_data = sine_wave_audio_data_multiple(&[100.0, 200.0, 400.0], 1000, 2000);
@@ -465,3 +502,619 @@ fn test_divide_by_n_has_effect() {
         );
     }
 }
+
+/// Tests that the phase of a sine and a cosine wave differ by 90 degrees.
+#[cfg(feature = "size-1024")]
+#[test]
+fn test_complex_spectrum_phase() {
+    use crate::samples_fft_to_complex_spectrum;
+    use core::f32::consts::PI;
+
+    // frequency resolution: 1024 / 1024 = 1 Hz => 64 Hz is exactly hit
+    let sampling_rate = 1024;
+    let cosine = (0..1024)
+        .map(|i| libm::cosf(2.0 * PI * 64.0 * i as f32 / sampling_rate as f32))
+        .collect::<Vec<_>>();
+    let sine = (0..1024)
+        .map(|i| libm::sinf(2.0 * PI * 64.0 * i as f32 / sampling_rate as f32))
+        .collect::<Vec<_>>();
+
+    let cosine_spectrum =
+        samples_fft_to_complex_spectrum(&cosine, sampling_rate, FrequencyLimit::All).unwrap();
+    let sine_spectrum =
+        samples_fft_to_complex_spectrum(&sine, sampling_rate, FrequencyLimit::All).unwrap();
+
+    float_cmp::assert_approx_eq!(f32, cosine_spectrum.phase_at(64.0), 0.0, epsilon = 0.01);
+    float_cmp::assert_approx_eq!(f32, sine_spectrum.phase_at(64.0), -PI / 2.0, epsilon = 0.01);
+    // magnitude of a sine with amplitude 1 is N/2
+    float_cmp::assert_approx_eq!(
+        f32,
+        sine_spectrum.magnitude_at(64.0).val(),
+        512.0,
+        epsilon = 0.5
+    );
+    assert_eq!(
+        sine_spectrum.to_magnitude_spectrum().max().0.val(),
+        64.0,
+        "magnitude spectrum must have peak at 64 Hz"
+    );
+}
+
+/// Tests that the interpolated maximum is closer to the real frequency than
+/// the bin frequency.
+#[cfg(feature = "size-4096")]
+#[test]
+fn test_max_interpolated() {
+    let frequency = 1013.0;
+    let sine_audio = sine_wave_audio_data_multiple(&[frequency], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<f32>>();
+    let window = hann_window(&sine_audio[0..4096]);
+    let spectrum = samples_fft_to_spectrum(&window, 44100, FrequencyLimit::All, None).unwrap();
+
+    let bin_error = (spectrum.max().0.val() - frequency).abs();
+    let interpolated_error = (spectrum.max_interpolated().0.val() - frequency).abs();
+    assert!(
+        interpolated_error < bin_error,
+        "interpolation must improve the accuracy: {} vs {}",
+        interpolated_error,
+        bin_error
+    );
+    assert!(interpolated_error < 1.0);
+}
+
+/// Tests that the A-weighting attenuates low frequencies.
+#[cfg(feature = "size-4096")]
+#[test]
+fn test_a_weighting() {
+    use crate::weighting::Weighting;
+
+    let sine_audio = sine_wave_audio_data_multiple(&[100.0, 1000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<f32>>();
+    let window = hann_window(&sine_audio[0..4096]);
+    let mut spectrum =
+        samples_fft_to_spectrum(&window, 44100, FrequencyLimit::Max(2000.0), None).unwrap();
+    let before = spectrum.freq_val_closest(100.0).1.val();
+    spectrum.apply_weighting(Weighting::A);
+    let after = spectrum.freq_val_closest(100.0).1.val();
+
+    // roughly -19 dB
+    assert!(after < before * 0.15);
+    assert_eq!(spectrum.max().0, spectrum.freq_val_closest(1000.0).0);
+}
+
+/// Tests that the tuner maps the strongest frequency to the nearest note.
+#[cfg(feature = "size-4096")]
+#[test]
+fn test_tuner() {
+    use crate::note::{tuner, NoteName, DEFAULT_REFERENCE_A4};
+
+    // A4, slightly sharp
+    let sine_audio = sine_wave_audio_data_multiple(&[442.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<f32>>();
+    let window = hann_window(&sine_audio[0..4096]);
+    let spectrum = samples_fft_to_spectrum(&window, 44100, FrequencyLimit::All, None).unwrap();
+    let note = tuner(&spectrum, DEFAULT_REFERENCE_A4).unwrap();
+    assert_eq!(NoteName::A, note.name);
+    assert_eq!(4, note.octave);
+}
+
+/// Tests that samples of arbitrary length can be analyzed with padding.
+#[cfg(feature = "size-8192")]
+#[test]
+fn test_zero_padding() {
+    use crate::padding::Padding;
+    use crate::samples_fft_to_spectrum_padded;
+
+    let sine_audio = sine_wave_audio_data_multiple(&[1000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<f32>>();
+    let window = hann_window(&sine_audio[0..1000]);
+
+    // `rustfft` supports the length without padding
+    #[cfg(not(feature = "rustfft"))]
+    {
+        let err = samples_fft_to_spectrum_padded(
+            &window,
+            44100,
+            FrequencyLimit::All,
+            None,
+            Padding::None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+        ));
+    }
+
+    let spectrum = samples_fft_to_spectrum_padded(
+        &window,
+        44100,
+        FrequencyLimit::All,
+        None,
+        Padding::ZeroToNextPowerOfTwo,
+    )
+    .unwrap();
+    assert_eq!(spectrum.samples_len(), 1024);
+    assert_eq!(spectrum.unpadded_samples_len(), 1000);
+    assert_eq!(spectrum.frequency_resolution(), 44100.0 / 1024.0);
+    float_cmp::assert_approx_eq!(
+        f32,
+        spectrum.true_frequency_resolution(),
+        44.1,
+        epsilon = 0.001
+    );
+    assert!((spectrum.max().0.val() - 1000.0).abs() <= spectrum.frequency_resolution());
+
+    // interpolated spectrum for a finer peak localization
+    let interpolated = samples_fft_to_spectrum_padded(
+        &window,
+        44100,
+        FrequencyLimit::All,
+        None,
+        Padding::Factor(8),
+    )
+    .unwrap();
+    assert_eq!(interpolated.samples_len(), 8192);
+    assert_eq!(
+        interpolated.true_frequency_resolution(),
+        spectrum.true_frequency_resolution()
+    );
+    assert!((interpolated.max().0.val() - 1000.0).abs() < (spectrum.max().0.val() - 1000.0).abs());
+
+    let err = samples_fft_to_spectrum_padded(
+        &[1.0],
+        44100,
+        FrequencyLimit::All,
+        None,
+        Padding::ZeroToNextPowerOfTwo,
+    )
+    .unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::TooFewSamples));
+}
+
+/// A custom FFT that returns a result of the wrong length must be detected.
+#[test]
+#[should_panic]
+fn test_custom_fft_wrong_len() {
+    #[derive(Debug)]
+    struct BrokenFft;
+
+    impl crate::Fft for BrokenFft {
+        fn calc(&self, samples: &[f32]) -> Vec<crate::Complex32> {
+            vec![crate::Complex32::new(0.0, 0.0); samples.len()]
+        }
+    }
+
+    let _ = crate::samples_fft_to_spectrum_with_fft(
+        &[0.0; 8],
+        44100,
+        FrequencyLimit::All,
+        None,
+        &BrokenFft,
+    );
+}
+
+/// A custom FFT decides which lengths it supports, also if it is used by the
+/// analyzer.
+#[test]
+fn test_custom_fft_any_len() {
+    use crate::{Complex32, Fft, FftBackend, SpectrumAnalyzerBuilder};
+
+    /// Naive DFT of at most 8 samples.
+    #[derive(Debug)]
+    struct SmallDft;
+
+    impl Fft for SmallDft {
+        fn validate_len(&self, len: usize) -> Result<(), SpectrumAnalyzerError> {
+            if len > 8 {
+                return Err(SpectrumAnalyzerError::TooManySamples);
+            }
+            Ok(())
+        }
+
+        fn calc(&self, samples: &[f32]) -> Vec<Complex32> {
+            let n = samples.len();
+            (0..=n / 2)
+                .map(|k| {
+                    samples
+                        .iter()
+                        .enumerate()
+                        .fold(Complex32::new(0.0, 0.0), |acc, (i, x)| {
+                            let angle = -2.0 * core::f32::consts::PI * (k * i) as f32 / n as f32;
+                            acc + Complex32::new(libm::cosf(angle), libm::sinf(angle)) * *x
+                        })
+                })
+                .collect()
+        }
+    }
+
+    // 2 Hz
+    let samples = [1.0, -0.5, -0.5, 1.0, -0.5, -0.5];
+    let spectrum =
+        crate::samples_fft_to_spectrum_with_fft(&samples, 6, FrequencyLimit::All, None, &SmallDft)
+            .unwrap();
+    assert_eq!(spectrum.data().len(), 4);
+    assert_eq!(spectrum.max().0.val(), 2.0);
+
+    let err = crate::samples_fft_to_spectrum_with_fft(
+        &samples,
+        6,
+        FrequencyLimit::All,
+        None,
+        &FftBackend::MicrofftReal,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+    ));
+    let err = crate::samples_fft_to_spectrum_with_fft(
+        &[0.0; 16],
+        16,
+        FrequencyLimit::All,
+        None,
+        &SmallDft,
+    )
+    .unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::TooManySamples));
+
+    let mut analyzer = SpectrumAnalyzerBuilder::new(6, 6)
+        .fft_backend(SmallDft)
+        .build()
+        .unwrap();
+    assert_eq!(analyzer.analyze(&samples).unwrap().max().0.val(), 2.0);
+    let mut out = vec![(0.0.into(), 0.0.into()); analyzer.bins_len()];
+    assert!(matches!(
+        analyzer.analyze_into(&samples, &mut out),
+        Err(SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo)
+    ));
+    assert!(matches!(
+        SpectrumAnalyzerBuilder::new(16, 16)
+            .fft_backend(SmallDft)
+            .build(),
+        Err(SpectrumAnalyzerError::TooManySamples)
+    ));
+}
+
+/// Each spectrum of disjoint ranges must equal the spectrum of the single
+/// range and stay contiguous.
+#[cfg(feature = "size-4096")]
+#[test]
+fn test_spectra_in_ranges() {
+    use crate::samples_fft_to_spectra_in_ranges;
+
+    let samples = sine_wave_audio_data_multiple(&[100.0, 1500.0], 4096, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let samples = &samples[0..4096];
+    let ranges = [(1000.0, 2000.0), (50.0, 200.0)];
+    let spectra =
+        samples_fft_to_spectra_in_ranges(samples, 4096, &ranges, Some(&divide_by_N)).unwrap();
+    assert_eq!(spectra.len(), 2);
+    for ((min, max), spectrum) in ranges.iter().zip(&spectra) {
+        let expected = samples_fft_to_spectrum(
+            samples,
+            4096,
+            FrequencyLimit::Range(*min, *max),
+            Some(&divide_by_N),
+        )
+        .unwrap();
+        assert_eq!(spectrum.data(), expected.data());
+        assert_eq!(spectrum.max(), expected.max());
+    }
+    assert_eq!(spectra[0].max().0.val(), 1500.0);
+    assert_eq!(spectra[1].max().0.val(), 100.0);
+
+    let err = samples_fft_to_spectra_in_ranges(samples, 4096, &[(10.0, 10.5)], None).unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::InvalidFrequencyResolution(_)
+    ));
+    let err =
+        samples_fft_to_spectra_in_ranges(samples, 4096, &[(10.0, 20.0), (30.0, 5000.0)], None)
+            .unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::InvalidFrequencyLimit(_)
+    ));
+}
+
+/// No ranges result in no spectra instead of a panic, but the samples are
+/// still checked.
+#[test]
+fn test_spectra_in_no_ranges() {
+    use crate::samples_fft_to_spectra_in_ranges;
+
+    let spectra = samples_fft_to_spectra_in_ranges(&[0.0_f32; 4], 4, &[], None).unwrap();
+    assert!(spectra.is_empty());
+    let err = samples_fft_to_spectra_in_ranges(&[0.0_f32], 4, &[], None).unwrap_err();
+    assert!(matches!(err, SpectrumAnalyzerError::TooFewSamples));
+}
+
+/// All powers of two up to the biggest length enabled via the `size-*`
+/// features must be supported by each kind of FFT.
+#[test]
+fn test_all_enabled_fft_lens() {
+    let mut len = 2;
+    while len <= crate::MAX_SAMPLES_LEN {
+        let samples = vec![1.0; len];
+        let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+        assert_eq!(spectrum.data().len(), len / 2 + 1);
+        let _ = crate::samples_fft_to_spectrum_with_fft(
+            &samples,
+            44100,
+            FrequencyLimit::All,
+            None,
+            &crate::FftBackend::MicrofftComplex,
+        )
+        .unwrap();
+        len *= 2;
+    }
+}
+
+/// The in-place real FFT path must produce the same spectrum as the generic
+/// path via the [`crate::Fft`] trait.
+#[cfg(feature = "size-4096")]
+#[test]
+fn test_in_place_fft_matches_backend() {
+    let samples = sine_wave_audio_data_multiple(&[440.0, 2000.0], 44100, 100)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let samples = &samples[0..4096];
+
+    for limit in [FrequencyLimit::All, FrequencyLimit::Range(300.0, 22050.0)] {
+        let expected = crate::samples_fft_to_spectrum_with_fft(
+            samples,
+            44100,
+            limit,
+            Some(&divide_by_N),
+            &crate::FftBackend::MicrofftReal,
+        )
+        .unwrap();
+        let actual = samples_fft_to_spectrum(samples, 44100, limit, Some(&divide_by_N)).unwrap();
+        assert_eq!(actual.data(), expected.data());
+        assert_eq!(actual.median(), expected.median());
+    }
+    // Nyquist frequency is included
+    let spectrum = samples_fft_to_spectrum(samples, 44100, FrequencyLimit::All, None).unwrap();
+    assert_eq!(spectrum.data().last().unwrap().0.val(), 22050.0);
+}
+
+#[cfg(feature = "size-256")]
+#[test]
+fn test_complex_samples_fft_to_spectrum() {
+    use crate::{complex_samples_fft_to_spectrum, Complex32};
+
+    // two complex exponentials with positive and negative frequency and
+    // different amplitudes
+    let samples = (0..256)
+        .map(|i| {
+            let phase = 2.0 * core::f32::consts::PI * i as f32 / 256.0;
+            Complex32::new(libm::cosf(phase * 10.0), libm::sinf(phase * 10.0)) * 2.0
+                + Complex32::new(libm::cosf(phase * 30.0), -libm::sinf(phase * 30.0))
+        })
+        .collect::<Vec<_>>();
+    let spectrum = complex_samples_fft_to_spectrum(&samples, 256, None).unwrap();
+
+    assert_eq!(spectrum.data().len(), 256);
+    assert_eq!(spectrum.data()[0].0.val(), -128.0);
+    assert_eq!(spectrum.data()[255].0.val(), 127.0);
+    assert_eq!(spectrum.max().0.val(), 10.0);
+    float_cmp::assert_approx_eq!(f32, spectrum.max().1.val(), 512.0, epsilon = 0.1);
+    float_cmp::assert_approx_eq!(
+        f32,
+        spectrum.freq_val_exact(-30.0).val(),
+        256.0,
+        epsilon = 0.1
+    );
+
+    let err = complex_samples_fft_to_spectrum(&samples[..100], 256, None).unwrap_err();
+    assert!(matches!(
+        err,
+        SpectrumAnalyzerError::SamplesLengthNotAPowerOfTwo
+    ));
+}
+
+#[cfg(feature = "size-2048")]
+#[test]
+fn test_two_sided_spectrum() {
+    use crate::samples_fft_to_two_sided_spectrum;
+
+    let samples = sine_wave_audio_data_multiple(&[100.0, 3000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let samples = hann_window(&samples[0..2048]);
+    let one_sided = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+    let two_sided = samples_fft_to_two_sided_spectrum(&samples, 44100, None).unwrap();
+
+    assert_eq!(two_sided.data().len(), 2048);
+    assert_eq!(two_sided.data()[1024], one_sided.data()[0]);
+    assert_eq!(two_sided.data()[0].0.val(), -22050.0);
+    assert_eq!(two_sided.data()[0].1, one_sided.data()[1024].1);
+    // symmetry
+    for i in 1..1024 {
+        assert_eq!(two_sided.data()[1024 - i].1, two_sided.data()[1024 + i].1);
+        assert_eq!(
+            two_sided.data()[1024 - i].0.val(),
+            -two_sided.data()[1024 + i].0.val()
+        );
+    }
+    assert_eq!(two_sided.max().1, one_sided.max().1);
+}
+
+/// The energy and power of the spectrum must match the time domain
+/// (Parseval's theorem), also with padding.
+#[cfg(feature = "size-1024")]
+#[test]
+fn test_total_energy_and_power() {
+    use crate::padding::Padding;
+    use crate::samples_fft_to_spectrum_padded;
+
+    let samples = sine_wave_audio_data_multiple(&[50.0, 1337.0, 4000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32 / i16::MAX as f32)
+        .collect::<Vec<_>>();
+    let samples = &samples[0..1000];
+    let energy = samples.iter().map(|x| x * x).sum::<f32>();
+
+    let spectrum =
+        samples_fft_to_spectrum(&samples[0..512], 44100, FrequencyLimit::All, None).unwrap();
+    let expected = samples[0..512].iter().map(|x| x * x).sum::<f32>();
+    float_cmp::assert_approx_eq!(f32, spectrum.total_energy(), expected, epsilon = 0.01);
+    float_cmp::assert_approx_eq!(
+        f32,
+        spectrum.total_power(),
+        expected / 512.0,
+        epsilon = 0.0001
+    );
+
+    let spectrum = samples_fft_to_spectrum_padded(
+        samples,
+        44100,
+        FrequencyLimit::All,
+        None,
+        Padding::ZeroToNextPowerOfTwo,
+    )
+    .unwrap();
+    float_cmp::assert_approx_eq!(f32, spectrum.total_energy(), energy, epsilon = 0.01);
+    float_cmp::assert_approx_eq!(
+        f32,
+        spectrum.total_power(),
+        energy / 1000.0,
+        epsilon = 0.0001
+    );
+}
+
+/// Two-sided spectra contain the negative frequencies themselves, so that
+/// their values must not be counted twice.
+#[cfg(feature = "size-1024")]
+#[test]
+fn test_total_energy_of_two_sided_spectrum() {
+    use crate::{complex_samples_fft_to_spectrum, samples_fft_to_two_sided_spectrum, Complex32};
+
+    let samples = sine_wave_audio_data_multiple(&[50.0, 1337.0, 4000.0], 44100, 1000)
+        .into_iter()
+        .map(|x| x as f32 / i16::MAX as f32)
+        .collect::<Vec<_>>();
+    let samples = &samples[0..1024];
+    let energy = samples.iter().map(|x| x * x).sum::<f32>();
+
+    let spectrum = samples_fft_to_two_sided_spectrum(samples, 44100, None).unwrap();
+    float_cmp::assert_approx_eq!(f32, spectrum.total_energy(), energy, epsilon = 0.01);
+
+    // IQ samples whose spectrum isn't symmetric
+    let complex_samples = samples
+        .iter()
+        .zip(samples.iter().skip(1))
+        .map(|(re, im)| Complex32::new(*re, 0.5 * im))
+        .collect::<Vec<_>>();
+    let complex_samples = &complex_samples[0..512];
+    let energy = complex_samples.iter().map(|x| x.norm_sqr()).sum::<f32>();
+    let spectrum = complex_samples_fft_to_spectrum(complex_samples, 44100, None).unwrap();
+    float_cmp::assert_approx_eq!(f32, spectrum.total_energy(), energy, epsilon = 0.01);
+    float_cmp::assert_approx_eq!(
+        f32,
+        spectrum.total_power(),
+        energy / 512.0,
+        epsilon = 0.0001
+    );
+}
+
+#[cfg(feature = "size-1024")]
+#[test]
+fn test_windowed_spectrum() {
+    let samples = sine_wave_audio_data_multiple(&[440.0], 44100, 100);
+    let samples = &samples[0..1024];
+    let samples_f32 = samples.iter().map(|x| *x as f32).collect::<Vec<_>>();
+    let expected =
+        samples_fft_to_spectrum(&hann_window(&samples_f32), 44100, FrequencyLimit::All, None)
+            .unwrap();
+
+    // borrowed f32 samples and converted i16 samples
+    let spectrum_f32 = samples_fft_to_spectrum_windowed(
+        &samples_f32,
+        44100,
+        Some(Window::Hann),
+        FrequencyLimit::All,
+        None,
+    )
+    .unwrap();
+    let spectrum_i16 = samples_fft_to_spectrum_windowed(
+        samples,
+        44100,
+        Some(Window::Hann),
+        FrequencyLimit::All,
+        None,
+    )
+    .unwrap();
+    assert_eq!(spectrum_f32.data(), expected.data());
+    assert_eq!(spectrum_i16.max().0, expected.max().0);
+    // the input stays unchanged
+    assert_eq!(samples_f32[1], samples[1] as f32);
+}
+
+#[cfg(feature = "defmt")]
+#[test]
+fn test_defmt_format() {
+    fn assert_format<T: defmt::Format>() {}
+    assert_format::<crate::Frequency>();
+    assert_format::<crate::FrequencyF64>();
+    assert_format::<crate::FrequencySpectrum>();
+    assert_format::<crate::error::SpectrumAnalyzerError>();
+    assert_format::<crate::FrequencyLimitError>();
+    assert_format::<crate::frame::FrameError>();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_roundtrip() {
+    let samples = sine_wave_audio_data_multiple(&[440.0], 44100, 100)
+        .into_iter()
+        .map(|x| x as f32)
+        .collect::<Vec<_>>();
+    let spectrum =
+        samples_fft_to_spectrum(&samples[0..1024], 44100, FrequencyLimit::All, None).unwrap();
+
+    let json = serde_json::to_string(&spectrum).unwrap();
+    let deserialized: crate::FrequencySpectrum = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.data(), spectrum.data());
+    assert_eq!(deserialized.max(), spectrum.max());
+    assert_eq!(deserialized.median(), spectrum.median());
+    assert_eq!(
+        deserialized.frequency_resolution(),
+        spectrum.frequency_resolution()
+    );
+
+    let complex_spectrum =
+        crate::samples_fft_to_complex_spectrum(&samples[0..1024], 44100, FrequencyLimit::All)
+            .unwrap();
+    let json = serde_json::to_string(&complex_spectrum).unwrap();
+    let deserialized: crate::ComplexFrequencySpectrum = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.data(), complex_spectrum.data());
+
+    let mut waterfall = crate::waterfall::Waterfall::new(spectrum.data().len(), 2);
+    waterfall.push(&spectrum);
+    let json = serde_json::to_string(&waterfall).unwrap();
+    let mut deserialized: crate::waterfall::Waterfall = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.make_contiguous(), waterfall.make_contiguous());
+
+    let spectrogram = crate::spectrogram::Spectrogram::from_spectra(vec![spectrum], 0.1);
+    let json = serde_json::to_string(&spectrogram).unwrap();
+    let deserialized: crate::spectrogram::Spectrogram = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.len(), 1);
+    assert_eq!(
+        deserialized.value_at(0.0, 440.0),
+        spectrogram.value_at(0.0, 440.0)
+    );
+}
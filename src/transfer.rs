@@ -0,0 +1,426 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Measurement of the transfer function (frequency response) of a device
+//! under test, e.g. a loudspeaker or a filter, from a reference signal and
+//! the measured response to it.
+//!
+//! The transfer function is estimated with the H1 estimator
+//! `H(f) = S_xy(f) / S_xx(f)`, i.e. the cross spectrum of the reference `x`
+//! and the measured signal `y` divided by the auto spectrum of the
+//! reference. Averaging over (overlapping) frames reduces the influence of
+//! uncorrelated noise in the measured signal. Suitable reference signals
+//! are for example white noise or sine sweeps.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::{Complex32, FftImpl};
+use crate::windows::WindowFunction;
+use crate::{
+    fft_calc_frequency_resolution, fft_result_with_frequencies, validate_input, validate_samples,
+    ComplexFrequencySpectrum, Frequency, FrequencyLimit,
+};
+use alloc::vec::Vec;
+
+/// Estimates the complex transfer function `H(f)` between `reference` and
+/// `measured`. The magnitude and the phase of each frequency are available
+/// via [`ComplexFrequencySpectrum::magnitude_at`] and
+/// [`ComplexFrequencySpectrum::phase_at`].
+///
+/// ## Parameters
+/// * `reference` Signal that was fed into the device under test.
+/// * `measured` Signal that was measured at the output of the device under
+///              test. Must have the same length as `reference`.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `segment_len` Length of each frame. Must be a power of two. Use the
+///                 length of the signals to estimate the transfer function
+///                 from a single frame without averaging.
+/// * `overlap` Number of samples two consecutive frames overlap, e.g.
+///             `segment_len / 2`. Must be smaller than `segment_len`.
+/// * `window_fn` Optional window function that is applied to each frame,
+///               e.g. [`crate::windows::hann_window`].
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+///
+/// ## Return value
+/// The transfer function. Frequencies that are not excited by the reference
+/// signal at all have the value zero.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-16")] {
+/// use spectrum_analyzer::transfer::transfer_function;
+/// use spectrum_analyzer::FrequencyLimit;
+/// let reference = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+/// // the device under test halves the amplitude
+/// let measured = reference.map(|x| x * 0.5);
+/// let h = transfer_function(&reference, &measured, 8000, 8, 0, None, FrequencyLimit::All)
+///     .unwrap();
+/// assert_eq!(h.magnitude_at(1000.0).val(), 0.5);
+/// # }
+/// ```
+///
+/// ## Errors
+/// * [`SpectrumAnalyzerError::SamplesLengthMismatch`] if `measured` and
+///   `reference` have different lengths,
+/// * see [`crate::psd::welch_psd`] for all other errors.
+pub fn transfer_function(
+    reference: &[f32],
+    measured: &[f32],
+    sampling_rate: u32,
+    segment_len: usize,
+    overlap: usize,
+    window_fn: Option<&WindowFunction>,
+    frequency_limit: FrequencyLimit,
+) -> Result<ComplexFrequencySpectrum, SpectrumAnalyzerError> {
+    if reference.len() != measured.len() {
+        return Err(SpectrumAnalyzerError::SamplesLengthMismatch(
+            reference.len(),
+            measured.len(),
+        ));
+    }
+    if overlap >= segment_len {
+        return Err(SpectrumAnalyzerError::InvalidSegmentOverlap(
+            segment_len,
+            overlap,
+        ));
+    }
+    if reference.len() < segment_len {
+        return Err(SpectrumAnalyzerError::TooFewSamples);
+    }
+    // checks the first segment for a valid length and the frequency limit
+    validate_input(&reference[0..segment_len], sampling_rate, &frequency_limit)?;
+    validate_samples(reference)?;
+    validate_samples(measured)?;
+
+    let window = {
+        let ones = vec![1.0; segment_len];
+        window_fn.map_or_else(|| ones.clone(), |window_fn| window_fn(&ones))
+    };
+
+    // cross spectrum S_xy and auto spectrum S_xx of the reference
+    let mut cross_acc = vec![Complex32::default(); segment_len / 2 + 1];
+    let mut auto_acc = vec![0.0_f32; segment_len / 2 + 1];
+    let mut segment_x = vec![0.0; segment_len];
+    let mut segment_y = vec![0.0; segment_len];
+    let hop = segment_len - overlap;
+    let mut start = 0;
+    while start + segment_len <= reference.len() {
+        for (i, w) in window.iter().enumerate() {
+            segment_x[i] = reference[start + i] * w;
+            segment_y[i] = measured[start + i] * w;
+        }
+        let fft_x = FftImpl::calc(&segment_x);
+        let fft_y = FftImpl::calc(&segment_y);
+        for (((cross, auto), x), y) in cross_acc
+            .iter_mut()
+            .zip(auto_acc.iter_mut())
+            .zip(&fft_x)
+            .zip(&fft_y)
+        {
+            *cross += x.conj() * y;
+            *auto += x.norm_sqr();
+        }
+        start += hop;
+    }
+
+    let transfer = cross_acc
+        .iter()
+        .zip(&auto_acc)
+        .map(|(cross, auto)| {
+            // frequencies that the reference doesn't excite
+            if *auto < f32::MIN_POSITIVE {
+                Complex32::default()
+            } else {
+                cross / *auto
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let frequency_resolution = fft_calc_frequency_resolution(sampling_rate, segment_len as u32);
+    let data = fft_result_with_frequencies(
+        segment_len,
+        &transfer,
+        frequency_resolution,
+        frequency_limit,
+    )
+    .map(|(fr, h)| (Frequency::from(fr), *h))
+    .collect::<Vec<(Frequency, Complex32)>>();
+
+    Ok(ComplexFrequencySpectrum::new(
+        data,
+        frequency_resolution,
+        segment_len as u32,
+    ))
+}
+
+/// Window that is applied to an impulse response, see [`impulse_response`].
+/// Cutting the impulse response off removes late reflections of a room and
+/// noise, which is common practice to obtain a (quasi-)anechoic measurement.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImpulseResponseWindow {
+    /// Keeps the first `n` samples and drops the rest.
+    Truncate(usize),
+    /// Keeps the first `len` samples and fades the last `fade_len` of them
+    /// out with the falling half of a Hann window to avoid a discontinuity.
+    FadeOut {
+        /// Number of samples to keep.
+        len: usize,
+        /// Number of samples of the fade out. Must not be bigger than `len`.
+        fade_len: usize,
+    },
+}
+
+impl ImpulseResponseWindow {
+    /// Applies the window to the impulse response.
+    ///
+    /// ## Panics
+    /// If `fade_len` is bigger than `len`.
+    #[must_use]
+    pub fn apply(&self, impulse_response: &[f32]) -> Vec<f32> {
+        match *self {
+            Self::Truncate(len) => impulse_response.iter().copied().take(len).collect(),
+            Self::FadeOut { len, fade_len } => {
+                assert!(
+                    fade_len <= len,
+                    "The fade out must not be longer than the window!"
+                );
+                let fade_start = len - fade_len;
+                impulse_response
+                    .iter()
+                    .take(len)
+                    .enumerate()
+                    .map(|(i, sample)| {
+                        if i < fade_start {
+                            *sample
+                        } else {
+                            // falling half of a Hann window
+                            let x = (i - fade_start + 1) as f32 / (fade_len + 1) as f32;
+                            sample * 0.5 * (1.0 + libm::cosf(core::f32::consts::PI * x))
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Extracts the impulse response of the device under test from the inverse
+/// FFT of its transfer function. See [`transfer_function`].
+///
+/// ## Parameters
+/// * `reference` See [`transfer_function`].
+/// * `measured` See [`transfer_function`].
+/// * `segment_len` See [`transfer_function`]. This is also the length of
+///                 the impulse response. It must be longer than the actual
+///                 impulse response, as it wraps around otherwise.
+/// * `overlap` See [`transfer_function`].
+/// * `window_fn` Optional window function that is applied to each frame
+///               for the estimation of the transfer function.
+/// * `ir_window` Optional window that is applied to the impulse response.
+///               See [`ImpulseResponseWindow`].
+///
+/// ## Return value
+/// The impulse response, where index `0` corresponds to no delay.
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-16")] {
+/// use spectrum_analyzer::transfer::{impulse_response, ImpulseResponseWindow};
+/// let reference = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+/// // the device under test delays the signal by two samples
+/// let measured = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+/// let ir = impulse_response(
+///     &reference,
+///     &measured,
+///     8,
+///     0,
+///     None,
+///     Some(ImpulseResponseWindow::Truncate(4)),
+/// )
+/// .unwrap();
+/// assert_eq!(ir.len(), 4);
+/// assert!((ir[2] - 1.0).abs() < 1e-6);
+/// # }
+/// ```
+///
+/// ## Errors
+/// See [`transfer_function`].
+///
+/// ## Panics
+/// See [`ImpulseResponseWindow::apply`].
+pub fn impulse_response(
+    reference: &[f32],
+    measured: &[f32],
+    segment_len: usize,
+    overlap: usize,
+    window_fn: Option<&WindowFunction>,
+    ir_window: Option<ImpulseResponseWindow>,
+) -> Result<Vec<f32>, SpectrumAnalyzerError> {
+    // The sampling rate doesn't influence the impulse response. Any rate
+    // with a valid frequency resolution works.
+    let transfer = transfer_function(
+        reference,
+        measured,
+        segment_len as u32,
+        segment_len,
+        overlap,
+        window_fn,
+        FrequencyLimit::All,
+    )?;
+    let mut impulse_response = transfer.to_samples();
+    if let Some(ir_window) = ir_window {
+        impulse_response = ir_window.apply(&impulse_response);
+    }
+    Ok(impulse_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use core::f32::consts::PI;
+
+    /// Deterministic white noise.
+    fn noise(len: usize) -> Vec<f32> {
+        let mut state = 0x1234_5678_u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    /// Simple FIR filter `y[n] = 0.5 * x[n] + 0.25 * x[n - 1]`.
+    fn fir(x: &[f32], circular: bool) -> Vec<f32> {
+        (0..x.len())
+            .map(|n| {
+                let previous = match n {
+                    0 if circular => x[x.len() - 1],
+                    0 => 0.0,
+                    _ => x[n - 1],
+                };
+                0.5 * x[n] + 0.25 * previous
+            })
+            .collect()
+    }
+
+    /// Expected transfer function of [`fir`].
+    fn expected(fr: f32, sampling_rate: f32) -> Complex32 {
+        let omega = 2.0 * PI * fr / sampling_rate;
+        Complex32::new(0.5 + 0.25 * libm::cosf(omega), -0.25 * libm::sinf(omega))
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_single_frame() {
+        let reference = noise(256);
+        let measured = fir(&reference, true);
+        let h = transfer_function(
+            &reference,
+            &measured,
+            8000,
+            256,
+            0,
+            None,
+            FrequencyLimit::All,
+        )
+        .unwrap();
+        for (fr, val) in h.data() {
+            let expected = expected(fr.val(), 8000.0);
+            assert!(
+                (val - expected).norm_sqr() < 1e-8,
+                "{fr}: {val} != {expected}"
+            );
+        }
+    }
+
+    #[cfg(feature = "size-512")]
+    #[test]
+    fn test_averaged_frames() {
+        let reference = noise(8192);
+        let measured = fir(&reference, false);
+        let h = transfer_function(
+            &reference,
+            &measured,
+            8000,
+            512,
+            256,
+            Some(&hann_window),
+            FrequencyLimit::Range(100.0, 3900.0),
+        )
+        .unwrap();
+        for (fr, val) in h.data() {
+            let expected = expected(fr.val(), 8000.0);
+            assert!(
+                (val - expected).norm_sqr() < 1e-4,
+                "{fr}: {val} != {expected}"
+            );
+        }
+        let expected_phase = libm::atan2f(expected(1000.0, 8000.0).im, expected(1000.0, 8000.0).re);
+        assert!((h.phase_at(1000.0) - expected_phase).abs() < 0.01);
+    }
+
+    #[cfg(feature = "size-256")]
+    #[test]
+    fn test_impulse_response() {
+        let reference = noise(256);
+        let measured = fir(&reference, true);
+        let ir = impulse_response(&reference, &measured, 256, 0, None, None).unwrap();
+        assert_eq!(ir.len(), 256);
+        assert!((ir[0] - 0.5).abs() < 1e-4);
+        assert!((ir[1] - 0.25).abs() < 1e-4);
+        assert!(ir[2..].iter().all(|x| x.abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_impulse_response_window() {
+        let ir = [1.0; 8];
+        assert_eq!(ImpulseResponseWindow::Truncate(3).apply(&ir), vec![1.0; 3]);
+        let faded = ImpulseResponseWindow::FadeOut {
+            len: 6,
+            fade_len: 3,
+        }
+        .apply(&ir);
+        assert_eq!(faded.len(), 6);
+        assert_eq!(&faded[..3], &[1.0; 3]);
+        assert!((faded[3] - 0.853_553_4).abs() < 1e-6);
+        assert!((faded[4] - 0.5).abs() < 1e-6);
+        assert!((faded[5] - 0.146_446_6).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "size-8")]
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            transfer_function(&[0.0; 8], &[0.0; 4], 8000, 4, 0, None, FrequencyLimit::All),
+            Err(SpectrumAnalyzerError::SamplesLengthMismatch(8, 4))
+        ));
+        // the reference doesn't excite any frequency
+        let h =
+            transfer_function(&[0.0; 8], &[1.0; 8], 8000, 8, 0, None, FrequencyLimit::All).unwrap();
+        assert!(h
+            .data()
+            .iter()
+            .all(|(_fr, val)| *val == Complex32::default()));
+    }
+}
@@ -0,0 +1,272 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for [`Waterfall`], a fixed-capacity history of spectra for
+//! real-time waterfall displays, as known from SDR applications.
+
+use crate::spectrum::FrequencySpectrum;
+use alloc::vec::Vec;
+
+/// Keeps the values of the last `capacity` spectra in a ring buffer of
+/// `capacity` rows with `width` values each. The memory is allocated once,
+/// pushing a spectrum never allocates. When the waterfall is full, the
+/// oldest row is overwritten.
+///
+/// All rows are stored in a single contiguous buffer. Use
+/// [`Self::make_contiguous`] to get the rows ordered from the oldest to the
+/// newest, e.g. for a texture upload. Alternatively, upload
+/// [`Self::as_raw_slice`] as is and offset the texture coordinates by
+/// [`Self::oldest_row_index`].
+///
+/// ## Example
+/// ```rust
+/// # #[cfg(feature = "size-1024")] {
+/// use spectrum_analyzer::waterfall::Waterfall;
+/// use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+/// let samples = vec![0.0; 1024];
+/// let mut waterfall = Waterfall::new(513, 100);
+/// for _ in 0..3 {
+///     let spectrum = samples_fft_to_spectrum(&samples, 44100, FrequencyLimit::All, None).unwrap();
+///     waterfall.push(&spectrum);
+/// }
+/// let texture: &[f32] = waterfall.make_contiguous();
+/// assert_eq!(texture.len(), 3 * 513);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Waterfall {
+    width: usize,
+    capacity: usize,
+    /// `capacity * width` values.
+    data: Vec<f32>,
+    /// Index of the row that is written next.
+    next_row: usize,
+    /// Number of valid rows.
+    len: usize,
+}
+
+impl Waterfall {
+    /// Creates a new waterfall.
+    ///
+    /// ## Parameters
+    /// * `width` Number of values of each spectrum, i.e. the number of
+    ///           frequencies.
+    /// * `capacity` Maximum number of rows (spectra).
+    ///
+    /// ## Panics
+    /// If `width` or `capacity` is zero.
+    #[must_use]
+    pub fn new(width: usize, capacity: usize) -> Self {
+        assert!(width > 0, "width must not be zero!");
+        assert!(capacity > 0, "capacity must not be zero!");
+        Self {
+            width,
+            capacity,
+            data: alloc::vec![0.0; width * capacity],
+            next_row: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values of each row.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the maximum number of rows.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of rows.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the waterfall has no rows.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the waterfall has [`Self::capacity`] rows, i.e. the
+    /// next push overwrites the oldest row.
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Appends the values of the spectrum as newest row.
+    ///
+    /// ## Panics
+    /// If the number of frequencies of the spectrum doesn't match
+    /// [`Self::width`].
+    pub fn push(&mut self, spectrum: &FrequencySpectrum) {
+        let data = spectrum.data();
+        assert_eq!(
+            data.len(),
+            self.width,
+            "The spectrum has {} frequencies but the waterfall is {} wide!",
+            data.len(),
+            self.width
+        );
+        for (dst, (_fr, fr_val)) in self.next_row_mut().iter_mut().zip(data) {
+            *dst = fr_val.val();
+        }
+        self.advance();
+    }
+
+    /// Appends the values as newest row, e.g. values that were reduced to
+    /// bars before.
+    ///
+    /// ## Panics
+    /// If the length of `values` doesn't match [`Self::width`].
+    pub fn push_values(&mut self, values: &[f32]) {
+        assert_eq!(
+            values.len(),
+            self.width,
+            "Got {} values but the waterfall is {} wide!",
+            values.len(),
+            self.width
+        );
+        self.next_row_mut().copy_from_slice(values);
+        self.advance();
+    }
+
+    /// Returns the row with the given index, where `0` is the oldest row.
+    #[must_use]
+    pub fn row(&self, index: usize) -> Option<&[f32]> {
+        if index >= self.len {
+            return None;
+        }
+        let row = (self.oldest_row_index() + index) % self.capacity;
+        Some(&self.data[row * self.width..(row + 1) * self.width])
+    }
+
+    /// Returns an iterator over all rows, from the oldest to the newest.
+    #[must_use]
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[f32]> + ExactSizeIterator {
+        (0..self.len).map(move |index| self.row(index).unwrap())
+    }
+
+    /// Returns the index of the oldest row inside [`Self::as_raw_slice`].
+    #[must_use]
+    pub const fn oldest_row_index(&self) -> usize {
+        if self.is_full() {
+            self.next_row
+        } else {
+            0
+        }
+    }
+
+    /// Returns the underlying buffer of `capacity * width` values as it is,
+    /// i.e. the rows are not ordered if the ring buffer has wrapped around.
+    /// See [`Self::oldest_row_index`]. Rows that were not written yet
+    /// contain zeros.
+    #[must_use]
+    pub fn as_raw_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Rotates the underlying buffer in place, so that the rows are ordered
+    /// from the oldest to the newest, and returns all rows as matrix in
+    /// row-major order, i.e. `len * width` values.
+    pub fn make_contiguous(&mut self) -> &[f32] {
+        if self.is_full() {
+            self.data.rotate_left(self.next_row * self.width);
+            self.next_row = 0;
+        }
+        &self.data[..self.len * self.width]
+    }
+
+    /// Removes all rows.
+    pub fn clear(&mut self) {
+        self.data.fill(0.0);
+        self.next_row = 0;
+        self.len = 0;
+    }
+
+    fn next_row_mut(&mut self) -> &mut [f32] {
+        &mut self.data[self.next_row * self.width..(self.next_row + 1) * self.width]
+    }
+
+    fn advance(&mut self) {
+        self.next_row = (self.next_row + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waterfall() {
+        let mut waterfall = Waterfall::new(2, 3);
+        assert!(waterfall.is_empty());
+        waterfall.push_values(&[1.0, 1.5]);
+        waterfall.push_values(&[2.0, 2.5]);
+        assert_eq!(waterfall.len(), 2);
+        assert_eq!(waterfall.row(1), Some(&[2.0, 2.5][..]));
+        assert_eq!(waterfall.row(2), None);
+        assert_eq!(waterfall.make_contiguous(), &[1.0, 1.5, 2.0, 2.5]);
+
+        waterfall.push_values(&[3.0, 3.5]);
+        waterfall.push_values(&[4.0, 4.5]);
+        assert!(waterfall.is_full());
+        assert_eq!(waterfall.oldest_row_index(), 1);
+        assert_eq!(waterfall.as_raw_slice(), &[4.0, 4.5, 2.0, 2.5, 3.0, 3.5]);
+        let newest_first = waterfall.rows().rev().map(|row| row[0]).collect::<Vec<_>>();
+        assert_eq!(newest_first, [4.0, 3.0, 2.0]);
+
+        assert_eq!(waterfall.make_contiguous(), &[2.0, 2.5, 3.0, 3.5, 4.0, 4.5]);
+        assert_eq!(waterfall.oldest_row_index(), 0);
+        waterfall.push_values(&[5.0, 5.5]);
+        assert_eq!(waterfall.row(0), Some(&[3.0, 3.5][..]));
+        assert_eq!(waterfall.row(2), Some(&[5.0, 5.5][..]));
+
+        waterfall.clear();
+        assert!(waterfall.is_empty());
+        assert!(waterfall.make_contiguous().is_empty());
+    }
+
+    #[test]
+    fn test_push_spectrum() {
+        use crate::{samples_fft_to_spectrum, FrequencyLimit};
+        let spectrum =
+            samples_fft_to_spectrum(&[0.0, 1.0, 0.0, -1.0], 4, FrequencyLimit::All, None).unwrap();
+        let mut waterfall = Waterfall::new(3, 2);
+        waterfall.push(&spectrum);
+        assert_eq!(waterfall.row(0), Some(&[0.0, 2.0, 0.0][..]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_wrong_width() {
+        Waterfall::new(3, 2).push_values(&[1.0]);
+    }
+}
@@ -0,0 +1,335 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Convenient analysis of WAV files. The samples of all channels are mixed
+//! down to mono, split into (overlapping) frames, and each frame is analyzed
+//! separately. See [`analyze_wav`] and `analyze_wav_file` (needs the `std`
+//! feature).
+//!
+//! Supported are uncompressed WAV files with 8, 16, 24, or 32 bit integer
+//! samples and 32 bit float samples.
+
+use crate::error::SpectrumAnalyzerError;
+use crate::sample::Sample;
+use crate::windows::WindowFunction;
+use crate::{samples_fft_to_spectrum, FrequencyLimit, FrequencySpectrum};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::{Display, Formatter};
+
+/// Format tag of integer PCM samples.
+const WAVE_FORMAT_PCM: u16 = 1;
+/// Format tag of float samples.
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+/// Format tag of the extensible format, where the actual format tag is the
+/// beginning of the sub format GUID.
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+/// Possible errors of [`analyze_wav`] and `analyze_wav_file`.
+#[derive(Debug)]
+pub enum WavError {
+    /// The file couldn't be read.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The data is no valid WAV file. Contains a description.
+    InvalidFile(&'static str),
+    /// The WAV file uses an unsupported sample format. Contains the format
+    /// tag and the bits per sample.
+    UnsupportedFormat(u16, u16),
+    /// The frame or hop length is invalid, or the analysis of a frame
+    /// failed. See [`SpectrumAnalyzerError`].
+    Analysis(SpectrumAnalyzerError),
+}
+
+impl Display for WavError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "can't read WAV file: {e}"),
+            Self::InvalidFile(reason) => write!(f, "invalid WAV file: {reason}"),
+            Self::UnsupportedFormat(format, bits) => write!(
+                f,
+                "unsupported WAV sample format {format} with {bits} bits per sample"
+            ),
+            Self::Analysis(e) => write!(f, "analysis failed: {e:?}"),
+        }
+    }
+}
+
+impl From<SpectrumAnalyzerError> for WavError {
+    fn from(e: SpectrumAnalyzerError) -> Self {
+        Self::Analysis(e)
+    }
+}
+
+/// Result of [`analyze_wav`]: the spectra of all frames of a WAV file.
+#[derive(Debug)]
+pub struct WavAnalysis {
+    /// Sampling rate of the WAV file.
+    pub sampling_rate: u32,
+    /// Number of channels of the WAV file. The channels are mixed down to
+    /// mono before the analysis.
+    pub channels: u16,
+    /// Number of samples between the beginnings of two frames.
+    pub hop_len: usize,
+    /// Spectrum of each frame, in chronological order.
+    pub spectra: Vec<FrequencySpectrum>,
+}
+
+impl WavAnalysis {
+    /// Returns the time in seconds of the beginning of the frame of the
+    /// spectrum with the given index in [`Self::spectra`].
+    #[must_use]
+    pub fn timestamp(&self, index: usize) -> f32 {
+        (index * self.hop_len) as f32 / self.sampling_rate as f32
+    }
+}
+
+/// Reads the WAV file at `path` and analyzes it. See [`analyze_wav`].
+///
+/// ## Errors
+/// [`WavError::Io`] if the file can't be read. Otherwise, see
+/// [`analyze_wav`].
+#[cfg(feature = "std")]
+pub fn analyze_wav_file(
+    path: impl AsRef<std::path::Path>,
+    frame_len: usize,
+    hop_len: usize,
+    window_fn: Option<&WindowFunction>,
+    frequency_limit: FrequencyLimit,
+) -> Result<WavAnalysis, WavError> {
+    let bytes = std::fs::read(path).map_err(WavError::Io)?;
+    analyze_wav(&bytes, frame_len, hop_len, window_fn, frequency_limit)
+}
+
+/// Decodes the WAV file, mixes all channels down to mono, and analyzes each
+/// frame of `frame_len` samples. Incomplete frames at the end are ignored.
+///
+/// ## Parameters
+/// * `bytes` Content of a WAV file.
+/// * `frame_len` Number of samples of each frame. Must be a power of two.
+/// * `hop_len` Number of samples between the beginnings of two frames, e.g.
+///             `frame_len / 2` for an overlap of 50%. Must not be zero.
+/// * `window_fn` Optional window function that is applied to each frame,
+///               e.g. [`crate::windows::hann_window`].
+/// * `frequency_limit` Frequency limit. See [`FrequencyLimit`].
+///
+/// ## Errors
+/// * [`WavError::InvalidFile`] or [`WavError::UnsupportedFormat`] if the WAV
+///   file can't be decoded,
+/// * [`WavError::Analysis`] with [`SpectrumAnalyzerError::InvalidHopLength`]
+///   if `hop_len` is zero, and see [`crate::samples_fft_to_spectrum`] for the
+///   other errors.
+pub fn analyze_wav(
+    bytes: &[u8],
+    frame_len: usize,
+    hop_len: usize,
+    window_fn: Option<&WindowFunction>,
+    frequency_limit: FrequencyLimit,
+) -> Result<WavAnalysis, WavError> {
+    if hop_len == 0 {
+        return Err(SpectrumAnalyzerError::InvalidHopLength.into());
+    }
+    let (sampling_rate, channels, samples) = decode_wav(bytes)?;
+
+    let mut spectra = Vec::new();
+    let mut start = 0;
+    while start + frame_len <= samples.len() {
+        let frame = &samples[start..start + frame_len];
+        let spectrum = window_fn.map_or_else(
+            || samples_fft_to_spectrum(frame, sampling_rate, frequency_limit, None),
+            |window_fn| {
+                samples_fft_to_spectrum(&window_fn(frame), sampling_rate, frequency_limit, None)
+            },
+        )?;
+        spectra.push(spectrum);
+        start += hop_len;
+    }
+
+    Ok(WavAnalysis {
+        sampling_rate,
+        channels,
+        hop_len,
+        spectra,
+    })
+}
+
+/// Decodes a WAV file into the sampling rate, the number of channels, and
+/// the mono samples normalized to `[-1.0, 1.0]`.
+fn decode_wav(bytes: &[u8]) -> Result<(u32, u16, Vec<f32>), WavError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(WavError::InvalidFile("missing RIFF/WAVE header"));
+    }
+
+    // (format tag, channels, sampling rate, bits per sample)
+    let mut format = None;
+    let mut data = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let len = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let body = (pos + 8)
+            .checked_add(len)
+            .and_then(|end| bytes.get(pos + 8..end))
+            .ok_or(WavError::InvalidFile("truncated chunk"))?;
+        match id {
+            b"fmt " => {
+                if body.len() < 16 {
+                    return Err(WavError::InvalidFile("fmt chunk too short"));
+                }
+                let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+                let mut format_tag = u16_at(0);
+                if format_tag == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+                    format_tag = u16_at(24);
+                }
+                let rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                format = Some((format_tag, u16_at(2), rate, u16_at(14)));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // chunks are padded to an even length; `body` fits into `bytes`, so
+        // this can't overflow
+        pos += 8 + len + (len & 1);
+    }
+
+    let (format_tag, channels, sampling_rate, bits) =
+        format.ok_or(WavError::InvalidFile("missing fmt chunk"))?;
+    let data = data.ok_or(WavError::InvalidFile("missing data chunk"))?;
+    if channels == 0 {
+        return Err(WavError::InvalidFile("no channels"));
+    }
+
+    let sample_len = (bits / 8) as usize;
+    let to_f32: fn(&[u8]) -> f32 = match (format_tag, bits) {
+        (WAVE_FORMAT_PCM, 8) => |b| b[0].to_f32(),
+        (WAVE_FORMAT_PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]).to_f32(),
+        (WAVE_FORMAT_PCM, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]).to_f32(),
+        (WAVE_FORMAT_PCM, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_f32(),
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => return Err(WavError::UnsupportedFormat(format_tag, bits)),
+    };
+
+    let samples = data
+        .chunks_exact(sample_len * channels as usize)
+        .map(|frame| {
+            let sum = frame.chunks_exact(sample_len).map(to_f32).sum::<f32>();
+            sum / f32::from(channels)
+        })
+        .collect();
+    Ok((sampling_rate, channels, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use core::f32::consts::PI;
+
+    /// Creates a 16 bit stereo WAV file with a sine wave on both channels.
+    fn wav_16bit_stereo(frequency: f32, sampling_rate: u32, len: usize) -> Vec<u8> {
+        let data_len = (len * 4) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16_u32.to_le_bytes());
+        bytes.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+        bytes.extend_from_slice(&2_u16.to_le_bytes());
+        bytes.extend_from_slice(&sampling_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sampling_rate * 4).to_le_bytes());
+        bytes.extend_from_slice(&4_u16.to_le_bytes());
+        bytes.extend_from_slice(&16_u16.to_le_bytes());
+        // unknown chunks must be skipped
+        bytes.extend_from_slice(b"LIST");
+        bytes.extend_from_slice(&3_u32.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for i in 0..len {
+            let t = i as f32 / sampling_rate as f32;
+            let sample = (libm::sinf(2.0 * PI * frequency * t) * 16384.0) as i16;
+            bytes.extend_from_slice(&sample.to_le_bytes());
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_analyze_wav() {
+        let bytes = wav_16bit_stereo(1000.0, 8000, 4000);
+        let analysis =
+            analyze_wav(&bytes, 1024, 512, Some(&hann_window), FrequencyLimit::All).unwrap();
+        assert_eq!(analysis.sampling_rate, 8000);
+        assert_eq!(analysis.channels, 2);
+        // frames start at 0, 512, ..., 2560
+        assert_eq!(analysis.spectra.len(), 6);
+        assert_eq!(analysis.timestamp(2), 0.128);
+        for spectrum in &analysis.spectra {
+            assert_eq!(spectrum.max().0.val(), 1000.0);
+        }
+    }
+
+    #[test]
+    fn test_invalid_wav() {
+        assert!(matches!(
+            analyze_wav(b"RIFF", 1024, 512, None, FrequencyLimit::All),
+            Err(WavError::InvalidFile(_))
+        ));
+        let mut bytes = wav_16bit_stereo(1000.0, 8000, 16);
+        // 12 bit samples
+        bytes[34] = 12;
+        assert!(matches!(
+            analyze_wav(&bytes, 8, 8, None, FrequencyLimit::All),
+            Err(WavError::UnsupportedFormat(WAVE_FORMAT_PCM, 12))
+        ));
+        let bytes = wav_16bit_stereo(1000.0, 8000, 16);
+        assert!(matches!(
+            analyze_wav(&bytes, 8, 0, None, FrequencyLimit::All),
+            Err(WavError::Analysis(SpectrumAnalyzerError::InvalidHopLength))
+        ));
+        // chunk length that overflows the position
+        let mut bytes = wav_16bit_stereo(1000.0, 8000, 16);
+        bytes[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            analyze_wav(&bytes, 8, 8, None, FrequencyLimit::All),
+            Err(WavError::InvalidFile("truncated chunk"))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_analyze_wav_file() {
+        let path = std::env::temp_dir().join("spectrum-analyzer-test.wav");
+        std::fs::write(&path, wav_16bit_stereo(500.0, 8000, 2048)).unwrap();
+        let analysis = analyze_wav_file(&path, 2048, 2048, None, FrequencyLimit::All).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(analysis.spectra.len(), 1);
+        assert_eq!(analysis.spectra[0].max().0.val(), 500.0);
+        assert!(matches!(
+            analyze_wav_file(&path, 2048, 2048, None, FrequencyLimit::All),
+            Err(WavError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Standard frequency weighting curves (A, B, C, and Z) according to
+//! IEC 61672-1. They model the frequency dependent sensitivity of the human
+//! ear and are used for sound level measurements. In contrast to the
+//! functions in [`crate::scaling`], a weighting depends on the frequency of
+//! each value.
+//!
+//! More information: <https://en.wikipedia.org/wiki/A-weighting>
+
+/// A frequency weighting curve. Apply it to a spectrum with
+/// [`crate::FrequencySpectrum::apply_weighting`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Weighting {
+    /// A-weighting. The most common weighting for environmental noise.
+    A,
+    /// B-weighting. Rarely used nowadays.
+    B,
+    /// C-weighting. Used for high sound levels and peak measurements.
+    C,
+    /// Z-weighting, i.e. zero/no weighting. Every gain is `1.0`.
+    Z,
+}
+
+impl Weighting {
+    /// Returns the linear gain of the weighting at the given frequency.
+    /// Multiply a magnitude with this value to apply the weighting. The gain
+    /// at `0 Hz` is `0.0` for A, B and C.
+    #[must_use]
+    pub fn gain(self, fr: f32) -> f32 {
+        match self {
+            Self::A => a_weighting_gain(fr),
+            Self::B => b_weighting_gain(fr),
+            Self::C => c_weighting_gain(fr),
+            Self::Z => 1.0,
+        }
+    }
+
+    /// Returns the gain of the weighting at the given frequency in decibel.
+    ///
+    /// ## Panics
+    /// If `fr` is not bigger than zero, as the logarithm is undefined for a
+    /// gain of zero.
+    #[must_use]
+    pub fn db(self, fr: f32) -> f32 {
+        assert!(fr > 0.0, "Frequency must be bigger than 0 Hz!");
+        20.0 * libm::log10f(self.gain(fr))
+    }
+}
+
+// Pole frequencies of the weighting curves in Hertz, according to IEC 61672-1.
+const F1: f32 = 20.598_997;
+const F2: f32 = 107.652_65;
+const F3: f32 = 737.862_2;
+const F4: f32 = 12194.217;
+const F5: f32 = 158.5;
+
+/// Calculates the linear gain of the A-weighting curve at the given frequency.
+/// The curve is normalized to a gain of `1.0` (0 dB) at 1 kHz.
+#[must_use]
+pub fn a_weighting_gain(fr: f32) -> f32 {
+    let f2 = fr * fr;
+    let numerator = F4 * F4 * f2 * f2;
+    let denominator =
+        (f2 + F1 * F1) * libm::sqrtf((f2 + F2 * F2) * (f2 + F3 * F3)) * (f2 + F4 * F4);
+    // +2.00 dB
+    numerator / denominator * 1.258_925_4
+}
+
+/// Calculates the linear gain of the B-weighting curve at the given frequency.
+/// The curve is normalized to a gain of `1.0` (0 dB) at 1 kHz.
+#[must_use]
+pub fn b_weighting_gain(fr: f32) -> f32 {
+    let f2 = fr * fr;
+    let numerator = F4 * F4 * f2 * fr;
+    let denominator = (f2 + F1 * F1) * libm::sqrtf(f2 + F5 * F5) * (f2 + F4 * F4);
+    // +0.17 dB
+    numerator / denominator * 1.019_764_5
+}
+
+/// Calculates the linear gain of the C-weighting curve at the given frequency.
+/// The curve is normalized to a gain of `1.0` (0 dB) at 1 kHz.
+#[must_use]
+pub fn c_weighting_gain(fr: f32) -> f32 {
+    let f2 = fr * fr;
+    let numerator = F4 * F4 * f2;
+    let denominator = (f2 + F1 * F1) * (f2 + F4 * F4);
+    // +0.06 dB
+    numerator / denominator * 1.006_931_6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_values() {
+        // reference values from IEC 61672-1 (rounded to 0.1 dB); 31.5 Hz is
+        // nominal for the exact frequency 31.623 Hz
+        let check = |weighting: Weighting, fr: f32, expected_db: f32| {
+            float_cmp::assert_approx_eq!(f32, weighting.db(fr), expected_db, epsilon = 0.1);
+        };
+        check(Weighting::A, 1000.0, 0.0);
+        check(Weighting::A, 100.0, -19.1);
+        check(Weighting::A, 31.623, -39.4);
+        check(Weighting::A, 10000.0, -2.5);
+        check(Weighting::B, 1000.0, 0.0);
+        check(Weighting::B, 100.0, -5.6);
+        check(Weighting::C, 1000.0, 0.0);
+        check(Weighting::C, 100.0, -0.3);
+        check(Weighting::C, 31.623, -3.0);
+        check(Weighting::Z, 31.5, 0.0);
+    }
+
+    #[test]
+    fn test_dc() {
+        assert_eq!(Weighting::A.gain(0.0), 0.0);
+        assert_eq!(Weighting::B.gain(0.0), 0.0);
+        assert_eq!(Weighting::C.gain(0.0), 0.0);
+        assert_eq!(Weighting::Z.gain(0.0), 1.0);
+    }
+}
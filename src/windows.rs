@@ -25,12 +25,458 @@ SOFTWARE.
 //! For more information:
 //! - <https://en.wikipedia.org/wiki/Window_function>
 //! - <https://www.youtube.com/watch?v=dCeHOf4cJE0> (FFT and windowing by Texas Instruments)
+//!
+//! All window functions create periodic (DFT-even) windows, which are the
+//! right choice for spectral analysis. Symmetric windows, e.g. for filter
+//! design, are available via [`WindowSymmetry`].
 
+use crate::error::SpectrumAnalyzerError;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
 // replacement for std functions like sin and cos in no_std-environments
 use libm::cosf;
 
+/// Describes the type of all window functions in this module. A window
+/// function takes the samples and returns a new vector with the window
+/// applied to the values. The type works with static functions as well
+/// as dynamically created closures.
+pub type WindowFunction = dyn Fn(&[f32]) -> Vec<f32>;
+
+/// Variant of a window. See [`Window::coefficients_with_symmetry`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowSymmetry {
+    /// Periodic (DFT-even) window: the window of length `N` is the first
+    /// `N` coefficients of a symmetric window of length `N + 1`. Hence, the
+    /// window repeats seamlessly, which is the right choice for spectral
+    /// analysis and required for the perfect reconstruction of an STFT with
+    /// overlapping frames.
+    #[default]
+    Periodic,
+    /// Symmetric window, where the first and the last coefficient are equal.
+    /// This is the usual choice for the design of FIR filters.
+    Symmetric,
+}
+
+/// Selection of a window function as a configuration value, e.g. for
+/// [`crate::SpectrumAnalyzerBuilder::window`]. Each variant corresponds to
+/// one of the window functions of this module.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::windows::Window;
+/// let samples = [1.0, 2.0, 3.0, 4.0];
+/// let windowed = Window::Hann.apply(&samples);
+/// assert_eq!(Window::Tukey(0.5).coefficients(4).len(), 4);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Window {
+    /// No window, i.e. all coefficients are `1.0`.
+    Rectangular,
+    /// See [`hann_window`].
+    Hann,
+    /// See [`hamming_window`].
+    Hamming,
+    /// See [`blackman_window`].
+    Blackman,
+    /// See [`blackman_harris_4term`].
+    BlackmanHarris4Term,
+    /// See [`blackman_harris_7term`].
+    BlackmanHarris7Term,
+    /// See [`nuttall_window`].
+    Nuttall,
+    /// See [`flat_top_window`].
+    FlatTop,
+    /// See [`kaiser_window`]. Contains `beta`.
+    Kaiser(f32),
+    /// See [`gaussian_window`]. Contains `sigma`.
+    Gaussian(f32),
+    /// See [`tukey_window`]. Contains `alpha`.
+    Tukey(f32),
+    /// See [`planck_taper_window`]. Contains `epsilon`.
+    PlanckTaper(f32),
+}
+
+impl Window {
+    /// Applies the window to an array of samples.
+    ///
+    /// ## Return value
+    /// New vector with the window applied to the values.
+    ///
+    /// ## Panics
+    /// If the parameter of the window is invalid. See the corresponding
+    /// window function.
+    #[must_use]
+    pub fn apply(&self, samples: &[f32]) -> Vec<f32> {
+        self.windowed(samples).collect()
+    }
+
+    /// Like [`Self::apply`] but multiplies the samples with the window
+    /// in-place instead of allocating a new vector.
+    ///
+    /// ## Panics
+    /// See [`Self::apply`].
+    pub fn apply_in_place(&self, samples: &mut [f32]) {
+        self.validate();
+        let len = samples.len();
+        for (i, sample) in samples.iter_mut().enumerate() {
+            *sample *= self.coefficient_unchecked(i, len);
+        }
+    }
+
+    /// Returns an iterator that lazily multiplies the samples with the
+    /// window coefficients, e.g. to fill an existing buffer without an
+    /// intermediate vector.
+    ///
+    /// ## Panics
+    /// See [`Self::apply`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::windows::Window;
+    /// let samples = [1.0, 2.0, 3.0, 4.0];
+    /// let mut buffer = [0.0; 4];
+    /// for (dst, windowed) in buffer.iter_mut().zip(Window::Hann.windowed(&samples)) {
+    ///     *dst = windowed;
+    /// }
+    /// ```
+    pub fn windowed<'a>(&self, samples: &'a [f32]) -> impl Iterator<Item = f32> + 'a {
+        self.validate();
+        let window = *self;
+        let len = samples.len();
+        samples
+            .iter()
+            .enumerate()
+            .map(move |(i, sample)| sample * window.coefficient_unchecked(i, len))
+    }
+
+    /// Returns the coefficients of the window for `len` samples, i.e. the
+    /// window applied to samples with the value `1.0`.
+    ///
+    /// ## Panics
+    /// See [`Self::apply`].
+    #[must_use]
+    pub fn coefficients(&self, len: usize) -> Vec<f32> {
+        self.validate();
+        (0..len)
+            .map(|i| self.coefficient_unchecked(i, len))
+            .collect()
+    }
+
+    /// Like [`Self::apply`] but lets you choose between a periodic and a
+    /// symmetric window. See [`WindowSymmetry`].
+    ///
+    /// ## Panics
+    /// See [`Self::apply`].
+    #[must_use]
+    pub fn apply_with_symmetry(&self, samples: &[f32], symmetry: WindowSymmetry) -> Vec<f32> {
+        samples
+            .iter()
+            .zip(self.coefficients_with_symmetry(samples.len(), symmetry))
+            .map(|(sample, coefficient)| sample * coefficient)
+            .collect()
+    }
+
+    /// Like [`Self::coefficients`] but lets you choose between a periodic
+    /// and a symmetric window. See [`WindowSymmetry`].
+    ///
+    /// ## Panics
+    /// See [`Self::apply`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use spectrum_analyzer::windows::{Window, WindowSymmetry};
+    /// let symmetric = Window::Hann.coefficients_with_symmetry(5, WindowSymmetry::Symmetric);
+    /// assert_eq!(symmetric[0], symmetric[4]);
+    /// assert_eq!(symmetric[2], 1.0);
+    /// ```
+    #[must_use]
+    pub fn coefficients_with_symmetry(&self, len: usize, symmetry: WindowSymmetry) -> Vec<f32> {
+        match symmetry {
+            WindowSymmetry::Periodic => self.coefficients(len),
+            // a single coefficient has no edges
+            WindowSymmetry::Symmetric if len == 1 => vec![1.0],
+            // the symmetric window of length N has the coefficients of the
+            // periodic window of length N - 1, plus the repeated first one
+            WindowSymmetry::Symmetric => {
+                self.validate();
+                (0..len)
+                    .map(|i| self.coefficient_unchecked(i, len - 1))
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the coefficient of the window for the sample at `index` of
+    /// `len` samples.
+    ///
+    /// ## Panics
+    /// See [`Self::apply`].
+    #[must_use]
+    pub fn coefficient(&self, index: usize, len: usize) -> f32 {
+        self.validate();
+        self.coefficient_unchecked(index, len)
+    }
+
+    /// Checks the parameter of the window.
+    ///
+    /// ## Errors
+    /// [`SpectrumAnalyzerError::InvalidWindow`] if the parameter is out of
+    /// range. See [`Self::apply`].
+    pub(crate) fn try_validate(&self) -> Result<(), SpectrumAnalyzerError> {
+        match self.invalid_parameter() {
+            Some(_) => Err(SpectrumAnalyzerError::InvalidWindow(*self)),
+            None => Ok(()),
+        }
+    }
+
+    /// Like [`Self::try_validate`] but panics.
+    fn validate(&self) {
+        if let Some(message) = self.invalid_parameter() {
+            panic!("{}", message);
+        }
+    }
+
+    /// Returns why the parameter of the window is invalid, if it is.
+    fn invalid_parameter(&self) -> Option<&'static str> {
+        match *self {
+            Self::Kaiser(beta) if beta.is_nan() || beta < 0.0 => Some("beta must not be negative!"),
+            Self::Gaussian(sigma) if sigma.is_nan() || sigma <= 0.0 => {
+                Some("sigma must be positive!")
+            }
+            Self::Tukey(alpha) if !(0.0..=1.0).contains(&alpha) => {
+                Some("alpha must be in the range [0.0, 1.0]!")
+            }
+            Self::PlanckTaper(epsilon) if epsilon.is_nan() || epsilon <= 0.0 || epsilon > 0.5 => {
+                Some("epsilon must be in the range (0.0, 0.5]!")
+            }
+            _ => None,
+        }
+    }
+
+    /// Like [`Self::coefficient`] but without checking the parameter.
+    fn coefficient_unchecked(&self, index: usize, len: usize) -> f32 {
+        let len_f32 = len as f32;
+        match *self {
+            Self::Rectangular => 1.0,
+            Self::Hann => 0.5 * (1.0 - cosf(2.0 * PI * index as f32 / len_f32)),
+            Self::Hamming => 0.54 - 0.46 * cosf(2.0 * PI * index as f32 / len_f32),
+            // constants come from here:
+            // https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window
+            Self::BlackmanHarris4Term => {
+                cosine_sum(&[0.35875, -0.48829, 0.14128, -0.01168], index, len_f32)
+            }
+            // constants come from here:
+            // https://dsp.stackexchange.com/questions/51095/seven-term-blackman-harris-window
+            Self::BlackmanHarris7Term => cosine_sum(
+                &[
+                    0.271_051_4,
+                    -0.433_297_93,
+                    0.218_123,
+                    -0.065_925_45,
+                    0.010_811_742,
+                    -0.000_776_584_84,
+                    0.000_013_887_217,
+                ],
+                index,
+                len_f32,
+            ),
+            // the common truncated coefficients, not the ones of the "exact Blackman" window
+            Self::Blackman => cosine_sum(&[0.42, -0.5, 0.08], index, len_f32),
+            Self::Nuttall => cosine_sum(
+                &[0.355_768, -0.487_396, 0.144_232, -0.012_604],
+                index,
+                len_f32,
+            ),
+            // coefficients of Matlab's `flattopwin`
+            Self::FlatTop => cosine_sum(
+                &[
+                    0.215_578_95,
+                    -0.416_631_58,
+                    0.277_263_16,
+                    -0.083_578_95,
+                    0.006_947_368,
+                ],
+                index,
+                len_f32,
+            ),
+            Self::Kaiser(beta) => {
+                // position in [-1, 1)
+                let x = 2.0 * index as f32 / len_f32 - 1.0;
+                bessel_i0(beta * libm::sqrtf(1.0 - x * x)) / bessel_i0(beta)
+            }
+            Self::Gaussian(sigma) => {
+                let half_len = len_f32 / 2.0;
+                let x = (index as f32 - half_len) / (sigma * half_len);
+                libm::expf(-0.5 * x * x)
+            }
+            Self::Tukey(alpha) => {
+                let x = edge_distance(index, len_f32);
+                if x < alpha / 2.0 {
+                    0.5 * (1.0 - cosf(2.0 * PI * x / alpha))
+                } else {
+                    1.0
+                }
+            }
+            Self::PlanckTaper(epsilon) => {
+                let x = edge_distance(index, len_f32);
+                if x == 0.0 {
+                    0.0
+                } else if x < epsilon {
+                    1.0 / (1.0 + libm::expf(epsilon / x - epsilon / (epsilon - x)))
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// Precomputed coefficients of a window for a fixed number of samples. The
+/// window is calculated once and can then be applied to many frames, which
+/// saves the evaluation of `cos()` and similar functions per sample and
+/// frame. Applying the coefficients doesn't allocate.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::windows::{Window, WindowCoefficients};
+/// let coefficients = WindowCoefficients::new(Window::Hann, 1024);
+/// # let frames = vec![vec![1.0; 1024]; 2];
+/// for mut frame in frames {
+///     coefficients.apply(&mut frame);
+///     // ... FFT
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowCoefficients {
+    coefficients: Vec<f32>,
+}
+
+impl WindowCoefficients {
+    /// Calculates the coefficients of `window` for `len` samples.
+    ///
+    /// ## Panics
+    /// See [`Window::apply`].
+    #[must_use]
+    pub fn new(window: Window, len: usize) -> Self {
+        Self {
+            coefficients: window.coefficients(len),
+        }
+    }
+
+    /// Like [`Self::new`] but lets you choose between a periodic and a
+    /// symmetric window. See [`WindowSymmetry`].
+    ///
+    /// ## Panics
+    /// See [`Window::apply`].
+    #[must_use]
+    pub fn with_symmetry(window: Window, len: usize, symmetry: WindowSymmetry) -> Self {
+        Self {
+            coefficients: window.coefficients_with_symmetry(len, symmetry),
+        }
+    }
+
+    /// Calculates the coefficients of a window function, e.g. a custom one,
+    /// for `len` samples. The window function must multiply each sample with
+    /// a coefficient that only depends on the position of the sample.
+    #[must_use]
+    pub fn from_window_fn(window_fn: &WindowFunction, len: usize) -> Self {
+        Self {
+            coefficients: window_fn(&vec![1.0; len]),
+        }
+    }
+
+    /// Returns the number of samples the coefficients are calculated for.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Returns whether there are no coefficients.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Returns the coefficients.
+    #[must_use]
+    pub fn as_slice(&self) -> &[f32] {
+        &self.coefficients
+    }
+
+    /// Returns the coherent gain of the window, i.e. the mean of the
+    /// coefficients. The magnitude of a sine in a windowed spectrum is
+    /// reduced by this factor compared to the rectangular window, e.g. `0.5`
+    /// for the Hann window. Divide by it to correct amplitudes, see
+    /// [`crate::scaling::window_amplitude_correction`].
+    #[must_use]
+    pub fn coherent_gain(&self) -> f32 {
+        self.coefficients.iter().sum::<f32>() / self.coefficients.len() as f32
+    }
+
+    /// Returns the equivalent noise bandwidth (ENBW) of the window in
+    /// frequency bins, i.e. `N * sum(w[n]²) / sum(w[n])²`. This is `1.0` for
+    /// the rectangular window and `1.5` for the Hann window. Multiply it with
+    /// the frequency resolution to get the ENBW in Hertz. The power of
+    /// broadband noise in a windowed spectrum is increased by this factor,
+    /// see [`crate::scaling::window_power_density_correction`].
+    #[must_use]
+    pub fn enbw(&self) -> f32 {
+        let sum = self.coefficients.iter().sum::<f32>();
+        let power_sum = self.coefficients.iter().map(|w| w * w).sum::<f32>();
+        self.coefficients.len() as f32 * power_sum / (sum * sum)
+    }
+
+    /// Multiplies the samples of the frame with the coefficients in-place.
+    ///
+    /// ## Panics
+    /// If `frame` doesn't have the length [`Self::len`].
+    pub fn apply(&self, frame: &mut [f32]) {
+        assert_eq!(
+            frame.len(),
+            self.coefficients.len(),
+            "The frame must have the length of the window!"
+        );
+        frame
+            .iter_mut()
+            .zip(&self.coefficients)
+            .for_each(|(sample, coefficient)| *sample *= coefficient);
+    }
+
+    /// Like [`Self::apply`] but writes the windowed samples into `out`, so
+    /// that the frame itself stays unchanged.
+    ///
+    /// ## Panics
+    /// If `frame` or `out` don't have the length [`Self::len`].
+    pub fn apply_to(&self, frame: &[f32], out: &mut [f32]) {
+        assert_eq!(
+            frame.len(),
+            self.coefficients.len(),
+            "The frame must have the length of the window!"
+        );
+        assert_eq!(
+            out.len(),
+            self.coefficients.len(),
+            "The output must have the length of the window!"
+        );
+        for ((dst, sample), coefficient) in out.iter_mut().zip(frame).zip(&self.coefficients) {
+            *dst = sample * coefficient;
+        }
+    }
+}
+
+/// Multiplies the samples with the window in-place. Unlike the window
+/// functions, this doesn't allocate a new vector, which matters on embedded
+/// targets. See [`Window::apply_in_place`].
+///
+/// ## Panics
+/// See [`Window::apply`].
+pub fn apply_window_in_place(samples: &mut [f32], window: Window) {
+    window.apply_in_place(samples);
+}
+
 /// Applies a Hann window (<https://en.wikipedia.org/wiki/Window_function#Hann_and_Hamming_windows>)
 /// to an array of samples.
 ///
@@ -38,31 +484,17 @@ use libm::cosf;
 /// New vector with Hann window applied to the values.
 #[must_use]
 pub fn hann_window(samples: &[f32]) -> Vec<f32> {
-    let mut windowed_samples = Vec::with_capacity(samples.len());
-    let samples_len_f32 = samples.len() as f32;
-    for (i, sample) in samples.iter().enumerate() {
-        let two_pi_i = 2.0 * PI * i as f32;
-        let idontknowthename = cosf(two_pi_i / samples_len_f32);
-        let multiplier = 0.5 * (1.0 - idontknowthename);
-        windowed_samples.push(multiplier * sample)
-    }
-    windowed_samples
+    Window::Hann.apply(samples)
 }
 
 /// Applies a Hamming window (<https://en.wikipedia.org/wiki/Window_function#Hann_and_Hamming_windows>)
 /// to an array of samples.
 ///
 /// ## Return value
-/// New vector with Hann window applied to the values.
+/// New vector with Hamming window applied to the values.
 #[must_use]
 pub fn hamming_window(samples: &[f32]) -> Vec<f32> {
-    let mut windowed_samples = Vec::with_capacity(samples.len());
-    let samples_len_f32 = samples.len() as f32;
-    for (i, sample) in samples.iter().enumerate() {
-        let multiplier = 0.54 - (0.46 * (2.0 * PI * i as f32 / cosf(samples_len_f32 - 1.0)));
-        windowed_samples.push(multiplier * sample)
-    }
-    windowed_samples
+    Window::Hamming.apply(samples)
 }
 
 /// Applies a Blackman-Harris 4-term window (<https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window>)
@@ -72,11 +504,7 @@ pub fn hamming_window(samples: &[f32]) -> Vec<f32> {
 /// New vector with Blackman-Harris 4-term window applied to the values.
 #[must_use]
 pub fn blackman_harris_4term(samples: &[f32]) -> Vec<f32> {
-    // constants come from here:
-    // https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window
-    const ALPHA: [f32; 4] = [0.35875, -0.48829, 0.14128, -0.01168];
-
-    blackman_harris_xterm(samples, &ALPHA)
+    Window::BlackmanHarris4Term.apply(samples)
 }
 
 /// Applies a Blackman-Harris 7-term window to an array of samples.
@@ -90,51 +518,368 @@ pub fn blackman_harris_4term(samples: &[f32]) -> Vec<f32> {
 /// New vector with Blackman-Harris 7-term window applied to the values.
 #[must_use]
 pub fn blackman_harris_7term(samples: &[f32]) -> Vec<f32> {
-    // constants come from here:
-    // https://dsp.stackexchange.com/questions/51095/seven-term-blackman-harris-window
-    const ALPHA: [f32; 7] = [
-        0.271_051_4,
-        -0.433_297_93,
-        0.218_123,
-        -0.065_925_45,
-        0.010_811_742,
-        -0.000_776_584_84,
-        0.000_013_887_217,
-    ];
+    Window::BlackmanHarris7Term.apply(samples)
+}
 
-    blackman_harris_xterm(samples, &ALPHA)
+/// Applies a Blackman window (<https://en.wikipedia.org/wiki/Window_function#Blackman_window>)
+/// to an array of samples. It has lower side lobes than the Hann window at
+/// the cost of a wider main lobe.
+///
+/// ## Return value
+/// New vector with Blackman window applied to the values.
+#[must_use]
+pub fn blackman_window(samples: &[f32]) -> Vec<f32> {
+    Window::Blackman.apply(samples)
+}
+
+/// Applies a Nuttall window (<https://en.wikipedia.org/wiki/Window_function#Nuttall_window,_continuous_first_derivative>)
+/// to an array of samples. Similar to [`blackman_harris_4term`], but the
+/// side lobes decay faster, as the first derivative is continuous.
+///
+/// ## Return value
+/// New vector with Nuttall window applied to the values.
+#[must_use]
+pub fn nuttall_window(samples: &[f32]) -> Vec<f32> {
+    Window::Nuttall.apply(samples)
+}
+
+/// Applies a flat top window (<https://en.wikipedia.org/wiki/Window_function#Flat_top_window>)
+/// to an array of samples. Its main lobe is very flat, hence the amplitude
+/// of a sine is measured accurately (error below 0.01 dB), even if the
+/// frequency lies between two frequency bins. Use this for amplitude
+/// measurements, but not to distinguish close frequencies.
+///
+/// ## Return value
+/// New vector with flat top window applied to the values.
+#[must_use]
+pub fn flat_top_window(samples: &[f32]) -> Vec<f32> {
+    Window::FlatTop.apply(samples)
 }
 
-/// Applies a Blackman-Harris x-term window
-/// (<https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window>)
-/// to an array of samples. The x is specified by `alphas.len()`.
+/// Applies a Kaiser window (<https://en.wikipedia.org/wiki/Kaiser_window>)
+/// to an array of samples. The parameter `beta` trades the width of the
+/// main lobe against the level of the side lobes: `0.0` equals a
+/// rectangular window, `5.0` is similar to a Hamming window and `8.6` to a
+/// Blackman window.
+///
+/// As [`WindowFunction`] has no parameters, use a closure to pass this
+/// function, e.g. `&|samples: &[f32]| kaiser_window(samples, 8.6)`.
+///
+/// ## Return value
+/// New vector with Kaiser window applied to the values.
+///
+/// ## Panics
+/// If `beta` is negative.
+#[must_use]
+pub fn kaiser_window(samples: &[f32], beta: f32) -> Vec<f32> {
+    Window::Kaiser(beta).apply(samples)
+}
+
+/// Applies a Gaussian window (<https://en.wikipedia.org/wiki/Window_function#Gaussian_window>)
+/// to an array of samples. The parameter `sigma` is the standard deviation
+/// relative to half the window length; smaller values make the window
+/// narrower. Usual values are between `0.3` and `0.5`.
+///
+/// As [`WindowFunction`] has no parameters, use a closure to pass this
+/// function, e.g. `&|samples: &[f32]| gaussian_window(samples, 0.4)`.
+///
+/// ## Return value
+/// New vector with Gaussian window applied to the values.
+///
+/// ## Panics
+/// If `sigma` is not positive.
+#[must_use]
+pub fn gaussian_window(samples: &[f32], sigma: f32) -> Vec<f32> {
+    Window::Gaussian(sigma).apply(samples)
+}
+
+/// Applies a Tukey window (tapered cosine window,
+/// <https://en.wikipedia.org/wiki/Window_function#Tukey_window>) to an array
+/// of samples. The middle part of the samples stays unchanged and only the
+/// edges are tapered with a cosine. The parameter `alpha` is the fraction of
+/// the window inside the tapered edges: `0.0` equals a rectangular window
+/// and `1.0` a Hann window.
+///
+/// As [`WindowFunction`] has no parameters, use a closure to pass this
+/// function, e.g. `&|samples: &[f32]| tukey_window(samples, 0.5)`.
+///
+/// ## Return value
+/// New vector with Tukey window applied to the values.
+///
+/// ## Panics
+/// If `alpha` is not in the range `[0.0, 1.0]`.
+#[must_use]
+pub fn tukey_window(samples: &[f32], alpha: f32) -> Vec<f32> {
+    Window::Tukey(alpha).apply(samples)
+}
+
+/// Applies a Planck-taper window (<https://en.wikipedia.org/wiki/Window_function#Planck-taper_window>)
+/// to an array of samples. Like [`tukey_window`], the middle part of the
+/// samples stays unchanged, but the taper is infinitely differentiable,
+/// which suppresses the leakage even better. The parameter `epsilon` is the
+/// fraction of the window inside each tapered edge.
+///
+/// As [`WindowFunction`] has no parameters, use a closure to pass this
+/// function, e.g. `&|samples: &[f32]| planck_taper_window(samples, 0.1)`.
 ///
 /// ## Return value
-/// New vector with Blackman-Harris x-term window applied to the values.
+/// New vector with Planck-taper window applied to the values.
+///
+/// ## Panics
+/// If `epsilon` is not in the range `(0.0, 0.5]`.
 #[must_use]
-fn blackman_harris_xterm(samples: &[f32], alphas: &[f32]) -> Vec<f32> {
-    let mut windowed_samples = Vec::with_capacity(samples.len());
+pub fn planck_taper_window(samples: &[f32], epsilon: f32) -> Vec<f32> {
+    Window::PlanckTaper(epsilon).apply(samples)
+}
+
+/// Calculates the coefficient of a generalized cosine window
+/// (Blackman-Harris x-term window), i.e.
+/// `ALPHA0 + ALPHA1 * cos(2PI * i / N) + ALPHA2 * cos(4PI * i / N) + ...`.
+fn cosine_sum(alphas: &[f32], i: usize, len: f32) -> f32 {
+    alphas
+        .iter()
+        .enumerate()
+        .map(|(alpha_i, alpha)| alpha * cosf(2.0 * PI * alpha_i as f32 * i as f32 / len))
+        .sum()
+}
+
+/// Returns the distance of index `i` to the closer edge of a (periodic)
+/// window of length `len`, relative to `len`.
+fn edge_distance(i: usize, len: f32) -> f32 {
+    let x = i as f32 / len;
+    x.min(1.0 - x)
+}
+
+/// Evaluates the zeroth-order modified Bessel function of the first kind
+/// with its power series. 50 terms are sufficient for all arguments of
+/// practical Kaiser windows (`beta` up to 30).
+fn bessel_i0(x: f32) -> f32 {
+    let quarter_x_squared = x * x / 4.0;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    // terms are (x/2)^2k / (k!)^2
+    for k in 1..=50 {
+        term *= quarter_x_squared / (k * k) as f32;
+        sum += term;
+    }
+    sum
+}
 
-    let samples_len_f32 = samples.len() as f32;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{samples_fft_to_spectrum, FrequencyLimit};
 
-    for sample in samples.iter() {
-        // Will result in something like that:
-        /* ALPHA0
-            + ALPHA1 * ((2.0 * PI * *samples[i])/samples_len_f32).cos()
-            + ALPHA2 * ((4.0 * PI * *samples[i])/samples_len_f32).cos()
-            + ALPHA3 * ((6.0 * PI * *samples[i])/samples_len_f32).cos()
-        */
+    #[test]
+    fn test_window_shapes() {
+        let windows: [(&WindowFunction, f32); 6] = [
+            (&hann_window, 0.0),
+            (&hamming_window, 0.08),
+            (&blackman_window, 0.0),
+            (&blackman_harris_4term, 0.000_06),
+            (&nuttall_window, 0.0),
+            (&flat_top_window, -0.000_421),
+        ];
+        let ones = [1.0; 64];
+        for (window_fn, first) in windows {
+            let window = window_fn(&ones);
+            assert!((window[0] - first).abs() < 1e-5, "{}", window[0]);
+            // maximum in the center
+            assert!((window[32] - 1.0).abs() < 1e-3, "{}", window[32]);
+            // symmetric
+            for i in 1..32 {
+                assert!((window[i] - window[64 - i]).abs() < 1e-5);
+            }
+        }
+    }
 
-        let mut acc = 0.0;
-        for (alpha_i, alpha) in alphas.iter().enumerate() {
-            // in 1. iter. 0PI, then 2PI, then 4 PI, then 6 PI
-            let two_pi_iteration = 2.0 * alpha_i as f32 * PI;
-            let cos = cosf((two_pi_iteration * sample) / samples_len_f32);
-            acc += alpha * cos;
+    #[test]
+    fn test_bessel_i0() {
+        assert_eq!(bessel_i0(0.0), 1.0);
+        assert!((bessel_i0(1.0) - 1.266_066).abs() < 1e-6);
+        assert!((bessel_i0(8.6) / 750.461_2 - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parameterized_windows() {
+        let ones = [1.0; 64];
+        assert_eq!(kaiser_window(&ones, 0.0), ones.to_vec());
+        for window in [kaiser_window(&ones, 8.6), gaussian_window(&ones, 0.4)] {
+            assert!((window[32] - 1.0).abs() < 1e-6, "{}", window[32]);
+            assert!(window[0] < 0.05, "{}", window[0]);
+            for i in 1..32 {
+                assert!((window[i] - window[64 - i]).abs() < 1e-5);
+                assert!(window[i] > window[i - 1]);
+            }
         }
+        // edge of the Gaussian window is at 2.5 standard deviations
+        assert!((gaussian_window(&ones, 0.4)[0] - libm::expf(-3.125)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tapered_windows() {
+        let ones = [1.0; 64];
+        assert_eq!(tukey_window(&ones, 0.0), ones.to_vec());
+        let hann = hann_window(&ones);
+        for (tukey, hann) in tukey_window(&ones, 1.0).iter().zip(&hann) {
+            assert!((tukey - hann).abs() < 1e-6);
+        }
+        for window in [tukey_window(&ones, 0.5), planck_taper_window(&ones, 0.25)] {
+            assert_eq!(window[0], 0.0);
+            // a quarter of the window at each edge is tapered
+            assert!(window[15] < 1.0);
+            assert!(window[16..=48].iter().all(|x| *x == 1.0));
+            for i in 1..32 {
+                assert!((window[i] - window[64 - i]).abs() < 1e-6);
+            }
+        }
+        // middle of the taper
+        assert!((planck_taper_window(&ones, 0.25)[8] - 0.5).abs() < 1e-6);
+        assert!((tukey_window(&ones, 0.5)[8] - 0.5).abs() < 1e-6);
+    }
 
-        windowed_samples.push(acc)
+    #[test]
+    fn test_window_enum() {
+        let samples = [0.5, 1.0, -2.0, 3.0, 4.0, 1.0, 0.0, 2.0];
+        assert_eq!(Window::Rectangular.apply(&samples), samples.to_vec());
+        assert_eq!(Window::Hann.apply(&samples), hann_window(&samples));
+        assert_eq!(
+            Window::Kaiser(5.0).apply(&samples),
+            kaiser_window(&samples, 5.0)
+        );
+        assert_eq!(Window::FlatTop.coefficients(8), flat_top_window(&[1.0; 8]));
     }
 
-    windowed_samples
+    #[test]
+    fn test_in_place_and_iterator() {
+        let samples = [0.5, 1.0, -2.0, 3.0, 4.0, 1.0, 0.0, 2.0];
+        let expected = Window::Blackman.apply(&samples);
+        let mut in_place = samples;
+        apply_window_in_place(&mut in_place, Window::Blackman);
+        assert_eq!(in_place.to_vec(), expected);
+        assert_eq!(
+            Window::Blackman.windowed(&samples).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(Window::Blackman.coefficient(3, 8) * samples[3], expected[3]);
+    }
+
+    #[test]
+    fn test_window_coefficients() {
+        let samples = [0.5, 1.0, -2.0, 3.0, 4.0, 1.0, 0.0, 2.0];
+        let coefficients = WindowCoefficients::new(Window::Nuttall, 8);
+        assert_eq!(coefficients.len(), 8);
+        assert_eq!(
+            coefficients,
+            WindowCoefficients::from_window_fn(&nuttall_window, 8)
+        );
+        let mut frame = samples;
+        coefficients.apply(&mut frame);
+        assert_eq!(frame.to_vec(), nuttall_window(&samples));
+        let mut out = [0.0; 8];
+        coefficients.apply_to(&samples, &mut out);
+        assert_eq!(out, frame);
+    }
+
+    #[test]
+    fn test_coherent_gain_and_enbw() {
+        let cases = [
+            (Window::Rectangular, 1.0, 1.0),
+            (Window::Hann, 0.5, 1.5),
+            (Window::Hamming, 0.54, 1.362_826),
+            (Window::BlackmanHarris4Term, 0.358_75, 2.004_353),
+            (Window::FlatTop, 0.215_578_95, 3.770_246),
+        ];
+        for (window, coherent_gain, enbw) in cases {
+            let coefficients = WindowCoefficients::new(window, 1024);
+            assert!(
+                (coefficients.coherent_gain() - coherent_gain).abs() < 1e-4,
+                "{window:?}: {}",
+                coefficients.coherent_gain()
+            );
+            assert!(
+                (coefficients.enbw() - enbw).abs() < 1e-3,
+                "{window:?}: {}",
+                coefficients.enbw()
+            );
+        }
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let windows = [
+            Window::Hann,
+            Window::Blackman,
+            Window::FlatTop,
+            Window::Kaiser(6.0),
+            Window::Gaussian(0.4),
+            Window::Tukey(0.5),
+            Window::PlanckTaper(0.2),
+        ];
+        for window in windows {
+            let periodic = window.coefficients(16);
+            assert_eq!(
+                periodic,
+                window.coefficients_with_symmetry(16, WindowSymmetry::Periodic)
+            );
+            let symmetric = window.coefficients_with_symmetry(17, WindowSymmetry::Symmetric);
+            // the periodic window is the symmetric one without the last value
+            for (a, b) in periodic.iter().zip(&symmetric) {
+                assert!((a - b).abs() < 1e-6, "{window:?}: {a} != {b}");
+            }
+            for i in 0..17 {
+                assert!(
+                    (symmetric[i] - symmetric[16 - i]).abs() < 1e-6,
+                    "{window:?}"
+                );
+            }
+        }
+        assert_eq!(
+            Window::Hann.coefficients_with_symmetry(1, WindowSymmetry::Symmetric),
+            vec![1.0]
+        );
+        assert_eq!(
+            WindowCoefficients::with_symmetry(Window::Hann, 4, WindowSymmetry::Symmetric)
+                .as_slice(),
+            Window::Hann
+                .apply_with_symmetry(&[1.0; 4], WindowSymmetry::Symmetric)
+                .as_slice()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_window_coefficients_wrong_len() {
+        WindowCoefficients::new(Window::Hann, 8).apply(&mut [1.0; 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_parameter() {
+        apply_window_in_place(&mut [1.0; 8], Window::Tukey(2.0));
+    }
+
+    #[test]
+    fn test_window_scales_samples() {
+        let samples = [2.0; 8];
+        assert_eq!(
+            blackman_harris_4term(&samples)[4],
+            2.0 * blackman_harris_4term(&[1.0; 8])[4]
+        );
+    }
+
+    #[cfg(feature = "size-1024")]
+    #[test]
+    fn test_flat_top_amplitude_accuracy() {
+        // frequency exactly between two bins (resolution 8 Hz), amplitude 1.0
+        let samples = (0..1024)
+            .map(|i| libm::sinf(2.0 * PI * 1004.0 * i as f32 / 8192.0))
+            .collect::<Vec<_>>();
+        let coherent_gain = flat_top_window(&[1.0; 1024]).iter().sum::<f32>();
+        let spectrum =
+            samples_fft_to_spectrum(&flat_top_window(&samples), 8192, FrequencyLimit::All, None)
+                .unwrap();
+        let amplitude = 2.0 * spectrum.max().1.val() / coherent_gain;
+        assert!((amplitude - 1.0).abs() < 0.002, "{amplitude}");
+    }
 }
@@ -0,0 +1,255 @@
+/*
+MIT License
+
+Copyright (c) 2023 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for a zoom FFT, i.e. a spectrum with a very fine frequency grid
+//! inside a narrow frequency band. See [`samples_to_zoom_spectrum`].
+
+use crate::error::SpectrumAnalyzerError;
+use crate::fft::{Complex32, FftImpl};
+use crate::frequency::{Frequency, FrequencyValue};
+use crate::goertzel::goertzel_magnitude;
+use crate::limit::{FrequencyLimit, FrequencyLimitError};
+use crate::spectrum::FrequencySpectrum;
+use crate::validate_samples;
+use alloc::vec::Vec;
+
+/// Calculates the spectrum inside the frequency band `band` on a frequency
+/// grid with the spacing `resolution`, e.g. `950 <= f <= 1050 Hz` in
+/// `0.1 Hz` steps. This is done without a gigantic FFT by evaluating the
+/// Fourier transform of the samples (more specifically, the
+/// [DTFT](https://en.wikipedia.org/wiki/Discrete-time_Fourier_transform))
+/// at each frequency of the grid with the [chirp Z-transform] (CZT). The CZT
+/// is calculated with Bluestein's algorithm, i.e. with three FFTs of length
+/// `L`, the next power of two of `samples.len() + number of frequencies - 1`.
+/// Hence, the costs are `O(L log L)`. If `L` is bigger than
+/// [`crate::MAX_SAMPLES_LEN`], the Goertzel algorithm is used for each
+/// frequency of the grid instead, which costs
+/// `O(samples.len() * number of frequencies)`.
+///
+/// Please note that the ability to separate two close frequencies is still
+/// limited by the duration of the signal (`sampling_rate / samples.len()`).
+/// The finer grid interpolates the spectrum and helps to locate peaks
+/// precisely. Apply a window function on the samples first.
+///
+/// The values have the same scale as the values of
+/// [`crate::samples_fft_to_spectrum`] without scaling function.
+///
+/// ## Parameters
+/// * `samples` raw audio, e.g. 16bit audio data but as f32. Any length of at
+///             least two samples is supported.
+/// * `sampling_rate` sampling_rate, e.g. `44100 [Hz]`
+/// * `band` The band of interest. See [`FrequencyLimit`].
+/// * `resolution` Spacing of the frequency grid in Hertz.
+///
+/// ## Example
+/// ```rust
+/// use spectrum_analyzer::zoom::samples_to_zoom_spectrum;
+/// use spectrum_analyzer::FrequencyLimit;
+/// let samples = vec![0.0; 4096];
+/// let spectrum =
+///     samples_to_zoom_spectrum(&samples, 44100, FrequencyLimit::Range(950.0, 1050.0), 0.5)
+///         .unwrap();
+/// assert_eq!(spectrum.data().len(), 201);
+/// ```
+///
+/// ## Errors
+/// See [`crate::samples_fft_to_spectrum`]. If `resolution` is not positive or
+/// bigger than the band, [`SpectrumAnalyzerError::InvalidFrequencyResolution`]
+/// is returned.
+///
+/// [chirp Z-transform]: https://en.wikipedia.org/wiki/Chirp_Z-transform
+pub fn samples_to_zoom_spectrum(
+    samples: &[f32],
+    sampling_rate: u32,
+    band: FrequencyLimit,
+    resolution: f32,
+) -> Result<FrequencySpectrum, SpectrumAnalyzerError> {
+    validate_samples(samples)?;
+    let max_detectable_frequency = sampling_rate as f32 / 2.0;
+    band.verify(max_detectable_frequency)
+        .map_err(SpectrumAnalyzerError::InvalidFrequencyLimit)?;
+    let min_fr = band.maybe_min().unwrap_or(0.0);
+    let max_fr = band.maybe_max().unwrap_or(max_detectable_frequency);
+    if min_fr >= max_fr {
+        return Err(SpectrumAnalyzerError::InvalidFrequencyLimit(
+            FrequencyLimitError::InvalidRange(min_fr, max_fr),
+        ));
+    }
+    if !(resolution > 0.0 && resolution <= max_fr - min_fr) {
+        return Err(SpectrumAnalyzerError::InvalidFrequencyResolution(
+            resolution,
+        ));
+    }
+
+    let count = libm::floorf((max_fr - min_fr) / resolution) as usize + 1;
+    let magnitudes = chirp_z_magnitudes(samples, sampling_rate, min_fr, resolution, count)
+        .unwrap_or_else(|| {
+            (0..count)
+                .map(|i| goertzel_magnitude(samples, sampling_rate, min_fr + i as f32 * resolution))
+                .collect()
+        });
+    let data = magnitudes
+        .into_iter()
+        .enumerate()
+        .map(|(i, magnitude)| {
+            (
+                Frequency::from(min_fr + i as f32 * resolution),
+                FrequencyValue::from(magnitude),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut working_buffer = data.clone();
+    Ok(FrequencySpectrum::new(
+        data,
+        resolution,
+        samples.len() as u32,
+        &mut working_buffer,
+    ))
+}
+
+/// Calculates the magnitudes of the DTFT at the `count` frequencies
+/// `min_fr + k * resolution` with the chirp Z-transform via Bluestein's
+/// algorithm. It uses `nk = (n² + k² - (k - n)²) / 2` to turn the transform
+/// into a convolution with a chirp, which is calculated with FFTs.
+///
+/// ## Return value
+/// `None` if the FFT length is bigger than [`crate::MAX_SAMPLES_LEN`].
+fn chirp_z_magnitudes(
+    samples: &[f32],
+    sampling_rate: u32,
+    min_fr: f32,
+    resolution: f32,
+    count: usize,
+) -> Option<Vec<f32>> {
+    let len = (samples.len() + count - 1).next_power_of_two();
+    if len > FftImpl::MAX_LEN {
+        return None;
+    }
+    let sampling_rate = f64::from(sampling_rate);
+    // `e^(2πi * periods)`. The phases of the chirp grow quadratically, hence,
+    // they are calculated with f64 and reduced to one period first.
+    let unit = |periods: f64| {
+        let phase = 2.0 * core::f64::consts::PI * (periods % 1.0);
+        Complex32::new(libm::cos(phase) as f32, libm::sin(phase) as f32)
+    };
+    // `e^(πi * resolution / sampling_rate * m²)`
+    let chirp = |m: usize| unit(0.5 * (m * m) as f64 * f64::from(resolution) / sampling_rate);
+
+    // shift `min_fr` to 0 Hz and multiply with the conjugated chirp
+    let mut weighted = vec![Complex32::new(0.0, 0.0); len];
+    for (n, (weighted, sample)) in weighted.iter_mut().zip(samples).enumerate() {
+        let shift = unit(-(n as f64) * f64::from(min_fr) / sampling_rate);
+        *weighted = shift * chirp(n).conj() * *sample;
+    }
+    // chirp for the distances `k - n` in range `-(N - 1)..M`, where the
+    // negative ones wrap around
+    let mut chirps = vec![Complex32::new(0.0, 0.0); len];
+    for (m, chirp_m) in chirps.iter_mut().enumerate().take(count) {
+        *chirp_m = chirp(m);
+    }
+    for m in 1..samples.len() {
+        chirps[len - m] = chirp(m);
+    }
+
+    let weighted = FftImpl::calc_complex_input(weighted);
+    let chirps = FftImpl::calc_complex_input(chirps);
+    // inverse FFT of the product via `ifft(x) = conj(fft(conj(x))) / len`
+    let product = weighted
+        .iter()
+        .zip(&chirps)
+        .map(|(a, b)| (a * b).conj())
+        .collect::<Vec<_>>();
+    let convolution = FftImpl::calc_complex_input(product);
+    // the final multiplication with a chirp doesn't change the magnitude
+    Some(
+        convolution
+            .iter()
+            .take(count)
+            .map(|c| libm::sqrtf(c.norm_sqr()) / len as f32)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::windows::hann_window;
+    use core::f32::consts::PI;
+
+    #[test]
+    fn test_zoom() {
+        // FFT resolution would be 44100 / 4096 = 10.8 Hz
+        let samples = (0..4096)
+            .map(|i| libm::sinf(2.0 * PI * 1003.3 * i as f32 / 44100.0))
+            .collect::<Vec<_>>();
+        let samples = hann_window(&samples);
+        let spectrum =
+            samples_to_zoom_spectrum(&samples, 44100, FrequencyLimit::Range(950.0, 1050.0), 0.1)
+                .unwrap();
+        assert_eq!(spectrum.min_fr().val(), 950.0);
+        float_cmp::assert_approx_eq!(f32, spectrum.max_fr().val(), 1050.0, epsilon = 0.01);
+        float_cmp::assert_approx_eq!(f32, spectrum.max().0.val(), 1003.3, epsilon = 0.15);
+    }
+
+    #[cfg(feature = "size-2048")]
+    #[test]
+    fn test_chirp_z_equals_goertzel() {
+        let samples = (0..1000)
+            .map(|i| {
+                libm::sinf(2.0 * PI * 1003.3 * i as f32 / 44100.0)
+                    + 0.5 * libm::sinf(2.0 * PI * 1020.0 * i as f32 / 44100.0)
+            })
+            .collect::<Vec<_>>();
+        let chirp_z = chirp_z_magnitudes(&samples, 44100, 950.0, 0.5, 201).unwrap();
+        let max = chirp_z.iter().copied().fold(0.0, f32::max);
+        for (i, magnitude) in chirp_z.iter().enumerate() {
+            let expected = goertzel_magnitude(&samples, 44100, 950.0 + i as f32 * 0.5);
+            assert!(
+                (magnitude - expected).abs() < max * 1e-3,
+                "{i}: {magnitude} != {expected}"
+            );
+        }
+        // too long for the FFT
+        assert!(chirp_z_magnitudes(&samples, 44100, 950.0, 0.5, crate::MAX_SAMPLES_LEN).is_none());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let samples = vec![0.0; 64];
+        let band = FrequencyLimit::Range(100.0, 200.0);
+        assert!(matches!(
+            samples_to_zoom_spectrum(&samples, 1000, band, 0.0).unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyResolution(_)
+        ));
+        assert!(matches!(
+            samples_to_zoom_spectrum(&samples, 1000, band, 101.0).unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyResolution(_)
+        ));
+        assert!(matches!(
+            samples_to_zoom_spectrum(&samples, 1000, FrequencyLimit::Range(100.0, 100.0), 1.0)
+                .unwrap_err(),
+            SpectrumAnalyzerError::InvalidFrequencyLimit(_)
+        ));
+    }
+}